@@ -4,21 +4,27 @@
 //!   - Tool dispatch (XML parsing, native parsing)
 //!   - Memory store/recall cycles (SQLite backend)
 //!   - Agent turn cycle (full orchestration loop)
+//!   - Telemetry turn action sequence encoding
+//!   - Telemetry action record construction (owned vs borrowed)
+//!   - Telemetry write throughput by writer thread count
 //!
 //! Run: `cargo bench`
 //!
 //! Ref: https://github.com/zeroclaw-labs/zeroclaw/issues/618 (item 7)
 
-use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
 use std::sync::{Arc, Mutex};
 
 use zeroclaw::agent::agent::Agent;
 use zeroclaw::agent::dispatcher::{NativeToolDispatcher, ToolDispatcher, XmlToolDispatcher};
-use zeroclaw::config::MemoryConfig;
+use zeroclaw::config::{ChannelKind, IndexStrategy, MemoryConfig, TelemetryConfig};
 use zeroclaw::memory;
 use zeroclaw::memory::{Memory, MemoryCategory};
 use zeroclaw::observability::{NoopObserver, Observer};
 use zeroclaw::providers::{ChatRequest, ChatResponse, Provider, ToolCall};
+use zeroclaw::telemetry::observer::TurnSequence;
+use zeroclaw::telemetry::store::ActionRecordRef;
+use zeroclaw::telemetry::{ActionRecord, TelemetrySqliteStore};
 use zeroclaw::tools::{Tool, ToolResult};
 
 use anyhow::Result;
@@ -311,11 +317,223 @@ fn bench_agent_turn(c: &mut Criterion) {
     });
 }
 
+// ─────────────────────────────────────────────────────────────────────────────
+// Benchmark: Telemetry turn action sequence (100-event turn)
+// ─────────────────────────────────────────────────────────────────────────────
+
+fn bench_turn_action_sequence(c: &mut Criterion) {
+    const EVENTS: usize = 100;
+    const EVENT_TYPES: [&str; 2] = ["llm_response", "tool_call"];
+    let mut group = c.benchmark_group("turn_action_sequence_100_events");
+
+    // Previous behaviour: re-serialize the whole sequence for every event.
+    group.bench_function("reserialize", |b| {
+        b.iter(|| {
+            let mut seq: Vec<String> = Vec::new();
+            for i in 0..EVENTS {
+                let json = serde_json::to_string(&seq).unwrap();
+                black_box(json);
+                seq.push(EVENT_TYPES[i % 2].to_string());
+            }
+        });
+    });
+
+    // Current behaviour: append per event, encode once at `TurnComplete`.
+    group.bench_function("encode_at_turn_end", |b| {
+        b.iter(|| {
+            let mut seq = TurnSequence::new();
+            for i in 0..EVENTS {
+                seq.push(EVENT_TYPES[i % 2]);
+            }
+            black_box(seq.json().to_string());
+        });
+    });
+
+    group.finish();
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Benchmark: Telemetry action record construction
+// ─────────────────────────────────────────────────────────────────────────────
+
+fn bench_action_record(c: &mut Criterion) {
+    let session_id = "3f2b8c1e-9a4d-4e2f-8b7a-1c2d3e4f5a6b".to_string();
+    let provider = "openrouter".to_string();
+    let model = "anthropic/claude-sonnet-4-20250514".to_string();
+    // Both paths submit to a live store, so the benchmark includes the copy
+    // the borrowed view makes into a pooled record on enqueue.
+    let tmp = tempfile::TempDir::new().unwrap();
+    let store = TelemetrySqliteStore::open(tmp.path(), TelemetryConfig::default()).unwrap();
+    let mut group = c.benchmark_group("action_record");
+
+    group.bench_function("owned", |b| {
+        b.iter(|| {
+            store.submit_action(black_box(ActionRecord {
+                ts: String::new(),
+                ts_epoch_ms: 0,
+                session_id: session_id.clone(),
+                turn_id: String::new(),
+                sequence_index: 0,
+                event_type: "llm_response".into(),
+                provider: Some(provider.clone()),
+                model: Some(model.clone()),
+                tool_name: None,
+                tool_type_embedding: None,
+                arguments_hash: None,
+                tool_success: Some(true),
+                duration_ms: Some(10),
+                tokens_in: None,
+                tokens_out: None,
+                is_user_initiated: false,
+                iteration_index: 0,
+                previous_action_type: None,
+                turn_action_sequence: None,
+                error_message: None,
+                correlation_id: None,
+                parent_action_id: None,
+                estimated_cost_usd: None,
+                metadata_json: None,
+                call_depth: 0,
+            }));
+        });
+    });
+
+    group.bench_function("borrowed", |b| {
+        b.iter(|| {
+            store.submit_action_ref(black_box(ActionRecordRef {
+                ts: "".into(),
+                ts_epoch_ms: 0,
+                session_id: session_id.as_str().into(),
+                turn_id: "".into(),
+                sequence_index: 0,
+                event_type: "llm_response".into(),
+                provider: Some(provider.as_str().into()),
+                model: Some(model.as_str().into()),
+                tool_name: None,
+                tool_type_embedding: None,
+                arguments_hash: None,
+                tool_success: Some(true),
+                duration_ms: Some(10),
+                tokens_in: None,
+                tokens_out: None,
+                is_user_initiated: false,
+                iteration_index: 0,
+                previous_action_type: None,
+                turn_action_sequence: None,
+                error_message: None,
+                correlation_id: None,
+                parent_action_id: None,
+                estimated_cost_usd: None,
+                metadata_json: None,
+                call_depth: 0,
+            }));
+        });
+    });
+
+    group.finish();
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Benchmark: Telemetry write throughput by writer thread count
+// ─────────────────────────────────────────────────────────────────────────────
+
+fn bench_telemetry_writer_threads(c: &mut Criterion) {
+    const EVENTS: u64 = 5_000;
+    let mut group = c.benchmark_group("telemetry_writer_threads");
+    group.throughput(Throughput::Elements(EVENTS));
+    group.sample_size(10);
+
+    for threads in [1, 2, 4, 8] {
+        group.bench_with_input(
+            BenchmarkId::from_parameter(threads),
+            &threads,
+            |b, &threads| {
+                b.iter(|| {
+                    let tmp = tempfile::TempDir::new().unwrap();
+                    let config = TelemetryConfig {
+                        num_writer_threads: threads,
+                        channel_kind: ChannelKind::Unbounded,
+                        max_batch_size: 50,
+                        flush_timeout_ms: 1,
+                        ..TelemetryConfig::default()
+                    };
+                    let store = TelemetrySqliteStore::open(tmp.path(), config).unwrap();
+                    for i in 0..EVENTS {
+                        store.submit_action(ActionRecord {
+                            ts: "2026-01-01T00:00:00Z".into(),
+                            ts_epoch_ms: i as i64,
+                            session_id: format!("sess-{}", i % 16),
+                            turn_id: "turn-1".into(),
+                            event_type: "tool_call".into(),
+                            tool_name: Some("shell".into()),
+                            ..ActionRecord::default()
+                        });
+                    }
+                    // Dropping the store waits for every batch to commit.
+                    drop(store);
+                });
+            },
+        );
+    }
+
+    group.finish();
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Benchmark: Bulk telemetry import with eager vs deferred index creation
+// ─────────────────────────────────────────────────────────────────────────────
+
+fn bench_telemetry_index_strategy(c: &mut Criterion) {
+    const EVENTS: u64 = 20_000;
+    let mut group = c.benchmark_group("telemetry_index_strategy");
+    group.throughput(Throughput::Elements(EVENTS));
+    group.sample_size(10);
+
+    for (name, create_before_insert) in [("eager", true), ("deferred", false)] {
+        group.bench_function(name, |b| {
+            b.iter(|| {
+                let tmp = tempfile::TempDir::new().unwrap();
+                let config = TelemetryConfig {
+                    index_strategy: IndexStrategy {
+                        create_before_insert,
+                    },
+                    channel_kind: ChannelKind::Unbounded,
+                    max_batch_size: 500,
+                    flush_timeout_ms: 1,
+                    ..TelemetryConfig::default()
+                };
+                let mut store = TelemetrySqliteStore::open(tmp.path(), config).unwrap();
+                for i in 0..EVENTS {
+                    store.submit_action(ActionRecord {
+                        ts: "2026-01-01T00:00:00Z".into(),
+                        ts_epoch_ms: i as i64,
+                        session_id: format!("sess-{}", i % 16),
+                        turn_id: format!("turn-{}", i % 256),
+                        event_type: "tool_call".into(),
+                        tool_name: Some("shell".into()),
+                        tool_success: Some(i % 10 != 0),
+                        ..ActionRecord::default()
+                    });
+                }
+                // Commit the import, then build whatever is still missing.
+                store.shutdown();
+                store.create_indexes().unwrap();
+            });
+        });
+    }
+
+    group.finish();
+}
+
 criterion_group!(
     benches,
     bench_xml_parsing,
     bench_native_parsing,
     bench_memory_operations,
     bench_agent_turn,
+    bench_turn_action_sequence,
+    bench_action_record,
+    bench_telemetry_writer_threads,
+    bench_telemetry_index_strategy,
 );
 criterion_main!(benches);
@@ -0,0 +1,102 @@
+// SPDX-License-Identifier: GPL-2.0
+//
+// Times getaddrinfo(3) calls in the agent process and reports each lookup
+// through the DNS_QUERIES ring buffer. The loader attaches the probes to
+// libc for this pid only, so no TGID filter is needed here.
+//
+// Built by build.rs with `clang -target bpf -O2 -g` when the
+// `telemetry-ebpf` feature is enabled.
+
+#include <linux/bpf.h>
+#include <linux/types.h>
+
+#define SEC(name) __attribute__((section(name), used))
+#define __uint(name, val) int (*name)[val]
+#define __type(name, val) typeof(val) *name
+
+#define HOSTNAME_LEN 128
+
+// uprobe context is the architecture's struct pt_regs; build.rs defines
+// __TARGET_ARCH_* for the Cargo target. Indices are in unsigned longs.
+struct pt_regs {
+    unsigned long regs[21];
+};
+
+#if defined(__TARGET_ARCH_arm64)
+#define PT_REGS_PARM1(x) ((x)->regs[0])
+#define PT_REGS_RC(x) ((x)->regs[0])
+#elif defined(__TARGET_ARCH_x86)
+#define PT_REGS_PARM1(x) ((x)->regs[14]) // di
+#define PT_REGS_RC(x) ((x)->regs[10])    // ax
+#else
+#error "dns.bpf.c supports x86_64 and aarch64 only"
+#endif
+
+static void *(*bpf_map_lookup_elem)(void *map, const void *key) = (void *)BPF_FUNC_map_lookup_elem;
+static long (*bpf_map_update_elem)(void *map, const void *key, const void *value,
+                                   __u64 flags) = (void *)BPF_FUNC_map_update_elem;
+static long (*bpf_map_delete_elem)(void *map, const void *key) = (void *)BPF_FUNC_map_delete_elem;
+static void *(*bpf_ringbuf_reserve)(void *ringbuf, __u64 size, __u64 flags) =
+    (void *)BPF_FUNC_ringbuf_reserve;
+static void (*bpf_ringbuf_submit)(void *data, __u64 flags) = (void *)BPF_FUNC_ringbuf_submit;
+static long (*bpf_probe_read_user_str)(void *dst, __u32 size, const void *unsafe_ptr) =
+    (void *)BPF_FUNC_probe_read_user_str;
+static __u64 (*bpf_get_current_pid_tgid)(void) = (void *)BPF_FUNC_get_current_pid_tgid;
+static __u64 (*bpf_ktime_get_ns)(void) = (void *)BPF_FUNC_ktime_get_ns;
+
+// Must match `RawDnsEvent` in src/telemetry/ebpf/dns.rs.
+struct dns_event {
+    // bpf_ktime_get_ns() (CLOCK_MONOTONIC) when getaddrinfo was entered.
+    __u64 start_ns;
+    __u64 duration_ns;
+    __u32 pid;
+    __s32 ret;
+    char hostname[HOSTNAME_LEN];
+};
+
+struct {
+    __uint(type, BPF_MAP_TYPE_HASH);
+    __uint(max_entries, 1024);
+    __type(key, __u64);
+    __type(value, struct dns_event);
+} PENDING SEC(".maps");
+
+struct {
+    __uint(type, BPF_MAP_TYPE_RINGBUF);
+    __uint(max_entries, 64 * 1024);
+} DNS_QUERIES SEC(".maps");
+
+SEC("uprobe/getaddrinfo")
+int getaddrinfo_enter(struct pt_regs *ctx)
+{
+    __u64 pid_tgid = bpf_get_current_pid_tgid();
+    struct dns_event event = {};
+    event.pid = (__u32)pid_tgid;
+    event.start_ns = bpf_ktime_get_ns();
+    if (bpf_probe_read_user_str(event.hostname, sizeof(event.hostname),
+                                (const void *)PT_REGS_PARM1(ctx)) < 0)
+        return 0;
+    bpf_map_update_elem(&PENDING, &pid_tgid, &event, BPF_ANY);
+    return 0;
+}
+
+SEC("uretprobe/getaddrinfo")
+int getaddrinfo_exit(struct pt_regs *ctx)
+{
+    __u64 pid_tgid = bpf_get_current_pid_tgid();
+    struct dns_event *pending = bpf_map_lookup_elem(&PENDING, &pid_tgid);
+    if (!pending)
+        return 0;
+
+    struct dns_event *event = bpf_ringbuf_reserve(&DNS_QUERIES, sizeof(*event), 0);
+    if (event) {
+        *event = *pending;
+        event->duration_ns = bpf_ktime_get_ns() - pending->start_ns;
+        event->ret = (__s32)PT_REGS_RC(ctx);
+        bpf_ringbuf_submit(event, 0);
+    }
+    bpf_map_delete_elem(&PENDING, &pid_tgid);
+    return 0;
+}
+
+char LICENSE[] SEC("license") = "GPL";
@@ -0,0 +1,70 @@
+// SPDX-License-Identifier: GPL-2.0
+//
+// Reports every openat(2) issued by a single process (TARGET_TGID) through
+// the FILE_OPENS ring buffer. Userspace drops /proc and /sys noise before
+// recording anything.
+//
+// Built by build.rs with `clang -target bpf -O2 -g` when the
+// `telemetry-ebpf` feature is enabled.
+
+#include <linux/bpf.h>
+#include <linux/types.h>
+
+#define SEC(name) __attribute__((section(name), used))
+#define __uint(name, val) int (*name)[val]
+
+#define PATH_MAX_LEN 256
+
+static void *(*bpf_ringbuf_reserve)(void *ringbuf, __u64 size, __u64 flags) =
+    (void *)BPF_FUNC_ringbuf_reserve;
+static void (*bpf_ringbuf_submit)(void *data, __u64 flags) = (void *)BPF_FUNC_ringbuf_submit;
+static long (*bpf_probe_read_user_str)(void *dst, __u32 size, const void *unsafe_ptr) =
+    (void *)BPF_FUNC_probe_read_user_str;
+static __u64 (*bpf_get_current_pid_tgid)(void) = (void *)BPF_FUNC_get_current_pid_tgid;
+
+struct {
+    __uint(type, BPF_MAP_TYPE_RINGBUF);
+    __uint(max_entries, 256 * 1024);
+} FILE_OPENS SEC(".maps");
+
+// Patched by the loader (EbpfLoader::set_global) before the program is loaded.
+volatile const __u32 TARGET_TGID = 0;
+
+// Must match `RawOpenEvent` in src/telemetry/ebpf/file_access.rs.
+struct open_event {
+    __u32 pid;
+    __s32 flags;
+    char filename[PATH_MAX_LEN];
+};
+
+// Layout of tracepoint/syscalls/sys_enter_openat after the common header.
+struct sys_enter_openat_ctx {
+    __u64 common;
+    __s32 syscall_nr;
+    __u32 pad;
+    __u64 dfd;
+    const char *filename;
+    __u64 flags;
+    __u64 mode;
+};
+
+SEC("tracepoint/syscalls/sys_enter_openat")
+int file_access(struct sys_enter_openat_ctx *ctx)
+{
+    __u64 pid_tgid = bpf_get_current_pid_tgid();
+    if (TARGET_TGID != 0 && (pid_tgid >> 32) != TARGET_TGID)
+        return 0;
+
+    struct open_event *event = bpf_ringbuf_reserve(&FILE_OPENS, sizeof(*event), 0);
+    if (!event)
+        return 0;
+
+    event->pid = (__u32)pid_tgid;
+    event->flags = (__s32)ctx->flags;
+    if (bpf_probe_read_user_str(event->filename, sizeof(event->filename), ctx->filename) < 0)
+        event->filename[0] = '\0';
+    bpf_ringbuf_submit(event, 0);
+    return 0;
+}
+
+char LICENSE[] SEC("license") = "GPL";
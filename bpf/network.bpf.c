@@ -0,0 +1,114 @@
+// SPDX-License-Identifier: GPL-2.0
+//
+// Reports outbound IPv4 TCP connects made by a single process (TARGET_TGID)
+// through the TCP_CONNECTS ring buffer. The entry probe stashes the
+// destination per thread; the return probe adds the result and emits it.
+//
+// Built by build.rs with `clang -target bpf -O2 -g` when the
+// `telemetry-ebpf` feature is enabled.
+
+#include <linux/bpf.h>
+#include <linux/types.h>
+
+#define SEC(name) __attribute__((section(name), used))
+#define __uint(name, val) int (*name)[val]
+#define __type(name, val) typeof(val) *name
+
+#define AF_INET 2
+
+// kprobe context is the architecture's struct pt_regs; build.rs defines
+// __TARGET_ARCH_* for the Cargo target. Indices are in unsigned longs.
+struct pt_regs {
+    unsigned long regs[21];
+};
+
+#if defined(__TARGET_ARCH_arm64)
+#define PT_REGS_PARM1(x) ((x)->regs[0])
+#define PT_REGS_RC(x) ((x)->regs[0])
+#elif defined(__TARGET_ARCH_x86)
+#define PT_REGS_PARM1(x) ((x)->regs[14]) // di
+#define PT_REGS_RC(x) ((x)->regs[10])    // ax
+#else
+#error "network.bpf.c supports x86_64 and aarch64 only"
+#endif
+
+static void *(*bpf_map_lookup_elem)(void *map, const void *key) = (void *)BPF_FUNC_map_lookup_elem;
+static long (*bpf_map_update_elem)(void *map, const void *key, const void *value,
+                                   __u64 flags) = (void *)BPF_FUNC_map_update_elem;
+static long (*bpf_map_delete_elem)(void *map, const void *key) = (void *)BPF_FUNC_map_delete_elem;
+static void *(*bpf_ringbuf_reserve)(void *ringbuf, __u64 size, __u64 flags) =
+    (void *)BPF_FUNC_ringbuf_reserve;
+static void (*bpf_ringbuf_submit)(void *data, __u64 flags) = (void *)BPF_FUNC_ringbuf_submit;
+static long (*bpf_probe_read_kernel)(void *dst, __u32 size, const void *unsafe_ptr) =
+    (void *)BPF_FUNC_probe_read_kernel;
+static __u64 (*bpf_get_current_pid_tgid)(void) = (void *)BPF_FUNC_get_current_pid_tgid;
+
+// Must match `RawConnectEvent` in src/telemetry/ebpf/network.rs.
+struct connect_event {
+    __u32 pid;
+    __u32 daddr;  // network byte order
+    __u16 dport;  // network byte order
+    __u8 success;
+    __u8 pad;
+};
+
+struct {
+    __uint(type, BPF_MAP_TYPE_HASH);
+    __uint(max_entries, 1024);
+    __type(key, __u64);
+    __type(value, struct connect_event);
+} PENDING SEC(".maps");
+
+struct {
+    __uint(type, BPF_MAP_TYPE_RINGBUF);
+    __uint(max_entries, 64 * 1024);
+} TCP_CONNECTS SEC(".maps");
+
+// Patched by the loader (EbpfLoader::set_global) before the program is loaded.
+volatile const __u32 TARGET_TGID = 0;
+
+// Offsets into struct sock_common; stable across kernel versions.
+#define SKC_DADDR_OFF 0
+#define SKC_DPORT_OFF 12
+#define SKC_FAMILY_OFF 16
+
+SEC("kprobe/tcp_connect")
+int tcp_connect_enter(struct pt_regs *ctx)
+{
+    __u64 pid_tgid = bpf_get_current_pid_tgid();
+    if (TARGET_TGID != 0 && (pid_tgid >> 32) != TARGET_TGID)
+        return 0;
+
+    const char *sk = (const char *)PT_REGS_PARM1(ctx);
+    __u16 family = 0;
+    bpf_probe_read_kernel(&family, sizeof(family), sk + SKC_FAMILY_OFF);
+    if (family != AF_INET)
+        return 0;
+
+    struct connect_event event = {};
+    event.pid = (__u32)pid_tgid;
+    bpf_probe_read_kernel(&event.daddr, sizeof(event.daddr), sk + SKC_DADDR_OFF);
+    bpf_probe_read_kernel(&event.dport, sizeof(event.dport), sk + SKC_DPORT_OFF);
+    bpf_map_update_elem(&PENDING, &pid_tgid, &event, BPF_ANY);
+    return 0;
+}
+
+SEC("kretprobe/tcp_connect")
+int tcp_connect_exit(struct pt_regs *ctx)
+{
+    __u64 pid_tgid = bpf_get_current_pid_tgid();
+    struct connect_event *pending = bpf_map_lookup_elem(&PENDING, &pid_tgid);
+    if (!pending)
+        return 0;
+
+    struct connect_event *event = bpf_ringbuf_reserve(&TCP_CONNECTS, sizeof(*event), 0);
+    if (event) {
+        *event = *pending;
+        event->success = (int)PT_REGS_RC(ctx) == 0;
+        bpf_ringbuf_submit(event, 0);
+    }
+    bpf_map_delete_elem(&PENDING, &pid_tgid);
+    return 0;
+}
+
+char LICENSE[] SEC("license") = "GPL";
@@ -0,0 +1,56 @@
+// SPDX-License-Identifier: GPL-2.0
+//
+// Counts syscalls issued by a single process (TARGET_TGID) keyed by the raw
+// syscall number. Userspace reads SYSCALL_COUNTS and diffs it per sample.
+//
+// Built by build.rs with `clang -target bpf -O2 -g` when the
+// `telemetry-ebpf` feature is enabled.
+
+#include <linux/bpf.h>
+#include <linux/types.h>
+
+#define SEC(name) __attribute__((section(name), used))
+#define __uint(name, val) int (*name)[val]
+#define __type(name, val) typeof(val) *name
+
+static void *(*bpf_map_lookup_elem)(void *map, const void *key) = (void *)BPF_FUNC_map_lookup_elem;
+static long (*bpf_map_update_elem)(void *map, const void *key, const void *value,
+                                   __u64 flags) = (void *)BPF_FUNC_map_update_elem;
+static __u64 (*bpf_get_current_pid_tgid)(void) = (void *)BPF_FUNC_get_current_pid_tgid;
+
+struct {
+    __uint(type, BPF_MAP_TYPE_HASH);
+    __uint(max_entries, 512);
+    __type(key, __u32);
+    __type(value, __u64);
+} SYSCALL_COUNTS SEC(".maps");
+
+// Patched by the loader (EbpfLoader::set_global) before the program is loaded.
+volatile const __u32 TARGET_TGID = 0;
+
+// Layout of tracepoint/raw_syscalls/sys_enter after the common header.
+struct sys_enter_ctx {
+    __u64 common;
+    long id;
+    unsigned long args[6];
+};
+
+SEC("tracepoint/raw_syscalls/sys_enter")
+int syscall_freq(struct sys_enter_ctx *ctx)
+{
+    __u32 tgid = bpf_get_current_pid_tgid() >> 32;
+    if (TARGET_TGID != 0 && tgid != TARGET_TGID)
+        return 0;
+
+    __u32 nr = (__u32)ctx->id;
+    __u64 *count = bpf_map_lookup_elem(&SYSCALL_COUNTS, &nr);
+    if (count) {
+        __sync_fetch_and_add(count, 1);
+    } else {
+        __u64 one = 1;
+        bpf_map_update_elem(&SYSCALL_COUNTS, &nr, &one, BPF_NOEXIST);
+    }
+    return 0;
+}
+
+char LICENSE[] SEC("license") = "GPL";
@@ -0,0 +1,61 @@
+//! Compiles the eBPF programs under `bpf/` when the `telemetry-ebpf` feature
+//! is enabled on a Linux target. Every other build is a no-op.
+
+use std::path::PathBuf;
+use std::process::Command;
+
+fn main() {
+    println!("cargo:rerun-if-changed=build.rs");
+    println!("cargo:rerun-if-changed=bpf");
+
+    let ebpf_enabled = std::env::var_os("CARGO_FEATURE_TELEMETRY_EBPF").is_some();
+    let target_os = std::env::var("CARGO_CFG_TARGET_OS").unwrap_or_default();
+    if !ebpf_enabled || target_os != "linux" {
+        return;
+    }
+
+    let out_dir = PathBuf::from(std::env::var_os("OUT_DIR").expect("OUT_DIR is set by cargo"));
+    let clang = std::env::var("CLANG").unwrap_or_else(|_| "clang".to_string());
+    // Selects the pt_regs layout used by kprobe programs.
+    let target_arch = match std::env::var("CARGO_CFG_TARGET_ARCH").as_deref() {
+        Ok("aarch64") => "arm64",
+        _ => "x86",
+    };
+
+    let mut sources: Vec<PathBuf> = std::fs::read_dir("bpf")
+        .expect("bpf/ directory is missing")
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| path.to_string_lossy().ends_with(".bpf.c"))
+        .collect();
+    sources.sort();
+
+    for src in sources {
+        let stem = src
+            .file_name()
+            .and_then(|name| name.to_str())
+            .and_then(|name| name.strip_suffix(".c"))
+            .expect("eBPF source names are UTF-8");
+        let obj = out_dir.join(format!("{stem}.o"));
+        println!("cargo:rerun-if-changed={}", src.display());
+
+        let status = Command::new(&clang)
+            .args(["-target", "bpf", "-O2", "-g", "-c"])
+            .arg(format!("-D__TARGET_ARCH_{target_arch}"))
+            .arg(&src)
+            .arg("-o")
+            .arg(&obj)
+            .status()
+            .unwrap_or_else(|e| {
+                panic!(
+                    "telemetry-ebpf needs clang with the BPF target to build {} \
+                     (set CLANG to override): {e}",
+                    src.display()
+                )
+            });
+        assert!(
+            status.success(),
+            "clang failed to compile {}",
+            src.display()
+        );
+    }
+}
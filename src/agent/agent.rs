@@ -35,6 +35,7 @@ pub struct Agent {
     history: Vec<ConversationMessage>,
     classification_config: crate::config::QueryClassificationConfig,
     available_hints: Vec<String>,
+    session_id: String,
 }
 
 pub struct AgentBuilder {
@@ -54,6 +55,7 @@ pub struct AgentBuilder {
     auto_save: Option<bool>,
     classification_config: Option<crate::config::QueryClassificationConfig>,
     available_hints: Option<Vec<String>>,
+    session_id: Option<String>,
 }
 
 impl AgentBuilder {
@@ -75,6 +77,7 @@ impl AgentBuilder {
             auto_save: None,
             classification_config: None,
             available_hints: None,
+            session_id: None,
         }
     }
 
@@ -161,6 +164,11 @@ impl AgentBuilder {
         self
     }
 
+    pub fn session_id(mut self, session_id: String) -> Self {
+        self.session_id = Some(session_id);
+        self
+    }
+
     pub fn build(self) -> Result<Agent> {
         let tools = self
             .tools
@@ -202,6 +210,9 @@ impl AgentBuilder {
             history: Vec::new(),
             classification_config: self.classification_config.unwrap_or_default(),
             available_hints: self.available_hints.unwrap_or_default(),
+            session_id: self
+                .session_id
+                .unwrap_or_else(|| uuid::Uuid::new_v4().to_string()),
         })
     }
 }
@@ -211,6 +222,10 @@ impl Agent {
         AgentBuilder::new()
     }
 
+    pub fn session_id(&self) -> &str {
+        &self.session_id
+    }
+
     pub fn history(&self) -> &[ConversationMessage] {
         &self.history
     }
@@ -221,16 +236,10 @@ impl Agent {
 
     pub fn from_config(config: &Config) -> Result<Self> {
         let base_observer = observability::create_observer(&config.observability);
+        let session_id = uuid::Uuid::new_v4().to_string();
         let observer: Arc<dyn Observer> = if config.telemetry.enabled {
-            let telem_dir = config.workspace_dir.join("telemetry");
-            let store = Arc::new(
-                crate::telemetry::TelemetrySqliteStore::open(
-                    &telem_dir,
-                    config.telemetry.buffer_capacity,
-                )?,
-            );
-            let session_id = uuid::Uuid::new_v4().to_string();
-            let telem_obs = crate::telemetry::TelemetryObserver::new(store, session_id);
+            let telem_obs =
+                crate::telemetry::TelemetryObserver::from_config(config, session_id.clone())?;
             Arc::new(observability::MultiObserver::new(vec![
                 base_observer,
                 Box::new(telem_obs),
@@ -323,6 +332,7 @@ impl Agent {
             .workspace_dir(config.workspace_dir.clone())
             .classification_config(config.query_classification.clone())
             .available_hints(available_hints)
+            .session_id(session_id)
             .identity_config(config.identity.clone())
             .skills(crate::skills::load_skills(&config.workspace_dir))
             .auto_save(config.memory.auto_save)
@@ -369,19 +379,15 @@ impl Agent {
         self.prompt_builder.build(&ctx)
     }
 
-    fn compute_arguments_hash(arguments: &serde_json::Value) -> Option<String> {
-        use sha2::Digest;
-        let bytes = serde_json::to_vec(arguments).ok()?;
-        Some(hex::encode(sha2::Sha256::digest(&bytes)))
-    }
-
     async fn execute_tool_call(
         &self,
         call: &ParsedToolCall,
         iteration: Option<u32>,
     ) -> ToolExecutionResult {
         let start = Instant::now();
-        let arguments_hash = Self::compute_arguments_hash(&call.arguments);
+        let arguments_hash = Some(crate::telemetry::embeddings::hash_arguments(
+            &call.arguments,
+        ));
 
         let result = if let Some(tool) = self.tools.iter().find(|t| t.name() == call.name) {
             match tool.execute(call.arguments.clone()).await {
@@ -390,6 +396,7 @@ impl Agent {
                         tool: call.name.clone(),
                         duration: start.elapsed(),
                         success: r.success,
+                        arguments: Some(call.arguments.clone()),
                         arguments_hash: arguments_hash.clone(),
                         iteration,
                     });
@@ -404,6 +411,7 @@ impl Agent {
                         tool: call.name.clone(),
                         duration: start.elapsed(),
                         success: false,
+                        arguments: Some(call.arguments.clone()),
                         arguments_hash: arguments_hash.clone(),
                         iteration,
                     });
@@ -454,6 +462,13 @@ impl Agent {
     }
 
     pub async fn turn(&mut self, user_message: &str) -> Result<String> {
+        self.observer.record_event(&ObserverEvent::TurnStart);
+        let result = self.run_turn(user_message).await;
+        self.observer.record_event(&ObserverEvent::TurnComplete);
+        result
+    }
+
+    async fn run_turn(&mut self, user_message: &str) -> Result<String> {
         if self.history.is_empty() {
             let system_prompt = self.build_system_prompt()?;
             self.history
@@ -650,6 +665,9 @@ pub async fn run(
         provider: provider_name.clone(),
         model: model_name.clone(),
     });
+    agent.observer.record_event(&ObserverEvent::SessionStart {
+        session_id: agent.session_id().to_string(),
+    });
 
     if let Some(msg) = message {
         let response = agent.run_single(&msg).await?;
@@ -658,6 +676,9 @@ pub async fn run(
         agent.run_interactive().await?;
     }
 
+    agent.observer.record_event(&ObserverEvent::SessionEnd {
+        session_id: agent.session_id().to_string(),
+    });
     agent.observer.record_event(&ObserverEvent::AgentEnd {
         provider: provider_name,
         model: model_name,
@@ -814,4 +835,63 @@ mod tests {
             .iter()
             .any(|msg| matches!(msg, ConversationMessage::ToolResults(_))));
     }
+
+    #[tokio::test]
+    async fn turn_records_action_sequence_through_telemetry_observer() {
+        let provider = Box::new(MockProvider {
+            responses: Mutex::new(vec![crate::providers::ChatResponse {
+                text: Some(String::new()),
+                tool_calls: vec![crate::providers::ToolCall {
+                    id: "tc1".into(),
+                    name: "echo".into(),
+                    arguments: "{}".into(),
+                }],
+                usage: None,
+            }]),
+        });
+
+        let memory_cfg = crate::config::MemoryConfig {
+            backend: "none".into(),
+            ..crate::config::MemoryConfig::default()
+        };
+        let mem: Arc<dyn Memory> = Arc::from(
+            crate::memory::create_memory(&memory_cfg, std::path::Path::new("/tmp"), None).unwrap(),
+        );
+
+        let tmp = tempfile::TempDir::new().unwrap();
+        let store = Arc::new(
+            crate::telemetry::TelemetrySqliteStore::open(
+                tmp.path(),
+                crate::config::TelemetryConfig::default(),
+            )
+            .unwrap(),
+        );
+        let observer: Arc<dyn Observer> = Arc::new(crate::telemetry::TelemetryObserver::new(
+            store.clone(),
+            "agent-sess".into(),
+        ));
+        let mut agent = Agent::builder()
+            .provider(provider)
+            .tools(vec![Box::new(MockTool)])
+            .memory(mem)
+            .observer(observer)
+            .tool_dispatcher(Box::new(NativeToolDispatcher))
+            .workspace_dir(std::path::PathBuf::from("/tmp"))
+            .build()
+            .unwrap();
+
+        agent.turn("hi").await.unwrap();
+        drop(agent);
+        drop(store);
+
+        let conn = rusqlite::Connection::open(tmp.path().join("research.db")).unwrap();
+        let sequence: String = conn
+            .query_row(
+                "SELECT turn_action_sequence FROM action_events WHERE event_type = 'turn_complete'",
+                [],
+                |r| r.get(0),
+            )
+            .unwrap();
+        assert_eq!(sequence, r#"["llm_response","tool_call","llm_response"]"#);
+    }
 }
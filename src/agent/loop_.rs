@@ -1038,6 +1038,7 @@ pub(crate) async fn run_tool_call_loop(
                             tool: call.name.clone(),
                             duration: start.elapsed(),
                             success: r.success,
+                            arguments: Some(call.arguments.clone()),
                             arguments_hash: None,
                             iteration: None,
                         });
@@ -1052,6 +1053,7 @@ pub(crate) async fn run_tool_call_loop(
                             tool: call.name.clone(),
                             duration: start.elapsed(),
                             success: false,
+                            arguments: Some(call.arguments.clone()),
                             arguments_hash: None,
                             iteration: None,
                         });
@@ -1132,7 +1134,17 @@ pub async fn run(
 ) -> Result<String> {
     // ── Wire up agnostic subsystems ──────────────────────────────
     let base_observer = observability::create_observer(&config.observability);
-    let observer: Arc<dyn Observer> = Arc::from(base_observer);
+    let session_id = Uuid::new_v4().to_string();
+    let observer: Arc<dyn Observer> = if config.telemetry.enabled {
+        let telem_obs =
+            crate::telemetry::TelemetryObserver::from_config(&config, session_id.clone())?;
+        Arc::new(observability::MultiObserver::new(vec![
+            base_observer,
+            Box::new(telem_obs),
+        ]))
+    } else {
+        Arc::from(base_observer)
+    };
     let runtime: Arc<dyn runtime::RuntimeAdapter> =
         Arc::from(runtime::create_runtime(&config.runtime)?);
     let security = Arc::new(SecurityPolicy::from_config(
@@ -1212,6 +1224,9 @@ pub async fn run(
         provider: provider_name.to_string(),
         model: model_name.to_string(),
     });
+    observer.record_event(&ObserverEvent::SessionStart {
+        session_id: session_id.clone(),
+    });
 
     // ── Hardware RAG (datasheet retrieval when peripherals + datasheet_dir) ──
     let hardware_rag: Option<crate::rag::HardwareRag> = config
@@ -1393,7 +1408,8 @@ pub async fn run(
             ChatMessage::user(&enriched),
         ];
 
-        let response = run_tool_call_loop(
+        observer.record_event(&ObserverEvent::TurnStart);
+        let response = match run_tool_call_loop(
             provider.as_ref(),
             &mut history,
             &tools_registry,
@@ -1407,7 +1423,16 @@ pub async fn run(
             config.agent.max_tool_iterations,
             None,
         )
-        .await?;
+        .await
+        {
+            Ok(response) => response,
+            Err(e) => {
+                // Close the turn and session so the failed run still has a duration.
+                observer.record_event(&ObserverEvent::TurnComplete);
+                observer.record_event(&ObserverEvent::SessionEnd { session_id });
+                return Err(e);
+            }
+        };
         final_output = response.clone();
         println!("{response}");
         observer.record_event(&ObserverEvent::TurnComplete);
@@ -1519,6 +1544,7 @@ pub async fn run(
 
             history.push(ChatMessage::user(&enriched));
 
+            observer.record_event(&ObserverEvent::TurnStart);
             let response = match run_tool_call_loop(
                 provider.as_ref(),
                 &mut history,
@@ -1538,6 +1564,7 @@ pub async fn run(
                 Ok(resp) => resp,
                 Err(e) => {
                     eprintln!("\nError: {e}\n");
+                    observer.record_event(&ObserverEvent::TurnComplete);
                     continue;
                 }
             };
@@ -1579,6 +1606,7 @@ pub async fn run(
         }
     }
 
+    observer.record_event(&ObserverEvent::SessionEnd { session_id });
     let duration = start.elapsed();
     observer.record_event(&ObserverEvent::AgentEnd {
         provider: provider_name.to_string(),
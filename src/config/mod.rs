@@ -4,16 +4,17 @@ pub mod schema;
 pub use schema::{
     apply_runtime_proxy_to_builder, build_runtime_proxy_client,
     build_runtime_proxy_client_with_timeouts, runtime_proxy_config, set_runtime_proxy_config,
-    AgentConfig, AuditConfig, AutonomyConfig, BrowserComputerUseConfig, BrowserConfig,
-    ChannelsConfig, ClassificationRule, ComposioConfig, Config, CostConfig, CronConfig,
-    DelegateAgentConfig, DiscordConfig, DockerRuntimeConfig, GatewayConfig, HardwareConfig,
-    HardwareTransport, HeartbeatConfig, HttpRequestConfig, IMessageConfig, IdentityConfig,
-    LarkConfig, MatrixConfig, MemoryConfig, ModelRouteConfig, ObservabilityConfig,
-    PeripheralBoardConfig, PeripheralsConfig, ProxyConfig, ProxyScope, QueryClassificationConfig,
-    ReliabilityConfig, ResourceLimitsConfig, RuntimeConfig, SandboxBackend, SandboxConfig,
-    SchedulerConfig, SecretsConfig, SecurityConfig, SlackConfig, StorageConfig,
-    StorageProviderConfig, StorageProviderSection, StreamMode, TelegramConfig, TelemetryConfig,
-    TunnelConfig, WebSearchConfig, WebhookConfig,
+    AgentConfig, AuditConfig, AutoVacuumMode, AutonomyConfig, BrowserComputerUseConfig,
+    BrowserConfig, ChannelKind, ChannelsConfig, ClassificationRule, ComposioConfig, Config,
+    ConfigError, CostConfig, CronConfig, DelegateAgentConfig, DiscordConfig, DockerRuntimeConfig,
+    EmbeddingDim, GatewayConfig, HardwareConfig, HardwareTransport, HeartbeatConfig,
+    HttpRequestConfig, IMessageConfig, IdentityConfig, IndexStrategy, KafkaCompression, KafkaConfig,
+    LarkConfig, MatrixConfig, MemoryConfig, ModelRouteConfig, MqttConfig, MqttQos,
+    ObservabilityConfig, OverflowStrategy, PeripheralBoardConfig, PeripheralsConfig, ProxyConfig,
+    ProxyScope, QueryClassificationConfig, ReliabilityConfig, ResourceLimitsConfig, RuntimeConfig,
+    SandboxBackend, SandboxConfig, SchedulerConfig, SecretsConfig, SecurityConfig, SlackConfig,
+    SqliteSynchronous, StorageConfig, StorageProviderConfig, StorageProviderSection, StreamMode,
+    TelegramConfig, TelemetryConfig, TunnelConfig, TurnIdFormat, WebSearchConfig, WebhookConfig,
 };
 
 #[cfg(test)]
@@ -1513,6 +1513,203 @@ impl Default for ObservabilityConfig {
 
 // ── Research Telemetry ────────────────────────────────────────────
 
+/// What the telemetry store does with a submission when its writer channel
+/// is full.
+///
+/// - `drop` — discard the record and log a warning. Never blocks and keeps
+///   memory bounded, but loses data under sustained load.
+/// - `block` — queue the record for the store's overflow thread, which waits
+///   for space and delivers overflowing records in order. Callers (including
+///   async tasks) are never blocked and nothing is lost, but the overflow
+///   queue is unbounded: under sustained load it holds every waiting record
+///   in memory. The store keeps one extra thread for it.
+/// - `sample_random` — admit overflowing records with the given probability
+///   (delivered like `block`) and drop the rest. Sheds load proportionally
+///   while keeping a representative sample.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, Default, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum OverflowStrategy {
+    #[default]
+    Drop,
+    Block,
+    SampleRandom(f64),
+}
+
+/// Kind of channel carrying writes to the telemetry writer thread.
+///
+/// - `bounded` — holds up to `buffer_capacity` writes; when full,
+///   `overflow_strategy` applies. Memory stays bounded, and a stalled or
+///   crashed writer thread costs dropped records rather than a hang.
+/// - `unbounded` — never full, so submits never drop or wait, but the queue
+///   grows without limit if the writer falls behind or dies. Use only in
+///   tests or single-session environments where the writer thread is known
+///   to keep up.
+/// - `auto_grow` — starts at `initial` writes and doubles whenever a submit
+///   finds it full, up to `max`; only then does `overflow_strategy` apply.
+///   Absorbs bursts without reserving the peak size up front.
+///   `buffer_capacity` is ignored.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ChannelKind {
+    #[default]
+    Bounded,
+    Unbounded,
+    AutoGrow {
+        initial: usize,
+        max: usize,
+    },
+}
+
+/// SQLite `synchronous` level for the telemetry database.
+///
+/// - `off` — never fsync. Fastest; a power loss or OS crash can lose recent
+///   commits or corrupt the database. Suited to ephemeral environments
+///   such as CI.
+/// - `normal` — fsync at WAL checkpoints only. A power loss may roll back
+///   the most recent commits but the database stays consistent.
+/// - `full` — fsync on every commit. No committed record is lost, at the
+///   cost of one disk flush per writer batch.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum SqliteSynchronous {
+    Off,
+    #[default]
+    Normal,
+    Full,
+}
+
+/// SQLite `auto_vacuum` mode for the telemetry database.
+///
+/// - `none` — freed pages stay in the file for reuse; it never shrinks.
+/// - `full` — the file is truncated on every commit that frees pages.
+/// - `incremental` — freed pages are tracked and returned to the OS when
+///   `PRAGMA incremental_vacuum` runs.
+///
+/// Only takes effect when the database is created; changing it for an
+/// existing database requires a `VACUUM`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum AutoVacuumMode {
+    #[default]
+    None,
+    Full,
+    Incremental,
+}
+
+/// Size in bytes of tool embeddings written to `tool_embeddings_cache`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum EmbeddingDim {
+    D8,
+    D16,
+    #[default]
+    D32,
+    D64,
+}
+
+impl EmbeddingDim {
+    /// Embedding length in bytes.
+    pub fn bytes(self) -> usize {
+        match self {
+            Self::D8 => 8,
+            Self::D16 => 16,
+            Self::D32 => 32,
+            Self::D64 => 64,
+        }
+    }
+}
+
+/// Format of telemetry `turn_id` values.
+///
+/// - `compact` — an 8-character base36 hash of the session ID, then the
+///   turn counter in base36, zero-padded to 4 characters (`k2p0x9am-002s`).
+/// - `full` — `{session_id}-t{turn_counter}`.
+/// - `ulid` — a fresh ULID per turn, unique across sessions and sortable by
+///   start time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum TurnIdFormat {
+    Compact,
+    #[default]
+    Full,
+    Ulid,
+}
+
+/// Compression codec for Kafka-forwarded action events.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum KafkaCompression {
+    #[default]
+    None,
+    Gzip,
+    Snappy,
+    Lz4,
+    Zstd,
+}
+
+/// When the telemetry database's indexes are built.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct IndexStrategy {
+    /// Build indexes when the store opens. Set to false before a large
+    /// initial import, which then inserts without index maintenance, and
+    /// call `TelemetrySqliteStore::create_indexes` once it has finished.
+    /// Indexes that already exist are kept. Default: true.
+    #[serde(default = "default_true")]
+    pub create_before_insert: bool,
+}
+
+impl Default for IndexStrategy {
+    fn default() -> Self {
+        Self {
+            create_before_insert: true,
+        }
+    }
+}
+
+/// Kafka topic that action events are forwarded to, in addition to SQLite.
+/// Only used when built with the `kafka` feature.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct KafkaConfig {
+    /// Bootstrap brokers, as `host:port`.
+    pub brokers: Vec<String>,
+    /// Topic each action event is produced to, keyed by session ID.
+    pub topic: String,
+    /// Default: none.
+    #[serde(default)]
+    pub compression: KafkaCompression,
+}
+
+/// MQTT delivery guarantee for published system samples.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+#[allow(clippy::enum_variant_names)]
+pub enum MqttQos {
+    AtMostOnce,
+    #[default]
+    AtLeastOnce,
+    ExactlyOnce,
+}
+
+/// MQTT broker that system samples are published to, in addition to
+/// SQLite. Only used when built with the `mqtt` feature.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct MqttConfig {
+    /// Broker address: `mqtt://host:port`, `tcp://host:port` or `host[:port]`.
+    /// The port defaults to 1883.
+    pub broker_url: String,
+    pub client_id: String,
+    /// Default: at_least_once.
+    #[serde(default)]
+    pub qos: MqttQos,
+}
+
+/// Research telemetry, written to a local SQLite database by a background
+/// writer thread.
+///
+/// A writer thread that panics is restarted on a fresh connection (see
+/// `TelemetrySqliteStore::writer_restart_count`) only in builds that unwind.
+/// The `release` and `dist` profiles set `panic = "abort"`, so there a
+/// writer panic ends the process and the restart never runs.
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[allow(clippy::struct_excessive_bools)]
 pub struct TelemetryConfig {
@@ -1528,10 +1725,30 @@ pub struct TelemetryConfig {
     #[serde(default = "default_true")]
     pub system_enabled: bool,
 
-    /// System metrics sampling interval in seconds. Default: 1.
+    /// System metrics sampling interval in seconds. Default: 10.
     #[serde(default = "default_system_interval_secs")]
     pub system_interval_secs: u64,
 
+    /// Warn when a system sample counts more open network connections than
+    /// this, and count the surge (see
+    /// `TelemetrySqliteStore::net_surges_detected`). Default: unset (off).
+    #[serde(default)]
+    pub net_connection_alert_threshold: Option<u64>,
+
+    /// Warn, listing the most frequent destinations, when a system sample's
+    /// destination IP entropy (bits) exceeds this; a spread of connections
+    /// over many hosts can indicate data exfiltration. Counted by
+    /// `TelemetrySqliteStore::entropy_alerts`. Default: unset (off).
+    #[serde(default)]
+    pub max_entropy_threshold: Option<f64>,
+
+    /// Warn, listing the new process names, when more than this many
+    /// processes appear between two system samples; a burst of unexpected
+    /// child processes can follow a prompt-injection attack. Counted by
+    /// `TelemetrySqliteStore::spawn_burst_alerts`. Default: unset (off).
+    #[serde(default)]
+    pub max_spawn_rate: Option<u32>,
+
     /// Enable eBPF syscall tracing (Linux only, requires CAP_BPF). Default: false.
     #[serde(default)]
     pub ebpf_enabled: bool,
@@ -1540,23 +1757,222 @@ pub struct TelemetryConfig {
     #[serde(default)]
     pub tool_embeddings_enabled: bool,
 
+    /// Size of cached tool embeddings. Default: `d32`.
+    #[serde(default)]
+    pub tool_embedding_dim: EmbeddingDim,
+
+    /// Delete `tool_embeddings_cache` rows older than this when the store
+    /// opens, so they are recomputed. Default: unset (keep forever).
+    #[serde(default)]
+    pub embedding_cache_ttl_secs: Option<u64>,
+
+    /// How many days of telemetry to keep. Not enforced yet; rows are kept
+    /// until deleted. Default: unset (keep forever).
+    #[serde(default)]
+    pub retention_days: Option<u32>,
+
+    /// Smoothing factor for an exponential moving average of sampled CPU
+    /// usage. Stored samples keep the raw reading; nothing applies the
+    /// average yet. Default: 0.3.
+    #[serde(default = "default_ema_alpha")]
+    pub ema_alpha: f64,
+
     /// Maximum telemetry database size in MB. Default: 1024.
     #[serde(default = "default_max_db_size_mb")]
     pub max_db_size_mb: u64,
 
-    /// Bounded channel capacity for the writer thread. Default: 100.
+    /// Bounded channel capacity for the writer thread. Default: 256.
     #[serde(default = "default_buffer_capacity")]
     pub buffer_capacity: usize,
+
+    /// Maximum number of writes committed in one transaction. Default: 20.
+    #[serde(default = "default_max_batch_size")]
+    pub max_batch_size: usize,
+
+    /// How long the writer waits for more writes before committing a
+    /// single-write batch, in milliseconds. Default: 2000.
+    #[serde(default = "default_flush_timeout_ms")]
+    pub flush_timeout_ms: u64,
+
+    /// Behaviour when the writer channel is full. Default: drop.
+    #[serde(default)]
+    pub overflow_strategy: OverflowStrategy,
+
+    /// Bounded or unbounded writer channel. Default: bounded.
+    #[serde(default)]
+    pub channel_kind: ChannelKind,
+
+    /// SQLite writer threads, each with its own connection. Above 1, batches
+    /// are spread across the threads and may commit out of submission order.
+    /// Ignored when `write_ahead_dir` is set. Default: 1.
+    #[serde(default = "default_num_writer_threads")]
+    pub num_writer_threads: usize,
+
+    /// Wrap each write in its own SAVEPOINT so a failing record is rolled
+    /// back on its own, including any rows it had already written, without
+    /// touching the rest of the batch. Default: false.
+    #[serde(default)]
+    pub use_savepoints: bool,
+
+    /// Whether indexes are built on open or deferred until
+    /// `TelemetrySqliteStore::create_indexes`. Default: on open.
+    #[serde(default)]
+    pub index_strategy: IndexStrategy,
+
+    /// Local directory for a write-ahead log. When set, the writer appends
+    /// records here and a background compactor imports them into SQLite —
+    /// useful when the database lives on slow storage (e.g. NFS).
+    /// Default: unset (write directly to SQLite).
+    #[serde(default)]
+    pub write_ahead_dir: Option<String>,
+
+    /// Encrypt `error_message` with AES-256-GCM before storage. The key is
+    /// kept in `telemetry/.field_key`. Default: false.
+    #[serde(default)]
+    pub encrypt_error_messages: bool,
+
+    /// Redact emails, IPv4 addresses, UUIDs, and absolute paths from
+    /// `error_message` before storage. Default: false.
+    #[serde(default)]
+    pub anonymize_pii: bool,
+
+    /// Correlation ID stamped on every action event so that events from
+    /// several processes serving one request can be joined. A per-thread ID
+    /// set with `telemetry::observer::set_correlation_id` takes precedence.
+    /// Default: unset.
+    #[serde(default)]
+    pub correlation_id: Option<String>,
+
+    /// Per-tool latency thresholds in milliseconds. Tool calls slower than
+    /// their threshold log an SLA-breach warning. Default: empty.
+    #[serde(default)]
+    pub tool_sla_ms: std::collections::HashMap<String, u64>,
+
+    /// Warn when the P99 of recent LLM response times for a provider/model
+    /// pair exceeds this many milliseconds. Default: unset (no alert).
+    #[serde(default)]
+    pub alert_p99_ms: Option<u64>,
+
+    /// SQLite page cache size for the telemetry database, in KiB; must be
+    /// positive. Default: 1000 (≈1 MB).
+    #[serde(default)]
+    pub cache_size_kb: Option<i64>,
+
+    /// SQLite memory-mapped I/O size for the telemetry database, in bytes.
+    /// Default: 4194304 (4 MB).
+    #[serde(default)]
+    pub mmap_size_bytes: Option<i64>,
+
+    /// SQLite `synchronous` level (durability vs write speed).
+    /// Default: normal.
+    #[serde(default)]
+    pub synchronous: SqliteSynchronous,
+
+    /// SQLite `auto_vacuum` mode, applied when the database is created.
+    /// Default: none.
+    #[serde(default)]
+    pub auto_vacuum: AutoVacuumMode,
+
+    /// Format of the `turn_id` recorded on action events.
+    /// Default: full.
+    #[serde(default)]
+    pub turn_id_format: TurnIdFormat,
+
+    /// Also forward every action event to Kafka. Requires the `kafka`
+    /// feature. Default: none.
+    #[serde(default)]
+    pub kafka: Option<KafkaConfig>,
+
+    /// Also publish every system sample as JSON to
+    /// `telemetry/system/{hostname}/metrics`. Requires the `mqtt` feature.
+    /// Default: none.
+    #[serde(default)]
+    pub mqtt: Option<MqttConfig>,
+}
+
+/// Why a configuration value was rejected.
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+pub enum ConfigError {
+    #[error("telemetry.{field} is invalid: {reason}")]
+    InvalidTelemetryField { field: &'static str, reason: String },
+}
+
+impl TelemetryConfig {
+    /// Reject values the store cannot run with. The error is a
+    /// [`ConfigError`] naming the offending field.
+    pub fn validate(&self) -> Result<()> {
+        let invalid = |field, reason: String| ConfigError::InvalidTelemetryField { field, reason };
+        if self.system_interval_secs < 1 {
+            return Err(invalid("system_interval_secs", "must be at least 1 second".into()).into());
+        }
+        if self.buffer_capacity < 10 {
+            return Err(invalid(
+                "buffer_capacity",
+                format!("must be at least 10, got {}", self.buffer_capacity),
+            )
+            .into());
+        }
+        if self.max_batch_size < 1 {
+            return Err(invalid("max_batch_size", "must be at least 1".into()).into());
+        }
+        if self.num_writer_threads < 1 {
+            return Err(invalid("num_writer_threads", "must be at least 1".into()).into());
+        }
+        if self.retention_days == Some(0) {
+            return Err(invalid("retention_days", "must be at least 1 when set".into()).into());
+        }
+        if let Some(kb) = self.cache_size_kb.filter(|kb| *kb < 1) {
+            return Err(invalid("cache_size_kb", format!("must be positive, got {kb}")).into());
+        }
+        if !(self.ema_alpha > 0.0 && self.ema_alpha <= 1.0) {
+            return Err(invalid(
+                "ema_alpha",
+                format!("must be within (0, 1], got {}", self.ema_alpha),
+            )
+            .into());
+        }
+        if let ChannelKind::AutoGrow { initial, max } = self.channel_kind {
+            if initial < 1 || max < initial {
+                return Err(invalid(
+                    "channel_kind",
+                    format!("auto_grow needs 1 <= initial <= max, got {initial} and {max}"),
+                )
+                .into());
+            }
+        }
+        if let OverflowStrategy::SampleRandom(rate) = self.overflow_strategy {
+            if !(0.0..=1.0).contains(&rate) {
+                return Err(invalid(
+                    "overflow_strategy",
+                    format!("sample_random rate must be within [0, 1], got {rate}"),
+                )
+                .into());
+            }
+        }
+        Ok(())
+    }
 }
 
 fn default_system_interval_secs() -> u64 {
-    1
+    10
+}
+fn default_ema_alpha() -> f64 {
+    0.3
 }
 fn default_max_db_size_mb() -> u64 {
     1024
 }
 fn default_buffer_capacity() -> usize {
-    100
+    256
+}
+fn default_num_writer_threads() -> usize {
+    1
+}
+fn default_max_batch_size() -> usize {
+    20
+}
+fn default_flush_timeout_ms() -> u64 {
+    2000
 }
 
 impl Default for TelemetryConfig {
@@ -1565,11 +1981,38 @@ impl Default for TelemetryConfig {
             enabled: true,
             actions_enabled: true,
             system_enabled: true,
-            system_interval_secs: 1,
+            system_interval_secs: 10,
+            net_connection_alert_threshold: None,
+            max_entropy_threshold: None,
+            max_spawn_rate: None,
             ebpf_enabled: false,
             tool_embeddings_enabled: false,
+            tool_embedding_dim: EmbeddingDim::default(),
+            embedding_cache_ttl_secs: None,
+            retention_days: None,
+            ema_alpha: 0.3,
             max_db_size_mb: 1024,
-            buffer_capacity: 100,
+            buffer_capacity: 256,
+            max_batch_size: 20,
+            flush_timeout_ms: 2000,
+            overflow_strategy: OverflowStrategy::Drop,
+            channel_kind: ChannelKind::Bounded,
+            num_writer_threads: 1,
+            use_savepoints: false,
+            index_strategy: IndexStrategy::default(),
+            write_ahead_dir: None,
+            encrypt_error_messages: false,
+            anonymize_pii: false,
+            correlation_id: None,
+            tool_sla_ms: std::collections::HashMap::new(),
+            alert_p99_ms: None,
+            cache_size_kb: None,
+            mmap_size_bytes: None,
+            synchronous: SqliteSynchronous::Normal,
+            auto_vacuum: AutoVacuumMode::None,
+            turn_id_format: TurnIdFormat::Full,
+            kafka: None,
+            mqtt: None,
         }
     }
 }
@@ -3287,6 +3730,91 @@ mod tests {
         assert_eq!(o.backend, "none");
     }
 
+    #[test]
+    async fn telemetry_config_overflow_strategy_parses() {
+        let parsed: TelemetryConfig = toml::from_str("").unwrap();
+        assert_eq!(parsed.overflow_strategy, OverflowStrategy::Drop);
+
+        let parsed: TelemetryConfig = toml::from_str(r#"overflow_strategy = "block""#).unwrap();
+        assert_eq!(parsed.overflow_strategy, OverflowStrategy::Block);
+
+        let parsed: TelemetryConfig =
+            toml::from_str("overflow_strategy = { sample_random = 0.25 }").unwrap();
+        assert_eq!(
+            parsed.overflow_strategy,
+            OverflowStrategy::SampleRandom(0.25)
+        );
+    }
+
+    #[test]
+    async fn telemetry_config_validate_names_invalid_field() {
+        assert!(TelemetryConfig::default().validate().is_ok());
+
+        let cases = [
+            (
+                TelemetryConfig {
+                    system_interval_secs: 0,
+                    ..TelemetryConfig::default()
+                },
+                "system_interval_secs",
+            ),
+            (
+                TelemetryConfig {
+                    max_batch_size: 0,
+                    ..TelemetryConfig::default()
+                },
+                "max_batch_size",
+            ),
+            (
+                TelemetryConfig {
+                    overflow_strategy: OverflowStrategy::SampleRandom(1.5),
+                    ..TelemetryConfig::default()
+                },
+                "overflow_strategy",
+            ),
+            (
+                TelemetryConfig {
+                    channel_kind: ChannelKind::AutoGrow {
+                        initial: 64,
+                        max: 16,
+                    },
+                    ..TelemetryConfig::default()
+                },
+                "channel_kind",
+            ),
+            (
+                TelemetryConfig {
+                    retention_days: Some(0),
+                    ..TelemetryConfig::default()
+                },
+                "retention_days",
+            ),
+            (
+                TelemetryConfig {
+                    cache_size_kb: Some(-64),
+                    ..TelemetryConfig::default()
+                },
+                "cache_size_kb",
+            ),
+            (
+                TelemetryConfig {
+                    ema_alpha: 0.0,
+                    ..TelemetryConfig::default()
+                },
+                "ema_alpha",
+            ),
+        ];
+        for (config, expected) in cases {
+            let err = config.validate().unwrap_err();
+            let ConfigError::InvalidTelemetryField { field, .. } =
+                err.downcast_ref::<ConfigError>().unwrap();
+            assert_eq!(*field, expected);
+            assert!(err
+                .to_string()
+                .starts_with(&format!("telemetry.{expected} is invalid")));
+        }
+    }
+
     #[test]
     async fn autonomy_config_default() {
         let a = AutonomyConfig::default();
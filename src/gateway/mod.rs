@@ -514,7 +514,7 @@ pub async fn run_gateway(host: &str, port: u16, config: Config) -> Result<()> {
             let telem_dir = config.workspace_dir.join("telemetry");
             match crate::telemetry::TelemetrySqliteStore::open(
                 &telem_dir,
-                config.telemetry.buffer_capacity,
+                config.telemetry.clone(),
             ) {
                 Ok(store) => Some(Arc::new(store)),
                 Err(e) => {
@@ -625,10 +625,21 @@ async fn handle_telemetry_download(
     let db_path = store.db_path().to_path_buf();
     let limit = params.limit.unwrap_or(10_000).min(100_000);
     let since = params.since_epoch_ms;
+    let decrypt = state.config.lock().telemetry.encrypt_error_messages;
 
     // Perform the read-only query on a blocking thread to avoid blocking tokio.
     let result = tokio::task::spawn_blocking(move || {
-        let reader = crate::telemetry::reader::TelemetryReader::open(&db_path)?;
+        let mut reader = crate::telemetry::reader::TelemetryReader::open(&db_path)?;
+        if decrypt {
+            if let Some(key) = db_path
+                .parent()
+                .map(crate::telemetry::crypto::load_field_key)
+                .transpose()?
+                .flatten()
+            {
+                reader = reader.with_decryption_key(key);
+            }
+        }
         let action_events = reader.export_action_events(since, limit)?;
         let system_samples = reader.export_system_samples(since, limit)?;
         Ok::<_, anyhow::Error>(serde_json::json!({
@@ -1,6 +1,6 @@
 use super::traits::{Observer, ObserverEvent, ObserverMetric};
 use std::any::Any;
-use tracing::info;
+use tracing::{debug, info};
 
 /// Log-based observer — uses tracing, zero external deps
 pub struct LogObserver;
@@ -39,15 +39,48 @@ impl Observer for LogObserver {
                 let ms = u64::try_from(duration.as_millis()).unwrap_or(u64::MAX);
                 info!(tool = %tool, duration_ms = ms, success = success, "tool.call");
             }
+            ObserverEvent::StreamingTokenChunk {
+                provider,
+                model,
+                chunk_tokens,
+            } => {
+                debug!(provider = %provider, model = %model, tokens = chunk_tokens, "llm.stream_chunk");
+            }
+            ObserverEvent::FileOperation {
+                path,
+                operation,
+                bytes,
+                success,
+            } => {
+                info!(path = %path.display(), operation = ?operation, bytes = bytes, success = success, "file.op");
+            }
+            ObserverEvent::AgentThinking { tokens_used, model } => {
+                info!(model = %model, tokens = tokens_used, "agent.thinking");
+            }
+            ObserverEvent::TurnStart => {
+                info!("turn.start");
+            }
             ObserverEvent::TurnComplete => {
                 info!("turn.complete");
             }
+            ObserverEvent::SessionStart { session_id } => {
+                info!(session_id = %session_id, "session.start");
+            }
+            ObserverEvent::SessionEnd { session_id } => {
+                info!(session_id = %session_id, "session.end");
+            }
             ObserverEvent::ChannelMessage { channel, direction } => {
                 info!(channel = %channel, direction = %direction, "channel.message");
             }
             ObserverEvent::HeartbeatTick => {
                 info!("heartbeat.tick");
             }
+            ObserverEvent::BudgetExceeded {
+                session_id,
+                actual_cost,
+            } => {
+                info!(session_id = %session_id, cost_usd = actual_cost, "budget.exceeded");
+            }
             ObserverEvent::Error { component, message } => {
                 info!(component = %component, error = %message, "error");
             }
@@ -149,6 +182,7 @@ mod tests {
             tool: "shell".into(),
             duration: Duration::from_millis(10),
             success: false,
+            arguments: None,
             arguments_hash: None,
             iteration: None,
         });
@@ -56,6 +56,7 @@ mod tests {
             tool: "shell".into(),
             duration: Duration::from_secs(1),
             success: true,
+            arguments: None,
             arguments_hash: None,
             iteration: None,
         });
@@ -186,7 +186,14 @@ impl Observer for OtelObserver {
             }
             ObserverEvent::LlmRequest { .. }
             | ObserverEvent::ToolCallStart { .. }
-            | ObserverEvent::TurnComplete => {}
+            | ObserverEvent::AgentThinking { .. }
+            | ObserverEvent::FileOperation { .. }
+            | ObserverEvent::StreamingTokenChunk { .. }
+            | ObserverEvent::TurnStart
+            | ObserverEvent::TurnComplete
+            | ObserverEvent::SessionStart { .. }
+            | ObserverEvent::SessionEnd { .. }
+            | ObserverEvent::BudgetExceeded { .. } => {}
             ObserverEvent::LlmResponse {
                 provider,
                 model,
@@ -438,6 +445,7 @@ mod tests {
             tool: "shell".into(),
             duration: Duration::from_millis(10),
             success: true,
+            arguments: None,
             arguments_hash: None,
             iteration: None,
         });
@@ -445,6 +453,7 @@ mod tests {
             tool: "file_read".into(),
             duration: Duration::from_millis(5),
             success: false,
+            arguments: None,
             arguments_hash: None,
             iteration: None,
         });
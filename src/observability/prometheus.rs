@@ -167,7 +167,14 @@ impl Observer for PrometheusObserver {
                 }
             }
             ObserverEvent::ToolCallStart { tool: _ }
+            | ObserverEvent::AgentThinking { .. }
+            | ObserverEvent::FileOperation { .. }
+            | ObserverEvent::StreamingTokenChunk { .. }
+            | ObserverEvent::TurnStart
             | ObserverEvent::TurnComplete
+            | ObserverEvent::SessionStart { .. }
+            | ObserverEvent::SessionEnd { .. }
+            | ObserverEvent::BudgetExceeded { .. }
             | ObserverEvent::LlmRequest { .. }
             | ObserverEvent::LlmResponse { .. } => {}
             ObserverEvent::ToolCall {
@@ -266,6 +273,7 @@ mod tests {
             tool: "shell".into(),
             duration: Duration::from_millis(10),
             success: true,
+            arguments: None,
             arguments_hash: None,
             iteration: None,
         });
@@ -273,6 +281,7 @@ mod tests {
             tool: "file_read".into(),
             duration: Duration::from_millis(5),
             success: false,
+            arguments: None,
             arguments_hash: None,
             iteration: None,
         });
@@ -308,6 +317,7 @@ mod tests {
             tool: "shell".into(),
             duration: Duration::from_millis(100),
             success: true,
+            arguments: None,
             arguments_hash: None,
             iteration: None,
         });
@@ -341,6 +351,7 @@ mod tests {
             tool: "shell".into(),
             duration: Duration::from_millis(10),
             success: true,
+            arguments: None,
             arguments_hash: None,
             iteration: None,
         });
@@ -348,6 +359,7 @@ mod tests {
             tool: "shell".into(),
             duration: Duration::from_millis(10),
             success: true,
+            arguments: None,
             arguments_hash: None,
             iteration: None,
         });
@@ -355,6 +367,7 @@ mod tests {
             tool: "shell".into(),
             duration: Duration::from_millis(10),
             success: false,
+            arguments: None,
             arguments_hash: None,
             iteration: None,
         });
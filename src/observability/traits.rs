@@ -1,5 +1,16 @@
+use std::path::PathBuf;
 use std::time::Duration;
 
+/// Kind of file operation reported by [`ObserverEvent::FileOperation`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FileOp {
+    Read,
+    Write,
+    Delete,
+    Rename,
+}
+
 /// Events the observer can record
 #[derive(Debug, Clone)]
 pub enum ObserverEvent {
@@ -26,6 +37,12 @@ pub enum ObserverEvent {
         tokens_in: Option<u64>,
         tokens_out: Option<u64>,
     },
+    /// A chunk of a streamed LLM response arrived.
+    StreamingTokenChunk {
+        provider: String,
+        model: String,
+        chunk_tokens: usize,
+    },
     AgentEnd {
         provider: String,
         model: String,
@@ -41,16 +58,44 @@ pub enum ObserverEvent {
         tool: String,
         duration: Duration,
         success: bool,
+        arguments: Option<serde_json::Value>,
         arguments_hash: Option<String>,
         iteration: Option<u32>,
     },
+    /// The agent read, wrote, deleted, or renamed a file.
+    FileOperation {
+        path: PathBuf,
+        operation: FileOp,
+        bytes: usize,
+        success: bool,
+    },
+    /// The model spent tokens on intermediate reasoning between actions.
+    AgentThinking {
+        tokens_used: usize,
+        model: String,
+    },
+    /// The agent started working on a new user message.
+    TurnStart,
     /// The agent produced a final answer for the current user message.
     TurnComplete,
+    /// A conversation session began.
+    SessionStart {
+        session_id: String,
+    },
+    /// A conversation session ended.
+    SessionEnd {
+        session_id: String,
+    },
     ChannelMessage {
         channel: String,
         direction: String,
     },
     HeartbeatTick,
+    /// Estimated spend for a session passed its configured budget.
+    BudgetExceeded {
+        session_id: String,
+        actual_cost: f64,
+    },
     Error {
         component: String,
         message: String,
@@ -146,6 +191,7 @@ mod tests {
             tool: "shell".into(),
             duration: Duration::from_millis(10),
             success: true,
+            arguments: None,
             arguments_hash: None,
             iteration: None,
         };
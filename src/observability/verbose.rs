@@ -98,6 +98,7 @@ mod tests {
             tool: "shell".into(),
             duration: Duration::from_millis(2),
             success: true,
+            arguments: None,
             arguments_hash: None,
             iteration: None,
         });
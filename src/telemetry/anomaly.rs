@@ -0,0 +1,120 @@
+use crate::telemetry::reader::SystemSampleRow;
+
+/// Numeric [`SystemSampleRow`] field to check for anomalies.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SampleField {
+    CpuUsagePct,
+    MemoryUsedBytes,
+    ProcessCount,
+    ProcessSpawnRate,
+    FileReadBytes,
+    FileWriteBytes,
+    NetConnections,
+    DestIpEntropy,
+}
+
+impl SampleField {
+    pub fn value(self, sample: &SystemSampleRow) -> f64 {
+        match self {
+            Self::CpuUsagePct => sample.cpu_usage_pct,
+            Self::MemoryUsedBytes => sample.memory_used_bytes as f64,
+            Self::ProcessCount => sample.process_count as f64,
+            Self::ProcessSpawnRate => sample.process_spawn_rate as f64,
+            Self::FileReadBytes => sample.file_read_bytes as f64,
+            Self::FileWriteBytes => sample.file_write_bytes as f64,
+            Self::NetConnections => sample.net_connections as f64,
+            Self::DestIpEntropy => sample.dest_ip_entropy,
+        }
+    }
+}
+
+/// A system sample that deviated from the preceding window.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct AnomalousSample {
+    pub sample: SystemSampleRow,
+    /// Signed deviation from the window mean, in standard deviations.
+    pub z_score: f64,
+}
+
+/// Mean and variance of a sliding window, updated with Welford's online
+/// algorithm so each push or pop is O(1).
+#[derive(Debug, Clone, Default)]
+pub struct RollingStats {
+    count: usize,
+    mean: f64,
+    /// Sum of squared deviations from the mean.
+    m2: f64,
+}
+
+impl RollingStats {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push(&mut self, x: f64) {
+        self.count += 1;
+        let delta = x - self.mean;
+        self.mean += delta / self.count as f64;
+        self.m2 += delta * (x - self.mean);
+    }
+
+    /// Remove a value previously pushed.
+    pub fn pop(&mut self, x: f64) {
+        if self.count <= 1 {
+            *self = Self::default();
+            return;
+        }
+        let old_mean = self.mean;
+        self.count -= 1;
+        self.mean = (old_mean * (self.count + 1) as f64 - x) / self.count as f64;
+        // Rounding can leave a tiny negative remainder.
+        self.m2 = (self.m2 - (x - old_mean) * (x - self.mean)).max(0.0);
+    }
+
+    pub fn len(&self) -> usize {
+        self.count
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.count == 0
+    }
+
+    pub fn mean(&self) -> f64 {
+        self.mean
+    }
+
+    /// Population standard deviation of the window.
+    pub fn std_dev(&self) -> f64 {
+        if self.count == 0 {
+            0.0
+        } else {
+            (self.m2 / self.count as f64).sqrt()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rolling_stats_track_sliding_window() {
+        let mut stats = RollingStats::new();
+        for x in [100.0, 2.0, 4.0, 4.0, 4.0, 5.0, 5.0, 7.0, 9.0] {
+            stats.push(x);
+        }
+        stats.pop(100.0);
+        assert_eq!(stats.len(), 8);
+        assert!((stats.mean() - 5.0).abs() < 1e-9);
+        assert!((stats.std_dev() - 2.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn popping_last_value_resets() {
+        let mut stats = RollingStats::new();
+        stats.push(3.0);
+        stats.pop(3.0);
+        assert!(stats.is_empty());
+        assert!(stats.std_dev().abs() < f64::EPSILON);
+    }
+}
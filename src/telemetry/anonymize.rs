@@ -0,0 +1,72 @@
+use regex::Regex;
+use std::sync::LazyLock;
+
+const REDACTED: &str = "[REDACTED]";
+
+static EMAIL_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"[A-Za-z0-9._%+\-]+@[A-Za-z0-9.\-]+\.[A-Za-z]{2,}").unwrap());
+
+static UUID_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"\b[0-9a-fA-F]{8}-[0-9a-fA-F]{4}-[0-9a-fA-F]{4}-[0-9a-fA-F]{4}-[0-9a-fA-F]{12}\b")
+        .unwrap()
+});
+
+static IPV4_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"\b(?:(?:25[0-5]|2[0-4]\d|1\d\d|[1-9]?\d)\.){3}(?:25[0-5]|2[0-4]\d|1\d\d|[1-9]?\d)\b",
+    )
+    .unwrap()
+});
+
+/// Absolute Unix (`/home/x`) or Windows (`C:\Users\x`) paths. The leading
+/// group keeps the delimiter so URLs like `https://host/path` are untouched.
+static ABS_PATH_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#"(^|[\s"'(=])(?:/[^\s"'()]+|[A-Za-z]:\\[^\s"'()]+)"#).unwrap());
+
+/// Replace emails, UUIDs, IPv4 addresses, and absolute file paths in an
+/// error message with `[REDACTED]`.
+pub fn anonymize_error_message(msg: &str) -> String {
+    let msg = EMAIL_RE.replace_all(msg, REDACTED);
+    let msg = UUID_RE.replace_all(&msg, REDACTED);
+    let msg = IPV4_RE.replace_all(&msg, REDACTED);
+    let msg = ABS_PATH_RE.replace_all(&msg, format!("${{1}}{REDACTED}"));
+    msg.into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn redacts_all_pattern_types() {
+        let msg = "user alice@example.com (session 3f2b8c1e-9a4d-4e2f-8b7a-1c2d3e4f5a6b) \
+                   failed to reach 192.168.1.20 while reading /home/alice/.ssh/id_rsa";
+        assert_eq!(
+            anonymize_error_message(msg),
+            "user [REDACTED] (session [REDACTED]) \
+             failed to reach [REDACTED] while reading [REDACTED]"
+        );
+    }
+
+    #[test]
+    fn redacts_windows_paths() {
+        assert_eq!(
+            anonymize_error_message(r"cannot open C:\Users\bob\report.docx"),
+            "cannot open [REDACTED]"
+        );
+    }
+
+    #[test]
+    fn leaves_urls_and_plain_text_alone() {
+        let msg = "HTTP 429 from https://api.openai.com/v1/chat/completions: rate limited";
+        assert_eq!(anonymize_error_message(msg), msg);
+    }
+
+    #[test]
+    fn does_not_redact_version_numbers() {
+        assert_eq!(
+            anonymize_error_message("requires version 1.2.3"),
+            "requires version 1.2.3"
+        );
+    }
+}
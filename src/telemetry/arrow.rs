@@ -0,0 +1,157 @@
+//! Arrow IPC encoding of action events, for loading exports straight into
+//! Polars, DataFusion or pyarrow without a JSON round trip.
+
+use crate::telemetry::reader::ActionEventRow;
+use anyhow::{Context, Result};
+use arrow2::array::{Array, BooleanArray, Float64Array, Int64Array, UInt32Array, Utf8Array};
+use arrow2::chunk::Chunk;
+use arrow2::datatypes::{DataType, Field, Schema};
+use arrow2::io::ipc::write::{StreamWriter, WriteOptions};
+
+/// Arrow schema of an encoded [`ActionEventRow`] batch, in field order.
+pub fn action_events_schema() -> Schema {
+    let field =
+        |name: &str, data_type: DataType, nullable: bool| Field::new(name, data_type, nullable);
+    Schema::from(vec![
+        field("ts", DataType::Utf8, false),
+        field("ts_epoch_ms", DataType::Int64, false),
+        field("session_id", DataType::Utf8, false),
+        field("turn_id", DataType::Utf8, false),
+        field("sequence_index", DataType::Int64, false),
+        field("event_type", DataType::Utf8, false),
+        field("provider", DataType::Utf8, true),
+        field("model", DataType::Utf8, true),
+        field("tool_name", DataType::Utf8, true),
+        field("arguments_hash", DataType::Utf8, true),
+        field("tool_success", DataType::Boolean, true),
+        field("duration_ms", DataType::Int64, true),
+        field("tokens_in", DataType::Int64, true),
+        field("tokens_out", DataType::Int64, true),
+        field("is_user_initiated", DataType::Boolean, false),
+        field("iteration_index", DataType::Int64, false),
+        field("previous_action_type", DataType::Utf8, true),
+        field("turn_action_sequence", DataType::Utf8, true),
+        field("error_message", DataType::Utf8, true),
+        field("correlation_id", DataType::Utf8, true),
+        field("id", DataType::Int64, false),
+        field("parent_action_id", DataType::Int64, true),
+        field("estimated_cost_usd", DataType::Float64, true),
+        field("metadata_json", DataType::Utf8, true),
+        field("call_depth", DataType::UInt32, false),
+    ])
+}
+
+/// Encode `rows` as an Arrow IPC stream holding a single record batch.
+pub fn action_events_to_ipc(rows: &[ActionEventRow]) -> Result<Vec<u8>> {
+    fn utf8(values: impl Iterator<Item = Option<String>>) -> Box<dyn Array> {
+        values.collect::<Utf8Array<i32>>().boxed()
+    }
+    fn int64(values: impl Iterator<Item = Option<i64>>) -> Box<dyn Array> {
+        values.collect::<Int64Array>().boxed()
+    }
+    fn boolean(values: impl Iterator<Item = Option<bool>>) -> Box<dyn Array> {
+        values.collect::<BooleanArray>().boxed()
+    }
+
+    let columns = vec![
+        utf8(rows.iter().map(|r| Some(r.ts.clone()))),
+        int64(rows.iter().map(|r| Some(r.ts_epoch_ms))),
+        utf8(rows.iter().map(|r| Some(r.session_id.clone()))),
+        utf8(rows.iter().map(|r| Some(r.turn_id.clone()))),
+        int64(rows.iter().map(|r| Some(r.sequence_index))),
+        utf8(rows.iter().map(|r| Some(r.event_type.clone()))),
+        utf8(rows.iter().map(|r| r.provider.clone())),
+        utf8(rows.iter().map(|r| r.model.clone())),
+        utf8(rows.iter().map(|r| r.tool_name.clone())),
+        utf8(rows.iter().map(|r| r.arguments_hash.clone())),
+        boolean(rows.iter().map(|r| r.tool_success)),
+        int64(rows.iter().map(|r| r.duration_ms)),
+        int64(rows.iter().map(|r| r.tokens_in)),
+        int64(rows.iter().map(|r| r.tokens_out)),
+        boolean(rows.iter().map(|r| Some(r.is_user_initiated))),
+        int64(rows.iter().map(|r| Some(r.iteration_index))),
+        utf8(rows.iter().map(|r| r.previous_action_type.clone())),
+        utf8(rows.iter().map(|r| r.turn_action_sequence.clone())),
+        utf8(rows.iter().map(|r| r.error_message.clone())),
+        utf8(rows.iter().map(|r| r.correlation_id.clone())),
+        int64(rows.iter().map(|r| Some(r.id))),
+        int64(rows.iter().map(|r| r.parent_action_id)),
+        rows.iter()
+            .map(|r| r.estimated_cost_usd)
+            .collect::<Float64Array>()
+            .boxed(),
+        utf8(rows.iter().map(|r| r.metadata_json.clone())),
+        rows.iter()
+            .map(|r| Some(r.call_depth))
+            .collect::<UInt32Array>()
+            .boxed(),
+    ];
+    let chunk = Chunk::try_new(columns).context("building Arrow record batch")?;
+
+    let mut out = Vec::new();
+    let mut writer = StreamWriter::new(&mut out, WriteOptions { compression: None });
+    writer
+        .start(&action_events_schema(), None)
+        .context("writing Arrow IPC schema")?;
+    writer
+        .write(&chunk, None)
+        .context("writing Arrow IPC batch")?;
+    writer.finish().context("finishing Arrow IPC stream")?;
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::TelemetryConfig;
+    use crate::telemetry::reader::TelemetryReader;
+    use crate::telemetry::store::{ActionRecord, TelemetrySqliteStore};
+    use crate::telemetry::testing;
+    use arrow2::datatypes::DataType;
+    use arrow2::io::ipc::read::{read_stream_metadata, StreamReader, StreamState};
+    use tempfile::TempDir;
+
+    #[test]
+    fn ipc_stream_round_trips() {
+        let tmp = TempDir::new().unwrap();
+        let store = TelemetrySqliteStore::open(tmp.path(), TelemetryConfig::default()).unwrap();
+        for i in 0..3 {
+            store.submit_action(ActionRecord {
+                sequence_index: i,
+                tool_name: Some("shell".into()),
+                tool_success: (i > 0).then_some(i == 1),
+                ..testing::action("s1", "t1", 1_000 + i, "tool_call")
+            });
+        }
+        store.flush().unwrap();
+
+        let reader = TelemetryReader::open(&tmp.path().join("research.db")).unwrap();
+        let bytes = reader.export_action_events_arrow(None, 100).unwrap();
+
+        let mut cursor = std::io::Cursor::new(bytes);
+        let metadata = read_stream_metadata(&mut cursor).unwrap();
+        let data_type = |name: &str| {
+            metadata
+                .schema
+                .fields
+                .iter()
+                .find(|f| f.name == name)
+                .map(|f| f.data_type.clone())
+        };
+        assert_eq!(data_type("ts_epoch_ms"), Some(DataType::Int64));
+        assert_eq!(data_type("tool_success"), Some(DataType::Boolean));
+        assert_eq!(data_type("session_id"), Some(DataType::Utf8));
+
+        let mut rows = 0;
+        for state in StreamReader::new(cursor, metadata, None) {
+            match state.unwrap() {
+                StreamState::Some(chunk) => {
+                    rows += chunk.len();
+                    assert_eq!(chunk.arrays()[10].null_count(), 1);
+                }
+                StreamState::Waiting => break,
+            }
+        }
+        assert_eq!(rows, 3);
+        drop(store);
+    }
+}
@@ -0,0 +1,109 @@
+use crate::telemetry::reader::ActionEventRow;
+use crate::telemetry::store::ActionRecord;
+use std::sync::Arc;
+use tokio::sync::broadcast;
+
+/// Committed action events kept for subscribers; a receiver that falls
+/// further behind skips ahead to the most recent ones.
+pub const DEFAULT_BUS_CAPACITY: usize = 1024;
+
+/// In-process fan-out of committed action events.
+///
+/// Components that react to events (metrics aggregation, dashboards, alert
+/// checks) subscribe here instead of polling SQLite or registering their
+/// own observer. The writer publishes each action event once its batch
+/// commits, both as the submitted [`ActionRecord`] and as an
+/// [`ActionEventRow`] carrying its database id (the feed behind
+/// [`TelemetrySqliteStore::subscribe_live`]). Delivery is best-effort: a
+/// lagging receiver gets [`broadcast::error::RecvError::Lagged`] followed
+/// by the newest `capacity` events.
+///
+/// [`TelemetrySqliteStore::subscribe_live`]:
+///     crate::telemetry::store::TelemetrySqliteStore::subscribe_live
+#[derive(Clone)]
+pub struct TelemetryBus {
+    records: broadcast::Sender<Arc<ActionRecord>>,
+    rows: broadcast::Sender<ActionEventRow>,
+}
+
+impl TelemetryBus {
+    pub fn new(capacity: usize) -> Self {
+        let (records, _) = broadcast::channel(capacity.max(1));
+        let (rows, _) = broadcast::channel(capacity.max(1));
+        Self { records, rows }
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<Arc<ActionRecord>> {
+        self.records.subscribe()
+    }
+
+    /// Committed events as rows, with their database `id`.
+    pub fn subscribe_rows(&self) -> broadcast::Receiver<ActionEventRow> {
+        self.rows.subscribe()
+    }
+
+    /// Broadcast `record`, committed as row `id`, on whichever feeds have
+    /// subscribers. Free when nobody is subscribed.
+    pub fn publish(&self, id: i64, record: &ActionRecord) {
+        if self.records.receiver_count() > 0 {
+            let _ = self.records.send(Arc::new(record.clone()));
+        }
+        if self.rows.receiver_count() > 0 {
+            let _ = self.rows.send(ActionEventRow::from_record(id, record));
+        }
+    }
+
+    /// Whether either feed has a subscriber, i.e. [`Self::publish`] would
+    /// do anything.
+    pub fn has_subscribers(&self) -> bool {
+        self.records.receiver_count() > 0 || self.rows.receiver_count() > 0
+    }
+
+    pub fn subscriber_count(&self) -> usize {
+        self.records.receiver_count()
+    }
+}
+
+impl Default for TelemetryBus {
+    fn default() -> Self {
+        Self::new(DEFAULT_BUS_CAPACITY)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use broadcast::error::TryRecvError;
+
+    fn record(sequence_index: i64) -> ActionRecord {
+        ActionRecord {
+            sequence_index,
+            ..ActionRecord::default()
+        }
+    }
+
+    #[test]
+    fn every_subscriber_sees_each_record() {
+        let bus = TelemetryBus::new(8);
+        let mut a = bus.subscribe();
+        let mut b = bus.subscribe();
+        let mut rows = bus.subscribe_rows();
+        bus.publish(7, &record(1));
+        assert_eq!(a.try_recv().unwrap().sequence_index, 1);
+        assert_eq!(b.try_recv().unwrap().sequence_index, 1);
+        assert_eq!(rows.try_recv().unwrap().id, 7);
+        assert!(matches!(a.try_recv(), Err(TryRecvError::Empty)));
+    }
+
+    #[test]
+    fn lagged_subscriber_keeps_newest_records() {
+        let bus = TelemetryBus::new(2);
+        let mut rx = bus.subscribe();
+        for i in 0..5 {
+            bus.publish(i, &record(i));
+        }
+        assert!(matches!(rx.try_recv(), Err(TryRecvError::Lagged(3))));
+        assert_eq!(rx.try_recv().unwrap().sequence_index, 3);
+        assert_eq!(rx.try_recv().unwrap().sequence_index, 4);
+    }
+}
@@ -0,0 +1,119 @@
+use crate::telemetry::reader::TelemetryReader;
+use anyhow::Result;
+
+/// Sessions whose typical turn looks alike.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct SessionCluster {
+    /// Mode turn sequence of the session that founded the cluster.
+    pub representative_sequence: Vec<String>,
+    pub session_ids: Vec<String>,
+}
+
+/// Group sessions by the similarity of their most common turn action
+/// sequence.
+///
+/// Sessions are visited in ID order; each joins the first cluster whose
+/// representative is within `max_distance` (see [`hamming_distance`]) of
+/// its own mode sequence, or founds a new cluster. Sessions with no
+/// completed turns are left out.
+pub fn cluster_sessions_by_tool_sequence(
+    reader: &TelemetryReader,
+    max_distance: usize,
+) -> Result<Vec<SessionCluster>> {
+    let mut clusters: Vec<SessionCluster> = Vec::new();
+    for (session_id, sequence) in reader.mode_turn_sequences()? {
+        match clusters
+            .iter_mut()
+            .find(|c| hamming_distance(&c.representative_sequence, &sequence) <= max_distance)
+        {
+            Some(cluster) => cluster.session_ids.push(session_id),
+            None => clusters.push(SessionCluster {
+                representative_sequence: sequence,
+                session_ids: vec![session_id],
+            }),
+        }
+    }
+    Ok(clusters)
+}
+
+/// Positions at which two event-type sequences differ. The shorter sequence
+/// is treated as padded, so each extra step of the longer one counts as a
+/// mismatch.
+pub fn hamming_distance(a: &[String], b: &[String]) -> usize {
+    let mismatches = a.iter().zip(b).filter(|(x, y)| x != y).count();
+    mismatches + a.len().abs_diff(b.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::TelemetryConfig;
+    use crate::telemetry::store::{ActionRecord, TelemetrySqliteStore};
+    use crate::telemetry::testing;
+    use tempfile::TempDir;
+
+    fn seq(steps: &[&str]) -> Vec<String> {
+        steps.iter().map(|s| (*s).to_string()).collect()
+    }
+
+    #[test]
+    fn hamming_distance_counts_length_difference() {
+        let a = seq(&["llm_response", "tool_call"]);
+        assert_eq!(hamming_distance(&a, &a), 0);
+        assert_eq!(
+            hamming_distance(&a, &seq(&["llm_response", "agent_thinking"])),
+            1
+        );
+        assert_eq!(
+            hamming_distance(&a, &seq(&["llm_response", "tool_call", "tool_call"])),
+            1
+        );
+        assert_eq!(hamming_distance(&a, &[]), 2);
+    }
+
+    #[test]
+    fn clusters_sessions_by_mode_sequence() {
+        let tmp = TempDir::new().unwrap();
+        let store = TelemetrySqliteStore::open(tmp.path(), TelemetryConfig::default()).unwrap();
+        let turns = [
+            ("a", r#"["llm_response","tool_call"]"#),
+            ("a", r#"["llm_response","tool_call"]"#),
+            ("a", r#"["llm_response"]"#),
+            ("b", r#"["llm_response","tool_call","tool_call"]"#),
+            ("c", r#"["agent_thinking","file_op","file_op","file_op"]"#),
+        ];
+        for (i, (session_id, sequence)) in turns.into_iter().enumerate() {
+            store.submit_action(ActionRecord {
+                turn_action_sequence: Some(sequence.into()),
+                ..testing::action(
+                    session_id,
+                    &format!("{session_id}-t{i}"),
+                    1_000 + i as i64,
+                    "turn_complete",
+                )
+            });
+        }
+        drop(store);
+
+        let reader = TelemetryReader::open(&tmp.path().join("research.db")).unwrap();
+        let clusters = cluster_sessions_by_tool_sequence(&reader, 1).unwrap();
+        assert_eq!(
+            clusters,
+            [
+                SessionCluster {
+                    representative_sequence: seq(&["llm_response", "tool_call"]),
+                    session_ids: vec!["a".into(), "b".into()],
+                },
+                SessionCluster {
+                    representative_sequence: seq(&[
+                        "agent_thinking",
+                        "file_op",
+                        "file_op",
+                        "file_op"
+                    ]),
+                    session_ids: vec!["c".into()],
+                },
+            ]
+        );
+    }
+}
@@ -1,26 +1,58 @@
 use crate::config::TelemetryConfig;
+#[cfg(feature = "mqtt")]
+use crate::config::{MqttConfig, MqttQos};
 use crate::telemetry::store::{SystemSample, TelemetrySqliteStore};
+#[cfg(feature = "mqtt")]
+use anyhow::{Context, Result};
 use std::sync::Arc;
 
 /// Run the system metrics collector as a background tokio task.
 ///
 /// Samples CPU, memory, process count, file I/O, and network connection
-/// metrics at the configured interval and submits them to the telemetry store.
+/// metrics and submits them to the telemetry store. The interval is re-read
+/// from [`TelemetrySqliteStore::config`] before every sample, so changes made
+/// with `update_config` apply from the next one. Samples are also published
+/// to MQTT when `config.mqtt` is set, and each one's connection count feeds
+/// [`TelemetrySqliteStore::net_surges_detected`]. Spawn bursts above
+/// `max_spawn_rate` are logged with the new process names, and memory that
+/// grows across [`MEMORY_LEAK_WINDOW`] samples is logged as a possible leak.
 pub async fn run_system_collector(store: Arc<TelemetrySqliteStore>, config: TelemetryConfig) {
     use sysinfo::System;
 
-    let interval = std::time::Duration::from_secs(config.system_interval_secs.max(1));
+    #[cfg(feature = "mqtt")]
+    let mqtt = config
+        .mqtt
+        .as_ref()
+        .and_then(|mqtt| match MqttPublisher::connect(mqtt) {
+            Ok(publisher) => Some(publisher),
+            Err(e) => {
+                tracing::warn!("telemetry MQTT publishing disabled: {e:#}");
+                None
+            }
+        });
+    #[cfg(not(feature = "mqtt"))]
+    if config.mqtt.is_some() {
+        tracing::warn!("telemetry.mqtt is set but this build lacks the `mqtt` feature");
+    }
+
     let mut sys = System::new();
 
     // Initial refresh to get a baseline for CPU (first reading is always 0).
     sys.refresh_all();
     let mut prev_process_count: i64 = sys.processes().len() as i64;
+    let mut prev_pids: std::collections::HashSet<sysinfo::Pid> =
+        sys.processes().keys().copied().collect();
 
     #[cfg(target_os = "linux")]
     let mut prev_io = read_proc_self_io();
 
+    let mut recent: std::collections::VecDeque<SystemSample> =
+        std::collections::VecDeque::with_capacity(MEMORY_LEAK_WINDOW);
+    let mut leak_reported = false;
+
     loop {
-        tokio::time::sleep(interval).await;
+        let interval_secs = store.config().system_interval_secs.max(1);
+        tokio::time::sleep(std::time::Duration::from_secs(interval_secs)).await;
 
         sys.refresh_all();
 
@@ -30,6 +62,21 @@ pub async fn run_system_collector(store: Arc<TelemetrySqliteStore>, config: Tele
         let process_count = sys.processes().len() as i64;
         let process_spawn_rate = (process_count - prev_process_count).max(0);
         prev_process_count = process_count;
+        if store
+            .config()
+            .max_spawn_rate
+            .is_some_and(|max| process_spawn_rate > i64::from(max))
+        {
+            let new_processes: Vec<String> = sys
+                .processes()
+                .iter()
+                .filter(|(pid, _)| !prev_pids.contains(pid))
+                .map(|(_, p)| p.name().to_string_lossy().into_owned())
+                .collect();
+            check_spawn_burst(&store, process_spawn_rate, &new_processes);
+        }
+        prev_pids.clear();
+        prev_pids.extend(sys.processes().keys().copied());
 
         // File I/O from /proc/self/io (Linux only)
         #[cfg(target_os = "linux")]
@@ -43,11 +90,16 @@ pub async fn run_system_collector(store: Arc<TelemetrySqliteStore>, config: Tele
         #[cfg(not(target_os = "linux"))]
         let (file_read_bytes, file_write_bytes) = (0i64, 0i64);
 
-        // Network connections + dest IP entropy (Linux only)
+        // Network connections, dest IP entropy, TCP states (Linux only)
         #[cfg(target_os = "linux")]
-        let (net_connections, dest_ip_entropy) = read_net_connections();
+        let (net_connections, dest_ip_entropy, tcp_state_json) = {
+            let (net_connections, dest_ips, tcp_state_json) = read_net_connections();
+            let entropy = shannon_entropy(&dest_ips);
+            check_dest_ip_entropy(&store, entropy, &dest_ips);
+            (net_connections, entropy, tcp_state_json)
+        };
         #[cfg(not(target_os = "linux"))]
-        let (net_connections, dest_ip_entropy) = (0i64, 0.0f64);
+        let (net_connections, dest_ip_entropy, tcp_state_json) = (0i64, 0.0f64, None);
 
         // eBPF syscall frequency (when available)
         let syscall_freq_json = super::ebpf::try_read_syscall_freq();
@@ -58,7 +110,7 @@ pub async fn run_system_collector(store: Arc<TelemetrySqliteStore>, config: Tele
         let ts_epoch_ms = i64::try_from(now.as_millis()).unwrap_or(i64::MAX);
         let ts = chrono::Utc::now().to_rfc3339();
 
-        store.submit_system_sample(SystemSample {
+        let sample = SystemSample {
             ts,
             ts_epoch_ms,
             cpu_usage_pct,
@@ -70,8 +122,172 @@ pub async fn run_system_collector(store: Arc<TelemetrySqliteStore>, config: Tele
             file_write_bytes,
             net_connections,
             dest_ip_entropy,
+            tcp_state_json,
             syscall_freq_json,
+        };
+        #[cfg(feature = "mqtt")]
+        if let Some(mqtt) = &mqtt {
+            mqtt.publish(&sample);
+        }
+        store.record_net_connections(sample.net_connections);
+
+        if recent.len() == MEMORY_LEAK_WINDOW {
+            recent.pop_front();
+        }
+        recent.push_back(sample.clone());
+        let window = recent.make_contiguous();
+        if detect_memory_leak(window, MEMORY_LEAK_WINDOW) {
+            // Warn once per growth run rather than on every sample.
+            if !leak_reported {
+                let growth =
+                    window[window.len() - 1].memory_used_bytes - window[0].memory_used_bytes;
+                let slope_bytes_per_sample = growth / (window.len() as i64 - 1);
+                tracing::warn!(
+                    samples = MEMORY_LEAK_WINDOW,
+                    slope_bytes_per_sample,
+                    "memory usage grew in every recent sample; possible leak"
+                );
+                leak_reported = true;
+            }
+        } else {
+            leak_reported = false;
+        }
+
+        store.submit_system_sample(sample);
+    }
+}
+
+/// Consecutive samples checked by [`detect_memory_leak`] in the collector.
+pub const MEMORY_LEAK_WINDOW: usize = 10;
+
+/// Whether `memory_used_bytes` strictly increases across each of the last
+/// `window` samples. Needs at least `window` (and at least two) samples.
+pub fn detect_memory_leak(samples: &[SystemSample], window: usize) -> bool {
+    if window < 2 || samples.len() < window {
+        return false;
+    }
+    samples[samples.len() - window..]
+        .windows(2)
+        .all(|pair| pair[1].memory_used_bytes > pair[0].memory_used_bytes)
+}
+
+/// New process names listed in a spawn burst warning.
+const SPAWN_BURST_MAX_NAMES: usize = 20;
+
+/// Warn and count an alert when `spawn_rate` exceeds the configured
+/// `max_spawn_rate`.
+fn check_spawn_burst(store: &TelemetrySqliteStore, spawn_rate: i64, new_processes: &[String]) {
+    let Some(max_spawn_rate) = store.config().max_spawn_rate else {
+        return;
+    };
+    if spawn_rate <= i64::from(max_spawn_rate) {
+        return;
+    }
+    store.count_spawn_burst_alert();
+    let new_processes = &new_processes[..new_processes.len().min(SPAWN_BURST_MAX_NAMES)];
+    tracing::warn!(
+        spawn_rate,
+        max_spawn_rate,
+        ?new_processes,
+        "process spawn burst above threshold; possible unexpected child processes"
+    );
+}
+
+/// Publishes system samples as JSON to `telemetry/system/{hostname}/metrics`.
+///
+/// Publishing never waits on the broker: samples that do not fit in the
+/// client's request queue are dropped, and a background task keeps
+/// reconnecting while the broker is unreachable.
+#[cfg(feature = "mqtt")]
+pub struct MqttPublisher {
+    client: rumqttc::AsyncClient,
+    topic: String,
+    qos: rumqttc::QoS,
+    event_loop: tokio::task::JoinHandle<()>,
+}
+
+#[cfg(feature = "mqtt")]
+impl MqttPublisher {
+    /// Requests buffered for the event loop before samples are dropped.
+    const REQUEST_CAPACITY: usize = 64;
+    const RECONNECT_DELAY: std::time::Duration = std::time::Duration::from_secs(1);
+
+    /// Connect to the broker in `config`. Must be called inside a tokio
+    /// runtime, which drives the connection.
+    pub fn connect(config: &MqttConfig) -> Result<Self> {
+        let (host, port) = parse_broker_url(&config.broker_url)?;
+        let options = rumqttc::MqttOptions::new(&config.client_id, host, port);
+        let (client, mut event_loop) = rumqttc::AsyncClient::new(options, Self::REQUEST_CAPACITY);
+        let event_loop = tokio::spawn(async move {
+            loop {
+                if let Err(e) = event_loop.poll().await {
+                    tracing::debug!("telemetry MQTT connection error: {e}");
+                    tokio::time::sleep(Self::RECONNECT_DELAY).await;
+                }
+            }
         });
+        let hostname = hostname::get().map_or_else(
+            |_| "unknown".to_string(),
+            |h| h.to_string_lossy().into_owned(),
+        );
+        Ok(Self {
+            client,
+            topic: format!("telemetry/system/{hostname}/metrics"),
+            qos: match config.qos {
+                MqttQos::AtMostOnce => rumqttc::QoS::AtMostOnce,
+                MqttQos::AtLeastOnce => rumqttc::QoS::AtLeastOnce,
+                MqttQos::ExactlyOnce => rumqttc::QoS::ExactlyOnce,
+            },
+            event_loop,
+        })
+    }
+
+    /// Topic samples are published to.
+    pub fn topic(&self) -> &str {
+        &self.topic
+    }
+
+    pub fn publish(&self, sample: &SystemSample) {
+        let payload = match serde_json::to_vec(sample) {
+            Ok(payload) => payload,
+            Err(e) => {
+                tracing::warn!("serializing system sample for MQTT failed: {e}");
+                return;
+            }
+        };
+        if let Err(e) = self
+            .client
+            .try_publish(&self.topic, self.qos, false, payload)
+        {
+            tracing::debug!("telemetry MQTT publish dropped: {e}");
+        }
+    }
+}
+
+#[cfg(feature = "mqtt")]
+impl Drop for MqttPublisher {
+    fn drop(&mut self) {
+        self.event_loop.abort();
+    }
+}
+
+/// Split an MQTT broker URL into host and port (default 1883).
+#[cfg(feature = "mqtt")]
+fn parse_broker_url(url: &str) -> Result<(String, u16)> {
+    let address = url
+        .strip_prefix("mqtt://")
+        .or_else(|| url.strip_prefix("tcp://"))
+        .unwrap_or(url)
+        .trim_end_matches('/');
+    match address.rsplit_once(':') {
+        Some((host, port)) => {
+            let port = port
+                .parse()
+                .with_context(|| format!("invalid MQTT broker port in {url}"))?;
+            Ok((host.to_string(), port))
+        }
+        None if !address.is_empty() => Ok((address.to_string(), 1883)),
+        None => anyhow::bail!("empty MQTT broker URL"),
     }
 }
 
@@ -94,11 +310,20 @@ fn read_proc_self_io() -> (i64, i64) {
     (read_bytes, write_bytes)
 }
 
-/// Read /proc/net/tcp + /proc/net/tcp6 to count connections and compute
-/// Shannon entropy of destination IP addresses.
+/// Read /proc/net/tcp + /proc/net/tcp6 to count connections, compute
+/// Shannon entropy of destination IP addresses, and tally TCP states.
+///
+/// Loopback and unspecified (listening socket) destinations are left out of
+/// the returned destinations, used for entropy, but still counted as
+/// connections. The TCP
+/// state distribution is returned as a JSON object keyed by state name,
+/// e.g. `{"ESTABLISHED":12,"TIME_WAIT":3}`.
 #[cfg(target_os = "linux")]
-fn read_net_connections() -> (i64, f64) {
-    let mut dest_ips: Vec<String> = Vec::new();
+fn read_net_connections() -> (i64, Vec<std::net::IpAddr>, Option<String>) {
+    let mut net_connections: i64 = 0;
+    let mut dest_ips: Vec<std::net::IpAddr> = Vec::new();
+    let mut tcp_states: std::collections::BTreeMap<&'static str, i64> =
+        std::collections::BTreeMap::new();
 
     for path in &["/proc/net/tcp", "/proc/net/tcp6"] {
         if let Ok(content) = std::fs::read_to_string(path) {
@@ -106,29 +331,145 @@ fn read_net_connections() -> (i64, f64) {
                 // Fields: sl local_address rem_address st ...
                 let parts: Vec<&str> = line.split_whitespace().collect();
                 if parts.len() >= 3 {
+                    net_connections += 1;
+                    if let Some(state) = parts.get(3) {
+                        *tcp_states.entry(tcp_state_name(state)).or_insert(0) += 1;
+                    }
                     // rem_address is like "0100007F:1F90" (hex IP:port)
-                    if let Some(ip_hex) = parts[2].split(':').next() {
-                        dest_ips.push(ip_hex.to_string());
+                    if let Some(ip) = parts[2].split(':').next().and_then(parse_proc_net_ip) {
+                        let ip = ip.to_canonical();
+                        if !ip.is_loopback() && !ip.is_unspecified() {
+                            dest_ips.push(ip);
+                        }
                     }
                 }
             }
         }
     }
 
-    let net_connections = dest_ips.len() as i64;
-    let entropy = shannon_entropy(&dest_ips);
-    (net_connections, entropy)
+    let tcp_state_json = if tcp_states.is_empty() {
+        None
+    } else {
+        serde_json::to_string(&tcp_states).ok()
+    };
+    (net_connections, dest_ips, tcp_state_json)
+}
+
+/// Destinations listed in a high-entropy warning.
+#[cfg(target_os = "linux")]
+const ENTROPY_ALERT_TOP_IPS: usize = 5;
+
+/// Warn and count an alert when `entropy` exceeds the configured
+/// `max_entropy_threshold`.
+#[cfg(target_os = "linux")]
+fn check_dest_ip_entropy(
+    store: &TelemetrySqliteStore,
+    entropy: f64,
+    dest_ips: &[std::net::IpAddr],
+) {
+    let Some(threshold) = store.config().max_entropy_threshold else {
+        return;
+    };
+    if entropy <= threshold {
+        return;
+    }
+    store.count_entropy_alert();
+    let top_dest_ips: Vec<String> = most_frequent(dest_ips, ENTROPY_ALERT_TOP_IPS)
+        .into_iter()
+        .map(|(ip, count)| format!("{ip} ({count})"))
+        .collect();
+    tracing::warn!(
+        entropy,
+        threshold,
+        connections = dest_ips.len(),
+        ?top_dest_ips,
+        "destination IP entropy above threshold; possible data exfiltration"
+    );
 }
 
-/// Compute Shannon entropy of a set of string values.
+/// The `n` most frequent values with their counts, most frequent first;
+/// ties keep the order values first appeared.
 #[cfg(target_os = "linux")]
-fn shannon_entropy(values: &[String]) -> f64 {
+fn most_frequent<T: std::hash::Hash + Eq + Copy>(values: &[T], n: usize) -> Vec<(T, usize)> {
+    let mut counts: Vec<(T, usize)> = Vec::new();
+    let mut index: std::collections::HashMap<T, usize> = std::collections::HashMap::new();
+    for &v in values {
+        let i = *index.entry(v).or_insert_with(|| {
+            counts.push((v, 0));
+            counts.len() - 1
+        });
+        counts[i].1 += 1;
+    }
+    counts.sort_by(|a, b| b.1.cmp(&a.1));
+    counts.truncate(n);
+    counts
+}
+
+/// Map the hex `st` field of `/proc/net/tcp` to its kernel state name.
+///
+/// Codes follow `include/net/tcp_states.h`:
+///
+/// | code | state        | code | state      |
+/// |------|--------------|------|------------|
+/// | 01   | ESTABLISHED  | 07   | CLOSE      |
+/// | 02   | SYN_SENT     | 08   | CLOSE_WAIT |
+/// | 03   | SYN_RECV     | 09   | LAST_ACK   |
+/// | 04   | FIN_WAIT1    | 0A   | LISTEN     |
+/// | 05   | FIN_WAIT2    | 0B   | CLOSING    |
+/// | 06   | TIME_WAIT    | 0C   | NEW_SYN_RECV |
+///
+/// Anything else maps to `"UNKNOWN"`.
+#[cfg(target_os = "linux")]
+fn tcp_state_name(hex: &str) -> &'static str {
+    match u8::from_str_radix(hex, 16) {
+        Ok(0x01) => "ESTABLISHED",
+        Ok(0x02) => "SYN_SENT",
+        Ok(0x03) => "SYN_RECV",
+        Ok(0x04) => "FIN_WAIT1",
+        Ok(0x05) => "FIN_WAIT2",
+        Ok(0x06) => "TIME_WAIT",
+        Ok(0x07) => "CLOSE",
+        Ok(0x08) => "CLOSE_WAIT",
+        Ok(0x09) => "LAST_ACK",
+        Ok(0x0A) => "LISTEN",
+        Ok(0x0B) => "CLOSING",
+        Ok(0x0C) => "NEW_SYN_RECV",
+        _ => "UNKNOWN",
+    }
+}
+
+/// Parse the hex IP part of a `/proc/net/tcp{,6}` address field.
+///
+/// The kernel prints the address as 32-bit words in host byte order: 8 hex
+/// chars for IPv4 and 32 for IPv6.
+#[cfg(target_os = "linux")]
+fn parse_proc_net_ip(hex: &str) -> Option<std::net::IpAddr> {
+    fn word(hex: &str) -> Option<[u8; 4]> {
+        u32::from_str_radix(hex, 16).ok().map(u32::to_ne_bytes)
+    }
+
+    match hex.len() {
+        8 => word(hex).map(std::net::IpAddr::from),
+        32 => {
+            let mut octets = [0u8; 16];
+            for (i, chunk) in octets.chunks_exact_mut(4).enumerate() {
+                chunk.copy_from_slice(&word(hex.get(i * 8..i * 8 + 8)?)?);
+            }
+            Some(std::net::IpAddr::from(octets))
+        }
+        _ => None,
+    }
+}
+
+/// Compute Shannon entropy of a set of values.
+#[cfg(target_os = "linux")]
+fn shannon_entropy<T: std::hash::Hash + Eq>(values: &[T]) -> f64 {
     if values.is_empty() {
         return 0.0;
     }
-    let mut counts: std::collections::HashMap<&str, usize> = std::collections::HashMap::new();
+    let mut counts: std::collections::HashMap<&T, usize> = std::collections::HashMap::new();
     for v in values {
-        *counts.entry(v.as_str()).or_insert(0) += 1;
+        *counts.entry(v).or_insert(0) += 1;
     }
     let total = values.len() as f64;
     let mut entropy = 0.0;
@@ -140,3 +481,219 @@ fn shannon_entropy(values: &[String]) -> f64 {
     }
     entropy
 }
+
+#[cfg(all(test, target_os = "linux"))]
+mod tests {
+    use super::*;
+    use crate::telemetry::testing;
+    use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+    #[test]
+    fn parse_proc_net_ipv4() {
+        assert_eq!(
+            parse_proc_net_ip("0100007F"),
+            Some(IpAddr::V4(Ipv4Addr::LOCALHOST))
+        );
+        assert_eq!(
+            parse_proc_net_ip("0101A8C0"),
+            Some(IpAddr::V4(Ipv4Addr::new(192, 168, 1, 1)))
+        );
+        assert_eq!(
+            parse_proc_net_ip("00000000"),
+            Some(IpAddr::V4(Ipv4Addr::UNSPECIFIED))
+        );
+    }
+
+    #[test]
+    fn parse_proc_net_ipv6() {
+        assert_eq!(
+            parse_proc_net_ip("00000000000000000000000001000000"),
+            Some(IpAddr::V6(Ipv6Addr::LOCALHOST))
+        );
+        assert_eq!(
+            parse_proc_net_ip("0000000000000000FFFF00000100007F"),
+            Some(IpAddr::V6(Ipv4Addr::LOCALHOST.to_ipv6_mapped()))
+        );
+        assert_eq!(
+            parse_proc_net_ip("B80D0120000000000000000001000000"),
+            Some(IpAddr::V6("2001:db8::1".parse().unwrap()))
+        );
+    }
+
+    #[test]
+    fn parse_proc_net_ip_rejects_malformed() {
+        assert_eq!(parse_proc_net_ip(""), None);
+        assert_eq!(parse_proc_net_ip("0100007F:1F90"), None);
+        assert_eq!(parse_proc_net_ip("ZZZZZZZZ"), None);
+    }
+
+    #[test]
+    fn tcp_state_names_match_kernel_codes() {
+        assert_eq!(tcp_state_name("01"), "ESTABLISHED");
+        assert_eq!(tcp_state_name("06"), "TIME_WAIT");
+        assert_eq!(tcp_state_name("08"), "CLOSE_WAIT");
+        assert_eq!(tcp_state_name("0A"), "LISTEN");
+        assert_eq!(tcp_state_name("0c"), "NEW_SYN_RECV");
+        assert_eq!(tcp_state_name("FF"), "UNKNOWN");
+        assert_eq!(tcp_state_name("xx"), "UNKNOWN");
+    }
+
+    #[test]
+    fn entropy_counts_distinct_ips() {
+        let ip = IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1));
+        assert_eq!(shannon_entropy(&[ip, ip, ip]), 0.0);
+        let other = IpAddr::V4(Ipv4Addr::new(10, 0, 0, 2));
+        assert!((shannon_entropy(&[ip, other]) - 1.0).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn high_entropy_raises_alert_with_top_ips() {
+        use crate::config::TelemetryConfig;
+
+        let tmp = tempfile::TempDir::new().unwrap();
+        let config = TelemetryConfig {
+            max_entropy_threshold: Some(4.0),
+            ..TelemetryConfig::default()
+        };
+        let store = TelemetrySqliteStore::open(tmp.path(), config).unwrap();
+
+        // 32 distinct destinations: 5 bits of entropy.
+        let spread: Vec<IpAddr> = (0..32u8)
+            .map(|i| IpAddr::V4(Ipv4Addr::new(203, 0, 113, i)))
+            .collect();
+        check_dest_ip_entropy(&store, shannon_entropy(&spread), &spread);
+        assert_eq!(store.entropy_alerts(), 1);
+
+        let focused = vec![IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1)); 32];
+        check_dest_ip_entropy(&store, shannon_entropy(&focused), &focused);
+        assert_eq!(store.entropy_alerts(), 1);
+    }
+
+    fn memory_sample(memory_used_bytes: i64) -> SystemSample {
+        SystemSample {
+            memory_used_bytes,
+            memory_total_bytes: 1 << 30,
+            process_count: 1,
+            ..testing::sample(0)
+        }
+    }
+
+    #[test]
+    fn memory_leak_needs_growth_in_every_sample() {
+        let growing: Vec<_> = (0..6).map(|i| memory_sample(1000 + i * 64)).collect();
+        assert!(detect_memory_leak(&growing, 5));
+        assert!(detect_memory_leak(&growing, 6));
+        assert!(!detect_memory_leak(&growing, 7));
+
+        // A flat step inside the window breaks the run; outside it does not.
+        let mut stalled = growing.clone();
+        stalled[3].memory_used_bytes = stalled[2].memory_used_bytes;
+        assert!(!detect_memory_leak(&stalled, 4));
+        assert!(detect_memory_leak(&stalled, 3));
+    }
+
+    #[test]
+    fn spawn_burst_alerts_above_max_rate() {
+        use crate::config::TelemetryConfig;
+
+        let tmp = tempfile::TempDir::new().unwrap();
+        let config = TelemetryConfig {
+            max_spawn_rate: Some(10),
+            ..TelemetryConfig::default()
+        };
+        let store = TelemetrySqliteStore::open(tmp.path(), config).unwrap();
+
+        check_spawn_burst(&store, 10, &[]);
+        assert_eq!(store.spawn_burst_alerts(), 0);
+        check_spawn_burst(&store, 25, &["curl".to_string(), "sh".to_string()]);
+        assert_eq!(store.spawn_burst_alerts(), 1);
+    }
+
+    #[test]
+    fn most_frequent_orders_by_count() {
+        assert_eq!(most_frequent(&[1, 2, 2, 3, 3, 3, 4], 2), [(3, 3), (2, 2)]);
+        assert_eq!(most_frequent(&[7, 8], 5), [(7, 1), (8, 1)]);
+    }
+}
+
+#[cfg(all(test, feature = "mqtt"))]
+mod mqtt_tests {
+    use super::*;
+    use crate::telemetry::testing;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::{TcpListener, TcpStream};
+
+    /// Read one MQTT control packet, returning its type nibble and body.
+    async fn read_packet(stream: &mut TcpStream) -> (u8, Vec<u8>) {
+        let header = stream.read_u8().await.unwrap();
+        let mut len = 0usize;
+        for shift in (0..28).step_by(7) {
+            let byte = stream.read_u8().await.unwrap();
+            len |= usize::from(byte & 0x7f) << shift;
+            if byte & 0x80 == 0 {
+                break;
+            }
+        }
+        let mut body = vec![0; len];
+        stream.read_exact(&mut body).await.unwrap();
+        (header >> 4, body)
+    }
+
+    #[test]
+    fn parses_broker_urls() {
+        assert_eq!(
+            parse_broker_url("mqtt://broker.local:8883").unwrap(),
+            ("broker.local".to_string(), 8883)
+        );
+        assert_eq!(
+            parse_broker_url("10.0.0.5").unwrap(),
+            ("10.0.0.5".to_string(), 1883)
+        );
+        assert!(parse_broker_url("tcp://host:port").is_err());
+    }
+
+    #[tokio::test]
+    async fn publishes_samples_to_mock_broker() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let broker = tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let (packet_type, _) = read_packet(&mut stream).await;
+            assert_eq!(packet_type, 1, "expected CONNECT");
+            stream.write_all(&[0x20, 0x02, 0x00, 0x00]).await.unwrap();
+            loop {
+                let (packet_type, body) = read_packet(&mut stream).await;
+                if packet_type == 3 {
+                    let topic_len = usize::from(u16::from_be_bytes([body[0], body[1]]));
+                    let topic = String::from_utf8(body[2..2 + topic_len].to_vec()).unwrap();
+                    let payload = body[2 + topic_len..].to_vec();
+                    return (topic, payload);
+                }
+            }
+        });
+
+        let publisher = MqttPublisher::connect(&MqttConfig {
+            broker_url: format!("mqtt://127.0.0.1:{port}"),
+            client_id: "collector-test".into(),
+            qos: MqttQos::AtMostOnce,
+        })
+        .unwrap();
+        publisher.publish(&SystemSample {
+            cpu_usage_pct: 55.5,
+            memory_used_bytes: 1,
+            memory_total_bytes: 2,
+            process_count: 3,
+            ..testing::sample(42)
+        });
+
+        let (topic, payload) = tokio::time::timeout(std::time::Duration::from_secs(5), broker)
+            .await
+            .expect("publish within timeout")
+            .unwrap();
+        assert_eq!(topic, publisher.topic());
+        assert!(topic.starts_with("telemetry/system/") && topic.ends_with("/metrics"));
+        let json: serde_json::Value = serde_json::from_slice(&payload).unwrap();
+        assert_eq!(json["ts_epoch_ms"], 42);
+        assert_eq!(json["cpu_usage_pct"], 55.5);
+    }
+}
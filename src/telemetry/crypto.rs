@@ -0,0 +1,141 @@
+// Field-level encryption for sensitive telemetry columns.
+//
+// `error_message` can carry file paths or prompt fragments, so it may be
+// encrypted with AES-256-GCM before it reaches the database. Each value gets
+// a fresh random 12-byte nonce; the stored form is
+// `base64(nonce ‖ ciphertext ‖ tag)`.
+//
+// The key lives next to the database in `.field_key` (hex, mode 0600 on
+// Unix) and is created on first use.
+
+use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use anyhow::{Context, Result};
+use base64::Engine;
+use std::fs;
+use std::path::Path;
+
+/// AES-GCM nonce length in bytes.
+const NONCE_LEN: usize = 12;
+
+/// Name of the key file inside the telemetry directory.
+const KEY_FILE: &str = ".field_key";
+
+/// Encrypt `plaintext`, returning base64 of `nonce ‖ ciphertext ‖ tag`.
+pub fn encrypt_field(plaintext: &str, key: &[u8; 32]) -> Result<String> {
+    let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(key));
+    let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+    let ciphertext = cipher
+        .encrypt(&nonce, plaintext.as_bytes())
+        .map_err(|_| anyhow::anyhow!("encrypting telemetry field failed"))?;
+
+    let mut blob = Vec::with_capacity(NONCE_LEN + ciphertext.len());
+    blob.extend_from_slice(&nonce);
+    blob.extend_from_slice(&ciphertext);
+    Ok(base64::engine::general_purpose::STANDARD.encode(blob))
+}
+
+/// Decrypt a value produced by [`encrypt_field`].
+pub fn decrypt_field(ciphertext: &str, key: &[u8; 32]) -> Result<String> {
+    let blob = base64::engine::general_purpose::STANDARD
+        .decode(ciphertext)
+        .context("encrypted field is not valid base64")?;
+    anyhow::ensure!(
+        blob.len() > NONCE_LEN,
+        "encrypted field too short (missing nonce)"
+    );
+
+    let (nonce_bytes, ciphertext) = blob.split_at(NONCE_LEN);
+    let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(key));
+    let plaintext = cipher
+        .decrypt(Nonce::from_slice(nonce_bytes), ciphertext)
+        .map_err(|_| anyhow::anyhow!("decryption failed (wrong key or tampered data)"))?;
+    String::from_utf8(plaintext).context("decrypted field is not valid UTF-8")
+}
+
+/// Load the field encryption key from `telemetry_dir`, without creating one.
+/// `None` if no key has been written yet, so nothing was encrypted with it.
+pub fn load_field_key(telemetry_dir: &Path) -> Result<Option<[u8; 32]>> {
+    let path = telemetry_dir.join(KEY_FILE);
+    if !path.exists() {
+        return Ok(None);
+    }
+    let hex_key = fs::read_to_string(&path).context("reading telemetry field key")?;
+    let bytes = hex::decode(hex_key.trim()).context("telemetry field key is corrupt")?;
+    bytes
+        .try_into()
+        .map(Some)
+        .map_err(|_| anyhow::anyhow!("telemetry field key has wrong length"))
+}
+
+/// Load the field encryption key from `telemetry_dir`, creating it if absent.
+pub fn load_or_create_field_key(telemetry_dir: &Path) -> Result<[u8; 32]> {
+    if let Some(key) = load_field_key(telemetry_dir)? {
+        return Ok(key);
+    }
+
+    let path = telemetry_dir.join(KEY_FILE);
+    let key: [u8; 32] = Aes256Gcm::generate_key(&mut OsRng).into();
+    fs::create_dir_all(telemetry_dir)?;
+    fs::write(&path, hex::encode(key)).context("writing telemetry field key")?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o600))
+            .context("setting telemetry field key permissions")?;
+    }
+    Ok(key)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn encrypt_decrypt_roundtrip() {
+        let key = [7u8; 32];
+        let encrypted = encrypt_field("failed to read /home/alice/notes.txt", &key).unwrap();
+        assert!(!encrypted.contains("alice"));
+        assert_eq!(
+            decrypt_field(&encrypted, &key).unwrap(),
+            "failed to read /home/alice/notes.txt"
+        );
+    }
+
+    #[test]
+    fn encryption_uses_fresh_nonce() {
+        let key = [7u8; 32];
+        assert_ne!(
+            encrypt_field("same", &key).unwrap(),
+            encrypt_field("same", &key).unwrap()
+        );
+    }
+
+    #[test]
+    fn decrypt_with_wrong_key_fails() {
+        let encrypted = encrypt_field("secret", &[1u8; 32]).unwrap();
+        assert!(decrypt_field(&encrypted, &[2u8; 32]).is_err());
+    }
+
+    #[test]
+    fn decrypt_rejects_plaintext() {
+        assert!(decrypt_field("plain error", &[1u8; 32]).is_err());
+    }
+
+    #[test]
+    fn field_key_is_persisted() {
+        let tmp = TempDir::new().unwrap();
+        let first = load_or_create_field_key(tmp.path()).unwrap();
+        let second = load_or_create_field_key(tmp.path()).unwrap();
+        assert_eq!(first, second);
+        assert_eq!(load_field_key(tmp.path()).unwrap(), Some(first));
+    }
+
+    #[test]
+    fn load_field_key_does_not_create_one() {
+        let tmp = TempDir::new().unwrap();
+        assert_eq!(load_field_key(tmp.path()).unwrap(), None);
+        assert!(!tmp.path().join(KEY_FILE).exists());
+    }
+}
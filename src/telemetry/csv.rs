@@ -0,0 +1,111 @@
+//! CSV encoding of action events (RFC 4180: comma separated, CRLF line
+//! endings, fields quoted only when they need it).
+
+use crate::telemetry::reader::ActionEventRow;
+use anyhow::Result;
+use std::io::Write;
+
+/// Header row, in the order [`write_action_event`] emits fields.
+pub const ACTION_EVENT_HEADER: [&str; 25] = [
+    "ts",
+    "ts_epoch_ms",
+    "session_id",
+    "turn_id",
+    "sequence_index",
+    "event_type",
+    "provider",
+    "model",
+    "tool_name",
+    "arguments_hash",
+    "tool_success",
+    "duration_ms",
+    "tokens_in",
+    "tokens_out",
+    "is_user_initiated",
+    "iteration_index",
+    "previous_action_type",
+    "turn_action_sequence",
+    "error_message",
+    "correlation_id",
+    "id",
+    "parent_action_id",
+    "estimated_cost_usd",
+    "metadata_json",
+    "call_depth",
+];
+
+/// Write the header row.
+pub fn write_header(out: &mut dyn Write) -> Result<()> {
+    write_record(out, ACTION_EVENT_HEADER.iter().map(|h| (*h).to_string()))
+}
+
+/// Write one event as a CSV record. `None` values are empty fields.
+pub fn write_action_event(out: &mut dyn Write, row: &ActionEventRow) -> Result<()> {
+    fn opt<T: ToString>(value: Option<T>) -> String {
+        value.map(|v| v.to_string()).unwrap_or_default()
+    }
+    write_record(
+        out,
+        [
+            row.ts.clone(),
+            row.ts_epoch_ms.to_string(),
+            row.session_id.clone(),
+            row.turn_id.clone(),
+            row.sequence_index.to_string(),
+            row.event_type.clone(),
+            opt(row.provider.as_deref()),
+            opt(row.model.as_deref()),
+            opt(row.tool_name.as_deref()),
+            opt(row.arguments_hash.as_deref()),
+            opt(row.tool_success),
+            opt(row.duration_ms),
+            opt(row.tokens_in),
+            opt(row.tokens_out),
+            row.is_user_initiated.to_string(),
+            row.iteration_index.to_string(),
+            opt(row.previous_action_type.as_deref()),
+            opt(row.turn_action_sequence.as_deref()),
+            opt(row.error_message.as_deref()),
+            opt(row.correlation_id.as_deref()),
+            row.id.to_string(),
+            opt(row.parent_action_id),
+            opt(row.estimated_cost_usd),
+            opt(row.metadata_json.as_deref()),
+            row.call_depth.to_string(),
+        ],
+    )
+}
+
+fn write_record(out: &mut dyn Write, fields: impl IntoIterator<Item = String>) -> Result<()> {
+    for (i, field) in fields.into_iter().enumerate() {
+        if i > 0 {
+            out.write_all(b",")?;
+        }
+        if field.contains([',', '"', '\r', '\n']) {
+            write!(out, "\"{}\"", field.replace('"', "\"\""))?;
+        } else {
+            out.write_all(field.as_bytes())?;
+        }
+    }
+    out.write_all(b"\r\n")?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn quotes_only_fields_that_need_it() {
+        let mut out = Vec::new();
+        write_record(
+            &mut out,
+            ["plain", "a,b", "say \"hi\"", "two\nlines", ""].map(String::from),
+        )
+        .unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "plain,\"a,b\",\"say \"\"hi\"\"\",\"two\nlines\",\r\n"
+        );
+    }
+}
@@ -0,0 +1,180 @@
+use crate::telemetry::reader::{ActionEventRow, TelemetryReader};
+use anyhow::Result;
+
+/// Difference between the action sequences of two sessions.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct SessionDiff {
+    /// Longest common subsequence of event types.
+    pub common_steps: Vec<String>,
+    /// Event types of session A left unmatched by the common subsequence.
+    pub only_in_a: Vec<String>,
+    /// Event types of session B left unmatched by the common subsequence.
+    pub only_in_b: Vec<String>,
+    /// Aligned tool calls that invoked different tools.
+    pub tool_call_changes: Vec<ToolCallChange>,
+}
+
+/// A `tool_call` step present in both sessions with a different tool.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct ToolCallChange {
+    /// Position of the step in session A's action sequence.
+    pub index_a: usize,
+    /// Position of the step in session B's action sequence.
+    pub index_b: usize,
+    pub tool_a: Option<String>,
+    pub tool_b: Option<String>,
+}
+
+/// Compare the action sequences of two sessions.
+///
+/// Each session is reduced to its per-turn actions in write order (session
+/// markers excluded, `turn_complete` kept so turns line up), and the
+/// `event_type` sequences are aligned by their longest common subsequence.
+/// Takes `O(len_a * len_b)` time and memory.
+pub fn diff_sessions(
+    reader: &TelemetryReader,
+    session_a: &str,
+    session_b: &str,
+) -> Result<SessionDiff> {
+    let a = session_steps(reader, session_a)?;
+    let b = session_steps(reader, session_b)?;
+
+    // lcs[i][j] = LCS length of a[i..] and b[j..].
+    let mut lcs = vec![vec![0usize; b.len() + 1]; a.len() + 1];
+    for i in (0..a.len()).rev() {
+        for j in (0..b.len()).rev() {
+            lcs[i][j] = if a[i].event_type == b[j].event_type {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut diff = SessionDiff {
+        common_steps: Vec::new(),
+        only_in_a: Vec::new(),
+        only_in_b: Vec::new(),
+        tool_call_changes: Vec::new(),
+    };
+    let (mut i, mut j) = (0, 0);
+    while i < a.len() && j < b.len() {
+        if a[i].event_type == b[j].event_type {
+            if a[i].event_type == "tool_call" && a[i].tool_name != b[j].tool_name {
+                diff.tool_call_changes.push(ToolCallChange {
+                    index_a: i,
+                    index_b: j,
+                    tool_a: a[i].tool_name.clone(),
+                    tool_b: b[j].tool_name.clone(),
+                });
+            }
+            diff.common_steps.push(a[i].event_type.clone());
+            i += 1;
+            j += 1;
+        } else if lcs[i + 1][j] >= lcs[i][j + 1] {
+            diff.only_in_a.push(a[i].event_type.clone());
+            i += 1;
+        } else {
+            diff.only_in_b.push(b[j].event_type.clone());
+            j += 1;
+        }
+    }
+    diff.only_in_a
+        .extend(a[i..].iter().map(|row| row.event_type.clone()));
+    diff.only_in_b
+        .extend(b[j..].iter().map(|row| row.event_type.clone()));
+    Ok(diff)
+}
+
+fn session_steps(reader: &TelemetryReader, session_id: &str) -> Result<Vec<ActionEventRow>> {
+    Ok(reader
+        .export_session_events(session_id)?
+        .into_iter()
+        .filter(|row| !matches!(row.event_type.as_str(), "session_start" | "session_end"))
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::TelemetryConfig;
+    use crate::telemetry::store::{ActionRecord, TelemetrySqliteStore};
+    use crate::telemetry::testing;
+    use tempfile::TempDir;
+
+    fn record(store: &TelemetrySqliteStore, session_id: &str, steps: &[(&str, Option<&str>)]) {
+        for (i, (event_type, tool_name)) in steps.iter().enumerate() {
+            store.submit_action(ActionRecord {
+                tool_name: tool_name.map(Into::into),
+                ..testing::action(
+                    session_id,
+                    &format!("{session_id}-t0"),
+                    1_000 + i as i64,
+                    event_type,
+                )
+            });
+        }
+    }
+
+    #[test]
+    fn diff_aligns_common_steps_and_reports_tool_changes() {
+        let tmp = TempDir::new().unwrap();
+        let store = TelemetrySqliteStore::open(tmp.path(), TelemetryConfig::default()).unwrap();
+        record(
+            &store,
+            "a",
+            &[
+                ("session_start", None),
+                ("llm_response", None),
+                ("tool_call", Some("shell")),
+                ("llm_response", None),
+                ("turn_complete", None),
+            ],
+        );
+        record(
+            &store,
+            "b",
+            &[
+                ("llm_response", None),
+                ("tool_call", Some("file_read")),
+                ("tool_call", Some("shell")),
+                ("turn_complete", None),
+            ],
+        );
+        drop(store);
+
+        let reader = TelemetryReader::open(&tmp.path().join("research.db")).unwrap();
+        let diff = diff_sessions(&reader, "a", "b").unwrap();
+        assert_eq!(
+            diff.common_steps,
+            ["llm_response", "tool_call", "turn_complete"]
+        );
+        assert_eq!(diff.only_in_a, ["llm_response"]);
+        assert_eq!(diff.only_in_b, ["tool_call"]);
+        assert_eq!(
+            diff.tool_call_changes,
+            [ToolCallChange {
+                index_a: 1,
+                index_b: 1,
+                tool_a: Some("shell".into()),
+                tool_b: Some("file_read".into()),
+            }]
+        );
+    }
+
+    #[test]
+    fn identical_sessions_have_no_differences() {
+        let tmp = TempDir::new().unwrap();
+        let store = TelemetrySqliteStore::open(tmp.path(), TelemetryConfig::default()).unwrap();
+        let steps = [("llm_response", None), ("tool_call", Some("shell"))];
+        record(&store, "a", &steps);
+        record(&store, "b", &steps);
+        drop(store);
+
+        let reader = TelemetryReader::open(&tmp.path().join("research.db")).unwrap();
+        let diff = diff_sessions(&reader, "a", "b").unwrap();
+        assert_eq!(diff.common_steps.len(), 2);
+        assert!(diff.only_in_a.is_empty() && diff.only_in_b.is_empty());
+        assert!(diff.tool_call_changes.is_empty());
+    }
+}
@@ -1,19 +0,0 @@
-/// Try to read the eBPF syscall frequency map.
-///
-/// Returns `Some(json_string)` when the eBPF tracing feature is available
-/// and active, `None` otherwise.
-///
-/// The actual eBPF implementation requires `cfg(all(target_os = "linux",
-/// feature = "telemetry-ebpf"))` and the `aya` crate. This stub provides
-/// the fallback.
-pub fn try_read_syscall_freq() -> Option<String> {
-    #[cfg(all(target_os = "linux", feature = "telemetry-ebpf"))]
-    {
-        // TODO: Implement eBPF syscall tracing with aya when the feature is enabled.
-        None
-    }
-    #[cfg(not(all(target_os = "linux", feature = "telemetry-ebpf")))]
-    {
-        None
-    }
-}
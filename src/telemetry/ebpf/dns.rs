@@ -0,0 +1,184 @@
+//! Records hostname lookups made by the agent process, via a
+//! uprobe/uretprobe pair on libc's `getaddrinfo`.
+//!
+//! Each lookup becomes a [`DnsQuery`] row in `dns_queries` with its
+//! resolution time; [`TelemetryReader::dns_queries_with_tool_calls`] matches
+//! them to the tool calls that made them. Resolvers that bypass libc (e.g. a
+//! statically linked async resolver) are not seen.
+//!
+//! [`TelemetryReader::dns_queries_with_tool_calls`]:
+//!     crate::telemetry::reader::TelemetryReader::dns_queries_with_tool_calls
+
+use std::sync::Weak;
+
+use anyhow::{Context, Result};
+use aya::maps::{MapData, RingBuf};
+use aya::programs::UProbe;
+use aya::{include_bytes_aligned, Ebpf};
+use chrono::{DateTime, Utc};
+use tokio::io::unix::AsyncFd;
+use tokio::task::JoinHandle;
+
+use crate::telemetry::store::{DnsQuery, TelemetrySqliteStore};
+
+static DNS_OBJ: &[u8] = include_bytes_aligned!(concat!(env!("OUT_DIR"), "/dns.bpf.o"));
+
+/// Capacity of the hostname buffer in the BPF event; longer names are
+/// truncated by the kernel.
+const HOSTNAME_LEN: usize = 128;
+
+/// Event layout written by `bpf/dns.bpf.c`.
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct RawDnsEvent {
+    /// `CLOCK_MONOTONIC` nanoseconds when `getaddrinfo` was entered.
+    start_ns: u64,
+    duration_ns: u64,
+    pid: u32,
+    /// `getaddrinfo` return value; 0 on success, an `EAI_*` code otherwise.
+    ret: i32,
+    hostname: [u8; HOSTNAME_LEN],
+}
+
+impl RawDnsEvent {
+    /// Decode a ring buffer entry; `None` for short reads.
+    fn parse(bytes: &[u8]) -> Option<Self> {
+        if bytes.len() < std::mem::size_of::<Self>() {
+            return None;
+        }
+        // SAFETY: length checked above; RawDnsEvent is plain old data and
+        // read_unaligned has no alignment requirement.
+        Some(unsafe { std::ptr::read_unaligned(bytes.as_ptr().cast::<Self>()) })
+    }
+
+    /// Build the row, timestamped when the lookup started. `start_ns` is
+    /// mapped onto the wall clock through a paired reading of both clocks
+    /// (`monotonic_now_ns` taken at `wall_now`), so the time the ring buffer
+    /// was drained does not leak into the timestamp.
+    fn into_query(self, monotonic_now_ns: u64, wall_now: DateTime<Utc>) -> DnsQuery {
+        let age_ns = monotonic_now_ns.saturating_sub(self.start_ns);
+        let started =
+            wall_now - chrono::Duration::nanoseconds(i64::try_from(age_ns).unwrap_or(i64::MAX));
+        let len = self
+            .hostname
+            .iter()
+            .position(|&b| b == 0)
+            .unwrap_or(HOSTNAME_LEN);
+        DnsQuery {
+            ts: started.to_rfc3339(),
+            ts_epoch_ms: started.timestamp_millis(),
+            hostname: String::from_utf8_lossy(&self.hostname[..len]).into_owned(),
+            resolution_ms: self.duration_ns as f64 / 1_000_000.0,
+            success: self.ret == 0,
+        }
+    }
+}
+
+/// Current `CLOCK_MONOTONIC` time, the clock behind `bpf_ktime_get_ns`.
+fn monotonic_now_ns() -> u64 {
+    let mut now = libc::timespec {
+        tv_sec: 0,
+        tv_nsec: 0,
+    };
+    // SAFETY: `now` is a valid, writable timespec.
+    unsafe { libc::clock_gettime(libc::CLOCK_MONOTONIC, &raw mut now) };
+    u64::try_from(now.tv_sec).unwrap_or(0) * 1_000_000_000 + u64::try_from(now.tv_nsec).unwrap_or(0)
+}
+
+/// Attach the `getaddrinfo` probes to this process's libc and spawn a task
+/// that writes each lookup to the `dns_queries` table.
+///
+/// Must be called from within a tokio runtime. Fails when the kernel refuses
+/// the programs or libc cannot be located.
+pub fn spawn_dns_tracker(store: Weak<TelemetrySqliteStore>) -> Result<JoinHandle<()>> {
+    let pid = i32::try_from(std::process::id()).context("pid does not fit in pid_t")?;
+    let mut bpf = Ebpf::load(DNS_OBJ).context("failed to load dns eBPF object")?;
+    for name in ["getaddrinfo_enter", "getaddrinfo_exit"] {
+        let program: &mut UProbe = bpf
+            .program_mut(name)
+            .with_context(|| format!("{name} program missing from eBPF object"))?
+            .try_into()
+            .with_context(|| format!("{name} is not a uprobe program"))?;
+        program
+            .load()
+            .with_context(|| format!("kernel rejected {name}"))?;
+        program
+            .attach(Some("getaddrinfo"), 0, "libc", Some(pid))
+            .with_context(|| format!("failed to attach {name} to libc getaddrinfo"))?;
+    }
+
+    let ring = bpf
+        .take_map("DNS_QUERIES")
+        .context("DNS_QUERIES map missing from eBPF object")?;
+    let ring = RingBuf::try_from(ring).context("DNS_QUERIES is not a ring buffer")?;
+    let ring = AsyncFd::new(ring).context("failed to register DNS_QUERIES with tokio")?;
+
+    Ok(tokio::spawn(read_events(bpf, ring, store)))
+}
+
+/// Drain the ring buffer until the store is dropped or the task is aborted.
+/// `_bpf` is held so the probes stay attached for as long as the task runs.
+async fn read_events(
+    _bpf: Ebpf,
+    mut ring: AsyncFd<RingBuf<MapData>>,
+    store: Weak<TelemetrySqliteStore>,
+) {
+    loop {
+        let mut guard = match ring.readable_mut().await {
+            Ok(guard) => guard,
+            Err(e) => {
+                tracing::warn!("DNS tracker stopped: {e}");
+                return;
+            }
+        };
+        let Some(store) = store.upgrade() else {
+            return;
+        };
+        let (monotonic_now, wall_now) = (monotonic_now_ns(), Utc::now());
+        let buf = guard.get_inner_mut();
+        while let Some(item) = buf.next() {
+            if let Some(raw) = RawDnsEvent::parse(&item) {
+                store.submit_dns_query(raw.into_query(monotonic_now, wall_now));
+            }
+        }
+        guard.clear_ready();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decodes_lookup_events() {
+        let mut hostname = [0u8; HOSTNAME_LEN];
+        hostname[..11].copy_from_slice(b"example.com");
+        let raw = RawDnsEvent {
+            start_ns: 10_000_000_000,
+            duration_ns: 2_500_000,
+            pid: 42,
+            ret: -2,
+            hostname,
+        };
+        // SAFETY: RawDnsEvent is repr(C) with no padding.
+        let bytes = unsafe {
+            std::slice::from_raw_parts(
+                std::ptr::from_ref(&raw).cast::<u8>(),
+                std::mem::size_of::<RawDnsEvent>(),
+            )
+        };
+        let wall_now = DateTime::parse_from_rfc3339("2026-01-01T00:00:01Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        // Drained 750ms after the lookup started.
+        let query = RawDnsEvent::parse(bytes)
+            .unwrap()
+            .into_query(10_750_000_000, wall_now);
+        assert_eq!(query.hostname, "example.com");
+        assert_eq!(query.ts_epoch_ms, wall_now.timestamp_millis() - 750);
+        assert_eq!(query.ts, "2026-01-01T00:00:00.250+00:00");
+        assert!((query.resolution_ms - 2.5).abs() < f64::EPSILON);
+        assert!(!query.success);
+        assert!(RawDnsEvent::parse(&bytes[..16]).is_none());
+    }
+}
@@ -0,0 +1,231 @@
+//! Records which paths the agent process opens, via an eBPF program on
+//! `tracepoint/syscalls/sys_enter_openat`.
+//!
+//! Each open becomes an [`ActionRecord`] with `event_type = "file_open"` and
+//! the path in `tool_name`; the pid and raw open flags go in `metadata_json`.
+//! Paths under `/proc` and `/sys` are dropped — the runtime and sysinfo read
+//! them constantly and they would drown out everything else.
+
+use std::sync::Weak;
+
+use anyhow::{Context, Result};
+use aya::maps::{MapData, RingBuf};
+use aya::programs::TracePoint;
+use aya::{include_bytes_aligned, Ebpf, EbpfLoader};
+use tokio::io::unix::AsyncFd;
+use tokio::task::JoinHandle;
+
+use crate::telemetry::store::{ActionRecord, TelemetrySqliteStore};
+
+static FILE_ACCESS_OBJ: &[u8] =
+    include_bytes_aligned!(concat!(env!("OUT_DIR"), "/file_access.bpf.o"));
+
+/// Capacity of the filename buffer in the BPF event; longer paths are
+/// truncated by the kernel.
+const PATH_MAX_LEN: usize = 256;
+
+/// Path prefixes that are never recorded.
+const NOISE_PREFIXES: &[&str] = &["/proc", "/sys"];
+
+/// Event layout written by `bpf/file_access.bpf.c`.
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct RawOpenEvent {
+    pid: u32,
+    flags: i32,
+    filename: [u8; PATH_MAX_LEN],
+}
+
+/// A decoded `openat` event.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileOpenEvent {
+    pub pid: u32,
+    pub filename: String,
+    pub flags: i32,
+}
+
+impl FileOpenEvent {
+    /// Decode a ring buffer entry. Returns `None` for short reads or an
+    /// empty filename (the kernel could not copy the user string).
+    fn parse(bytes: &[u8]) -> Option<Self> {
+        if bytes.len() < std::mem::size_of::<RawOpenEvent>() {
+            return None;
+        }
+        // SAFETY: length checked above; RawOpenEvent is plain old data and
+        // read_unaligned has no alignment requirement.
+        let raw: RawOpenEvent =
+            unsafe { std::ptr::read_unaligned(bytes.as_ptr().cast::<RawOpenEvent>()) };
+        let len = raw
+            .filename
+            .iter()
+            .position(|&b| b == 0)
+            .unwrap_or(PATH_MAX_LEN);
+        if len == 0 {
+            return None;
+        }
+        Some(Self {
+            pid: raw.pid,
+            filename: String::from_utf8_lossy(&raw.filename[..len]).into_owned(),
+            flags: raw.flags,
+        })
+    }
+
+    fn into_record(self, session_id: &str, sequence_index: i64) -> ActionRecord {
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default();
+        let metadata = serde_json::json!({ "pid": self.pid, "flags": self.flags });
+        ActionRecord {
+            ts: chrono::Utc::now().to_rfc3339(),
+            ts_epoch_ms: i64::try_from(now.as_millis()).unwrap_or(i64::MAX),
+            session_id: session_id.to_string(),
+            sequence_index,
+            event_type: "file_open".into(),
+            tool_name: Some(self.filename),
+            metadata_json: Some(metadata.to_string()),
+            ..ActionRecord::default()
+        }
+    }
+}
+
+/// Whether `path` is pseudo-filesystem noise that should not be recorded.
+pub fn is_noise_path(path: &str) -> bool {
+    NOISE_PREFIXES.iter().any(|prefix| {
+        path.strip_prefix(prefix)
+            .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+    })
+}
+
+/// Load the `openat` tracer for this process and spawn a task that turns
+/// its events into `file_open` action records tagged with `session_id`.
+///
+/// Must be called from within a tokio runtime. Fails when the kernel refuses
+/// the program (usually missing `CAP_BPF`/`CAP_PERFMON`).
+pub fn spawn_file_access_tracker(
+    store: Weak<TelemetrySqliteStore>,
+    session_id: String,
+) -> Result<JoinHandle<()>> {
+    let pid = std::process::id();
+    let mut bpf = EbpfLoader::new()
+        .set_global("TARGET_TGID", &pid, true)
+        .load(FILE_ACCESS_OBJ)
+        .context("failed to load file_access eBPF object")?;
+    let program: &mut TracePoint = bpf
+        .program_mut("file_access")
+        .context("file_access program missing from eBPF object")?
+        .try_into()
+        .context("file_access is not a tracepoint program")?;
+    program.load().context("kernel rejected file_access")?;
+    program
+        .attach("syscalls", "sys_enter_openat")
+        .context("failed to attach syscalls/sys_enter_openat")?;
+
+    let ring = bpf
+        .take_map("FILE_OPENS")
+        .context("FILE_OPENS map missing from eBPF object")?;
+    let ring = RingBuf::try_from(ring).context("FILE_OPENS is not a ring buffer")?;
+    let ring = AsyncFd::new(ring).context("failed to register FILE_OPENS with tokio")?;
+
+    Ok(tokio::spawn(read_events(bpf, ring, store, session_id)))
+}
+
+/// Drain the ring buffer until the store is dropped or the task is aborted.
+/// `_bpf` is held so the program stays attached for as long as the task
+/// runs.
+async fn read_events(
+    _bpf: Ebpf,
+    mut ring: AsyncFd<RingBuf<MapData>>,
+    store: Weak<TelemetrySqliteStore>,
+    session_id: String,
+) {
+    let mut sequence_index = 0i64;
+    loop {
+        let mut guard = match ring.readable_mut().await {
+            Ok(guard) => guard,
+            Err(e) => {
+                tracing::warn!("file access tracker stopped: {e}");
+                return;
+            }
+        };
+        let Some(store) = store.upgrade() else {
+            return;
+        };
+        let buf = guard.get_inner_mut();
+        while let Some(item) = buf.next() {
+            let Some(event) = FileOpenEvent::parse(&item) else {
+                continue;
+            };
+            if is_noise_path(&event.filename) {
+                continue;
+            }
+            store.submit_action(event.into_record(&session_id, sequence_index));
+            sequence_index += 1;
+        }
+        guard.clear_ready();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn raw_event(pid: u32, flags: i32, path: &str) -> Vec<u8> {
+        let mut filename = [0u8; PATH_MAX_LEN];
+        filename[..path.len()].copy_from_slice(path.as_bytes());
+        let raw = RawOpenEvent {
+            pid,
+            flags,
+            filename,
+        };
+        // SAFETY: RawOpenEvent is repr(C) with no padding.
+        unsafe {
+            std::slice::from_raw_parts(
+                std::ptr::from_ref(&raw).cast::<u8>(),
+                std::mem::size_of::<RawOpenEvent>(),
+            )
+        }
+        .to_vec()
+    }
+
+    #[test]
+    fn parses_ring_buffer_entries() {
+        let event = FileOpenEvent::parse(&raw_event(42, 0o100, "/tmp/notes.md")).unwrap();
+        assert_eq!(
+            event,
+            FileOpenEvent {
+                pid: 42,
+                filename: "/tmp/notes.md".into(),
+                flags: 0o100,
+            }
+        );
+        assert!(FileOpenEvent::parse(&raw_event(42, 0, "")).is_none());
+        assert!(FileOpenEvent::parse(&[0u8; 4]).is_none());
+    }
+
+    #[test]
+    fn filters_proc_and_sys_paths() {
+        assert!(is_noise_path("/proc/self/stat"));
+        assert!(is_noise_path("/sys"));
+        assert!(is_noise_path("/sys/fs/cgroup/cpu.max"));
+        assert!(!is_noise_path("/procedures/run.md"));
+        assert!(!is_noise_path("/home/user/.config/zeroclaw/config.toml"));
+    }
+
+    #[test]
+    fn records_file_open_action() {
+        let event = FileOpenEvent {
+            pid: 7,
+            filename: "/etc/hosts".into(),
+            flags: 0,
+        };
+        let record = event.into_record("sess-1", 3);
+        assert_eq!(record.event_type, "file_open");
+        assert_eq!(record.tool_name.as_deref(), Some("/etc/hosts"));
+        assert_eq!(record.session_id, "sess-1");
+        assert_eq!(record.sequence_index, 3);
+        assert_eq!(
+            record.metadata_json.as_deref(),
+            Some(r#"{"flags":0,"pid":7}"#)
+        );
+    }
+}
@@ -0,0 +1,252 @@
+//! eBPF-backed process tracing (Linux, `telemetry-ebpf` feature).
+//!
+//! The BPF objects are compiled from `bpf/*.bpf.c` by `build.rs` and embedded
+//! into the binary. Everything here degrades to `None` when the feature is
+//! off, the target is not Linux, or the kernel refuses to load the program
+//! (missing privileges, no BTF, locked-down kernel, ...).
+
+#[cfg(all(target_os = "linux", feature = "telemetry-ebpf"))]
+pub mod dns;
+#[cfg(all(target_os = "linux", feature = "telemetry-ebpf"))]
+pub mod file_access;
+#[cfg(all(target_os = "linux", feature = "telemetry-ebpf"))]
+pub mod network;
+#[cfg(all(target_os = "linux", feature = "telemetry-ebpf"))]
+mod syscalls;
+
+#[cfg(all(target_os = "linux", feature = "telemetry-ebpf"))]
+pub use syscalls::syscall_name;
+#[cfg(all(target_os = "linux", feature = "telemetry-ebpf"))]
+pub use trackers::EbpfTrackers;
+
+/// Number of syscalls reported per sample.
+pub const TOP_SYSCALLS: usize = 20;
+
+/// Try to read the eBPF syscall frequency map.
+///
+/// Returns `Some(json_string)` when the eBPF tracing feature is available
+/// and active, `None` otherwise. The JSON object maps syscall names to the
+/// number of calls made by this process since the previous read, limited to
+/// the [`TOP_SYSCALLS`] most frequent.
+pub fn try_read_syscall_freq() -> Option<String> {
+    #[cfg(all(target_os = "linux", feature = "telemetry-ebpf"))]
+    {
+        tracer::read_syscall_freq()
+    }
+    #[cfg(not(all(target_os = "linux", feature = "telemetry-ebpf")))]
+    {
+        None
+    }
+}
+
+/// Render per-syscall counts as a JSON object of the `limit` most frequent.
+/// Ties are broken by name so the selection is stable.
+fn top_counts_json(counts: impl IntoIterator<Item = (String, u64)>, limit: usize) -> String {
+    let mut counts: Vec<(String, u64)> = counts.into_iter().filter(|(_, n)| *n > 0).collect();
+    counts.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    counts.truncate(limit);
+    let map: serde_json::Map<String, serde_json::Value> = counts
+        .into_iter()
+        .map(|(name, n)| (name, serde_json::Value::from(n)))
+        .collect();
+    serde_json::Value::Object(map).to_string()
+}
+
+#[cfg(all(target_os = "linux", feature = "telemetry-ebpf"))]
+mod trackers {
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Arc;
+
+    use tokio::task::JoinHandle;
+
+    use crate::telemetry::store::TelemetrySqliteStore;
+
+    /// Set while an [`EbpfTrackers`] is alive.
+    static ACTIVE: AtomicBool = AtomicBool::new(false);
+
+    /// The file access, network and DNS trackers. At most one set is loaded
+    /// per process; dropping it stops the reader tasks, which detaches the
+    /// probes.
+    pub struct EbpfTrackers {
+        tasks: Vec<JoinHandle<()>>,
+    }
+
+    impl EbpfTrackers {
+        /// Load the trackers and have them write to `store`, tagging file
+        /// opens with `session_id`. The tasks hold only a weak reference,
+        /// so they never keep the store open.
+        ///
+        /// Returns `None` while another `EbpfTrackers` is alive, so a second
+        /// agent in the same process does not record every event twice.
+        /// Must be called from within a tokio runtime. A tracker the kernel
+        /// refuses is logged and skipped.
+        pub fn start(store: &Arc<TelemetrySqliteStore>, session_id: &str) -> Option<Self> {
+            if ACTIVE.swap(true, Ordering::AcqRel) {
+                tracing::debug!("eBPF trackers already running in this process");
+                return None;
+            }
+            let store = Arc::downgrade(store);
+            let mut tasks = Vec::new();
+            match super::file_access::spawn_file_access_tracker(
+                store.clone(),
+                session_id.to_string(),
+            ) {
+                Ok(task) => tasks.push(task),
+                Err(e) => tracing::warn!("eBPF file access tracking unavailable: {e:#}"),
+            }
+            match super::network::spawn_network_tracker(store.clone()) {
+                Ok(task) => tasks.push(task),
+                Err(e) => tracing::warn!("eBPF network tracking unavailable: {e:#}"),
+            }
+            match super::dns::spawn_dns_tracker(store) {
+                Ok(task) => tasks.push(task),
+                Err(e) => tracing::warn!("eBPF DNS tracking unavailable: {e:#}"),
+            }
+            Some(Self { tasks })
+        }
+    }
+
+    impl Drop for EbpfTrackers {
+        fn drop(&mut self) {
+            for task in &self.tasks {
+                task.abort();
+            }
+            ACTIVE.store(false, Ordering::Release);
+        }
+    }
+}
+
+#[cfg(all(target_os = "linux", feature = "telemetry-ebpf"))]
+mod tracer {
+    use std::collections::HashMap;
+    use std::sync::OnceLock;
+
+    use anyhow::{Context, Result};
+    use aya::maps::HashMap as BpfHashMap;
+    use aya::programs::TracePoint;
+    use aya::{include_bytes_aligned, Ebpf, EbpfLoader};
+    use parking_lot::Mutex;
+
+    static SYSCALL_FREQ_OBJ: &[u8] =
+        include_bytes_aligned!(concat!(env!("OUT_DIR"), "/syscall_freq.bpf.o"));
+
+    struct SyscallTracer {
+        bpf: Ebpf,
+        /// Cumulative counts seen on the previous read, for per-interval deltas.
+        prev: HashMap<u32, u64>,
+    }
+
+    /// `None` once loading has failed, so we only try (and warn) once.
+    static TRACER: OnceLock<Option<Mutex<SyscallTracer>>> = OnceLock::new();
+
+    fn load() -> Result<SyscallTracer> {
+        let pid = std::process::id();
+        let mut bpf = EbpfLoader::new()
+            .set_global("TARGET_TGID", &pid, true)
+            .load(SYSCALL_FREQ_OBJ)
+            .context("failed to load syscall_freq eBPF object")?;
+        let program: &mut TracePoint = bpf
+            .program_mut("syscall_freq")
+            .context("syscall_freq program missing from eBPF object")?
+            .try_into()
+            .context("syscall_freq is not a tracepoint program")?;
+        program.load().context("kernel rejected syscall_freq")?;
+        program
+            .attach("raw_syscalls", "sys_enter")
+            .context("failed to attach raw_syscalls/sys_enter")?;
+        Ok(SyscallTracer {
+            bpf,
+            prev: HashMap::new(),
+        })
+    }
+
+    pub(super) fn read_syscall_freq() -> Option<String> {
+        let tracer = TRACER.get_or_init(|| match load() {
+            Ok(tracer) => Some(Mutex::new(tracer)),
+            Err(e) => {
+                tracing::warn!("eBPF syscall tracing unavailable: {e:#}");
+                None
+            }
+        });
+        let mut tracer = tracer.as_ref()?.lock();
+        match tracer.sample() {
+            Ok(json) => Some(json),
+            Err(e) => {
+                tracing::debug!("failed to read eBPF syscall counts: {e:#}");
+                None
+            }
+        }
+    }
+
+    impl SyscallTracer {
+        fn sample(&mut self) -> Result<String> {
+            let map = self
+                .bpf
+                .map("SYSCALL_COUNTS")
+                .context("SYSCALL_COUNTS map missing from eBPF object")?;
+            let counts: BpfHashMap<_, u32, u64> =
+                BpfHashMap::try_from(map).context("SYSCALL_COUNTS has an unexpected type")?;
+
+            let mut current = HashMap::new();
+            for entry in counts.iter() {
+                let (nr, total) = entry.context("failed to iterate SYSCALL_COUNTS")?;
+                current.insert(nr, total);
+            }
+
+            let deltas: Vec<(String, u64)> = current
+                .iter()
+                .map(|(&nr, &total)| {
+                    let prev = self.prev.get(&nr).copied().unwrap_or(0);
+                    (
+                        super::syscall_name(nr).into_owned(),
+                        total.saturating_sub(prev),
+                    )
+                })
+                .collect();
+            self.prev = current;
+            Ok(super::top_counts_json(deltas, super::TOP_SYSCALLS))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn top_counts_json_orders_and_truncates() {
+        let counts = vec![
+            ("read".to_string(), 5),
+            ("write".to_string(), 9),
+            ("close".to_string(), 5),
+            ("openat".to_string(), 0),
+        ];
+        assert_eq!(top_counts_json(counts, 2), r#"{"close":5,"write":9}"#);
+    }
+
+    #[cfg(all(target_os = "linux", feature = "telemetry-ebpf"))]
+    #[tokio::test]
+    async fn trackers_load_once_per_process() {
+        use crate::config::TelemetryConfig;
+        use crate::telemetry::store::TelemetrySqliteStore;
+        use std::sync::Arc;
+
+        let tmp = tempfile::TempDir::new().unwrap();
+        let store =
+            Arc::new(TelemetrySqliteStore::open(tmp.path(), TelemetryConfig::default()).unwrap());
+        let first = EbpfTrackers::start(&store, "sess-1").unwrap();
+        assert!(EbpfTrackers::start(&store, "sess-2").is_none());
+        drop(first);
+        let again = EbpfTrackers::start(&store, "sess-3");
+        assert!(again.is_some());
+        drop(again);
+        // The trackers never keep the store alive.
+        assert!(Arc::into_inner(store).is_some());
+    }
+
+    #[cfg(not(all(target_os = "linux", feature = "telemetry-ebpf")))]
+    #[test]
+    fn syscall_freq_is_none_without_feature() {
+        assert!(try_read_syscall_freq().is_none());
+    }
+}
@@ -0,0 +1,153 @@
+//! Records outbound TCP connections made by the agent process, via a
+//! kprobe/kretprobe pair on `tcp_connect`.
+//!
+//! Each connect becomes a [`NetworkEvent`] row in `network_events`;
+//! [`TelemetryReader::network_events_with_tool_calls`] matches them to the
+//! tool calls that made them. Only IPv4 destinations are captured.
+//!
+//! [`TelemetryReader::network_events_with_tool_calls`]:
+//!     crate::telemetry::reader::TelemetryReader::network_events_with_tool_calls
+
+use std::net::Ipv4Addr;
+use std::sync::Weak;
+
+use anyhow::{Context, Result};
+use aya::maps::{MapData, RingBuf};
+use aya::programs::KProbe;
+use aya::{include_bytes_aligned, Ebpf, EbpfLoader};
+use tokio::io::unix::AsyncFd;
+use tokio::task::JoinHandle;
+
+use crate::telemetry::store::{NetworkEvent, TelemetrySqliteStore};
+
+static NETWORK_OBJ: &[u8] = include_bytes_aligned!(concat!(env!("OUT_DIR"), "/network.bpf.o"));
+
+/// Event layout written by `bpf/network.bpf.c`.
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct RawConnectEvent {
+    pid: u32,
+    /// Network byte order.
+    daddr: u32,
+    /// Network byte order.
+    dport: u16,
+    success: u8,
+    pad: u8,
+}
+
+impl RawConnectEvent {
+    /// Decode a ring buffer entry; `None` for short reads.
+    fn parse(bytes: &[u8]) -> Option<Self> {
+        if bytes.len() < std::mem::size_of::<Self>() {
+            return None;
+        }
+        // SAFETY: length checked above; RawConnectEvent is plain old data and
+        // read_unaligned has no alignment requirement.
+        Some(unsafe { std::ptr::read_unaligned(bytes.as_ptr().cast::<Self>()) })
+    }
+
+    fn into_event(self) -> NetworkEvent {
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default();
+        NetworkEvent {
+            ts: chrono::Utc::now().to_rfc3339(),
+            ts_epoch_ms: i64::try_from(now.as_millis()).unwrap_or(i64::MAX),
+            pid: self.pid,
+            dest_ip: Ipv4Addr::from(self.daddr.to_ne_bytes()).to_string(),
+            dest_port: u16::from_be(self.dport),
+            success: self.success != 0,
+        }
+    }
+}
+
+/// Load the `tcp_connect` probes for this process and spawn a task that
+/// writes each connection to the `network_events` table.
+///
+/// Must be called from within a tokio runtime. Fails when the kernel refuses
+/// the programs (usually missing `CAP_BPF`/`CAP_PERFMON`).
+pub fn spawn_network_tracker(store: Weak<TelemetrySqliteStore>) -> Result<JoinHandle<()>> {
+    let pid = std::process::id();
+    let mut bpf = EbpfLoader::new()
+        .set_global("TARGET_TGID", &pid, true)
+        .load(NETWORK_OBJ)
+        .context("failed to load network eBPF object")?;
+    for name in ["tcp_connect_enter", "tcp_connect_exit"] {
+        let program: &mut KProbe = bpf
+            .program_mut(name)
+            .with_context(|| format!("{name} program missing from eBPF object"))?
+            .try_into()
+            .with_context(|| format!("{name} is not a kprobe program"))?;
+        program
+            .load()
+            .with_context(|| format!("kernel rejected {name}"))?;
+        program
+            .attach("tcp_connect", 0)
+            .with_context(|| format!("failed to attach {name} to tcp_connect"))?;
+    }
+
+    let ring = bpf
+        .take_map("TCP_CONNECTS")
+        .context("TCP_CONNECTS map missing from eBPF object")?;
+    let ring = RingBuf::try_from(ring).context("TCP_CONNECTS is not a ring buffer")?;
+    let ring = AsyncFd::new(ring).context("failed to register TCP_CONNECTS with tokio")?;
+
+    Ok(tokio::spawn(read_events(bpf, ring, store)))
+}
+
+/// Drain the ring buffer until the store is dropped or the task is aborted.
+/// `_bpf` is held so the probes stay attached for as long as the task runs.
+async fn read_events(
+    _bpf: Ebpf,
+    mut ring: AsyncFd<RingBuf<MapData>>,
+    store: Weak<TelemetrySqliteStore>,
+) {
+    loop {
+        let mut guard = match ring.readable_mut().await {
+            Ok(guard) => guard,
+            Err(e) => {
+                tracing::warn!("network tracker stopped: {e}");
+                return;
+            }
+        };
+        let Some(store) = store.upgrade() else {
+            return;
+        };
+        let buf = guard.get_inner_mut();
+        while let Some(item) = buf.next() {
+            if let Some(raw) = RawConnectEvent::parse(&item) {
+                store.submit_network_event(raw.into_event());
+            }
+        }
+        guard.clear_ready();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decodes_network_byte_order() {
+        let raw = RawConnectEvent {
+            pid: 42,
+            daddr: u32::from_ne_bytes([93, 184, 216, 34]),
+            dport: 443u16.to_be(),
+            success: 1,
+            pad: 0,
+        };
+        // SAFETY: RawConnectEvent is repr(C) with no padding.
+        let bytes = unsafe {
+            std::slice::from_raw_parts(
+                std::ptr::from_ref(&raw).cast::<u8>(),
+                std::mem::size_of::<RawConnectEvent>(),
+            )
+        };
+        let event = RawConnectEvent::parse(bytes).unwrap().into_event();
+        assert_eq!(event.pid, 42);
+        assert_eq!(event.dest_ip, "93.184.216.34");
+        assert_eq!(event.dest_port, 443);
+        assert!(event.success);
+        assert!(RawConnectEvent::parse(&bytes[..4]).is_none());
+    }
+}
@@ -1,5 +1,12 @@
+use crate::config::EmbeddingDim;
+use anyhow::{Context, Result};
 use sha2::Digest;
 
+/// Prefix marking an LZ4-compressed `tool_type_embedding` blob. Blobs
+/// without it are stored raw, either because compression would not have
+/// made them smaller or because an older build wrote them.
+const COMPRESSED_EMBEDDING_MAGIC: &[u8; 4] = b"LZ4\0";
+
 /// Compute a deterministic 256-bit embedding for a tool name using SHA-256.
 ///
 /// Returns the raw hash bytes (32 bytes = 32 dimensions) and the dimension count.
@@ -9,6 +16,143 @@ pub fn compute_tool_embedding(tool_name: &str) -> (Vec<u8>, usize) {
     (hash.to_vec(), 32)
 }
 
+/// Compute a deterministic tool-name embedding of `dim` bytes: SHA-512
+/// truncated to the requested size. Returns the bytes and their count.
+pub fn compute_tool_embedding_with_dim(tool_name: &str, dim: EmbeddingDim) -> (Vec<u8>, usize) {
+    let mut hash = sha2::Sha512::digest(tool_name.as_bytes()).to_vec();
+    hash.truncate(dim.bytes());
+    (hash, dim.bytes())
+}
+
+/// Compute a unit-length tool-name embedding: the 32 SHA-256 bytes as
+/// `f32` values, L2-normalized so cosine similarity is a dot product.
+/// Stored with [`embedding_to_le_bytes`] (128 bytes).
+pub fn compute_normalized_tool_embedding(tool_name: &str) -> Vec<f32> {
+    let hash = sha2::Sha256::digest(tool_name.as_bytes());
+    let values: Vec<f32> = hash.iter().map(|&b| f32::from(b)).collect();
+    l2_normalize(values)
+}
+
+/// Scale `values` to unit length; an all-zero vector is returned unchanged.
+pub fn l2_normalize(mut values: Vec<f32>) -> Vec<f32> {
+    let norm = values.iter().map(|v| v * v).sum::<f32>().sqrt();
+    if norm > 0.0 {
+        for v in &mut values {
+            *v /= norm;
+        }
+    }
+    values
+}
+
+/// Encode an `f32` embedding as little-endian bytes.
+pub fn embedding_to_le_bytes(values: &[f32]) -> Vec<u8> {
+    values.iter().flat_map(|v| v.to_le_bytes()).collect()
+}
+
+/// Reverse [`embedding_to_le_bytes`]; trailing bytes short of a full `f32`
+/// are ignored.
+pub fn embedding_from_le_bytes(bytes: &[u8]) -> Vec<f32> {
+    bytes
+        .chunks_exact(4)
+        .map(|c| f32::from_le_bytes([c[0], c[1], c[2], c[3]]))
+        .collect()
+}
+
+/// Compute a 64-bit SimHash of a tool name, returned as 8 big-endian bytes
+/// and the bit count.
+///
+/// The name is split on `_` and `-`, and each token's SHA-256 votes on every
+/// bit. Names sharing tokens (`file_read` / `file_write`) land a small
+/// [`hamming_distance`] apart, unlike [`compute_tool_embedding`].
+pub fn compute_simhash_embedding(tool_name: &str) -> (Vec<u8>, usize) {
+    let mut votes = [0i32; 64];
+    for token in tool_name.split(['_', '-']).filter(|t| !t.is_empty()) {
+        let hash = sha2::Sha256::digest(token.as_bytes());
+        let bits = u64::from_be_bytes(hash[..8].try_into().expect("SHA-256 is 32 bytes"));
+        for (i, vote) in votes.iter_mut().enumerate() {
+            *vote += if bits >> i & 1 == 1 { 1 } else { -1 };
+        }
+    }
+    let simhash = votes
+        .iter()
+        .enumerate()
+        .filter(|(_, &vote)| vote > 0)
+        .fold(0u64, |acc, (i, _)| acc | 1 << i);
+    (simhash.to_be_bytes().to_vec(), 64)
+}
+
+/// Number of differing bits between two equal-length embeddings.
+pub fn hamming_distance(a: &[u8], b: &[u8]) -> u32 {
+    a.iter().zip(b).map(|(x, y)| (x ^ y).count_ones()).sum()
+}
+
+/// Compute a deterministic 256-bit embedding for a tool call, covering both
+/// the tool name and its arguments.
+///
+/// The input is the tool name, a NUL separator and the canonical (sorted-key)
+/// JSON of the arguments, so the same call always yields the same embedding
+/// and a name cannot run into its arguments.
+pub fn compute_call_embedding(tool_name: &str, arguments: &serde_json::Value) -> (Vec<u8>, usize) {
+    let combined = format!("{tool_name}\0{}", canonicalize(arguments));
+    let hash = sha2::Sha256::digest(combined.as_bytes());
+    (hash.to_vec(), 32)
+}
+
+/// Hash tool-call arguments to a hex SHA-256 digest.
+///
+/// Object keys are sorted recursively before serialization so the hash does
+/// not depend on the order the caller built the JSON in.
+pub fn hash_arguments(args: &serde_json::Value) -> String {
+    let canonical = canonicalize(args).to_string();
+    hex::encode(sha2::Sha256::digest(canonical.as_bytes()))
+}
+
+/// LZ4-compress an embedding for storage in `tool_type_embedding`.
+///
+/// Hash-based embeddings are incompressible, so the raw bytes are kept
+/// whenever the compressed blob would not be smaller. Raw bytes that happen
+/// to start with the compression prefix are always compressed so they read
+/// back unambiguously.
+pub fn compress_embedding(bytes: &[u8]) -> Vec<u8> {
+    let mut out = COMPRESSED_EMBEDDING_MAGIC.to_vec();
+    out.extend(lz4_flex::compress_prepend_size(bytes));
+    if out.len() < bytes.len() || bytes.starts_with(COMPRESSED_EMBEDDING_MAGIC) {
+        out
+    } else {
+        bytes.to_vec()
+    }
+}
+
+/// Reverse [`compress_embedding`]. Blobs stored before compression was
+/// introduced are returned unchanged.
+pub fn decompress_embedding(bytes: &[u8]) -> Result<Vec<u8>> {
+    match bytes.strip_prefix(COMPRESSED_EMBEDDING_MAGIC) {
+        Some(compressed) => {
+            lz4_flex::decompress_size_prepended(compressed).context("corrupt compressed embedding")
+        }
+        None => Ok(bytes.to_vec()),
+    }
+}
+
+/// Rebuild `value` with every object's keys inserted in sorted order.
+fn canonicalize(value: &serde_json::Value) -> serde_json::Value {
+    match value {
+        serde_json::Value::Object(map) => {
+            let mut keys: Vec<&String> = map.keys().collect();
+            keys.sort();
+            let sorted = keys
+                .into_iter()
+                .map(|k| (k.clone(), canonicalize(&map[k])))
+                .collect();
+            serde_json::Value::Object(sorted)
+        }
+        serde_json::Value::Array(items) => {
+            serde_json::Value::Array(items.iter().map(canonicalize).collect())
+        }
+        other => other.clone(),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -35,4 +179,118 @@ mod tests {
         assert_eq!(bytes.len(), 32);
         assert_eq!(dim, 32);
     }
+
+    #[test]
+    fn embedding_with_dim_truncates_sha512() {
+        let (full, dim) = compute_tool_embedding_with_dim("shell", EmbeddingDim::D64);
+        assert_eq!((full.len(), dim), (64, 64));
+        for d in [EmbeddingDim::D8, EmbeddingDim::D16, EmbeddingDim::D32] {
+            let (bytes, dim) = compute_tool_embedding_with_dim("shell", d);
+            assert_eq!(dim, d.bytes());
+            assert_eq!(bytes, full[..dim]);
+        }
+    }
+
+    #[test]
+    fn normalized_embedding_is_unit_length() {
+        let embedding = compute_normalized_tool_embedding("shell");
+        assert_eq!(embedding.len(), 32);
+        let norm: f32 = embedding.iter().map(|v| v * v).sum();
+        assert!((norm - 1.0).abs() < 1e-6);
+
+        let bytes = embedding_to_le_bytes(&embedding);
+        assert_eq!(bytes.len(), 128);
+        assert_eq!(embedding_from_le_bytes(&bytes), embedding);
+        assert_eq!(l2_normalize(vec![0.0; 4]), [0.0; 4]);
+    }
+
+    #[test]
+    fn simhash_keeps_related_names_close() {
+        let (read, bits) = compute_simhash_embedding("file_read");
+        let (write, _) = compute_simhash_embedding("file_write");
+        let (shell, _) = compute_simhash_embedding("shell");
+        assert_eq!((read.len(), bits), (8, 64));
+        assert_eq!(read, compute_simhash_embedding("file-read").0);
+        assert!(hamming_distance(&read, &write) < hamming_distance(&read, &shell));
+        assert_eq!(
+            compute_simhash_embedding("shell").0,
+            compute_tool_embedding("shell").0[..8]
+        );
+    }
+
+    #[test]
+    fn call_embedding_depends_on_arguments() {
+        let (a, dim) = compute_call_embedding("shell", &serde_json::json!({"command": "ls"}));
+        let (b, _) = compute_call_embedding("shell", &serde_json::json!({"command": "rm"}));
+        let (name_only, _) = compute_tool_embedding("shell");
+        assert_eq!(dim, 32);
+        assert_eq!(a.len(), 32);
+        assert_ne!(a, b);
+        assert_ne!(a, name_only);
+    }
+
+    #[test]
+    fn call_embedding_ignores_key_order() {
+        let a = serde_json::json!({"path": "a.txt", "limit": 10});
+        let b: serde_json::Value = serde_json::from_str(r#"{"limit":10,"path":"a.txt"}"#).unwrap();
+        assert_eq!(
+            compute_call_embedding("file_read", &a),
+            compute_call_embedding("file_read", &b)
+        );
+    }
+
+    #[test]
+    fn arguments_hash_ignores_key_order() {
+        let a: serde_json::Value =
+            serde_json::from_str(r#"{"command":"ls","opts":{"all":true,"long":false}}"#).unwrap();
+        let b: serde_json::Value =
+            serde_json::from_str(r#"{"opts":{"long":false,"all":true},"command":"ls"}"#).unwrap();
+        assert_eq!(hash_arguments(&a), hash_arguments(&b));
+        assert_eq!(hash_arguments(&a).len(), 64);
+    }
+
+    #[test]
+    fn compressed_embedding_round_trips() {
+        // 1536-dim float32 vector with plenty of repetition.
+        let embedding: Vec<u8> = (0..1536u32)
+            .flat_map(|i| ((i % 16) as f32 / 16.0).to_le_bytes())
+            .collect();
+        let compressed = compress_embedding(&embedding);
+        assert!(compressed.len() < embedding.len() / 4);
+        assert_eq!(decompress_embedding(&compressed).unwrap(), embedding);
+
+        let legacy = compute_tool_embedding("shell").0;
+        assert_eq!(decompress_embedding(&legacy).unwrap(), legacy);
+        assert!(decompress_embedding(b"LZ4\0\xff\xff").is_err());
+    }
+
+    #[test]
+    fn incompressible_embedding_is_stored_raw() {
+        let (embedding, dims) = compute_tool_embedding("shell");
+        assert_eq!(dims, 32);
+        let stored = compress_embedding(&embedding);
+        assert_eq!(stored, embedding);
+        assert_eq!(decompress_embedding(&stored).unwrap(), embedding);
+
+        // Raw bytes that look compressed are compressed to stay unambiguous.
+        let mut lookalike = COMPRESSED_EMBEDDING_MAGIC.to_vec();
+        lookalike.extend_from_slice(&embedding[4..]);
+        let stored = compress_embedding(&lookalike);
+        assert_ne!(stored, lookalike);
+        assert_eq!(decompress_embedding(&stored).unwrap(), lookalike);
+    }
+
+    #[test]
+    fn arguments_hash_distinguishes_values() {
+        let a = serde_json::json!({"command": "ls"});
+        let b = serde_json::json!({"command": "rm"});
+        assert_ne!(hash_arguments(&a), hash_arguments(&b));
+    }
+
+    #[test]
+    fn call_embedding_separates_name_from_arguments() {
+        let (a, _) = compute_call_embedding("ls1", &serde_json::json!(2));
+        let (b, _) = compute_call_embedding("ls", &serde_json::json!(12));
+        assert_ne!(a, b);
+    }
 }
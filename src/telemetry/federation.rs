@@ -0,0 +1,107 @@
+use crate::telemetry::reader::{ActionEventRow, TelemetryReader};
+use anyhow::Result;
+use parking_lot::Mutex;
+use rayon::prelude::*;
+use std::collections::HashSet;
+
+/// Queries several telemetry databases — e.g. one per agent instance — as
+/// if they were one.
+///
+/// SQLite connections cannot be shared between threads, so each reader sits
+/// behind its own lock; queries fan out across readers in parallel.
+pub struct FederatedTelemetryReader {
+    readers: Vec<Mutex<TelemetryReader>>,
+}
+
+impl FederatedTelemetryReader {
+    pub fn new(readers: Vec<TelemetryReader>) -> Self {
+        Self {
+            readers: readers.into_iter().map(Mutex::new).collect(),
+        }
+    }
+
+    /// Up to `limit` action events from every database, merged by
+    /// `ts_epoch_ms`.
+    ///
+    /// Events that appear in more than one database (same session, turn and
+    /// sequence index — e.g. a replicated or copied database) are returned
+    /// once, from the first reader that has them.
+    pub fn export_action_events_merged(
+        &self,
+        since_epoch_ms: Option<i64>,
+        limit: usize,
+    ) -> Result<Vec<ActionEventRow>> {
+        let per_reader = self
+            .readers
+            .par_iter()
+            .map(|reader| reader.lock().export_action_events(since_epoch_ms, limit))
+            .collect::<Result<Vec<_>>>()?;
+
+        // Stable sort keeps reader order among equal timestamps, so the
+        // first reader's copy of a duplicate wins.
+        let mut merged: Vec<ActionEventRow> = per_reader.into_iter().flatten().collect();
+        merged.sort_by_key(|e| (e.ts_epoch_ms, e.sequence_index));
+        let mut seen = HashSet::new();
+        merged.retain(|e| seen.insert((e.session_id.clone(), e.turn_id.clone(), e.sequence_index)));
+        merged.truncate(limit);
+        Ok(merged)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::TelemetryConfig;
+    use crate::telemetry::store::{ActionRecord, TelemetrySqliteStore};
+    use crate::telemetry::testing;
+    use tempfile::TempDir;
+
+    fn node(tmp: &TempDir, name: &str, events: &[(&str, i64)]) -> TelemetryReader {
+        let dir = tmp.path().join(name);
+        let store = TelemetrySqliteStore::open(&dir, TelemetryConfig::default()).unwrap();
+        for &(session_id, ts_epoch_ms) in events {
+            store.submit_action(ActionRecord {
+                sequence_index: ts_epoch_ms,
+                ..testing::action(
+                    session_id,
+                    &format!("{session_id}-t0"),
+                    ts_epoch_ms,
+                    "tool_call",
+                )
+            });
+        }
+        drop(store);
+        TelemetryReader::open(&dir.join("research.db")).unwrap()
+    }
+
+    #[test]
+    fn merges_by_timestamp_and_dedupes() {
+        let tmp = TempDir::new().unwrap();
+        let federated = FederatedTelemetryReader::new(vec![
+            node(&tmp, "a", &[("a", 1_000), ("a", 3_000), ("shared", 5_000)]),
+            node(&tmp, "b", &[("b", 2_000), ("shared", 5_000), ("b", 6_000)]),
+        ]);
+
+        let events = federated.export_action_events_merged(None, 100).unwrap();
+        let order: Vec<(&str, i64)> = events
+            .iter()
+            .map(|e| (e.session_id.as_str(), e.ts_epoch_ms))
+            .collect();
+        assert_eq!(
+            order,
+            [
+                ("a", 1_000),
+                ("b", 2_000),
+                ("a", 3_000),
+                ("shared", 5_000),
+                ("b", 6_000),
+            ]
+        );
+
+        let limited = federated
+            .export_action_events_merged(Some(2_500), 2)
+            .unwrap();
+        assert_eq!(limited.len(), 2);
+        assert_eq!(limited[0].ts_epoch_ms, 3_000);
+    }
+}
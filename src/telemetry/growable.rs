@@ -0,0 +1,273 @@
+//! Multi-producer, single-consumer queue whose capacity starts small and
+//! doubles, up to a limit, whenever a send finds it full. Backs
+//! [`ChannelKind::AutoGrow`](crate::config::ChannelKind::AutoGrow) writer
+//! channels; errors mirror `std::sync::mpsc` so callers treat both alike.
+
+use parking_lot::{Condvar, Mutex, MutexGuard};
+use std::collections::VecDeque;
+use std::sync::mpsc::{RecvTimeoutError, SendError, TryRecvError, TrySendError};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+struct State<T> {
+    queue: VecDeque<T>,
+    capacity: usize,
+    senders: usize,
+    receiver_alive: bool,
+}
+
+struct Shared<T> {
+    state: Mutex<State<T>>,
+    not_empty: Condvar,
+    not_full: Condvar,
+    max: usize,
+}
+
+impl<T> Shared<T> {
+    /// Make room for one more item, doubling the capacity if the queue is
+    /// full and still below `max`. Returns whether there is room.
+    fn reserve_slot(&self, state: &mut MutexGuard<'_, State<T>>) -> bool {
+        if state.queue.len() < state.capacity {
+            return true;
+        }
+        if state.capacity >= self.max {
+            return false;
+        }
+        state.capacity = (state.capacity * 2).min(self.max);
+        tracing::info!(
+            "telemetry writer channel grew to {} (max {})",
+            state.capacity,
+            self.max
+        );
+        true
+    }
+
+    fn push(&self, state: &mut MutexGuard<'_, State<T>>, item: T) {
+        state.queue.push_back(item);
+        self.not_empty.notify_one();
+    }
+}
+
+/// Sending half of a [`channel`].
+pub(crate) struct GrowableSender<T> {
+    shared: Arc<Shared<T>>,
+}
+
+/// Receiving half of a [`channel`].
+pub(crate) struct GrowableReceiver<T> {
+    shared: Arc<Shared<T>>,
+}
+
+/// Create a queue holding `initial` items that may grow to `max`.
+pub(crate) fn channel<T>(initial: usize, max: usize) -> (GrowableSender<T>, GrowableReceiver<T>) {
+    let initial = initial.max(1);
+    let shared = Arc::new(Shared {
+        state: Mutex::new(State {
+            queue: VecDeque::with_capacity(initial),
+            capacity: initial,
+            senders: 1,
+            receiver_alive: true,
+        }),
+        not_empty: Condvar::new(),
+        not_full: Condvar::new(),
+        max: max.max(initial),
+    });
+    (
+        GrowableSender {
+            shared: shared.clone(),
+        },
+        GrowableReceiver { shared },
+    )
+}
+
+// Errors hand the item back to the caller, as std's senders do.
+#[allow(clippy::result_large_err)]
+impl<T> GrowableSender<T> {
+    /// Never blocks: grows the queue if it is full, and fails only once it
+    /// is at `max`.
+    pub(crate) fn try_send(&self, item: T) -> Result<(), TrySendError<T>> {
+        let mut state = self.shared.state.lock();
+        if !state.receiver_alive {
+            return Err(TrySendError::Disconnected(item));
+        }
+        if !self.shared.reserve_slot(&mut state) {
+            return Err(TrySendError::Full(item));
+        }
+        self.shared.push(&mut state, item);
+        Ok(())
+    }
+
+    /// Blocks while the queue is full at `max`.
+    pub(crate) fn send(&self, item: T) -> Result<(), SendError<T>> {
+        let mut state = self.shared.state.lock();
+        loop {
+            if !state.receiver_alive {
+                return Err(SendError(item));
+            }
+            if self.shared.reserve_slot(&mut state) {
+                self.shared.push(&mut state, item);
+                return Ok(());
+            }
+            self.shared.not_full.wait(&mut state);
+        }
+    }
+
+    /// Blocks while the queue is full at `max`, for at most `timeout`;
+    /// `Full` means the wait timed out.
+    pub(crate) fn send_timeout(&self, item: T, timeout: Duration) -> Result<(), TrySendError<T>> {
+        let deadline = Instant::now() + timeout;
+        let mut state = self.shared.state.lock();
+        loop {
+            if !state.receiver_alive {
+                return Err(TrySendError::Disconnected(item));
+            }
+            if self.shared.reserve_slot(&mut state) {
+                self.shared.push(&mut state, item);
+                return Ok(());
+            }
+            if Instant::now() >= deadline {
+                return Err(TrySendError::Full(item));
+            }
+            self.shared.not_full.wait_until(&mut state, deadline);
+        }
+    }
+
+    #[cfg(test)]
+    fn capacity(&self) -> usize {
+        self.shared.state.lock().capacity
+    }
+}
+
+impl<T> Clone for GrowableSender<T> {
+    fn clone(&self) -> Self {
+        self.shared.state.lock().senders += 1;
+        Self {
+            shared: self.shared.clone(),
+        }
+    }
+}
+
+impl<T> Drop for GrowableSender<T> {
+    fn drop(&mut self) {
+        let mut state = self.shared.state.lock();
+        state.senders -= 1;
+        if state.senders == 0 {
+            self.shared.not_empty.notify_all();
+        }
+    }
+}
+
+impl<T> GrowableReceiver<T> {
+    pub(crate) fn try_recv(&self) -> Result<T, TryRecvError> {
+        let mut state = self.shared.state.lock();
+        match state.queue.pop_front() {
+            Some(item) => {
+                self.shared.not_full.notify_one();
+                Ok(item)
+            }
+            None if state.senders == 0 => Err(TryRecvError::Disconnected),
+            None => Err(TryRecvError::Empty),
+        }
+    }
+
+    pub(crate) fn recv_timeout(&self, timeout: Duration) -> Result<T, RecvTimeoutError> {
+        let deadline = Instant::now() + timeout;
+        let mut state = self.shared.state.lock();
+        loop {
+            if let Some(item) = state.queue.pop_front() {
+                self.shared.not_full.notify_one();
+                return Ok(item);
+            }
+            if state.senders == 0 {
+                return Err(RecvTimeoutError::Disconnected);
+            }
+            if self
+                .shared
+                .not_empty
+                .wait_until(&mut state, deadline)
+                .timed_out()
+            {
+                return match state.queue.pop_front() {
+                    Some(item) => {
+                        self.shared.not_full.notify_one();
+                        Ok(item)
+                    }
+                    None => Err(RecvTimeoutError::Timeout),
+                };
+            }
+        }
+    }
+}
+
+impl<T> Drop for GrowableReceiver<T> {
+    fn drop(&mut self) {
+        self.shared.state.lock().receiver_alive = false;
+        self.shared.not_full.notify_all();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn doubles_up_to_max_then_reports_full() {
+        let (tx, rx) = channel(2, 5);
+        for i in 0..5 {
+            tx.try_send(i).unwrap();
+        }
+        assert_eq!(tx.capacity(), 5);
+        assert!(matches!(tx.try_send(5), Err(TrySendError::Full(5))));
+
+        assert_eq!(rx.try_recv(), Ok(0));
+        tx.try_send(5).unwrap();
+        let drained: Vec<_> = std::iter::from_fn(|| rx.try_recv().ok()).collect();
+        assert_eq!(drained, [1, 2, 3, 4, 5]);
+        assert_eq!(rx.try_recv(), Err(TryRecvError::Empty));
+    }
+
+    #[test]
+    fn blocked_send_resumes_when_receiver_drains() {
+        let (tx, rx) = channel(1, 1);
+        tx.send(0).unwrap();
+        let sender = std::thread::spawn(move || tx.send(1));
+        assert_eq!(rx.recv_timeout(Duration::from_secs(5)), Ok(0));
+        assert_eq!(rx.recv_timeout(Duration::from_secs(5)), Ok(1));
+        sender.join().unwrap().unwrap();
+        assert_eq!(
+            rx.recv_timeout(Duration::from_millis(10)),
+            Err(RecvTimeoutError::Disconnected)
+        );
+    }
+
+    #[test]
+    fn send_timeout_gives_up_while_full() {
+        let (tx, rx) = channel(1, 1);
+        tx.send(0).unwrap();
+        assert!(matches!(
+            tx.send_timeout(1, Duration::from_millis(10)),
+            Err(TrySendError::Full(1))
+        ));
+        let sender = std::thread::spawn(move || tx.send_timeout(1, Duration::from_secs(5)));
+        assert_eq!(rx.recv_timeout(Duration::from_secs(5)), Ok(0));
+        assert_eq!(rx.recv_timeout(Duration::from_secs(5)), Ok(1));
+        sender.join().unwrap().unwrap();
+    }
+
+    #[test]
+    fn dropping_either_half_disconnects_the_other() {
+        let (tx, rx) = channel::<u8>(1, 1);
+        let tx2 = tx.clone();
+        drop(tx);
+        assert_eq!(
+            rx.recv_timeout(Duration::from_millis(10)),
+            Err(RecvTimeoutError::Timeout)
+        );
+        drop(rx);
+        assert!(matches!(
+            tx2.try_send(1),
+            Err(TrySendError::Disconnected(1))
+        ));
+        assert_eq!(tx2.send(2), Err(SendError(2)));
+    }
+}
@@ -0,0 +1,547 @@
+//! gRPC ingestion endpoint for remote agents (`proto/telemetry.proto`).
+//!
+//! Message types are declared with prost derives that mirror the `.proto`
+//! file rather than generated at build time, so the default build needs
+//! neither `protoc` nor a build script. Keep both in sync;
+//! `proto_module_matches_proto_file` fails when they drift.
+
+use crate::security::pairing::constant_time_eq;
+use crate::telemetry::store::{ActionRecord, SystemSample, TelemetrySqliteStore};
+use anyhow::{Context as _, Result};
+use std::convert::Infallible;
+use std::future::{ready, Ready};
+use std::net::SocketAddr;
+use std::sync::Arc;
+use tokio::sync::Notify;
+use tonic::codegen::{http, Body, BoxFuture, Context, Poll, Service, StdError};
+use tonic::server::NamedService;
+use tonic::Status;
+use tonic_prost::ProstCodec;
+
+/// Fully-qualified gRPC service name from `proto/telemetry.proto`.
+pub const SERVICE_NAME: &str = "zeroclaw.telemetry.TelemetryIngest";
+
+/// Wire types for `proto/telemetry.proto`.
+pub mod proto {
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct ActionRecord {
+        #[prost(string, tag = "1")]
+        pub ts: String,
+        #[prost(int64, tag = "2")]
+        pub ts_epoch_ms: i64,
+        #[prost(string, tag = "3")]
+        pub session_id: String,
+        #[prost(string, tag = "4")]
+        pub turn_id: String,
+        #[prost(int64, tag = "5")]
+        pub sequence_index: i64,
+        #[prost(string, tag = "6")]
+        pub event_type: String,
+        #[prost(string, optional, tag = "7")]
+        pub provider: Option<String>,
+        #[prost(string, optional, tag = "8")]
+        pub model: Option<String>,
+        #[prost(string, optional, tag = "9")]
+        pub tool_name: Option<String>,
+        #[prost(bytes = "vec", optional, tag = "10")]
+        pub tool_type_embedding: Option<Vec<u8>>,
+        #[prost(string, optional, tag = "11")]
+        pub arguments_hash: Option<String>,
+        #[prost(bool, optional, tag = "12")]
+        pub tool_success: Option<bool>,
+        #[prost(int64, optional, tag = "13")]
+        pub duration_ms: Option<i64>,
+        #[prost(int64, optional, tag = "14")]
+        pub tokens_in: Option<i64>,
+        #[prost(int64, optional, tag = "15")]
+        pub tokens_out: Option<i64>,
+        #[prost(bool, tag = "16")]
+        pub is_user_initiated: bool,
+        #[prost(int64, tag = "17")]
+        pub iteration_index: i64,
+        #[prost(string, optional, tag = "18")]
+        pub previous_action_type: Option<String>,
+        #[prost(string, optional, tag = "19")]
+        pub turn_action_sequence: Option<String>,
+        #[prost(string, optional, tag = "20")]
+        pub error_message: Option<String>,
+        #[prost(string, optional, tag = "21")]
+        pub correlation_id: Option<String>,
+        #[prost(int64, optional, tag = "22")]
+        pub parent_action_id: Option<i64>,
+        #[prost(double, optional, tag = "23")]
+        pub estimated_cost_usd: Option<f64>,
+        #[prost(string, optional, tag = "24")]
+        pub metadata_json: Option<String>,
+        #[prost(uint32, tag = "25")]
+        pub call_depth: u32,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct SystemSample {
+        #[prost(string, tag = "1")]
+        pub ts: String,
+        #[prost(int64, tag = "2")]
+        pub ts_epoch_ms: i64,
+        #[prost(double, tag = "3")]
+        pub cpu_usage_pct: f64,
+        #[prost(int64, tag = "4")]
+        pub memory_used_bytes: i64,
+        #[prost(int64, tag = "5")]
+        pub memory_total_bytes: i64,
+        #[prost(int64, tag = "6")]
+        pub process_count: i64,
+        #[prost(int64, tag = "7")]
+        pub process_spawn_rate: i64,
+        #[prost(int64, tag = "8")]
+        pub file_read_bytes: i64,
+        #[prost(int64, tag = "9")]
+        pub file_write_bytes: i64,
+        #[prost(int64, tag = "10")]
+        pub net_connections: i64,
+        #[prost(double, tag = "11")]
+        pub dest_ip_entropy: f64,
+        #[prost(string, optional, tag = "12")]
+        pub tcp_state_json: Option<String>,
+        #[prost(string, optional, tag = "13")]
+        pub syscall_freq_json: Option<String>,
+    }
+
+    #[derive(Clone, Copy, PartialEq, prost::Message)]
+    pub struct ShutdownRequest {}
+
+    #[derive(Clone, Copy, PartialEq, prost::Message)]
+    pub struct Ack {}
+}
+
+impl From<proto::ActionRecord> for ActionRecord {
+    fn from(m: proto::ActionRecord) -> Self {
+        Self {
+            ts: m.ts,
+            ts_epoch_ms: m.ts_epoch_ms,
+            session_id: m.session_id,
+            turn_id: m.turn_id,
+            sequence_index: m.sequence_index,
+            event_type: m.event_type,
+            provider: m.provider,
+            model: m.model,
+            tool_name: m.tool_name,
+            tool_type_embedding: m.tool_type_embedding,
+            arguments_hash: m.arguments_hash,
+            tool_success: m.tool_success,
+            duration_ms: m.duration_ms,
+            tokens_in: m.tokens_in,
+            tokens_out: m.tokens_out,
+            is_user_initiated: m.is_user_initiated,
+            iteration_index: m.iteration_index,
+            previous_action_type: m.previous_action_type,
+            turn_action_sequence: m.turn_action_sequence,
+            error_message: m.error_message,
+            correlation_id: m.correlation_id,
+            parent_action_id: m.parent_action_id,
+            estimated_cost_usd: m.estimated_cost_usd,
+            metadata_json: m.metadata_json,
+            call_depth: m.call_depth,
+        }
+    }
+}
+
+impl From<proto::SystemSample> for SystemSample {
+    fn from(m: proto::SystemSample) -> Self {
+        Self {
+            ts: m.ts,
+            ts_epoch_ms: m.ts_epoch_ms,
+            cpu_usage_pct: m.cpu_usage_pct,
+            memory_used_bytes: m.memory_used_bytes,
+            memory_total_bytes: m.memory_total_bytes,
+            process_count: m.process_count,
+            process_spawn_rate: m.process_spawn_rate,
+            file_read_bytes: m.file_read_bytes,
+            file_write_bytes: m.file_write_bytes,
+            net_connections: m.net_connections,
+            dest_ip_entropy: m.dest_ip_entropy,
+            tcp_state_json: m.tcp_state_json,
+            syscall_freq_json: m.syscall_freq_json,
+        }
+    }
+}
+
+/// `TelemetryIngest` service that forwards every request to a local store.
+#[derive(Clone)]
+pub struct TelemetryIngestServer {
+    store: Arc<TelemetrySqliteStore>,
+    shutdown: Arc<Notify>,
+    shutdown_token: Option<Arc<str>>,
+}
+
+impl TelemetryIngestServer {
+    /// Serve `store`. `Shutdown` is refused until a token is set with
+    /// [`Self::with_shutdown_token`].
+    pub fn new(store: Arc<TelemetrySqliteStore>) -> Self {
+        Self {
+            store,
+            shutdown: Arc::new(Notify::new()),
+            shutdown_token: None,
+        }
+    }
+
+    /// Accept `Shutdown` from callers that send `authorization: Bearer
+    /// {token}` metadata.
+    pub fn with_shutdown_token(mut self, token: impl Into<String>) -> Self {
+        self.shutdown_token = Some(token.into().into());
+        self
+    }
+
+    /// Whether `metadata` carries the configured shutdown token.
+    fn authorize_shutdown(&self, metadata: &tonic::metadata::MetadataMap) -> Result<(), Status> {
+        let Some(expected) = self.shutdown_token.as_deref() else {
+            return Err(Status::permission_denied(
+                "Shutdown is disabled on this server",
+            ));
+        };
+        let presented = metadata
+            .get("authorization")
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "));
+        match presented {
+            Some(token) if constant_time_eq(token, expected) => Ok(()),
+            _ => Err(Status::unauthenticated("invalid shutdown token")),
+        }
+    }
+
+    /// Resolves once a client has called `Shutdown`.
+    pub async fn shutdown_requested(&self) {
+        self.shutdown.notified().await;
+    }
+}
+
+impl NamedService for TelemetryIngestServer {
+    const NAME: &'static str = SERVICE_NAME;
+}
+
+impl<B> Service<http::Request<B>> for TelemetryIngestServer
+where
+    B: Body + Send + 'static,
+    B::Error: Into<StdError> + Send + 'static,
+{
+    type Response = http::Response<tonic::body::Body>;
+    type Error = Infallible;
+    type Future = BoxFuture<Self::Response, Self::Error>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, req: http::Request<B>) -> Self::Future {
+        let this = self.clone();
+        Box::pin(async move {
+            let method = req
+                .uri()
+                .path()
+                .strip_prefix(&format!("/{SERVICE_NAME}/"))
+                .unwrap_or_default()
+                .to_string();
+            let response = match method.as_str() {
+                "SubmitAction" => {
+                    unary(req, |m: tonic::Request<proto::ActionRecord>| {
+                        this.store.submit_action(m.into_inner().into());
+                        Ok(proto::Ack {})
+                    })
+                    .await
+                }
+                "SubmitSystemSample" => {
+                    unary(req, |m: tonic::Request<proto::SystemSample>| {
+                        this.store.submit_system_sample(m.into_inner().into());
+                        Ok(proto::Ack {})
+                    })
+                    .await
+                }
+                "Shutdown" => {
+                    unary(req, |m: tonic::Request<proto::ShutdownRequest>| {
+                        this.authorize_shutdown(m.metadata())?;
+                        // Stores a permit if the server is not yet waiting.
+                        this.shutdown.notify_one();
+                        Ok(proto::Ack {})
+                    })
+                    .await
+                }
+                _ => Status::unimplemented(format!("unknown method {method}")).into_http(),
+            };
+            Ok(response)
+        })
+    }
+}
+
+/// Adapts a synchronous message handler to tonic's unary service shape.
+struct Unary<F>(F);
+
+impl<M, R, F> Service<tonic::Request<M>> for Unary<F>
+where
+    F: FnMut(tonic::Request<M>) -> Result<R, Status>,
+{
+    type Response = tonic::Response<R>;
+    type Error = Status;
+    type Future = Ready<Result<Self::Response, Status>>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Status>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, request: tonic::Request<M>) -> Self::Future {
+        ready((self.0)(request).map(tonic::Response::new))
+    }
+}
+
+async fn unary<M, R, F, B>(req: http::Request<B>, handler: F) -> http::Response<tonic::body::Body>
+where
+    M: prost::Message + Default + Send + 'static,
+    R: prost::Message + Send + 'static,
+    F: FnMut(tonic::Request<M>) -> Result<R, Status>,
+    B: Body + Send + 'static,
+    B::Error: Into<StdError> + Send + 'static,
+{
+    tonic::server::Grpc::new(ProstCodec::<R, M>::default())
+        .unary(Unary(handler), req)
+        .await
+}
+
+/// Accept `TelemetryIngest` calls on `addr` until a client calls `Shutdown`
+/// with `shutdown_token`. Without a token the server runs until the task is
+/// dropped.
+pub async fn serve_grpc(
+    store: Arc<TelemetrySqliteStore>,
+    addr: SocketAddr,
+    shutdown_token: Option<String>,
+) -> Result<()> {
+    let mut service = TelemetryIngestServer::new(store);
+    if let Some(token) = shutdown_token {
+        service = service.with_shutdown_token(token);
+    }
+    let shutdown = service.clone();
+    tracing::info!("telemetry gRPC ingestion listening on {addr}");
+    tonic::transport::Server::builder()
+        .add_service(service)
+        .serve_with_shutdown(addr, async move { shutdown.shutdown_requested().await })
+        .await
+        .context("telemetry gRPC server")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::TelemetryConfig;
+    use crate::telemetry::reader::TelemetryReader;
+    use http_body_util::Full;
+    use prost::Message;
+    use tempfile::TempDir;
+    use tonic::codegen::Bytes;
+
+    /// Frame `message` as a gRPC request to `method`.
+    fn grpc_request(method: &str, message: &impl Message) -> http::Request<Full<Bytes>> {
+        grpc_request_with_token(method, message, None)
+    }
+
+    /// [`grpc_request`] with an optional bearer token.
+    fn grpc_request_with_token(
+        method: &str,
+        message: &impl Message,
+        token: Option<&str>,
+    ) -> http::Request<Full<Bytes>> {
+        let payload = message.encode_to_vec();
+        let mut frame = vec![0u8];
+        frame.extend_from_slice(&u32::try_from(payload.len()).unwrap().to_be_bytes());
+        frame.extend_from_slice(&payload);
+        let mut builder = http::Request::builder()
+            .method("POST")
+            .uri(format!("/{SERVICE_NAME}/{method}"))
+            .header("content-type", "application/grpc");
+        if let Some(token) = token {
+            builder = builder.header("authorization", format!("Bearer {token}"));
+        }
+        builder.body(Full::new(Bytes::from(frame))).unwrap()
+    }
+
+    #[tokio::test]
+    async fn submit_action_reaches_store() {
+        let tmp = TempDir::new().unwrap();
+        let store =
+            Arc::new(TelemetrySqliteStore::open(tmp.path(), TelemetryConfig::default()).unwrap());
+        let mut service = TelemetryIngestServer::new(store.clone());
+
+        let message = proto::ActionRecord {
+            ts: "t".into(),
+            session_id: "remote".into(),
+            turn_id: "remote-t0".into(),
+            event_type: "tool_call".into(),
+            tool_name: Some("shell".into()),
+            call_depth: 2,
+            ..proto::ActionRecord::default()
+        };
+        let response = service
+            .call(grpc_request("SubmitAction", &message))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), http::StatusCode::OK);
+
+        drop(service);
+        drop(Arc::into_inner(store).unwrap());
+        let reader = TelemetryReader::open(&tmp.path().join("research.db")).unwrap();
+        let events = reader.export_session_events("remote").unwrap();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].tool_name.as_deref(), Some("shell"));
+        assert_eq!(events[0].call_depth, 2);
+    }
+
+    #[tokio::test]
+    async fn shutdown_rpc_signals_server() {
+        let tmp = TempDir::new().unwrap();
+        let store =
+            Arc::new(TelemetrySqliteStore::open(tmp.path(), TelemetryConfig::default()).unwrap());
+        let mut service = TelemetryIngestServer::new(store).with_shutdown_token("s3cret");
+        service
+            .call(grpc_request_with_token(
+                "Shutdown",
+                &proto::ShutdownRequest {},
+                Some("s3cret"),
+            ))
+            .await
+            .unwrap();
+        tokio::time::timeout(
+            std::time::Duration::from_secs(1),
+            service.shutdown_requested(),
+        )
+        .await
+        .expect("shutdown signalled");
+    }
+
+    /// gRPC status code of `response`, from the trailers-only header.
+    fn grpc_status(response: &http::Response<tonic::body::Body>) -> Option<&str> {
+        response
+            .headers()
+            .get("grpc-status")
+            .and_then(|v| v.to_str().ok())
+    }
+
+    #[tokio::test]
+    async fn shutdown_rpc_requires_token() {
+        let tmp = TempDir::new().unwrap();
+        let store =
+            Arc::new(TelemetrySqliteStore::open(tmp.path(), TelemetryConfig::default()).unwrap());
+
+        let mut open = TelemetryIngestServer::new(store.clone());
+        let response = open
+            .call(grpc_request("Shutdown", &proto::ShutdownRequest {}))
+            .await
+            .unwrap();
+        assert_eq!(
+            grpc_status(&response),
+            Some((tonic::Code::PermissionDenied as i32).to_string().as_str())
+        );
+
+        let mut guarded = TelemetryIngestServer::new(store).with_shutdown_token("s3cret");
+        let response = guarded
+            .call(grpc_request_with_token(
+                "Shutdown",
+                &proto::ShutdownRequest {},
+                Some("wrong"),
+            ))
+            .await
+            .unwrap();
+        assert_eq!(
+            grpc_status(&response),
+            Some((tonic::Code::Unauthenticated as i32).to_string().as_str())
+        );
+
+        for service in [&open, &guarded] {
+            let signalled = tokio::time::timeout(
+                std::time::Duration::from_millis(100),
+                service.shutdown_requested(),
+            )
+            .await;
+            assert!(
+                signalled.is_err(),
+                "rejected Shutdown must not stop the server"
+            );
+        }
+    }
+
+    /// `(message, field, type, optional, tag)` for every field in `source`,
+    /// read from either the `.proto` file or the `proto` module above.
+    type Field = (String, String, String, bool, u32);
+
+    fn proto_file_fields(source: &str) -> Vec<Field> {
+        let mut fields = Vec::new();
+        let mut message = None;
+        for line in source.lines().map(str::trim) {
+            if let Some(rest) = line.strip_prefix("message ") {
+                message = rest.split_whitespace().next().map(str::to_string);
+            } else if line == "}" {
+                message = None;
+            } else if let (Some(message), Some((decl, tag))) = (&message, line.split_once(" = ")) {
+                let mut words: Vec<&str> = decl.split_whitespace().collect();
+                let optional = words.first() == Some(&"optional");
+                if optional {
+                    words.remove(0);
+                }
+                let [ty, name] = words[..] else {
+                    panic!("unexpected field line: {line}");
+                };
+                let tag = tag.trim_end_matches(';').parse().unwrap();
+                fields.push((message.clone(), name.into(), ty.into(), optional, tag));
+            }
+        }
+        fields
+    }
+
+    fn prost_module_fields(source: &str) -> Vec<Field> {
+        let module = source
+            .split_once("pub mod proto {")
+            .and_then(|(_, rest)| rest.split_once("\n}\n"))
+            .expect("proto module")
+            .0;
+        let mut fields = Vec::new();
+        let mut message = None;
+        let mut attribute: Option<(String, bool, u32)> = None;
+        for line in module.lines().map(str::trim) {
+            if let Some(rest) = line.strip_prefix("pub struct ") {
+                message = rest.split_whitespace().next().map(str::to_string);
+            } else if let Some(args) = line
+                .strip_prefix("#[prost(")
+                .and_then(|rest| rest.strip_suffix(")]"))
+            {
+                let args: Vec<&str> = args.split(", ").collect();
+                let ty = args[0].split(' ').next().unwrap().to_string();
+                let optional = args.contains(&"optional");
+                let tag = args
+                    .iter()
+                    .find_map(|arg| arg.strip_prefix("tag = \""))
+                    .and_then(|tag| tag.strip_suffix('"'))
+                    .expect("prost tag")
+                    .parse()
+                    .unwrap();
+                attribute = Some((ty, optional, tag));
+            } else if let Some(rest) = line.strip_prefix("pub ") {
+                let (ty, optional, tag) = attribute.take().expect("prost attribute");
+                let name = rest.split(':').next().unwrap().to_string();
+                fields.push((message.clone().unwrap(), name, ty, optional, tag));
+            }
+        }
+        fields
+    }
+
+    #[test]
+    fn proto_module_matches_proto_file() {
+        let proto_file = include_str!("../../proto/telemetry.proto");
+        let expected = proto_file_fields(proto_file);
+        assert!(!expected.is_empty());
+        assert_eq!(prost_module_fields(include_str!("grpc.rs")), expected);
+
+        let package = proto_file
+            .lines()
+            .find_map(|line| line.strip_prefix("package "))
+            .and_then(|rest| rest.strip_suffix(';'))
+            .unwrap();
+        assert_eq!(SERVICE_NAME, format!("{package}.TelemetryIngest"));
+        assert!(proto_file.contains("service TelemetryIngest {"));
+    }
+}
@@ -0,0 +1,138 @@
+use crate::telemetry::store::{ActionRecord, TelemetrySqliteStore};
+use anyhow::{Context, Result};
+use std::io::{BufRead, BufReader};
+use std::time::Duration;
+
+/// How long each imported record may wait for space in the store's channel
+/// before it is counted as an error.
+const SUBMIT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Outcome of [`import_action_events_json`].
+#[derive(Debug, Clone, Default, PartialEq, Eq, serde::Serialize)]
+pub struct ImportReport {
+    /// Records queued for the store's writer.
+    pub imported: usize,
+    /// Blank lines.
+    pub skipped: usize,
+    /// Lines that did not parse as an action record, or whose record the
+    /// store did not accept.
+    pub errors: usize,
+}
+
+/// Replay newline-delimited JSON action events (as written by
+/// [`TelemetryReader::export_action_events_ndjson`]) into `store`.
+///
+/// Each line is deserialized into an [`ActionRecord`] and submitted with
+/// [`TelemetrySqliteStore::submit_action_blocking`]; fields the source lacks
+/// take their defaults and fields the store does not know are ignored. Lines
+/// that still fail to parse, and records not accepted within
+/// [`SUBMIT_TIMEOUT`] (or after the store shut down), are counted in
+/// [`ImportReport::errors`] and logged. Row ids are not preserved, so
+/// `parent_action_id` keeps pointing at the source database's ids.
+///
+/// [`TelemetryReader::export_action_events_ndjson`]:
+///     crate::telemetry::reader::TelemetryReader::export_action_events_ndjson
+pub fn import_action_events_json(
+    store: &TelemetrySqliteStore,
+    reader: &mut dyn std::io::Read,
+) -> Result<ImportReport> {
+    let mut report = ImportReport::default();
+    for (index, line) in BufReader::new(reader).lines().enumerate() {
+        let line = line.with_context(|| format!("reading line {}", index + 1))?;
+        if line.trim().is_empty() {
+            report.skipped += 1;
+            continue;
+        }
+        match serde_json::from_str::<ActionRecord>(&line) {
+            Ok(record) => match store.submit_action_blocking(record, SUBMIT_TIMEOUT) {
+                Ok(()) => report.imported += 1,
+                Err(_) => {
+                    tracing::warn!(
+                        "telemetry import: line {} not accepted by the store",
+                        index + 1
+                    );
+                    report.errors += 1;
+                }
+            },
+            Err(e) => {
+                tracing::warn!("telemetry import: line {} skipped: {e}", index + 1);
+                report.errors += 1;
+            }
+        }
+    }
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::TelemetryConfig;
+    use crate::telemetry::reader::TelemetryReader;
+    use crate::telemetry::testing;
+    use tempfile::TempDir;
+
+    #[test]
+    fn round_trips_ndjson_export_into_new_store() {
+        let tmp = TempDir::new().unwrap();
+        let source =
+            TelemetrySqliteStore::open(&tmp.path().join("src"), TelemetryConfig::default())
+                .unwrap();
+        for i in 0..5 {
+            source.submit_action(ActionRecord {
+                sequence_index: i,
+                tool_name: Some("shell".into()),
+                tool_success: Some(i % 2 == 0),
+                ..testing::action("s1", "s1-t0", 1_000 + i, "tool_call")
+            });
+        }
+        drop(source);
+
+        let mut ndjson = Vec::new();
+        let exported = TelemetryReader::open(&tmp.path().join("src/research.db"))
+            .unwrap()
+            .export_action_events_ndjson(None, 100, &mut ndjson)
+            .unwrap();
+        assert_eq!(exported, 5);
+        // A line from a future schema and a blank line.
+        ndjson.extend_from_slice(b"{\"ts_epoch_ms\": \"not a number\"}\n\n");
+
+        let dest = TelemetrySqliteStore::open(&tmp.path().join("dest"), TelemetryConfig::default())
+            .unwrap();
+        let report = import_action_events_json(&dest, &mut ndjson.as_slice()).unwrap();
+        assert_eq!(
+            report,
+            ImportReport {
+                imported: 5,
+                skipped: 1,
+                errors: 1,
+            }
+        );
+        drop(dest);
+
+        let events = TelemetryReader::open(&tmp.path().join("dest/research.db"))
+            .unwrap()
+            .export_action_events(None, 100)
+            .unwrap();
+        assert_eq!(events.len(), 5);
+        assert_eq!(events[3].tool_name.as_deref(), Some("shell"));
+        assert_eq!(events[3].tool_success, Some(false));
+    }
+
+    #[test]
+    fn records_refused_by_the_store_count_as_errors() {
+        let tmp = TempDir::new().unwrap();
+        let mut store = TelemetrySqliteStore::open(tmp.path(), TelemetryConfig::default()).unwrap();
+        store.shutdown();
+
+        let mut ndjson: &[u8] = b"{\"session_id\": \"s1\"}\n";
+        let report = import_action_events_json(&store, &mut ndjson).unwrap();
+        assert_eq!(
+            report,
+            ImportReport {
+                imported: 0,
+                skipped: 0,
+                errors: 1,
+            }
+        );
+    }
+}
@@ -0,0 +1,103 @@
+use crate::config::{KafkaCompression, KafkaConfig};
+use crate::telemetry::store::ActionRecordRef;
+use anyhow::{Context, Result};
+use rdkafka::config::ClientConfig;
+use rdkafka::error::{KafkaError, RDKafkaErrorCode};
+use rdkafka::producer::{BaseProducer, BaseRecord, Producer};
+use std::time::Duration;
+
+/// Give up on an undelivered event after this long, so an unreachable
+/// broker never grows the producer queue without bound.
+const MESSAGE_TIMEOUT_MS: &str = "30000";
+/// How long [`KafkaForwarder`]'s drop waits for queued events.
+const FLUSH_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Fire-and-forget producer of JSON-encoded
+/// [`ActionRecord`](crate::telemetry::store::ActionRecord)s.
+///
+/// Sends only enqueue into librdkafka's buffer; delivery happens on its
+/// background threads. Events that cannot be queued or delivered are
+/// dropped with a log line — SQLite stays the system of record.
+pub struct KafkaForwarder {
+    producer: BaseProducer,
+    topic: String,
+}
+
+impl KafkaForwarder {
+    /// Create a producer for `config`. Brokers are contacted lazily, so an
+    /// unreachable cluster does not fail here.
+    pub fn connect(config: &KafkaConfig) -> Result<Self> {
+        let producer = ClientConfig::new()
+            .set("bootstrap.servers", config.brokers.join(","))
+            .set("compression.codec", compression_codec(config.compression))
+            .set("message.timeout.ms", MESSAGE_TIMEOUT_MS)
+            .create()
+            .context("creating Kafka producer")?;
+        Ok(Self {
+            producer,
+            topic: config.topic.clone(),
+        })
+    }
+
+    /// Queue `record` for the configured topic, keyed by session ID.
+    pub fn send(&self, record: &ActionRecordRef<'_>) {
+        let payload = match serde_json::to_vec(record) {
+            Ok(payload) => payload,
+            Err(e) => {
+                tracing::warn!("serializing action event for Kafka failed: {e}");
+                return;
+            }
+        };
+        let message = BaseRecord::to(&self.topic)
+            .key(record.session_id.as_ref())
+            .payload(&payload);
+        match self.producer.send(message) {
+            Ok(()) => {}
+            Err((KafkaError::MessageProduction(RDKafkaErrorCode::QueueFull), _)) => {
+                tracing::debug!("Kafka producer queue full — dropping action event");
+            }
+            Err((e, _)) => tracing::warn!("queueing action event for Kafka failed: {e}"),
+        }
+        // Serve delivery reports so the queue drains; never blocks.
+        self.producer.poll(Duration::ZERO);
+    }
+}
+
+impl Drop for KafkaForwarder {
+    fn drop(&mut self) {
+        if let Err(e) = self.producer.flush(FLUSH_TIMEOUT) {
+            tracing::warn!("flushing Kafka producer failed: {e}");
+        }
+    }
+}
+
+fn compression_codec(compression: KafkaCompression) -> &'static str {
+    match compression {
+        KafkaCompression::None => "none",
+        KafkaCompression::Gzip => "gzip",
+        KafkaCompression::Snappy => "snappy",
+        KafkaCompression::Lz4 => "lz4",
+        KafkaCompression::Zstd => "zstd",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::telemetry::store::ActionRecord;
+
+    #[test]
+    fn unreachable_broker_does_not_block_sends() {
+        let forwarder = KafkaForwarder::connect(&KafkaConfig {
+            brokers: vec!["127.0.0.1:1".into()],
+            topic: "actions".into(),
+            compression: KafkaCompression::Lz4,
+        })
+        .unwrap();
+        let started = std::time::Instant::now();
+        for _ in 0..100 {
+            forwarder.send(&ActionRecordRef::from(&ActionRecord::default()));
+        }
+        assert!(started.elapsed() < Duration::from_secs(1));
+    }
+}
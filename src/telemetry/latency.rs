@@ -0,0 +1,101 @@
+use std::collections::VecDeque;
+
+/// Number of recent samples kept per window.
+pub const DEFAULT_WINDOW_SIZE: usize = 100;
+
+/// Sliding window over the most recent latency samples, used to estimate
+/// tail latency without querying the database.
+#[derive(Debug, Clone)]
+pub struct LatencyWindow {
+    samples: VecDeque<u64>,
+    capacity: usize,
+    /// Whether the last check was over the alert threshold, so alerts fire
+    /// once per breach rather than on every sample.
+    alerting: bool,
+}
+
+impl LatencyWindow {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            samples: VecDeque::with_capacity(capacity),
+            capacity: capacity.max(1),
+            alerting: false,
+        }
+    }
+
+    /// Add a sample, evicting the oldest when the window is full.
+    pub fn push(&mut self, ms: u64) {
+        if self.samples.len() == self.capacity {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(ms);
+    }
+
+    pub fn len(&self) -> usize {
+        self.samples.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.samples.is_empty()
+    }
+
+    /// Nearest-rank percentile (`p` in `0.0..=100.0`) of the window.
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    pub fn percentile(&self, p: f64) -> Option<u64> {
+        if self.samples.is_empty() {
+            return None;
+        }
+        let mut sorted: Vec<u64> = self.samples.iter().copied().collect();
+        sorted.sort_unstable();
+        let rank = (p.clamp(0.0, 100.0) / 100.0 * sorted.len() as f64).ceil() as usize;
+        Some(sorted[rank.saturating_sub(1)])
+    }
+
+    /// Compare the window's P99 with `threshold_ms`. Returns the P99 when it
+    /// has just crossed above the threshold; stays quiet while the breach
+    /// persists and re-arms once P99 drops back under it.
+    pub fn check_p99(&mut self, threshold_ms: u64) -> Option<u64> {
+        let p99 = self.percentile(99.0)?;
+        let over = p99 > threshold_ms;
+        let newly_over = over && !self.alerting;
+        self.alerting = over;
+        newly_over.then_some(p99)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn percentile_uses_nearest_rank() {
+        let mut window = LatencyWindow::new(100);
+        for ms in 1..=100 {
+            window.push(ms);
+        }
+        assert_eq!(window.percentile(99.0), Some(99));
+        assert_eq!(window.percentile(50.0), Some(50));
+        assert_eq!(window.percentile(100.0), Some(100));
+    }
+
+    #[test]
+    fn window_evicts_oldest_samples() {
+        let mut window = LatencyWindow::new(3);
+        for ms in [1_000, 1, 2, 3] {
+            window.push(ms);
+        }
+        assert_eq!(window.len(), 3);
+        assert_eq!(window.percentile(100.0), Some(3));
+    }
+
+    #[test]
+    fn p99_alert_fires_once_per_breach() {
+        let mut window = LatencyWindow::new(10);
+        window.push(100);
+        assert_eq!(window.check_p99(500), None);
+        window.push(900);
+        assert_eq!(window.check_p99(500), Some(900));
+        window.push(100);
+        assert_eq!(window.check_p99(500), None);
+    }
+}
@@ -0,0 +1,217 @@
+use crate::telemetry::reader::{ActionEventRow, TelemetryReader};
+use crate::telemetry::store::TelemetrySqliteStore;
+use anyhow::{Context, Result};
+use futures_util::stream::SplitSink;
+use futures_util::{SinkExt, StreamExt};
+use std::net::SocketAddr;
+use std::sync::Arc;
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::broadcast::error::RecvError;
+use tokio::task::JoinHandle;
+use tokio_tungstenite::tungstenite::handshake::server::{Request, Response};
+use tokio_tungstenite::tungstenite::protocol::frame::coding::CloseCode;
+use tokio_tungstenite::tungstenite::protocol::CloseFrame;
+use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::WebSocketStream;
+
+/// Header a reconnecting client sends with the `id` of the last event it
+/// received.
+const LAST_EVENT_ID_HEADER: &str = "Last-Event-ID";
+/// Query parameter alternative to [`LAST_EVENT_ID_HEADER`] for clients that
+/// cannot set handshake headers (e.g. browsers).
+const LAST_EVENT_ID_PARAM: &str = "last_event_id";
+/// Rows fetched per query while replaying missed events.
+const REPLAY_PAGE: usize = 500;
+
+type EventSink = SplitSink<WebSocketStream<TcpStream>, Message>;
+
+/// Serve committed action events to WebSocket clients at `addr`.
+///
+/// Every event is sent as a text frame holding a JSON [`ActionEventRow`].
+/// A client that reconnects with its last seen `id` in a `Last-Event-ID`
+/// header (or `?last_event_id=` query parameter) first receives every event
+/// recorded after it, then the live stream, without gaps or duplicates.
+/// Clients that fall too far behind are closed with code 1013 (try again)
+/// and are expected to reconnect the same way.
+pub fn serve_live_events(store: Arc<TelemetrySqliteStore>, addr: SocketAddr) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let listener = match TcpListener::bind(addr).await {
+            Ok(listener) => listener,
+            Err(e) => {
+                tracing::error!("telemetry live events: bind {addr} failed: {e}");
+                return;
+            }
+        };
+        tracing::info!("telemetry live events listening on ws://{addr}");
+        loop {
+            let (stream, peer) = match listener.accept().await {
+                Ok(conn) => conn,
+                Err(e) => {
+                    tracing::warn!("telemetry live events: accept failed: {e}");
+                    continue;
+                }
+            };
+            let store = store.clone();
+            tokio::spawn(async move {
+                if let Err(e) = stream_to_client(&store, stream).await {
+                    tracing::debug!("telemetry live events: client {peer} dropped: {e:#}");
+                }
+            });
+        }
+    })
+}
+
+async fn stream_to_client(store: &TelemetrySqliteStore, stream: TcpStream) -> Result<()> {
+    let mut last_event_id = None;
+    let ws = tokio_tungstenite::accept_hdr_async(stream, |req: &Request, resp: Response| {
+        last_event_id = parse_last_event_id(req);
+        Ok(resp)
+    })
+    .await
+    .context("websocket handshake")?;
+
+    // Subscribe before replaying so nothing committed in between is missed;
+    // anything replayed is skipped when it arrives live.
+    let mut live = store.subscribe_live();
+    let (mut sink, mut incoming) = ws.split();
+    let mut last_sent = 0;
+    if let Some(after) = last_event_id {
+        last_sent = after;
+        loop {
+            let db_path = store.db_path().to_path_buf();
+            let page = tokio::task::spawn_blocking(move || {
+                TelemetryReader::open(&db_path)?.action_events_after(last_sent, None, REPLAY_PAGE)
+            })
+            .await
+            .context("replay task panicked")??;
+            let done = page.len() < REPLAY_PAGE;
+            for row in &page {
+                send_event(&mut sink, row).await?;
+                last_sent = row.id;
+            }
+            if done {
+                break;
+            }
+        }
+    }
+
+    loop {
+        tokio::select! {
+            event = live.recv() => match event {
+                Ok(row) if row.id <= last_sent => {}
+                Ok(row) => {
+                    send_event(&mut sink, &row).await?;
+                    last_sent = row.id;
+                }
+                Err(RecvError::Lagged(missed)) => {
+                    let reason = format!("lagged by {missed} events; reconnect with Last-Event-ID");
+                    sink.send(Message::Close(Some(CloseFrame {
+                        code: CloseCode::Again,
+                        reason: reason.into(),
+                    })))
+                    .await?;
+                    return Ok(());
+                }
+                Err(RecvError::Closed) => return Ok(()),
+            },
+            message = incoming.next() => match message {
+                Some(Ok(Message::Close(_))) | None => return Ok(()),
+                Some(Ok(_)) => {}
+                Some(Err(e)) => return Err(e.into()),
+            },
+        }
+    }
+}
+
+async fn send_event(sink: &mut EventSink, row: &ActionEventRow) -> Result<()> {
+    let json = serde_json::to_string(row)?;
+    sink.send(Message::Text(json)).await?;
+    Ok(())
+}
+
+/// Read the reconnection point from the handshake header, falling back to
+/// the query parameter.
+fn parse_last_event_id(req: &Request) -> Option<i64> {
+    let from_header = req
+        .headers()
+        .get(LAST_EVENT_ID_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.trim().parse().ok());
+    from_header.or_else(|| {
+        req.uri().query()?.split('&').find_map(|pair| {
+            let (key, value) = pair.split_once('=')?;
+            (key == LAST_EVENT_ID_PARAM).then(|| value.parse().ok())?
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::TelemetryConfig;
+    use crate::telemetry::store::ActionRecord;
+    use crate::telemetry::testing;
+    use std::time::Duration;
+    use tempfile::TempDir;
+    use tokio_tungstenite::tungstenite::client::IntoClientRequest;
+
+    fn record(event_type: &str) -> ActionRecord {
+        testing::action("s1", "s1-t0", 0, event_type)
+    }
+
+    async fn next_event(
+        ws: &mut WebSocketStream<tokio_tungstenite::MaybeTlsStream<TcpStream>>,
+    ) -> serde_json::Value {
+        let message = tokio::time::timeout(Duration::from_secs(5), ws.next())
+            .await
+            .expect("event within timeout")
+            .unwrap()
+            .unwrap();
+        serde_json::from_str(message.to_text().unwrap()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn replays_missed_events_then_streams_live() {
+        let tmp = TempDir::new().unwrap();
+        let store =
+            Arc::new(TelemetrySqliteStore::open(tmp.path(), TelemetryConfig::default()).unwrap());
+        store.submit_action(record("session_start"));
+        store.submit_action(record("llm_request"));
+        store.flush().unwrap();
+
+        let addr = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap();
+        let server = serve_live_events(store.clone(), addr);
+        tokio::time::sleep(Duration::from_millis(100)).await;
+
+        let mut request = format!("ws://{addr}/").into_client_request().unwrap();
+        request
+            .headers_mut()
+            .insert(LAST_EVENT_ID_HEADER, "1".parse().unwrap());
+        let (mut ws, _) = tokio_tungstenite::connect_async(request).await.unwrap();
+
+        let replayed = next_event(&mut ws).await;
+        assert_eq!(replayed["id"], 2);
+        assert_eq!(replayed["event_type"], "llm_request");
+
+        store.submit_action(record("llm_response"));
+        let live = next_event(&mut ws).await;
+        assert_eq!(live["id"], 3);
+        assert_eq!(live["event_type"], "llm_response");
+        server.abort();
+    }
+
+    #[test]
+    fn last_event_id_prefers_header_over_query() {
+        let mut req = Request::builder()
+            .uri("/?foo=1&last_event_id=7")
+            .body(())
+            .unwrap();
+        assert_eq!(parse_last_event_id(&req), Some(7));
+        req.headers_mut()
+            .insert(LAST_EVENT_ID_HEADER, " 42 ".parse().unwrap());
+        assert_eq!(parse_last_event_id(&req), Some(42));
+    }
+}
@@ -0,0 +1,108 @@
+use crate::telemetry::reader::{TelemetryReader, TurnSequenceRow};
+use anyhow::Result;
+
+/// Consecutive turns with an identical action sequence needed to count as a
+/// loop.
+pub const MIN_LOOP_REPETITIONS: usize = 3;
+
+/// A run of consecutive turns that repeated the same action sequence.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct AgentLoop {
+    pub start_turn_id: String,
+    pub end_turn_id: String,
+    pub repeated_sequence: Vec<String>,
+    pub repetition_count: usize,
+}
+
+/// Find spans of at least [`MIN_LOOP_REPETITIONS`] consecutive completed
+/// turns whose action sequences are identical — the signature of an agent
+/// calling the same tools over and over without making progress. Turns that
+/// recorded no actions are ignored.
+pub fn detect_agent_loops(reader: &TelemetryReader, session_id: &str) -> Result<Vec<AgentLoop>> {
+    Ok(find_loops(&reader.export_turn_sequences(session_id)?))
+}
+
+fn find_loops(turns: &[TurnSequenceRow]) -> Vec<AgentLoop> {
+    let mut loops = Vec::new();
+    let mut start = 0;
+    while start < turns.len() {
+        let sequence = &turns[start].sequence;
+        let len = turns[start..]
+            .iter()
+            .take_while(|turn| &turn.sequence == sequence)
+            .count();
+        if len >= MIN_LOOP_REPETITIONS && !sequence.is_empty() {
+            loops.push(AgentLoop {
+                start_turn_id: turns[start].turn_id.clone(),
+                end_turn_id: turns[start + len - 1].turn_id.clone(),
+                repeated_sequence: sequence.clone(),
+                repetition_count: len,
+            });
+        }
+        start += len;
+    }
+    loops
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::TelemetryConfig;
+    use crate::telemetry::store::{ActionRecord, TelemetrySqliteStore};
+    use crate::telemetry::testing;
+    use tempfile::TempDir;
+
+    fn turn(turn_id: &str, sequence: &[&str]) -> TurnSequenceRow {
+        TurnSequenceRow {
+            turn_id: turn_id.into(),
+            ts_epoch_ms: 0,
+            sequence: sequence.iter().map(|s| (*s).to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn finds_runs_of_three_or_more_identical_turns() {
+        let shell = ["llm_response", "tool_call"];
+        let turns = [
+            turn("t0", &["llm_response"]),
+            turn("t1", &shell),
+            turn("t2", &shell),
+            turn("t3", &shell),
+            turn("t4", &shell),
+            turn("t5", &["llm_response"]),
+            turn("t6", &["llm_response"]),
+            turn("t7", &[]),
+            turn("t8", &[]),
+            turn("t9", &[]),
+        ];
+        assert_eq!(
+            find_loops(&turns),
+            [AgentLoop {
+                start_turn_id: "t1".into(),
+                end_turn_id: "t4".into(),
+                repeated_sequence: vec!["llm_response".into(), "tool_call".into()],
+                repetition_count: 4,
+            }]
+        );
+    }
+
+    #[test]
+    fn detects_loops_from_stored_turns() {
+        let tmp = TempDir::new().unwrap();
+        let store = TelemetrySqliteStore::open(tmp.path(), TelemetryConfig::default()).unwrap();
+        for i in 0..3 {
+            store.submit_action(ActionRecord {
+                turn_action_sequence: Some(r#"["tool_call"]"#.into()),
+                ..testing::action("s1", &format!("s1-t{i}"), 1_000 + i, "turn_complete")
+            });
+        }
+        drop(store);
+
+        let reader = TelemetryReader::open(&tmp.path().join("research.db")).unwrap();
+        let loops = detect_agent_loops(&reader, "s1").unwrap();
+        assert_eq!(loops.len(), 1);
+        assert_eq!(loops[0].start_turn_id, "s1-t0");
+        assert_eq!(loops[0].end_turn_id, "s1-t2");
+        assert_eq!(loops[0].repetition_count, 3);
+    }
+}
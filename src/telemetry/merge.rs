@@ -0,0 +1,269 @@
+use crate::config::TelemetryConfig;
+use crate::telemetry::schema;
+use crate::telemetry::store::init_schema;
+use anyhow::{bail, Context, Result};
+use rusqlite::Connection;
+use std::path::Path;
+
+/// Outcome of [`merge_databases`].
+#[derive(Debug, Clone, Default, PartialEq, Eq, serde::Serialize)]
+pub struct MergeReport {
+    pub action_events_merged: usize,
+    pub system_samples_merged: usize,
+    /// Rows of any table left out because `dest` already had them.
+    pub conflicts_skipped: usize,
+}
+
+/// Combine two telemetry databases — e.g. from parallel agent runs — into a
+/// new database at `dest`, using SQLite's `ATTACH DATABASE`.
+///
+/// `primary` is copied first with its row ids intact. Rows from `secondary`
+/// are renumbered after the primary's highest id (with `parent_action_id`
+/// links shifted to match), so independent runs never collide on the
+/// autoincrement keys; a row that is identical to one already merged, apart
+/// from those ids, is skipped as a conflict. Tables keyed by natural keys
+/// (`session_tags`, `tool_embeddings_cache`) use `INSERT OR IGNORE`, except
+/// `tool_success_rates`, whose counters are summed.
+///
+/// Either source may come from an older build: only columns both sides
+/// have are copied, and tables a source lacks are skipped. `dest` must not
+/// exist.
+pub fn merge_databases(primary: &Path, secondary: &Path, dest: &Path) -> Result<MergeReport> {
+    if dest.exists() {
+        bail!("merge target {} already exists", dest.display());
+    }
+    let conn = Connection::open(dest)
+        .with_context(|| format!("creating merged db: {}", dest.display()))?;
+    conn.execute_batch(&schema::pragmas(&TelemetryConfig::default()))
+        .context("telemetry PRAGMA setup")?;
+    init_schema(&conn)?;
+
+    let mut report = MergeReport::default();
+    for (alias, path) in [("primary_db", primary), ("secondary_db", secondary)] {
+        conn.execute(
+            &format!("ATTACH DATABASE ?1 AS {alias}"),
+            [path.to_string_lossy()],
+        )
+        .with_context(|| format!("attaching {}", path.display()))?;
+    }
+
+    let tables: Vec<String> = conn
+        .prepare(
+            "SELECT name FROM main.sqlite_master
+             WHERE type = 'table' AND name NOT LIKE 'sqlite_%'
+             ORDER BY name",
+        )?
+        .query_map([], |row| row.get(0))?
+        .collect::<rusqlite::Result<_>>()?;
+
+    conn.execute_batch("BEGIN")?;
+    for alias in ["primary_db", "secondary_db"] {
+        for table in &tables {
+            let (merged, skipped) = merge_table(&conn, alias, table)
+                .with_context(|| format!("merging {alias}.{table}"))?;
+            match table.as_str() {
+                "action_events" => report.action_events_merged += merged,
+                "system_samples" => report.system_samples_merged += merged,
+                _ => {}
+            }
+            report.conflicts_skipped += skipped;
+        }
+    }
+    conn.execute_batch("COMMIT")?;
+    conn.execute_batch("DETACH DATABASE primary_db; DETACH DATABASE secondary_db;")?;
+    Ok(report)
+}
+
+/// Columns of `alias.table`; empty when the table does not exist there.
+fn table_columns(conn: &Connection, alias: &str, table: &str) -> Result<Vec<String>> {
+    Ok(conn
+        .prepare("SELECT name FROM pragma_table_info(?1, ?2) ORDER BY cid")?
+        .query_map([table, alias], |row| row.get(0))?
+        .collect::<rusqlite::Result<_>>()?)
+}
+
+/// Copy `alias.table` into `main.table`; returns `(inserted, skipped)`.
+fn merge_table(conn: &Connection, alias: &str, table: &str) -> Result<(usize, usize)> {
+    let source_columns = table_columns(conn, alias, table)?;
+    if source_columns.is_empty() {
+        return Ok((0, 0));
+    }
+    let columns: Vec<String> = table_columns(conn, "main", table)?
+        .into_iter()
+        .filter(|c| source_columns.contains(c))
+        .collect();
+    let total: usize =
+        conn.query_row(&format!("SELECT COUNT(*) FROM {alias}.{table}"), [], |r| {
+            r.get(0)
+        })?;
+
+    let inserted = if table == "tool_success_rates" {
+        // `WHERE true` keeps SQLite from parsing ON CONFLICT as a join
+        // constraint.
+        conn.execute(
+            &format!(
+                "INSERT INTO main.{table} (tool_name, success_count, failure_count, last_updated)
+                 SELECT tool_name, success_count, failure_count, last_updated
+                 FROM {alias}.{table} WHERE true
+                 ON CONFLICT (tool_name) DO UPDATE SET
+                     success_count = success_count + excluded.success_count,
+                     failure_count = failure_count + excluded.failure_count,
+                     last_updated  = MAX(last_updated, excluded.last_updated)"
+            ),
+            [],
+        )?
+    } else if columns.iter().any(|c| c == "id") {
+        // Autoincrement key: renumber after the rows already merged.
+        let offset: i64 = conn.query_row(
+            &format!("SELECT COALESCE(MAX(id), 0) FROM main.{table}"),
+            [],
+            |r| r.get(0),
+        )?;
+        let select: Vec<String> = columns
+            .iter()
+            .map(|c| match c.as_str() {
+                "id" | "parent_action_id" => format!("src.{c} + ?1"),
+                _ => format!("src.{c}"),
+            })
+            .collect();
+        let same_row: Vec<String> = columns
+            .iter()
+            .filter(|c| !matches!(c.as_str(), "id" | "parent_action_id"))
+            .map(|c| format!("m.{c} IS src.{c}"))
+            .collect();
+        conn.execute(
+            &format!(
+                "INSERT INTO main.{table} ({columns})
+                 SELECT {select} FROM {alias}.{table} src
+                 WHERE NOT EXISTS (SELECT 1 FROM main.{table} m WHERE {same_row})
+                 ORDER BY src.id",
+                columns = columns.join(", "),
+                select = select.join(", "),
+                same_row = same_row.join(" AND "),
+            ),
+            [offset],
+        )?
+    } else {
+        conn.execute(
+            &format!(
+                "INSERT OR IGNORE INTO main.{table} ({columns})
+                 SELECT {columns} FROM {alias}.{table}",
+                columns = columns.join(", "),
+            ),
+            [],
+        )?
+    };
+    Ok((inserted, total - inserted))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::telemetry::reader::TelemetryReader;
+    use crate::telemetry::store::{ActionRecord, SystemSample, TelemetrySqliteStore};
+    use crate::telemetry::testing;
+    use tempfile::TempDir;
+
+    fn action(session_id: &str, sequence_index: i64, parent: Option<i64>) -> ActionRecord {
+        ActionRecord {
+            sequence_index,
+            parent_action_id: parent,
+            ..testing::action(
+                session_id,
+                &format!("{session_id}-t0"),
+                1_000 + sequence_index,
+                "tool_call",
+            )
+        }
+    }
+
+    fn sample(ts_epoch_ms: i64) -> SystemSample {
+        SystemSample {
+            cpu_usage_pct: 10.0,
+            ..testing::sample(ts_epoch_ms)
+        }
+    }
+
+    fn run(tmp: &TempDir, name: &str, actions: Vec<ActionRecord>, samples: &[i64]) {
+        let store =
+            TelemetrySqliteStore::open(&tmp.path().join(name), TelemetryConfig::default()).unwrap();
+        for record in actions {
+            store.submit_action(record);
+        }
+        for &ts in samples {
+            store.submit_system_sample(sample(ts));
+        }
+        store.tag_session(name, "merged");
+    }
+
+    #[test]
+    fn merges_parallel_runs_and_skips_duplicates() {
+        let tmp = TempDir::new().unwrap();
+        run(
+            &tmp,
+            "a",
+            vec![
+                action("a", 0, None),
+                action("a", 1, Some(1)),
+                action("shared", 9, None),
+            ],
+            &[1_000, 2_000],
+        );
+        run(
+            &tmp,
+            "b",
+            vec![
+                action("b", 0, None),
+                action("b", 1, Some(1)),
+                action("shared", 9, None),
+            ],
+            &[2_000, 3_000],
+        );
+
+        let dest = tmp.path().join("merged.db");
+        let report = merge_databases(
+            &tmp.path().join("a/research.db"),
+            &tmp.path().join("b/research.db"),
+            &dest,
+        )
+        .unwrap();
+        assert_eq!(
+            report,
+            MergeReport {
+                action_events_merged: 5,
+                system_samples_merged: 3,
+                conflicts_skipped: 2,
+            }
+        );
+        assert!(merge_databases(&dest, &dest, &dest).is_err());
+
+        let reader = TelemetryReader::open(&dest).unwrap();
+        let b_events = reader.export_session_events("b").unwrap();
+        // b's rows follow a's three; its child still points at its parent.
+        assert_eq!(b_events[0].id, 4);
+        assert_eq!(b_events[1].parent_action_id, Some(4));
+        assert_eq!(reader.sessions_with_tag("merged").unwrap(), ["a", "b"]);
+    }
+
+    #[test]
+    fn sums_tool_success_counters() {
+        let tmp = TempDir::new().unwrap();
+        let shell = |session_id: &str, success: bool| ActionRecord {
+            tool_name: Some("shell".into()),
+            tool_success: Some(success),
+            ..action(session_id, 0, None)
+        };
+        run(&tmp, "a", vec![shell("a", true)], &[]);
+        run(&tmp, "b", vec![shell("b", false)], &[]);
+
+        let dest = tmp.path().join("merged.db");
+        merge_databases(
+            &tmp.path().join("a/research.db"),
+            &tmp.path().join("b/research.db"),
+            &dest,
+        )
+        .unwrap();
+        let reader = TelemetryReader::open(&dest).unwrap();
+        assert_eq!(reader.tool_success_rate("shell").unwrap(), Some(0.5));
+    }
+}
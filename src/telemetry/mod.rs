@@ -1,11 +1,54 @@
+pub mod anomaly;
+pub mod anonymize;
+pub mod arrow;
+pub mod backup;
+pub mod bus;
+pub mod cluster;
 pub mod collector;
+pub mod crypto;
+pub mod csv;
+pub mod diff;
 pub mod ebpf;
 pub mod embeddings;
+pub mod federation;
+mod growable;
+pub mod import;
+#[cfg(feature = "grpc")]
+pub mod grpc;
+#[cfg(feature = "kafka")]
+pub mod kafka;
+pub mod latency;
+pub mod live;
+pub mod loops;
+pub mod merge;
 pub mod observer;
+pub mod pool;
+pub mod pricing;
 pub mod reader;
+pub mod replay;
+pub mod report;
 pub mod schema;
+pub mod session;
+#[cfg(unix)]
+pub mod socket;
+pub mod sse;
 pub mod store;
+pub mod tagging;
+#[cfg(test)]
+mod testing;
+#[cfg(feature = "object-store")]
+pub mod upload;
+pub mod wal;
+mod writers;
 
 pub use observer::TelemetryObserver;
 #[allow(unused_imports)]
 pub use store::{ActionRecord, SystemSample, TelemetrySqliteStore};
+
+/// Commonly used telemetry types and helpers.
+// For library users; the binary builds this module too and uses none of it.
+#[allow(unused_imports)]
+pub mod prelude {
+    pub use super::session::{new_session_id, new_session_id_ulid};
+    pub use super::{ActionRecord, TelemetryObserver, TelemetrySqliteStore};
+}
@@ -1,32 +1,474 @@
+#[cfg(feature = "kafka")]
+use crate::config::KafkaConfig;
+use crate::config::{Config, TurnIdFormat};
 use crate::observability::traits::{Observer, ObserverEvent, ObserverMetric};
-use crate::telemetry::store::{ActionRecord, TelemetrySqliteStore};
-use parking_lot::Mutex;
+use crate::telemetry::embeddings::{
+    compute_call_embedding, compute_tool_embedding_with_dim, hash_arguments,
+};
+#[cfg(feature = "kafka")]
+use crate::telemetry::kafka::KafkaForwarder;
+use crate::telemetry::latency::{LatencyWindow, DEFAULT_WINDOW_SIZE};
+use crate::telemetry::pricing::{estimate_cost, TokenPriceTable};
+use crate::telemetry::{anonymize, crypto};
+use crate::telemetry::store::{ActionRecordRef, TelemetrySqliteStore};
+use anyhow::Result;
+use dashmap::DashMap;
+use parking_lot::{Mutex, RwLock};
 use std::any::Any;
-use std::sync::atomic::{AtomicU64, Ordering};
+use std::borrow::Cow;
+use std::cell::{Cell, RefCell};
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::sync::Arc;
+use std::time::Duration;
+use thread_local::ThreadLocal;
+
+/// How long to wait for writer-channel space when recording a session
+/// boundary, which must not be dropped like ordinary events.
+const SESSION_MARKER_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// `iteration_index` offset per level of agent nesting, so iterations of a
+/// sub-agent never collide with those of its caller.
+const CALL_DEPTH_ITERATION_STRIDE: i64 = 1_000;
+
+/// Gap between the sequence-number ranges of different threads; a thread
+/// can record this many events per turn before its range overlaps the next.
+const THREAD_SEQUENCE_STRIDE: u64 = 1_000_000;
+
+thread_local! {
+    static CORRELATION_ID: RefCell<Option<String>> = const { RefCell::new(None) };
+}
+
+/// One thread's sequence counter within a [`TelemetryObserver`]. A thread
+/// that exits hands its counter on to the next thread started, which keeps
+/// counting from where it stopped, so numbers stay unique.
+struct ThreadSequence {
+    /// Order in which the thread first recorded through the observer.
+    index: u64,
+    /// `sequence_generation` the count belongs to.
+    generation: Cell<u64>,
+    /// Events recorded in the current turn.
+    count: Cell<u64>,
+}
+
+impl ThreadSequence {
+    /// Events recorded in `generation`, optionally counting one more. A
+    /// newer generation restarts the count from zero.
+    fn count(&self, generation: u64, advance: bool) -> u64 {
+        if self.generation.get() != generation {
+            self.generation.set(generation);
+            self.count.set(0);
+        }
+        let seq = self.count.get();
+        if advance {
+            self.count.set(seq + 1);
+        }
+        seq
+    }
+}
+
+/// Set the correlation ID stamped on action events recorded from the current
+/// thread, overriding the one configured on the observer. Pass `None` to
+/// clear it.
+pub fn set_correlation_id(id: Option<String>) {
+    CORRELATION_ID.with(|c| *c.borrow_mut() = id);
+}
+
+/// Event types recorded so far in the current turn, together with their
+/// JSON encoding. The JSON is extended in place on each push so events late
+/// in a long turn don't pay to re-serialize the whole sequence.
+#[derive(Debug)]
+pub struct TurnSequence {
+    events: Vec<String>,
+    json: String,
+}
+
+impl TurnSequence {
+    pub fn new() -> Self {
+        Self {
+            events: Vec::new(),
+            json: "[]".into(),
+        }
+    }
+
+    pub fn push(&mut self, event_type: &str) {
+        self.json.pop(); // trailing ']'
+        if !self.events.is_empty() {
+            self.json.push(',');
+        }
+        // Serializing a &str cannot fail.
+        self.json
+            .push_str(&serde_json::to_string(event_type).unwrap_or_default());
+        self.json.push(']');
+        self.events.push(event_type.to_string());
+    }
+
+    /// Event types pushed so far, in order.
+    pub fn events(&self) -> &[String] {
+        &self.events
+    }
+
+    /// JSON array of the event types pushed so far.
+    pub fn json(&self) -> &str {
+        &self.json
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.events.clear();
+        self.json.clear();
+        self.json.push_str("[]");
+    }
+}
+
+/// Read-only copy of a [`TelemetryObserver`]'s per-session state, for
+/// assertions in tests.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ObserverSnapshot {
+    pub session_id: String,
+    /// Turns completed so far.
+    pub turn_count: u64,
+    /// Events recorded in the current turn by the calling thread.
+    pub sequence_count: u64,
+    pub previous_action_type: Option<String>,
+    pub turn_action_sequence: Vec<String>,
+}
+
+/// Lowercase base36 encoding of `n`.
+#[allow(clippy::cast_possible_truncation)]
+fn to_base36(mut n: u64) -> String {
+    const DIGITS: &[u8; 36] = b"0123456789abcdefghijklmnopqrstuvwxyz";
+    let mut out = Vec::new();
+    loop {
+        out.push(DIGITS[(n % 36) as usize]);
+        n /= 36;
+        if n == 0 {
+            break;
+        }
+    }
+    out.reverse();
+    String::from_utf8(out).unwrap_or_default()
+}
+
+/// Stable 8-character base36 tag derived from `session_id`, prefixed to
+/// compact turn IDs so they don't repeat across sessions.
+fn session_tag(session_id: &str) -> String {
+    use sha2::Digest;
+    let hash = sha2::Sha256::digest(session_id.as_bytes());
+    // 40 bits fit in 8 base36 digits (36^8 > 2^40).
+    let bits = hash[..5]
+        .iter()
+        .fold(0u64, |acc, &b| (acc << 8) | u64::from(b));
+    format!("{:0>8}", to_base36(bits))
+}
 
 /// Observer implementation that translates `ObserverEvent`s into telemetry
 /// `ActionRecord` submissions for the research database.
 pub struct TelemetryObserver {
     store: Arc<TelemetrySqliteStore>,
-    session_id: String,
+    /// Replaced by [`Self::reset_session`] when the observer is reused.
+    session_id: RwLock<String>,
     turn_counter: AtomicU64,
-    sequence_counter: AtomicU64,
+    /// Per-thread sequence counters, freed with the observer.
+    thread_sequences: ThreadLocal<ThreadSequence>,
+    /// Index handed to the next thread that records through this observer.
+    next_thread_index: AtomicU64,
+    /// Bumped when a turn or session ends; thread-local counters holding an
+    /// older generation start over from zero on their next use.
+    sequence_generation: AtomicU64,
+    /// Tool calls seen in the current turn; stands in for the event's
+    /// `iteration` when the caller does not supply one.
+    current_iteration: AtomicU64,
     previous_action_type: Mutex<Option<String>>,
-    turn_action_sequence: Mutex<Vec<String>>,
+    /// Event types seen in the current turn. Written once, on the
+    /// `turn_complete` row, rather than as a growing prefix on every event.
+    turn_action_sequence: Mutex<TurnSequence>,
     is_user_initiated: Mutex<bool>,
+    /// Epoch-ms timestamp of the last `TurnStart`, cleared on `TurnComplete`.
+    turn_started_at: Mutex<Option<i64>>,
+    /// Streamed output tokens per model not yet claimed by an `LlmResponse`.
+    streamed_tokens: Mutex<HashMap<String, usize>>,
+    error_key: Option<[u8; 32]>,
+    anonymize_pii: bool,
+    correlation_id: Option<String>,
+    price_table: Option<TokenPriceTable>,
+    /// Session budget in microdollars; `None` disables enforcement.
+    max_cost_micros: Option<u64>,
+    session_cost_micros: AtomicU64,
+    budget_exceeded: Arc<AtomicBool>,
+    tool_sla_ms: HashMap<String, u64>,
+    sla_breaches: AtomicU64,
+    alert_p99_ms: Option<u64>,
+    /// Recent LLM response times per `(provider, model)`.
+    llm_latency: DashMap<(String, String), LatencyWindow>,
+    turn_id_format: TurnIdFormat,
+    /// Nesting level of the agent this observer records for.
+    call_depth: u32,
+    /// ULID of the current turn, generated on first use when
+    /// `turn_id_format` is `Ulid` and cleared on `TurnComplete`.
+    turn_ulid: Mutex<Option<String>>,
+    /// Tools whose embedding this observer has already cached.
+    embedded_tools: Mutex<HashSet<String>>,
+    /// Secondary sink for action events; SQLite is always written first.
+    #[cfg(feature = "kafka")]
+    kafka: Option<KafkaForwarder>,
+    /// eBPF trackers started by [`Self::from_config`]; stopped on drop.
+    #[cfg(all(target_os = "linux", feature = "telemetry-ebpf"))]
+    ebpf_trackers: Option<crate::telemetry::ebpf::EbpfTrackers>,
 }
 
 impl TelemetryObserver {
     pub fn new(store: Arc<TelemetrySqliteStore>, session_id: String) -> Self {
         Self {
             store,
-            session_id,
+            session_id: RwLock::new(session_id),
             turn_counter: AtomicU64::new(0),
-            sequence_counter: AtomicU64::new(0),
+            thread_sequences: ThreadLocal::new(),
+            next_thread_index: AtomicU64::new(0),
+            sequence_generation: AtomicU64::new(0),
+            current_iteration: AtomicU64::new(0),
             previous_action_type: Mutex::new(None),
-            turn_action_sequence: Mutex::new(Vec::new()),
+            turn_action_sequence: Mutex::new(TurnSequence::new()),
             is_user_initiated: Mutex::new(false),
+            turn_started_at: Mutex::new(None),
+            streamed_tokens: Mutex::new(HashMap::new()),
+            error_key: None,
+            anonymize_pii: false,
+            correlation_id: None,
+            price_table: None,
+            max_cost_micros: None,
+            session_cost_micros: AtomicU64::new(0),
+            budget_exceeded: Arc::new(AtomicBool::new(false)),
+            tool_sla_ms: HashMap::new(),
+            sla_breaches: AtomicU64::new(0),
+            alert_p99_ms: None,
+            llm_latency: DashMap::new(),
+            turn_id_format: TurnIdFormat::default(),
+            call_depth: 0,
+            turn_ulid: Mutex::new(None),
+            embedded_tools: Mutex::new(HashSet::new()),
+            #[cfg(feature = "kafka")]
+            kafka: None,
+            #[cfg(all(target_os = "linux", feature = "telemetry-ebpf"))]
+            ebpf_trackers: None,
+        }
+    }
+
+    /// Open the telemetry store under `config.workspace_dir` and build an
+    /// observer for `session_id` with every option in `config.telemetry`
+    /// applied. Starts the eBPF trackers when the build and runtime allow
+    /// and no other observer in the process has them; they stop when this
+    /// observer is dropped.
+    pub fn from_config(config: &Config, session_id: String) -> Result<Self> {
+        let telem_dir = config.workspace_dir.join("telemetry");
+        let store = Arc::new(TelemetrySqliteStore::open(
+            &telem_dir,
+            config.telemetry.clone(),
+        )?);
+        #[cfg(all(target_os = "linux", feature = "telemetry-ebpf"))]
+        let ebpf_trackers = if tokio::runtime::Handle::try_current().is_ok() {
+            crate::telemetry::ebpf::EbpfTrackers::start(&store, &session_id)
+        } else {
+            None
+        };
+        let mut obs = Self::new(store, session_id)
+            .with_pii_anonymization(config.telemetry.anonymize_pii)
+            .with_correlation_id(config.telemetry.correlation_id.clone())
+            .with_price_table(TokenPriceTable::from_cost_config(&config.cost))
+            .with_tool_sla(config.telemetry.tool_sla_ms.clone())
+            .with_p99_alert(config.telemetry.alert_p99_ms)
+            .with_turn_id_format(config.telemetry.turn_id_format);
+        if let Some(kafka) = &config.telemetry.kafka {
+            #[cfg(feature = "kafka")]
+            {
+                obs = obs.with_kafka(kafka);
+            }
+            #[cfg(not(feature = "kafka"))]
+            tracing::warn!(
+                "telemetry.kafka is set but this build lacks the `kafka` feature; \
+                 forwarding to {} is disabled",
+                kafka.topic
+            );
+        }
+        if config.telemetry.encrypt_error_messages {
+            obs = obs.with_error_encryption(crypto::load_or_create_field_key(&telem_dir)?);
+        }
+        #[cfg(all(target_os = "linux", feature = "telemetry-ebpf"))]
+        {
+            obs.ebpf_trackers = ebpf_trackers;
+        }
+        Ok(obs)
+    }
+
+    /// Create an observer that tracks estimated session spend against
+    /// `max_cost_usd`. Once the budget is passed the flag returned by
+    /// [`Self::budget_exceeded`] is set and a `BudgetExceeded` event is
+    /// recorded; callers decide whether to abort.
+    pub fn with_budget(
+        store: Arc<TelemetrySqliteStore>,
+        session_id: String,
+        max_cost_usd: f64,
+        price_table: TokenPriceTable,
+    ) -> Self {
+        let mut obs = Self::new(store, session_id).with_price_table(price_table);
+        obs.max_cost_micros = Some(usd_to_micros(max_cost_usd));
+        obs
+    }
+
+    /// Warn when a tool call takes longer than its threshold in `sla_ms`.
+    pub fn with_tool_sla(mut self, sla_ms: HashMap<String, u64>) -> Self {
+        self.tool_sla_ms = sla_ms;
+        self
+    }
+
+    /// Number of tool calls that exceeded their SLA threshold.
+    pub fn sla_breach_count(&self) -> u64 {
+        self.sla_breaches.load(Ordering::Relaxed)
+    }
+
+    /// Warn when the P99 of recent LLM response times for a provider/model
+    /// pair exceeds `threshold_ms`.
+    pub fn with_p99_alert(mut self, threshold_ms: Option<u64>) -> Self {
+        self.alert_p99_ms = threshold_ms;
+        self
+    }
+
+    /// Record events as coming from an agent nested `depth` levels below
+    /// the top-level one (e.g. a sub-agent started by a tool call). Stamps
+    /// `call_depth` and offsets every `iteration_index` by
+    /// `depth * 1000`.
+    pub fn with_call_depth(mut self, depth: u32) -> Self {
+        self.call_depth = depth;
+        self
+    }
+
+    /// Also forward every action event to the Kafka topic in `config`. A
+    /// producer that cannot be created is logged and skipped.
+    #[cfg(feature = "kafka")]
+    pub fn with_kafka(mut self, config: &KafkaConfig) -> Self {
+        match KafkaForwarder::connect(config) {
+            Ok(forwarder) => self.kafka = Some(forwarder),
+            Err(e) => tracing::warn!("telemetry Kafka forwarding disabled: {e:#}"),
+        }
+        self
+    }
+
+    fn iteration_index(&self, iteration: i64) -> i64 {
+        iteration.saturating_add(i64::from(self.call_depth) * CALL_DEPTH_ITERATION_STRIDE)
+    }
+
+    /// Record `turn_id` values in `format`.
+    pub fn with_turn_id_format(mut self, format: TurnIdFormat) -> Self {
+        self.turn_id_format = format;
+        self
+    }
+
+    /// P99 of recent LLM response times for `provider`/`model`, if any have
+    /// been tracked.
+    pub fn llm_p99_ms(&self, provider: &str, model: &str) -> Option<u64> {
+        self.llm_latency
+            .get(&(provider.to_string(), model.to_string()))
+            .and_then(|w| w.percentile(99.0))
+    }
+
+    /// Feed an LLM response time into its window and warn on a P99 breach.
+    fn track_llm_latency(&self, provider: &str, model: &str, duration: Duration) {
+        let Some(threshold) = self.alert_p99_ms else {
+            return;
+        };
+        let ms = u64::try_from(duration.as_millis()).unwrap_or(u64::MAX);
+        let mut window = self
+            .llm_latency
+            .entry((provider.to_string(), model.to_string()))
+            .or_insert_with(|| LatencyWindow::new(DEFAULT_WINDOW_SIZE));
+        window.push(ms);
+        if let Some(p99) = window.check_p99(threshold) {
+            tracing::warn!(
+                "P99 latency alert: {provider}/{model} at {p99}ms over the last {} calls, limit {threshold}ms",
+                window.len()
+            );
+        }
+    }
+
+    /// Flag set once the session budget is exceeded, for callers to poll.
+    pub fn budget_exceeded(&self) -> Arc<AtomicBool> {
+        self.budget_exceeded.clone()
+    }
+
+    /// Estimated spend recorded so far in this session.
+    pub fn session_cost_usd(&self) -> f64 {
+        self.session_cost_micros.load(Ordering::Relaxed) as f64 / 1_000_000.0
+    }
+
+    /// Add `cost_usd` to the session total and fire `BudgetExceeded` the
+    /// first time the total passes the budget.
+    fn track_cost(&self, cost_usd: f64) {
+        let Some(max) = self.max_cost_micros else {
+            return;
+        };
+        let micros = usd_to_micros(cost_usd);
+        let total = self.session_cost_micros.fetch_add(micros, Ordering::Relaxed) + micros;
+        if total > max && !self.budget_exceeded.swap(true, Ordering::AcqRel) {
+            self.record_event(&ObserverEvent::BudgetExceeded {
+                session_id: self.session_id(),
+                actual_cost: total as f64 / 1_000_000.0,
+            });
+        }
+    }
+
+    /// Redact PII from `error_message` before it is stored (and before any
+    /// encryption).
+    pub fn with_pii_anonymization(mut self, enabled: bool) -> Self {
+        self.anonymize_pii = enabled;
+        self
+    }
+
+    /// Encrypt `error_message` with `key` before it is stored.
+    pub fn with_error_encryption(mut self, key: [u8; 32]) -> Self {
+        self.error_key = Some(key);
+        self
+    }
+
+    /// Stamp recorded events with `id` unless the recording thread has set
+    /// its own via [`set_correlation_id`].
+    pub fn with_correlation_id(mut self, id: Option<String>) -> Self {
+        self.correlation_id = id;
+        self
+    }
+
+    /// Estimate `estimated_cost_usd` for LLM responses from `table`.
+    pub fn with_price_table(mut self, table: TokenPriceTable) -> Self {
+        self.price_table = Some(table);
+        self
+    }
+
+    fn correlation_id(&self) -> Option<String> {
+        CORRELATION_ID
+            .with(|c| c.borrow().clone())
+            .or_else(|| self.correlation_id.clone())
+    }
+
+    /// Apply the configured protections to an error message before storage.
+    /// `None` if it could not be encrypted: the message is dropped rather
+    /// than stored in the clear.
+    fn protect_error_message(&self, msg: &str) -> Option<String> {
+        let msg = if self.anonymize_pii {
+            anonymize::anonymize_error_message(msg)
+        } else {
+            msg.to_string()
+        };
+        match self.error_key {
+            Some(ref key) => match crypto::encrypt_field(&msg, key) {
+                Ok(encrypted) => Some(encrypted),
+                Err(e) => {
+                    tracing::warn!("telemetry: dropping error message: {e:#}");
+                    None
+                }
+            },
+            None => Some(msg),
         }
     }
 
@@ -39,29 +481,203 @@ impl TelemetryObserver {
         (ts, epoch_ms)
     }
 
+    fn session_id(&self) -> String {
+        self.session_id.read().clone()
+    }
+
+    /// Capture the current per-session state without modifying it.
+    pub fn snapshot(&self) -> ObserverSnapshot {
+        // Holding the read lock keeps a concurrent `reset_session` from
+        // interleaving with the copy.
+        let session_id = self.session_id.read();
+        ObserverSnapshot {
+            session_id: session_id.clone(),
+            turn_count: self.turn_counter.load(Ordering::Relaxed),
+            sequence_count: self.local_sequence(false),
+            previous_action_type: self.previous_action_type.lock().clone(),
+            turn_action_sequence: self.turn_action_sequence.lock().events().to_vec(),
+        }
+    }
+
+    /// Start recording a new session on the same store.
+    ///
+    /// Lets a long-lived host serve sequential sessions without building a
+    /// new observer and store for each. Turn and sequence counters, the
+    /// turn action sequence, and the session's cost tracking start over;
+    /// the write lock on the session ID is held throughout so no turn ID is
+    /// built from a half-reset state.
+    pub fn reset_session(&self, new_session_id: String) {
+        let mut session_id = self.session_id.write();
+        *session_id = new_session_id;
+        self.turn_counter.store(0, Ordering::Relaxed);
+        self.sequence_generation.fetch_add(1, Ordering::AcqRel);
+        self.current_iteration.store(0, Ordering::Relaxed);
+        *self.previous_action_type.lock() = None;
+        self.turn_action_sequence.lock().clear();
+        *self.turn_started_at.lock() = None;
+        *self.turn_ulid.lock() = None;
+        self.streamed_tokens.lock().clear();
+        self.session_cost_micros.store(0, Ordering::Relaxed);
+        self.budget_exceeded.store(false, Ordering::Release);
+    }
+
+    /// Events this thread has recorded in the current turn, optionally
+    /// counting one more.
+    fn local_sequence(&self, advance: bool) -> u64 {
+        self.thread_sequence()
+            .count(self.sequence_generation.load(Ordering::Acquire), advance)
+    }
+
+    /// The calling thread's counter, created on its first event.
+    fn thread_sequence(&self) -> &ThreadSequence {
+        self.thread_sequences.get_or(|| ThreadSequence {
+            index: self.next_thread_index.fetch_add(1, Ordering::Relaxed),
+            generation: Cell::new(0),
+            count: Cell::new(0),
+        })
+    }
+
+    /// Next sequence number for an event in the current turn.
+    ///
+    /// Threads count independently, so concurrent recorders don't contend
+    /// on a shared counter; each thread's numbers are offset by its index
+    /// among this observer's recording threads times
+    /// [`THREAD_SEQUENCE_STRIDE`] to stay unique. A single-threaded
+    /// observer therefore numbers events `0, 1, 2, ...`. Numbers from
+    /// different threads say nothing about their relative order; events of
+    /// a turn are totally ordered by `(ts_epoch_ms, sequence_index)`.
     fn next_sequence(&self) -> i64 {
-        self.sequence_counter.fetch_add(1, Ordering::Relaxed) as i64
+        let local = self.local_sequence(true);
+        let thread = self.thread_sequence().index;
+        i64::try_from(thread * THREAD_SEQUENCE_STRIDE + local).unwrap_or(i64::MAX)
     }
 
-    fn turn_id(&self) -> String {
-        format!(
-            "{}-t{}",
-            self.session_id,
-            self.turn_counter.load(Ordering::Relaxed)
-        )
+    /// Current turn ID of `session_id`. Callers pass the session ID they
+    /// already hold the read lock for, so the pair is read under one lock.
+    fn turn_id_for(&self, session_id: &str) -> String {
+        let turn = self.turn_counter.load(Ordering::Relaxed);
+        match self.turn_id_format {
+            TurnIdFormat::Compact => {
+                format!("{}-{:0>4}", session_tag(session_id), to_base36(turn))
+            }
+            TurnIdFormat::Full => format!("{session_id}-t{turn}"),
+            TurnIdFormat::Ulid => self
+                .turn_ulid
+                .lock()
+                .get_or_insert_with(|| ulid::Ulid::new().to_string())
+                .clone(),
+        }
     }
 
-    fn record_action(&self, event_type: &str, record: ActionRecord) {
+    fn record_action(&self, event_type: &str, record: ActionRecordRef<'_>) {
         let mut prev = self.previous_action_type.lock();
         let mut seq = self.turn_action_sequence.lock();
-        seq.push(event_type.to_string());
+        seq.push(event_type);
         *prev = Some(event_type.to_string());
         drop(prev);
         drop(seq);
-        self.store.submit_action(record);
+        self.submit(record, None);
+    }
+
+    /// Cache `tool`'s embedding, at the configured `tool_embedding_dim`, the
+    /// first time this observer sees it. No-op unless
+    /// `tool_embeddings_enabled` is set.
+    fn ensure_tool_embedding(&self, tool: &str) {
+        let config = self.store.config();
+        if !config.tool_embeddings_enabled {
+            return;
+        }
+        if !self.embedded_tools.lock().insert(tool.to_string()) {
+            return;
+        }
+        let (bytes, dims) = compute_tool_embedding_with_dim(tool, config.tool_embedding_dim);
+        self.store.submit_tool_embedding(tool, bytes, dims);
+    }
+
+    /// Write `record` to the store, then forward it to Kafka if configured.
+    /// With `block_for`, wait up to that long for channel space instead of
+    /// applying the store's overflow strategy.
+    fn submit(&self, record: ActionRecordRef<'_>, block_for: Option<Duration>) {
+        #[cfg(feature = "kafka")]
+        let forwarded = self.kafka.as_ref().map(|kafka| (kafka, record.clone()));
+        match block_for {
+            None => self.store.submit_action_ref(record),
+            Some(timeout) => {
+                if let Err(record) = self
+                    .store
+                    .submit_action_blocking(record.into_owned(), timeout)
+                {
+                    tracing::warn!(
+                        "telemetry: dropped {} event for session {}",
+                        record.event_type,
+                        record.session_id
+                    );
+                }
+            }
+        }
+        #[cfg(feature = "kafka")]
+        if let Some((kafka, record)) = forwarded {
+            kafka.send(&record);
+        }
+    }
+
+    /// Record a session-level marker (`session_start`, `session_end`,
+    /// `budget_exceeded`). Markers stay out of the turn action sequence and
+    /// are submitted with a blocking send so session duration can always be
+    /// computed.
+    ///
+    /// Markers belong to the observer's own session; an event naming a
+    /// different one is logged and recorded under the observer's session
+    /// so its `session_id` and `turn_id` always agree.
+    fn record_session_marker(&self, session_id: &str, event_type: &str) {
+        // Read both under one lock, but don't hold it over the blocking send.
+        let (own_session, turn_id) = {
+            let own_session = self.session_id.read();
+            let turn_id = self.turn_id_for(&own_session);
+            (own_session.clone(), turn_id)
+        };
+        if session_id != own_session {
+            tracing::warn!(
+                "telemetry: {event_type} for session {session_id} recorded under observer session {own_session}"
+            );
+        }
+        let (ts, ts_epoch_ms) = Self::now_ts();
+        let record = ActionRecordRef {
+            ts: ts.into(),
+            ts_epoch_ms,
+            session_id: own_session.into(),
+            turn_id: turn_id.into(),
+            sequence_index: self.next_sequence(),
+            event_type: event_type.into(),
+            provider: None,
+            model: None,
+            tool_name: None,
+            tool_type_embedding: None,
+            arguments_hash: None,
+            tool_success: None,
+            duration_ms: None,
+            tokens_in: None,
+            tokens_out: None,
+            is_user_initiated: false,
+            iteration_index: self.iteration_index(0),
+            previous_action_type: None,
+            turn_action_sequence: None,
+            error_message: None,
+            correlation_id: self.correlation_id().map(Cow::Owned),
+            parent_action_id: None,
+            estimated_cost_usd: None,
+            metadata_json: None,
+            call_depth: self.call_depth,
+        };
+        self.submit(record, Some(SESSION_MARKER_TIMEOUT));
     }
 }
 
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+fn usd_to_micros(usd: f64) -> u64 {
+    (usd.max(0.0) * 1_000_000.0).round() as u64
+}
+
 impl Observer for TelemetryObserver {
     fn record_event(&self, event: &ObserverEvent) {
         match event {
@@ -80,32 +696,64 @@ impl Observer for TelemetryObserver {
                 let (ts, ts_epoch_ms) = Self::now_ts();
                 let seq = self.next_sequence();
                 let prev = self.previous_action_type.lock().clone();
-                let action_seq = serde_json::to_string(&*self.turn_action_sequence.lock()).ok();
                 let user_init = *self.is_user_initiated.lock();
+                let tokens_in = tokens_in.map(|t| i64::try_from(t).unwrap_or(i64::MAX));
+                // A streamed response ends here; its chunk total stands in
+                // for `tokens_out` when the provider did not report one.
+                let streamed = self.streamed_tokens.lock().remove(model.as_str());
+                let tokens_out = tokens_out
+                    .or_else(|| streamed.map(|t| t as u64))
+                    .map(|t| i64::try_from(t).unwrap_or(i64::MAX));
+                let estimated_cost_usd = match (&self.price_table, tokens_in, tokens_out) {
+                    (Some(table), Some(_), _) | (Some(table), _, Some(_)) => estimate_cost(
+                        tokens_in.unwrap_or(0),
+                        tokens_out.unwrap_or(0),
+                        provider,
+                        model,
+                        table,
+                    ),
+                    _ => None,
+                };
 
-                let record = ActionRecord {
-                    ts,
+                let session_id = self.session_id.read();
+                let turn_id = self.turn_id_for(&session_id);
+
+                let record = ActionRecordRef {
+                    ts: ts.into(),
                     ts_epoch_ms,
-                    session_id: self.session_id.clone(),
-                    turn_id: self.turn_id(),
+                    session_id: session_id.as_str().into(),
+                    turn_id: turn_id.into(),
                     sequence_index: seq,
                     event_type: "llm_response".into(),
-                    provider: Some(provider.clone()),
-                    model: Some(model.clone()),
+                    provider: Some(provider.as_str().into()),
+                    model: Some(model.as_str().into()),
                     tool_name: None,
                     tool_type_embedding: None,
                     arguments_hash: None,
                     tool_success: Some(*success),
                     duration_ms: Some(i64::try_from(duration.as_millis()).unwrap_or(i64::MAX)),
-                    tokens_in: tokens_in.map(|t| i64::try_from(t).unwrap_or(i64::MAX)),
-                    tokens_out: tokens_out.map(|t| i64::try_from(t).unwrap_or(i64::MAX)),
+                    tokens_in,
+                    tokens_out,
                     is_user_initiated: user_init,
-                    iteration_index: 0,
-                    previous_action_type: prev,
-                    turn_action_sequence: action_seq,
-                    error_message: error_message.clone(),
+                    iteration_index: self.iteration_index(0),
+                    previous_action_type: prev.map(Cow::Owned),
+                    turn_action_sequence: None,
+                    error_message: error_message
+                        .as_deref()
+                        .and_then(|m| self.protect_error_message(m))
+                        .map(Cow::Owned),
+                    correlation_id: self.correlation_id().map(Cow::Owned),
+                    parent_action_id: None,
+                    estimated_cost_usd,
+                    metadata_json: None,
+                    call_depth: self.call_depth,
                 };
                 self.record_action("llm_response", record);
+                drop(session_id);
+                if let Some(cost) = estimated_cost_usd {
+                    self.track_cost(cost);
+                }
+                self.track_llm_latency(provider, model, *duration);
 
                 // Clear user-initiated flag after first event in a turn
                 if user_init {
@@ -116,43 +764,219 @@ impl Observer for TelemetryObserver {
                 tool,
                 duration,
                 success,
+                arguments,
                 arguments_hash,
                 iteration,
             } => {
                 let (ts, ts_epoch_ms) = Self::now_ts();
                 let seq = self.next_sequence();
                 let prev = self.previous_action_type.lock().clone();
-                let action_seq = serde_json::to_string(&*self.turn_action_sequence.lock()).ok();
+                let auto_iteration = self.current_iteration.fetch_add(1, Ordering::Relaxed);
+                let iteration_index = match iteration {
+                    Some(i) => i64::from(*i),
+                    None => i64::try_from(auto_iteration).unwrap_or(i64::MAX),
+                };
+
+                let session_id = self.session_id.read();
+                let turn_id = self.turn_id_for(&session_id);
 
-                let record = ActionRecord {
-                    ts,
+                let record = ActionRecordRef {
+                    ts: ts.into(),
                     ts_epoch_ms,
-                    session_id: self.session_id.clone(),
-                    turn_id: self.turn_id(),
+                    session_id: session_id.as_str().into(),
+                    turn_id: turn_id.into(),
                     sequence_index: seq,
                     event_type: "tool_call".into(),
                     provider: None,
                     model: None,
-                    tool_name: Some(tool.clone()),
-                    tool_type_embedding: None,
-                    arguments_hash: arguments_hash.clone(),
+                    tool_name: Some(tool.as_str().into()),
+                    tool_type_embedding: arguments
+                        .as_ref()
+                        .map(|args| Cow::Owned(compute_call_embedding(tool, args).0)),
+                    arguments_hash: arguments
+                        .as_ref()
+                        .map(|args| Cow::Owned(hash_arguments(args)))
+                        .or_else(|| arguments_hash.as_deref().map(Cow::Borrowed)),
                     tool_success: Some(*success),
                     duration_ms: Some(i64::try_from(duration.as_millis()).unwrap_or(i64::MAX)),
                     tokens_in: None,
                     tokens_out: None,
                     is_user_initiated: false,
-                    iteration_index: i64::from(iteration.unwrap_or(0)),
-                    previous_action_type: prev,
-                    turn_action_sequence: action_seq,
+                    iteration_index: self.iteration_index(iteration_index),
+                    previous_action_type: prev.map(Cow::Owned),
+                    turn_action_sequence: None,
                     error_message: None,
+                    correlation_id: self.correlation_id().map(Cow::Owned),
+                    parent_action_id: None,
+                    estimated_cost_usd: None,
+                    metadata_json: None,
+                    call_depth: self.call_depth,
                 };
                 self.record_action("tool_call", record);
+                drop(session_id);
+                self.ensure_tool_embedding(tool);
+
+                if let Some(&sla) = self.tool_sla_ms.get(tool.as_str()) {
+                    let actual = duration.as_millis();
+                    if actual > u128::from(sla) {
+                        tracing::warn!("SLA breach: tool {tool} took {actual}ms, limit {sla}ms");
+                        self.sla_breaches.fetch_add(1, Ordering::Relaxed);
+                    }
+                }
+            }
+            ObserverEvent::StreamingTokenChunk {
+                model,
+                chunk_tokens,
+                ..
+            } => {
+                *self
+                    .streamed_tokens
+                    .lock()
+                    .entry(model.clone())
+                    .or_default() += chunk_tokens;
+            }
+            ObserverEvent::FileOperation {
+                path,
+                operation,
+                bytes,
+                success,
+            } => {
+                let (ts, ts_epoch_ms) = Self::now_ts();
+                let metadata = serde_json::json!({ "operation": operation, "bytes": bytes });
+                let session_id = self.session_id.read();
+                let turn_id = self.turn_id_for(&session_id);
+                let record = ActionRecordRef {
+                    ts: ts.into(),
+                    ts_epoch_ms,
+                    session_id: session_id.as_str().into(),
+                    turn_id: turn_id.into(),
+                    sequence_index: self.next_sequence(),
+                    event_type: "file_op".into(),
+                    provider: None,
+                    model: None,
+                    tool_name: Some(path.to_string_lossy()),
+                    tool_type_embedding: None,
+                    arguments_hash: None,
+                    tool_success: Some(*success),
+                    duration_ms: None,
+                    tokens_in: None,
+                    tokens_out: None,
+                    is_user_initiated: false,
+                    iteration_index: self.iteration_index(
+                        i64::try_from(self.current_iteration.load(Ordering::Relaxed))
+                            .unwrap_or(i64::MAX),
+                    ),
+                    previous_action_type: self.previous_action_type.lock().clone().map(Cow::Owned),
+                    turn_action_sequence: None,
+                    error_message: None,
+                    correlation_id: self.correlation_id().map(Cow::Owned),
+                    parent_action_id: None,
+                    estimated_cost_usd: None,
+                    metadata_json: Some(metadata.to_string().into()),
+                    call_depth: self.call_depth,
+                };
+                self.record_action("file_op", record);
+                drop(session_id);
+            }
+            ObserverEvent::AgentThinking { tokens_used, model } => {
+                let (ts, ts_epoch_ms) = Self::now_ts();
+                let session_id = self.session_id.read();
+                let turn_id = self.turn_id_for(&session_id);
+                let record = ActionRecordRef {
+                    ts: ts.into(),
+                    ts_epoch_ms,
+                    session_id: session_id.as_str().into(),
+                    turn_id: turn_id.into(),
+                    sequence_index: self.next_sequence(),
+                    event_type: "agent_thinking".into(),
+                    provider: None,
+                    model: Some(model.as_str().into()),
+                    tool_name: None,
+                    tool_type_embedding: None,
+                    arguments_hash: None,
+                    tool_success: None,
+                    duration_ms: None,
+                    tokens_in: Some(i64::try_from(*tokens_used).unwrap_or(i64::MAX)),
+                    tokens_out: None,
+                    is_user_initiated: false,
+                    iteration_index: self.iteration_index(
+                        i64::try_from(self.current_iteration.load(Ordering::Relaxed))
+                            .unwrap_or(i64::MAX),
+                    ),
+                    previous_action_type: self.previous_action_type.lock().clone().map(Cow::Owned),
+                    turn_action_sequence: None,
+                    error_message: None,
+                    correlation_id: self.correlation_id().map(Cow::Owned),
+                    parent_action_id: None,
+                    estimated_cost_usd: None,
+                    metadata_json: None,
+                    call_depth: self.call_depth,
+                };
+                self.record_action("agent_thinking", record);
+                drop(session_id);
+            }
+            ObserverEvent::SessionStart { session_id } => {
+                self.record_session_marker(session_id, "session_start");
+            }
+            ObserverEvent::SessionEnd { session_id } => {
+                self.record_session_marker(session_id, "session_end");
+            }
+            ObserverEvent::BudgetExceeded {
+                session_id,
+                actual_cost,
+            } => {
+                tracing::warn!(
+                    "telemetry: session {session_id} exceeded its budget (${actual_cost:.4})"
+                );
+                self.record_session_marker(session_id, "budget_exceeded");
+            }
+            ObserverEvent::TurnStart => {
+                *self.turn_started_at.lock() = Some(Self::now_ts().1);
             }
             ObserverEvent::TurnComplete => {
+                let (ts, ts_epoch_ms) = Self::now_ts();
+                let started_at = self.turn_started_at.lock().take();
+                let session_id = self.session_id.read();
+                let turn_id = self.turn_id_for(&session_id);
+                let record = ActionRecordRef {
+                    ts: ts.into(),
+                    ts_epoch_ms,
+                    session_id: session_id.as_str().into(),
+                    turn_id: turn_id.into(),
+                    sequence_index: self.next_sequence(),
+                    event_type: "turn_complete".into(),
+                    provider: None,
+                    model: None,
+                    tool_name: None,
+                    tool_type_embedding: None,
+                    arguments_hash: None,
+                    tool_success: None,
+                    duration_ms: started_at.map(|start| ts_epoch_ms - start),
+                    tokens_in: None,
+                    tokens_out: None,
+                    is_user_initiated: false,
+                    iteration_index: self.iteration_index(0),
+                    previous_action_type: self.previous_action_type.lock().clone().map(Cow::Owned),
+                    turn_action_sequence: Some(
+                        self.turn_action_sequence.lock().json().to_string().into(),
+                    ),
+                    error_message: None,
+                    correlation_id: self.correlation_id().map(Cow::Owned),
+                    parent_action_id: None,
+                    estimated_cost_usd: None,
+                    metadata_json: None,
+                    call_depth: self.call_depth,
+                };
+                self.submit(record, None);
+                drop(session_id);
+
                 self.turn_counter.fetch_add(1, Ordering::Relaxed);
-                self.sequence_counter.store(0, Ordering::Relaxed);
+                self.sequence_generation.fetch_add(1, Ordering::AcqRel);
+                self.current_iteration.store(0, Ordering::Relaxed);
                 *self.previous_action_type.lock() = None;
                 self.turn_action_sequence.lock().clear();
+                *self.turn_ulid.lock() = None;
+                self.streamed_tokens.lock().clear();
             }
             // Other events are not recorded in the telemetry store.
             _ => {}
@@ -181,11 +1005,13 @@ impl Observer for TelemetryObserver {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::config::{EmbeddingDim, TelemetryConfig};
+    use crate::observability::traits::FileOp;
     use std::time::Duration;
     use tempfile::TempDir;
 
     fn make_store(tmp: &TempDir) -> Arc<TelemetrySqliteStore> {
-        Arc::new(TelemetrySqliteStore::open(tmp.path(), 64).unwrap())
+        Arc::new(TelemetrySqliteStore::open(tmp.path(), TelemetryConfig::default()).unwrap())
     }
 
     #[test]
@@ -204,8 +1030,7 @@ mod tests {
             tokens_out: Some(50),
         });
 
-        // Give writer thread time to flush.
-        std::thread::sleep(Duration::from_millis(300));
+        // Dropping the store joins the writer thread.
         drop(obs);
         drop(store);
 
@@ -235,11 +1060,11 @@ mod tests {
             tool: "shell".into(),
             duration: Duration::from_millis(50),
             success: true,
+            arguments: None,
             arguments_hash: Some("abc123".into()),
             iteration: Some(0),
         });
 
-        std::thread::sleep(Duration::from_millis(300));
         drop(obs);
         drop(store);
 
@@ -255,28 +1080,866 @@ mod tests {
     }
 
     #[test]
-    fn turn_complete_resets_counters() {
+    fn observer_hashes_tool_call_arguments() {
         let tmp = TempDir::new().unwrap();
         let store = make_store(&tmp);
-        let obs = TelemetryObserver::new(store, "test-sess".into());
+        let obs = TelemetryObserver::new(store.clone(), "test-sess".into());
+        let args = serde_json::json!({"command": "ls", "cwd": "."});
 
-        obs.record_event(&ObserverEvent::LlmResponse {
-            provider: "test".into(),
-            model: "test".into(),
-            duration: Duration::from_millis(1),
+        obs.record_event(&ObserverEvent::ToolCall {
+            tool: "shell".into(),
+            duration: Duration::from_millis(5),
             success: true,
-            error_message: None,
+            arguments: Some(args.clone()),
+            arguments_hash: Some("caller-supplied".into()),
+            iteration: None,
+        });
+
+        drop(obs);
+        drop(store);
+
+        let conn = rusqlite::Connection::open(tmp.path().join("research.db")).unwrap();
+        let hash: String = conn
+            .query_row(
+                "SELECT arguments_hash FROM action_events LIMIT 1",
+                [],
+                |r| r.get(0),
+            )
+            .unwrap();
+        assert_eq!(hash, hash_arguments(&args));
+
+        let embedding: Vec<u8> = conn
+            .query_row(
+                "SELECT tool_type_embedding FROM action_events LIMIT 1",
+                [],
+                |r| r.get(0),
+            )
+            .unwrap();
+        assert_eq!(
+            crate::telemetry::embeddings::decompress_embedding(&embedding).unwrap(),
+            compute_call_embedding("shell", &args).0
+        );
+    }
+
+    #[test]
+    fn observer_caches_tool_embedding_at_configured_dim() {
+        let tmp = TempDir::new().unwrap();
+        let config = TelemetryConfig {
+            tool_embeddings_enabled: true,
+            tool_embedding_dim: EmbeddingDim::D16,
+            ..TelemetryConfig::default()
+        };
+        let store = Arc::new(TelemetrySqliteStore::open(tmp.path(), config).unwrap());
+        let obs = TelemetryObserver::new(store.clone(), "test-sess".into());
+
+        for _ in 0..2 {
+            obs.record_event(&ObserverEvent::ToolCall {
+                tool: "shell".into(),
+                duration: Duration::from_millis(5),
+                success: true,
+                arguments: None,
+                arguments_hash: None,
+                iteration: None,
+            });
+        }
+
+        drop(obs);
+        drop(store);
+
+        let conn = rusqlite::Connection::open(tmp.path().join("research.db")).unwrap();
+        let (embedding, dimensions, rows): (Vec<u8>, i64, i64) = conn
+            .query_row(
+                "SELECT embedding, dimensions, COUNT(*) FROM tool_embeddings_cache",
+                [],
+                |r| Ok((r.get(0)?, r.get(1)?, r.get(2)?)),
+            )
+            .unwrap();
+        assert_eq!((dimensions, rows), (16, 1));
+        assert_eq!(
+            embedding,
+            compute_tool_embedding_with_dim("shell", EmbeddingDim::D16).0
+        );
+    }
+
+    #[test]
+    fn observer_encrypts_error_message() {
+        let tmp = TempDir::new().unwrap();
+        let store = make_store(&tmp);
+        let key = [9u8; 32];
+        let obs =
+            TelemetryObserver::new(store.clone(), "test-sess".into()).with_error_encryption(key);
+
+        obs.record_event(&ObserverEvent::LlmResponse {
+            provider: "openai".into(),
+            model: "gpt-4".into(),
+            duration: Duration::from_millis(10),
+            success: false,
+            error_message: Some("cannot open /home/alice/secret.txt".into()),
             tokens_in: None,
             tokens_out: None,
         });
 
-        assert_eq!(obs.sequence_counter.load(Ordering::Relaxed), 1);
-        assert_eq!(obs.turn_counter.load(Ordering::Relaxed), 0);
+        drop(obs);
+        drop(store);
 
-        obs.record_event(&ObserverEvent::TurnComplete);
+        let conn = rusqlite::Connection::open(tmp.path().join("research.db")).unwrap();
+        let stored: String = conn
+            .query_row("SELECT error_message FROM action_events", [], |r| r.get(0))
+            .unwrap();
+        assert!(!stored.contains("alice"));
+        assert_eq!(
+            crypto::decrypt_field(&stored, &key).unwrap(),
+            "cannot open /home/alice/secret.txt"
+        );
+    }
 
-        assert_eq!(obs.sequence_counter.load(Ordering::Relaxed), 0);
-        assert_eq!(obs.turn_counter.load(Ordering::Relaxed), 1);
-        assert!(obs.turn_action_sequence.lock().is_empty());
+    #[test]
+    fn observer_anonymizes_error_message() {
+        let tmp = TempDir::new().unwrap();
+        let store = make_store(&tmp);
+        let obs =
+            TelemetryObserver::new(store.clone(), "test-sess".into()).with_pii_anonymization(true);
+
+        obs.record_event(&ObserverEvent::LlmResponse {
+            provider: "openai".into(),
+            model: "gpt-4".into(),
+            duration: Duration::from_millis(10),
+            success: false,
+            error_message: Some("bob@example.com denied at 10.0.0.5".into()),
+            tokens_in: None,
+            tokens_out: None,
+        });
+
+        drop(obs);
+        drop(store);
+
+        let conn = rusqlite::Connection::open(tmp.path().join("research.db")).unwrap();
+        let stored: String = conn
+            .query_row("SELECT error_message FROM action_events", [], |r| r.get(0))
+            .unwrap();
+        assert_eq!(stored, "[REDACTED] denied at [REDACTED]");
+    }
+
+    #[test]
+    fn turn_complete_resets_counters() {
+        let tmp = TempDir::new().unwrap();
+        let store = make_store(&tmp);
+        let obs = TelemetryObserver::new(store, "test-sess".into());
+
+        obs.record_event(&ObserverEvent::LlmResponse {
+            provider: "test".into(),
+            model: "test".into(),
+            duration: Duration::from_millis(1),
+            success: true,
+            error_message: None,
+            tokens_in: None,
+            tokens_out: None,
+        });
+
+        assert_eq!(obs.local_sequence(false), 1);
+        assert_eq!(obs.turn_counter.load(Ordering::Relaxed), 0);
+
+        obs.record_event(&ObserverEvent::TurnComplete);
+
+        assert_eq!(obs.local_sequence(false), 0);
+        assert_eq!(obs.turn_counter.load(Ordering::Relaxed), 1);
+        assert!(obs.turn_action_sequence.lock().is_empty());
+    }
+
+    #[test]
+    fn snapshot_reflects_state_without_changing_it() {
+        let tmp = TempDir::new().unwrap();
+        let store = make_store(&tmp);
+        let obs = TelemetryObserver::new(store, "test-sess".into());
+
+        obs.record_event(&ObserverEvent::LlmResponse {
+            provider: "test".into(),
+            model: "test".into(),
+            duration: Duration::from_millis(1),
+            success: true,
+            error_message: None,
+            tokens_in: None,
+            tokens_out: None,
+        });
+        let snapshot = obs.snapshot();
+        assert_eq!(
+            snapshot,
+            ObserverSnapshot {
+                session_id: "test-sess".into(),
+                turn_count: 0,
+                sequence_count: 1,
+                previous_action_type: Some("llm_response".into()),
+                turn_action_sequence: vec!["llm_response".into()],
+            }
+        );
+        assert_eq!(obs.snapshot(), snapshot);
+
+        obs.record_event(&ObserverEvent::TurnComplete);
+        assert_eq!(
+            obs.snapshot(),
+            ObserverSnapshot {
+                session_id: "test-sess".into(),
+                turn_count: 1,
+                sequence_count: 0,
+                previous_action_type: None,
+                turn_action_sequence: Vec::new(),
+            }
+        );
+    }
+
+    #[test]
+    fn thread_correlation_id_overrides_configured_one() {
+        let tmp = TempDir::new().unwrap();
+        let store = make_store(&tmp);
+        let obs = TelemetryObserver::new(store.clone(), "test-sess".into())
+            .with_correlation_id(Some("configured".into()));
+        let call = |obs: &TelemetryObserver| {
+            obs.record_event(&ObserverEvent::ToolCall {
+                tool: "shell".into(),
+                duration: Duration::from_millis(1),
+                success: true,
+                arguments: None,
+                arguments_hash: None,
+                iteration: None,
+            });
+        };
+
+        call(&obs);
+        set_correlation_id(Some("req-42".into()));
+        call(&obs);
+        set_correlation_id(None);
+
+        drop(obs);
+        drop(store);
+
+        let conn = rusqlite::Connection::open(tmp.path().join("research.db")).unwrap();
+        let ids: Vec<String> = conn
+            .prepare("SELECT correlation_id FROM action_events ORDER BY id")
+            .unwrap()
+            .query_map([], |r| r.get(0))
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(ids, ["configured", "req-42"]);
+    }
+
+    #[test]
+    fn observer_records_session_markers() {
+        let tmp = TempDir::new().unwrap();
+        let store = make_store(&tmp);
+        let obs = TelemetryObserver::new(store.clone(), "test-sess".into());
+
+        obs.record_event(&ObserverEvent::SessionStart {
+            session_id: "test-sess".into(),
+        });
+        obs.record_event(&ObserverEvent::SessionEnd {
+            session_id: "test-sess".into(),
+        });
+        assert!(obs.turn_action_sequence.lock().is_empty());
+
+        drop(obs);
+        drop(store);
+
+        let conn = rusqlite::Connection::open(tmp.path().join("research.db")).unwrap();
+        let types: Vec<String> = conn
+            .prepare("SELECT event_type FROM action_events ORDER BY id")
+            .unwrap()
+            .query_map([], |r| r.get(0))
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(types, ["session_start", "session_end"]);
+    }
+
+    #[test]
+    fn session_markers_take_turn_sequence_numbers() {
+        let tmp = TempDir::new().unwrap();
+        let store = make_store(&tmp);
+        let obs = TelemetryObserver::new(store.clone(), "test-sess".into());
+
+        obs.record_event(&ObserverEvent::SessionStart {
+            session_id: "other-sess".into(),
+        });
+        obs.record_event(&ObserverEvent::AgentThinking {
+            tokens_used: 1,
+            model: "m".into(),
+        });
+        drop(obs);
+        drop(Arc::into_inner(store).unwrap());
+
+        let conn = rusqlite::Connection::open(tmp.path().join("research.db")).unwrap();
+        let rows: Vec<(String, i64)> = conn
+            .prepare("SELECT session_id, sequence_index FROM action_events ORDER BY id")
+            .unwrap()
+            .query_map([], |r| Ok((r.get(0)?, r.get(1)?)))
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(rows.len(), 2);
+        assert!(rows.iter().all(|(session, _)| session == "test-sess"));
+        assert_ne!(rows[0].1, rows[1].1);
+    }
+
+    #[test]
+    fn turn_complete_records_turn_duration() {
+        let tmp = TempDir::new().unwrap();
+        let store = make_store(&tmp);
+        let obs = TelemetryObserver::new(store.clone(), "test-sess".into());
+
+        obs.record_event(&ObserverEvent::TurnStart);
+        std::thread::sleep(Duration::from_millis(50));
+        obs.record_event(&ObserverEvent::TurnComplete);
+        assert!(obs.turn_started_at.lock().is_none());
+
+        drop(obs);
+        drop(store);
+
+        let conn = rusqlite::Connection::open(tmp.path().join("research.db")).unwrap();
+        let (event_type, duration_ms): (String, i64) = conn
+            .query_row(
+                "SELECT event_type, duration_ms FROM action_events LIMIT 1",
+                [],
+                |r| Ok((r.get(0)?, r.get(1)?)),
+            )
+            .unwrap();
+        assert_eq!(event_type, "turn_complete");
+        assert!(
+            (50..1_000).contains(&duration_ms),
+            "unexpected turn duration {duration_ms}ms"
+        );
+    }
+
+    #[test]
+    fn turn_sequence_is_stored_only_on_turn_complete() {
+        let tmp = TempDir::new().unwrap();
+        let store = make_store(&tmp);
+        let obs = TelemetryObserver::new(store.clone(), "test-sess".into());
+        let call = ObserverEvent::ToolCall {
+            tool: "shell".into(),
+            duration: Duration::from_millis(1),
+            success: true,
+            arguments: None,
+            arguments_hash: None,
+            iteration: None,
+        };
+
+        obs.record_event(&call);
+        obs.record_event(&call);
+        obs.record_event(&ObserverEvent::TurnComplete);
+
+        drop(obs);
+        drop(store);
+
+        let conn = rusqlite::Connection::open(tmp.path().join("research.db")).unwrap();
+        let rows: Vec<(String, Option<String>)> = conn
+            .prepare("SELECT event_type, turn_action_sequence FROM action_events ORDER BY id")
+            .unwrap()
+            .query_map([], |r| Ok((r.get(0)?, r.get(1)?)))
+            .unwrap()
+            .map(Result::unwrap)
+            .collect();
+        assert_eq!(
+            rows,
+            [
+                ("tool_call".to_string(), None),
+                ("tool_call".to_string(), None),
+                (
+                    "turn_complete".to_string(),
+                    Some(r#"["tool_call","tool_call"]"#.to_string())
+                ),
+            ]
+        );
+    }
+
+    fn recorded_turn_ids(format: TurnIdFormat) -> Vec<String> {
+        let tmp = TempDir::new().unwrap();
+        let store = make_store(&tmp);
+        let obs =
+            TelemetryObserver::new(store.clone(), "test-sess".into()).with_turn_id_format(format);
+        let call = ObserverEvent::ToolCall {
+            tool: "shell".into(),
+            duration: Duration::from_millis(1),
+            success: true,
+            arguments: None,
+            arguments_hash: None,
+            iteration: None,
+        };
+
+        obs.record_event(&call);
+        obs.record_event(&ObserverEvent::TurnComplete);
+        obs.record_event(&call);
+
+        drop(obs);
+        drop(store);
+
+        let conn = rusqlite::Connection::open(tmp.path().join("research.db")).unwrap();
+        let ids = conn
+            .prepare("SELECT turn_id FROM action_events ORDER BY id")
+            .unwrap()
+            .query_map([], |r| r.get(0))
+            .unwrap()
+            .map(Result::unwrap)
+            .collect();
+        ids
+    }
+
+    #[test]
+    fn turn_id_follows_configured_format() {
+        let full = recorded_turn_ids(TurnIdFormat::Full);
+        assert_eq!(full, ["test-sess-t0", "test-sess-t0", "test-sess-t1"]);
+
+        let compact = recorded_turn_ids(TurnIdFormat::Compact);
+        let tag = session_tag("test-sess");
+        assert_eq!(
+            compact,
+            [
+                format!("{tag}-0000"),
+                format!("{tag}-0000"),
+                format!("{tag}-0001")
+            ]
+        );
+
+        let ulid = recorded_turn_ids(TurnIdFormat::Ulid);
+        assert_eq!(ulid[0], ulid[1], "turn id must be stable within a turn");
+        assert_ne!(ulid[1], ulid[2]);
+        assert!(ulid.iter().all(|id| id.parse::<ulid::Ulid>().is_ok()));
+    }
+
+    #[test]
+    fn compact_turn_ids_differ_across_sessions() {
+        assert_eq!(session_tag("sess-a").len(), 8);
+        assert_eq!(session_tag("sess-a"), session_tag("sess-a"));
+        assert_ne!(session_tag("sess-a"), session_tag("sess-b"));
+    }
+
+    #[test]
+    fn base36_encodes_turn_counter() {
+        assert_eq!(to_base36(0), "0");
+        assert_eq!(to_base36(35), "z");
+        assert_eq!(to_base36(36 * 36 * 36 * 36), "10000");
+    }
+
+    #[test]
+    fn reset_session_starts_counters_over() {
+        let tmp = TempDir::new().unwrap();
+        let store = make_store(&tmp);
+        let obs = TelemetryObserver::new(store.clone(), "sess-a".into());
+        let call = ObserverEvent::ToolCall {
+            tool: "shell".into(),
+            duration: Duration::from_millis(1),
+            success: true,
+            arguments: None,
+            arguments_hash: None,
+            iteration: None,
+        };
+
+        obs.record_event(&call);
+        obs.record_event(&ObserverEvent::TurnComplete);
+        obs.record_event(&call);
+        obs.reset_session("sess-b".into());
+        assert!(obs.previous_action_type.lock().is_none());
+        assert!(obs.turn_action_sequence.lock().is_empty());
+        obs.record_event(&call);
+
+        drop(obs);
+        drop(store);
+
+        let conn = rusqlite::Connection::open(tmp.path().join("research.db")).unwrap();
+        let rows: Vec<(String, String, i64)> = conn
+            .prepare(
+                "SELECT session_id, turn_id, sequence_index FROM action_events
+                 WHERE event_type = 'tool_call' ORDER BY id",
+            )
+            .unwrap()
+            .query_map([], |r| Ok((r.get(0)?, r.get(1)?, r.get(2)?)))
+            .unwrap()
+            .map(Result::unwrap)
+            .collect();
+        assert_eq!(
+            rows,
+            [
+                ("sess-a".to_string(), "sess-a-t0".to_string(), 0),
+                ("sess-a".to_string(), "sess-a-t1".to_string(), 0),
+                ("sess-b".to_string(), "sess-b-t0".to_string(), 0),
+            ]
+        );
+    }
+
+    #[test]
+    fn concurrent_threads_get_unique_sequence_numbers() {
+        let tmp = TempDir::new().unwrap();
+        let store = make_store(&tmp);
+        let obs = Arc::new(TelemetryObserver::new(store.clone(), "test-sess".into()));
+
+        let handles: Vec<_> = (0..4)
+            .map(|_| {
+                let obs = obs.clone();
+                std::thread::spawn(move || (0..25).map(|_| obs.next_sequence()).collect::<Vec<_>>())
+            })
+            .collect();
+        let mut all: Vec<i64> = handles
+            .into_iter()
+            .flat_map(|h| h.join().unwrap())
+            .collect();
+        all.sort_unstable();
+        all.dedup();
+        assert_eq!(all.len(), 100);
+        assert!(all.iter().all(|seq| seq / 1_000_000 < 4));
+    }
+
+    #[test]
+    fn sequences_are_numbered_per_observer() {
+        let tmp = TempDir::new().unwrap();
+        let store = make_store(&tmp);
+        let first = TelemetryObserver::new(store.clone(), "sess-a".into());
+        let second = TelemetryObserver::new(store, "sess-b".into());
+
+        assert_eq!(first.next_sequence(), 0);
+        assert_eq!(first.next_sequence(), 1);
+        // Another thread's first event on `first` takes the next range.
+        std::thread::scope(|scope| {
+            scope.spawn(|| assert_eq!(first.next_sequence(), 1_000_000));
+        });
+        assert_eq!(second.next_sequence(), 0);
+    }
+
+    #[test]
+    fn turn_complete_restarts_other_threads_sequences() {
+        let tmp = TempDir::new().unwrap();
+        let store = make_store(&tmp);
+        let obs = Arc::new(TelemetryObserver::new(store.clone(), "test-sess".into()));
+
+        obs.next_sequence();
+        obs.next_sequence();
+        assert_eq!(obs.local_sequence(false), 2);
+
+        let worker = {
+            let obs = obs.clone();
+            std::thread::spawn(move || {
+                let first = obs.local_sequence(true);
+                obs.record_event(&ObserverEvent::TurnComplete);
+                (first, obs.local_sequence(false))
+            })
+        };
+        assert_eq!(worker.join().unwrap(), (0, 0));
+        assert_eq!(obs.local_sequence(false), 0);
+    }
+
+    #[test]
+    fn agent_thinking_records_tokens_in() {
+        let tmp = TempDir::new().unwrap();
+        let store = make_store(&tmp);
+        let obs = TelemetryObserver::new(store.clone(), "test-sess".into());
+
+        obs.record_event(&ObserverEvent::AgentThinking {
+            tokens_used: 512,
+            model: "claude".into(),
+        });
+        assert_eq!(
+            obs.snapshot().turn_action_sequence,
+            ["agent_thinking".to_string()]
+        );
+
+        drop(obs);
+        drop(store);
+
+        let conn = rusqlite::Connection::open(tmp.path().join("research.db")).unwrap();
+        let row: (String, String, i64) = conn
+            .query_row(
+                "SELECT event_type, model, tokens_in FROM action_events",
+                [],
+                |r| Ok((r.get(0)?, r.get(1)?, r.get(2)?)),
+            )
+            .unwrap();
+        assert_eq!(row, ("agent_thinking".into(), "claude".into(), 512));
+    }
+
+    #[test]
+    fn streamed_chunks_fill_missing_tokens_out() {
+        let tmp = TempDir::new().unwrap();
+        let store = make_store(&tmp);
+        let obs = TelemetryObserver::new(store.clone(), "test-sess".into());
+        let chunk = |model: &str, chunk_tokens| ObserverEvent::StreamingTokenChunk {
+            provider: "openai".into(),
+            model: model.into(),
+            chunk_tokens,
+        };
+        let response = |tokens_out| ObserverEvent::LlmResponse {
+            provider: "openai".into(),
+            model: "gpt-4o".into(),
+            duration: Duration::from_millis(1),
+            success: true,
+            error_message: None,
+            tokens_in: None,
+            tokens_out,
+        };
+
+        obs.record_event(&chunk("gpt-4o", 10));
+        obs.record_event(&chunk("gpt-4o", 15));
+        obs.record_event(&chunk("other", 99));
+        obs.record_event(&response(None));
+        obs.record_event(&chunk("gpt-4o", 7));
+        obs.record_event(&response(Some(8)));
+        obs.record_event(&response(None));
+        obs.record_event(&chunk("gpt-4o", 3));
+        obs.record_event(&ObserverEvent::TurnComplete);
+        obs.record_event(&response(None));
+        assert!(obs.streamed_tokens.lock().is_empty());
+
+        drop(obs);
+        drop(store);
+
+        let conn = rusqlite::Connection::open(tmp.path().join("research.db")).unwrap();
+        let tokens_out: Vec<Option<i64>> = conn
+            .prepare("SELECT tokens_out FROM action_events WHERE event_type = 'llm_response' ORDER BY id")
+            .unwrap()
+            .query_map([], |r| r.get(0))
+            .unwrap()
+            .map(Result::unwrap)
+            .collect();
+        assert_eq!(tokens_out, [Some(25), Some(8), None, None]);
+    }
+
+    #[test]
+    fn file_operations_record_path_and_metadata() {
+        let tmp = TempDir::new().unwrap();
+        let store = make_store(&tmp);
+        let obs = TelemetryObserver::new(store.clone(), "test-sess".into());
+
+        obs.record_event(&ObserverEvent::FileOperation {
+            path: "/workspace/notes.md".into(),
+            operation: FileOp::Write,
+            bytes: 42,
+            success: true,
+        });
+
+        drop(obs);
+        drop(store);
+
+        let conn = rusqlite::Connection::open(tmp.path().join("research.db")).unwrap();
+        let (event_type, metadata): (String, String) = conn
+            .query_row(
+                "SELECT event_type, metadata_json FROM action_events
+                 WHERE tool_name = '/workspace/notes.md'",
+                [],
+                |r| Ok((r.get(0)?, r.get(1)?)),
+            )
+            .unwrap();
+        assert_eq!(event_type, "file_op");
+        let metadata: serde_json::Value = serde_json::from_str(&metadata).unwrap();
+        assert_eq!(
+            metadata,
+            serde_json::json!({ "operation": "write", "bytes": 42 })
+        );
+    }
+
+    #[test]
+    fn call_depth_offsets_iteration_index() {
+        let tmp = TempDir::new().unwrap();
+        let store = make_store(&tmp);
+        let obs = TelemetryObserver::new(store.clone(), "test-sess".into()).with_call_depth(2);
+
+        obs.record_event(&ObserverEvent::ToolCall {
+            tool: "delegate".into(),
+            duration: Duration::from_millis(1),
+            success: true,
+            arguments: None,
+            arguments_hash: None,
+            iteration: Some(3),
+        });
+
+        drop(obs);
+        drop(store);
+
+        let conn = rusqlite::Connection::open(tmp.path().join("research.db")).unwrap();
+        let (call_depth, iteration_index): (u32, i64) = conn
+            .query_row(
+                "SELECT call_depth, iteration_index FROM action_events",
+                [],
+                |r| Ok((r.get(0)?, r.get(1)?)),
+            )
+            .unwrap();
+        assert_eq!(call_depth, 2);
+        assert_eq!(iteration_index, 2_003);
+    }
+
+    #[test]
+    fn tool_calls_without_iteration_are_auto_indexed() {
+        let tmp = TempDir::new().unwrap();
+        let store = make_store(&tmp);
+        let obs = TelemetryObserver::new(store.clone(), "test-sess".into());
+        let call = |iteration: Option<u32>| ObserverEvent::ToolCall {
+            tool: "shell".into(),
+            duration: Duration::from_millis(1),
+            success: true,
+            arguments: None,
+            arguments_hash: None,
+            iteration,
+        };
+
+        obs.record_event(&call(None));
+        obs.record_event(&call(None));
+        obs.record_event(&call(Some(7)));
+        obs.record_event(&ObserverEvent::TurnComplete);
+        obs.record_event(&call(None));
+
+        drop(obs);
+        drop(store);
+
+        let conn = rusqlite::Connection::open(tmp.path().join("research.db")).unwrap();
+        let indexes: Vec<i64> = conn
+            .prepare("SELECT iteration_index FROM action_events WHERE event_type = 'tool_call' ORDER BY id")
+            .unwrap()
+            .query_map([], |r| r.get(0))
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(indexes, [0, 1, 7, 0]);
+    }
+
+    #[test]
+    fn observer_estimates_llm_cost() {
+        let tmp = TempDir::new().unwrap();
+        let store = make_store(&tmp);
+        let mut table = TokenPriceTable::new();
+        table.insert(
+            "openai",
+            "gpt-4",
+            crate::telemetry::pricing::TokenPrice {
+                input_usd_per_1k: 0.03,
+                output_usd_per_1k: 0.06,
+            },
+        );
+        let obs = TelemetryObserver::new(store.clone(), "test-sess".into()).with_price_table(table);
+
+        obs.record_event(&ObserverEvent::LlmResponse {
+            provider: "openai".into(),
+            model: "gpt-4".into(),
+            duration: Duration::from_millis(10),
+            success: true,
+            error_message: None,
+            tokens_in: Some(1000),
+            tokens_out: Some(500),
+        });
+
+        drop(obs);
+        drop(store);
+
+        let conn = rusqlite::Connection::open(tmp.path().join("research.db")).unwrap();
+        let cost: f64 = conn
+            .query_row(
+                "SELECT estimated_cost_usd FROM action_events LIMIT 1",
+                [],
+                |r| r.get(0),
+            )
+            .unwrap();
+        assert!((cost - 0.06).abs() < 1e-9);
+    }
+
+    #[test]
+    fn budget_exceeded_sets_flag_once() {
+        let tmp = TempDir::new().unwrap();
+        let store = make_store(&tmp);
+        let mut table = TokenPriceTable::new();
+        table.insert(
+            "openai",
+            "gpt-4",
+            crate::telemetry::pricing::TokenPrice {
+                input_usd_per_1k: 0.01,
+                output_usd_per_1k: 0.0,
+            },
+        );
+        let obs = TelemetryObserver::with_budget(store.clone(), "test-sess".into(), 0.015, table);
+        let exceeded = obs.budget_exceeded();
+        let response = ObserverEvent::LlmResponse {
+            provider: "openai".into(),
+            model: "gpt-4".into(),
+            duration: Duration::from_millis(1),
+            success: true,
+            error_message: None,
+            tokens_in: Some(1000),
+            tokens_out: Some(0),
+        };
+
+        obs.record_event(&response);
+        assert!(!exceeded.load(Ordering::Relaxed));
+        obs.record_event(&response);
+        assert!(exceeded.load(Ordering::Relaxed));
+        obs.record_event(&response);
+        assert!((obs.session_cost_usd() - 0.03).abs() < 1e-9);
+
+        drop(obs);
+        drop(store);
+
+        let conn = rusqlite::Connection::open(tmp.path().join("research.db")).unwrap();
+        let markers: i64 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM action_events WHERE event_type = 'budget_exceeded'",
+                [],
+                |r| r.get(0),
+            )
+            .unwrap();
+        assert_eq!(markers, 1);
+    }
+
+    #[test]
+    fn slow_tool_calls_count_as_sla_breaches() {
+        let tmp = TempDir::new().unwrap();
+        let store = make_store(&tmp);
+        let obs = TelemetryObserver::new(store, "test-sess".into())
+            .with_tool_sla(HashMap::from([("shell".to_string(), 100)]));
+        let call = |tool: &str, ms: u64| ObserverEvent::ToolCall {
+            tool: tool.into(),
+            duration: Duration::from_millis(ms),
+            success: true,
+            arguments: None,
+            arguments_hash: None,
+            iteration: None,
+        };
+
+        obs.record_event(&call("shell", 50));
+        obs.record_event(&call("shell", 100));
+        obs.record_event(&call("shell", 250));
+        obs.record_event(&call("file_read", 5_000));
+
+        assert_eq!(obs.sla_breach_count(), 1);
+    }
+
+    #[test]
+    fn llm_latency_is_tracked_per_model() {
+        let tmp = TempDir::new().unwrap();
+        let store = make_store(&tmp);
+        let obs = TelemetryObserver::new(store, "test-sess".into()).with_p99_alert(Some(500));
+        let response = |model: &str, ms: u64| ObserverEvent::LlmResponse {
+            provider: "openai".into(),
+            model: model.into(),
+            duration: Duration::from_millis(ms),
+            success: true,
+            error_message: None,
+            tokens_in: None,
+            tokens_out: None,
+        };
+
+        obs.record_event(&response("gpt-4", 100));
+        obs.record_event(&response("gpt-4", 900));
+        obs.record_event(&response("gpt-4o-mini", 20));
+
+        assert_eq!(obs.llm_p99_ms("openai", "gpt-4"), Some(900));
+        assert_eq!(obs.llm_p99_ms("openai", "gpt-4o-mini"), Some(20));
+        assert_eq!(obs.llm_p99_ms("anthropic", "gpt-4"), None);
+    }
+
+    #[test]
+    fn turn_sequence_json_matches_full_serialization() {
+        let mut seq = TurnSequence::new();
+        assert_eq!(seq.json(), "[]");
+        let events = ["llm_response", "tool_call", "quote\"d"];
+        for e in events {
+            seq.push(e);
+        }
+        assert_eq!(seq.json(), serde_json::to_string(&events).unwrap());
+        seq.clear();
+        assert!(seq.is_empty());
+        assert_eq!(seq.json(), "[]");
     }
 }
@@ -0,0 +1,100 @@
+use crate::telemetry::store::ActionRecord;
+use crossbeam_channel::{Receiver, Sender};
+
+/// Fixed-size pool of pre-allocated `Box<ActionRecord>` values.
+///
+/// The store takes a box from the pool for each submitted action event and
+/// the writer thread hands it back after insertion, so a steady stream of
+/// tool calls reuses the same allocations: the box itself and the buffers of
+/// its `ts`, `session_id`, `turn_id` and `event_type` strings, which
+/// [`TelemetrySqliteStore::submit_action_ref`] fills in place. Optional
+/// fields are freed when a record is returned; that happens on the writer
+/// thread, off the caller's path.
+///
+/// Trade-offs: the pool pins `size` records of memory for the lifetime of
+/// the store. When more records are in flight than the pool holds (e.g. a
+/// burst while the writer is stalled) the pool is exhausted and
+/// [`Self::acquire`] falls back to a fresh allocation; boxes returned to a
+/// full pool are simply freed, so it never grows beyond `size`.
+///
+/// [`TelemetrySqliteStore::submit_action_ref`]:
+///     crate::telemetry::store::TelemetrySqliteStore::submit_action_ref
+#[derive(Clone)]
+pub struct ActionRecordPool {
+    pool: Receiver<Box<ActionRecord>>,
+    recycle: Sender<Box<ActionRecord>>,
+}
+
+impl ActionRecordPool {
+    /// Create a pool pre-filled with `size` default records.
+    pub fn new(size: usize) -> Self {
+        let (recycle, pool) = crossbeam_channel::bounded(size);
+        for _ in 0..size {
+            let _ = recycle.try_send(Box::default());
+        }
+        Self { pool, recycle }
+    }
+
+    /// Take a record from the pool, or allocate one if it is exhausted.
+    pub fn acquire(&self) -> Box<ActionRecord> {
+        self.pool.try_recv().unwrap_or_default()
+    }
+
+    /// Reset `record`, keeping its string buffers, and return it to the
+    /// pool; it is dropped if the pool is already full.
+    pub fn release(&self, mut record: Box<ActionRecord>) {
+        let ActionRecord {
+            mut ts,
+            mut session_id,
+            mut turn_id,
+            mut event_type,
+            ..
+        } = std::mem::take(&mut *record);
+        for buffer in [&mut ts, &mut session_id, &mut turn_id, &mut event_type] {
+            buffer.clear();
+        }
+        *record = ActionRecord {
+            ts,
+            session_id,
+            turn_id,
+            event_type,
+            ..ActionRecord::default()
+        };
+        let _ = self.recycle.try_send(record);
+    }
+
+    /// Records currently available without allocating.
+    pub fn available(&self) -> usize {
+        self.pool.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn acquire_reuses_released_boxes() {
+        let pool = ActionRecordPool::new(1);
+        let mut record = pool.acquire();
+        assert_eq!(pool.available(), 0);
+        record.session_id.push_str("s1");
+        let addr = std::ptr::from_ref::<ActionRecord>(&record);
+
+        pool.release(record);
+        let reused = pool.acquire();
+        assert_eq!(std::ptr::from_ref::<ActionRecord>(&reused), addr);
+        assert!(reused.session_id.is_empty());
+        assert!(reused.session_id.capacity() >= 2, "string buffer is kept");
+    }
+
+    #[test]
+    fn exhausted_pool_falls_back_to_allocation() {
+        let pool = ActionRecordPool::new(1);
+        let first = pool.acquire();
+        let second = pool.acquire();
+        pool.release(first);
+        pool.release(second);
+        assert_eq!(pool.available(), 1);
+    }
+}
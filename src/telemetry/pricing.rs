@@ -0,0 +1,116 @@
+use crate::config::CostConfig;
+use std::collections::HashMap;
+
+/// Price of one thousand tokens for a single model.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TokenPrice {
+    pub input_usd_per_1k: f64,
+    pub output_usd_per_1k: f64,
+}
+
+/// Token prices keyed by `(provider, model)`.
+#[derive(Debug, Clone, Default)]
+pub struct TokenPriceTable {
+    pub entries: HashMap<(String, String), TokenPrice>,
+}
+
+impl TokenPriceTable {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn insert(&mut self, provider: &str, model: &str, price: TokenPrice) {
+        self.entries
+            .insert((provider.to_string(), model.to_string()), price);
+    }
+
+    /// Build a table from `[cost.prices]`, whose keys are `provider/model`
+    /// and whose prices are per million tokens.
+    pub fn from_cost_config(config: &CostConfig) -> Self {
+        let mut table = Self::new();
+        for (key, pricing) in &config.prices {
+            let Some((provider, model)) = key.split_once('/') else {
+                continue;
+            };
+            table.insert(
+                provider,
+                model,
+                TokenPrice {
+                    input_usd_per_1k: pricing.input / 1000.0,
+                    output_usd_per_1k: pricing.output / 1000.0,
+                },
+            );
+        }
+        table
+    }
+
+    /// Look up the price for `model` served by `provider`.
+    ///
+    /// Aggregators such as OpenRouter name models `vendor/model`, so when
+    /// there is no exact match the model name itself is split and retried.
+    pub fn get(&self, provider: &str, model: &str) -> Option<&TokenPrice> {
+        self.entries
+            .get(&(provider.to_string(), model.to_string()))
+            .or_else(|| {
+                let (vendor, name) = model.split_once('/')?;
+                self.entries.get(&(vendor.to_string(), name.to_string()))
+            })
+    }
+}
+
+/// Estimate the USD cost of a call, or `None` when the model is not priced.
+pub fn estimate_cost(
+    tokens_in: i64,
+    tokens_out: i64,
+    provider: &str,
+    model: &str,
+    table: &TokenPriceTable,
+) -> Option<f64> {
+    let price = table.get(provider, model)?;
+    Some(
+        tokens_in.max(0) as f64 / 1000.0 * price.input_usd_per_1k
+            + tokens_out.max(0) as f64 / 1000.0 * price.output_usd_per_1k,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn table() -> TokenPriceTable {
+        let mut table = TokenPriceTable::new();
+        table.insert(
+            "openai",
+            "gpt-4o",
+            TokenPrice {
+                input_usd_per_1k: 0.005,
+                output_usd_per_1k: 0.015,
+            },
+        );
+        table
+    }
+
+    #[test]
+    fn estimates_cost_from_token_counts() {
+        let cost = estimate_cost(2000, 1000, "openai", "gpt-4o", &table()).unwrap();
+        assert!((cost - 0.025).abs() < 1e-12);
+    }
+
+    #[test]
+    fn unknown_model_has_no_cost() {
+        assert_eq!(estimate_cost(10, 10, "openai", "gpt-5", &table()), None);
+    }
+
+    #[test]
+    fn aggregator_model_names_fall_back_to_vendor() {
+        assert!(estimate_cost(1000, 0, "openrouter", "openai/gpt-4o", &table()).is_some());
+    }
+
+    #[test]
+    fn table_from_cost_config_converts_to_per_1k() {
+        let table = TokenPriceTable::from_cost_config(&CostConfig::default());
+        let price = table.get("openai", "gpt-4o").unwrap();
+        assert!((price.input_usd_per_1k - 0.005).abs() < 1e-12);
+        assert!((price.output_usd_per_1k - 0.015).abs() < 1e-12);
+    }
+}
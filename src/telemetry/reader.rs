@@ -1,6 +1,19 @@
+use crate::telemetry::anomaly::{AnomalousSample, RollingStats, SampleField};
+use crate::telemetry::crypto;
+use crate::telemetry::csv;
+use crate::telemetry::embeddings::{decompress_embedding, embedding_from_le_bytes, l2_normalize};
+use crate::telemetry::schema;
+use crate::telemetry::store::ActionRecord;
 use anyhow::{Context, Result};
-use rusqlite::Connection;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use parking_lot::Mutex;
+use rusqlite::{Connection, OptionalExtension};
+use std::collections::{BTreeMap, VecDeque};
 use std::path::Path;
+use std::sync::Arc;
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::ReceiverStream;
 
 /// A read-only view of the telemetry database for export/download.
 ///
@@ -8,6 +21,29 @@ use std::path::Path;
 /// don't interfere with the writer thread (WAL mode allows this).
 pub struct TelemetryReader {
     conn: Connection,
+    decryption_key: Option<[u8; 32]>,
+}
+
+/// Gzip level for compressed exports. `None` still produces a valid gzip
+/// stream, just with stored (uncompressed) blocks.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CompressionLevel {
+    None,
+    Fast,
+    #[default]
+    Default,
+    Best,
+}
+
+impl From<CompressionLevel> for Compression {
+    fn from(level: CompressionLevel) -> Self {
+        match level {
+            CompressionLevel::None => Compression::none(),
+            CompressionLevel::Fast => Compression::fast(),
+            CompressionLevel::Default => Compression::default(),
+            CompressionLevel::Best => Compression::best(),
+        }
+    }
 }
 
 /// Action event record for serialization in the download endpoint.
@@ -32,6 +68,50 @@ pub struct ActionEventRow {
     pub previous_action_type: Option<String>,
     pub turn_action_sequence: Option<String>,
     pub error_message: Option<String>,
+    pub correlation_id: Option<String>,
+    pub id: i64,
+    pub parent_action_id: Option<i64>,
+    pub estimated_cost_usd: Option<f64>,
+    pub metadata_json: Option<String>,
+    pub call_depth: u32,
+    /// Decompressed `tool_type_embedding`; left out of serialized exports.
+    #[serde(skip)]
+    pub tool_type_embedding: Option<Vec<u8>>,
+}
+
+impl ActionEventRow {
+    /// Build the row a reader would return for `record` once it has been
+    /// inserted as row `id`.
+    pub(crate) fn from_record(id: i64, record: &ActionRecord) -> Self {
+        Self {
+            ts: record.ts.clone(),
+            ts_epoch_ms: record.ts_epoch_ms,
+            session_id: record.session_id.clone(),
+            turn_id: record.turn_id.clone(),
+            sequence_index: record.sequence_index,
+            event_type: record.event_type.clone(),
+            provider: record.provider.clone(),
+            model: record.model.clone(),
+            tool_name: record.tool_name.clone(),
+            arguments_hash: record.arguments_hash.clone(),
+            tool_success: record.tool_success,
+            duration_ms: record.duration_ms,
+            tokens_in: record.tokens_in,
+            tokens_out: record.tokens_out,
+            is_user_initiated: record.is_user_initiated,
+            iteration_index: record.iteration_index,
+            previous_action_type: record.previous_action_type.clone(),
+            turn_action_sequence: record.turn_action_sequence.clone(),
+            error_message: record.error_message.clone(),
+            correlation_id: record.correlation_id.clone(),
+            id,
+            parent_action_id: record.parent_action_id,
+            estimated_cost_usd: record.estimated_cost_usd,
+            metadata_json: record.metadata_json.clone(),
+            call_depth: record.call_depth,
+            tool_type_embedding: record.tool_type_embedding.clone(),
+        }
+    }
 }
 
 /// System sample record for serialization in the download endpoint.
@@ -48,9 +128,522 @@ pub struct SystemSampleRow {
     pub file_write_bytes: i64,
     pub net_connections: i64,
     pub dest_ip_entropy: f64,
+    pub tcp_state_json: Option<String>,
     pub syscall_freq_json: Option<String>,
 }
 
+/// A row of [`TelemetryReader::interpolated_samples`].
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct SystemSampleRowEx {
+    #[serde(flatten)]
+    pub sample: SystemSampleRow,
+    /// Synthesized to fill a gap rather than read from `system_samples`.
+    pub interpolated: bool,
+}
+
+/// A system sample alongside its change from the previous sample. Deltas
+/// are `None` for the first sample ever recorded.
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct SystemSampleDelta {
+    pub ts_epoch_ms: i64,
+    /// Time since the previous sample; divide a delta by it for a rate.
+    pub interval_ms: Option<i64>,
+    pub cpu_usage_pct: f64,
+    pub cpu_usage_pct_delta: Option<f64>,
+    pub memory_used_bytes: i64,
+    pub memory_used_bytes_delta: Option<i64>,
+    pub file_read_bytes: i64,
+    pub file_read_bytes_delta: Option<i64>,
+    pub file_write_bytes: i64,
+    pub file_write_bytes_delta: Option<i64>,
+    pub net_connections: i64,
+    pub net_connections_delta: Option<i64>,
+}
+
+/// An outbound connection and the tool call that was running when it was
+/// made, if any.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct NetworkEventRow {
+    pub ts: String,
+    pub ts_epoch_ms: i64,
+    pub pid: u32,
+    pub dest_ip: String,
+    pub dest_port: u16,
+    pub success: bool,
+    /// Row id of the `tool_call` action whose execution window contains
+    /// the connection.
+    pub action_event_id: Option<i64>,
+    pub tool_name: Option<String>,
+}
+
+/// A `getaddrinfo` lookup and the tool call that was running when it was
+/// made, if any.
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct DnsQueryRow {
+    pub ts: String,
+    pub ts_epoch_ms: i64,
+    pub hostname: String,
+    pub resolution_ms: f64,
+    pub success: bool,
+    /// Row id of the `tool_call` action whose execution window contains
+    /// the lookup.
+    pub action_event_id: Option<i64>,
+    pub tool_name: Option<String>,
+}
+
+/// Tables of the telemetry database, as returned by
+/// [`TelemetryReader::schema_info`].
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct SchemaInfo {
+    pub tables: Vec<TableInfo>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct TableInfo {
+    pub name: String,
+    pub columns: Vec<ColumnInfo>,
+    pub indexes: Vec<IndexInfo>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct ColumnInfo {
+    pub name: String,
+    /// Declared type, e.g. `INTEGER`; empty when none was declared.
+    pub sql_type: String,
+    pub not_null: bool,
+    pub primary_key: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct IndexInfo {
+    pub name: String,
+    pub unique: bool,
+    /// Indexed columns in key order.
+    pub columns: Vec<String>,
+}
+
+/// A full table scan found by [`detect_missing_indexes`].
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct IndexSuggestion {
+    pub table: String,
+    /// Columns the scanning query filters on, to index in this order.
+    pub columns: Vec<String>,
+    /// Rough speedup from the index: rows the scan reads over the
+    /// `log2(rows)` an index lookup would touch. 1 for an empty table.
+    pub estimated_improvement: f64,
+}
+
+/// Per-session aggregate built from `session_start` / `session_end` markers.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct SessionSummary {
+    pub session_id: String,
+    pub started_at_ms: Option<i64>,
+    pub ended_at_ms: Option<i64>,
+    /// `ended_at_ms - started_at_ms`; `None` while the session is open.
+    pub duration_ms: Option<i64>,
+    /// Action events in the session, excluding the boundary markers.
+    pub action_count: i64,
+}
+
+/// Which action events [`TelemetryReader::export_action_events_filtered`]
+/// returns. Every field left `None` matches all events.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ActionEventFilter {
+    /// Inclusive lower bound on `ts_epoch_ms`.
+    pub since_epoch_ms: Option<i64>,
+    /// Inclusive upper bound on `ts_epoch_ms`.
+    pub until_epoch_ms: Option<i64>,
+    /// Only sessions carrying this tag.
+    pub tag: Option<String>,
+}
+
+/// One row of the `session_stats` view.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct SessionStats {
+    pub session_id: String,
+    pub event_count: i64,
+    pub start_ms: i64,
+    pub end_ms: i64,
+    /// Sums over events that report tokens; 0 when none do.
+    pub total_tokens_in: i64,
+    pub total_tokens_out: i64,
+}
+
+/// Headline figures for a monitoring dashboard, from
+/// [`TelemetryReader::dashboard_summary`].
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct DashboardSummary {
+    pub total_sessions: i64,
+    /// Distinct `(session_id, turn_id)` pairs.
+    pub total_turns: i64,
+    pub total_llm_calls: i64,
+    pub total_tool_calls: i64,
+    /// `None` when no LLM response reported a duration.
+    pub avg_llm_latency_ms: Option<f64>,
+    pub total_tokens_in: i64,
+    pub total_tokens_out: i64,
+    /// Over tool calls with a recorded outcome; `None` when there are none.
+    pub tool_success_rate: Option<f64>,
+    /// Most called tools with their call counts, busiest first.
+    pub top_5_tools: Vec<(String, u64)>,
+    /// Nearest-rank 95th percentiles over system samples; `None` without
+    /// samples.
+    pub cpu_p95: Option<f64>,
+    pub memory_p95: Option<i64>,
+}
+
+/// Token spend of a session relative to the tool calls it produced.
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct TokenEfficiencyReport {
+    pub total_tokens_in: i64,
+    pub total_tokens_out: i64,
+    pub total_tool_calls: i64,
+    /// Input tokens spent per tool execution; a signal of how effective the
+    /// prompt is at getting the model to act.
+    pub tokens_per_tool_call: f64,
+    pub output_to_input_ratio: f64,
+    pub tool_calls_per_llm_call: f64,
+}
+
+/// Aggregate LLM response stats for one model, across every provider that
+/// served it.
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct ProviderModelStat {
+    pub model: String,
+    pub call_count: i64,
+    pub avg_latency_ms: f64,
+    pub total_tokens_in: i64,
+    pub total_tokens_out: i64,
+    /// Output tokens produced per input token.
+    pub token_efficiency: f64,
+    /// Fraction of calls that failed.
+    pub error_rate: f64,
+}
+
+/// A/B comparison of two models. Differences are `b - a`, so a positive
+/// `latency_diff_ms` means model B is slower.
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct ModelComparison {
+    pub model_a_stats: ProviderModelStat,
+    pub model_b_stats: ProviderModelStat,
+    pub latency_diff_ms: f64,
+    pub token_efficiency_diff: f64,
+    pub error_rate_diff: f64,
+}
+
+/// LLM token usage of one provider/model pair within a time window.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct ModelTokenUsage {
+    pub provider: String,
+    pub model: String,
+    pub tokens_in_total: i64,
+    pub tokens_out_total: i64,
+    pub call_count: i64,
+    /// Epoch-ms timestamp of the first call in the window.
+    pub first_seen_ts: i64,
+    /// Epoch-ms timestamp of the last call in the window.
+    pub last_seen_ts: i64,
+}
+
+/// An LLM call during which the host CPU ran hot.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct CpuSpikeDuringCall {
+    pub action_event: ActionEventRow,
+    /// Highest CPU usage sampled near the call.
+    pub peak_cpu: f64,
+    /// Epoch-ms timestamp of the peak sample.
+    pub sample_ts: i64,
+}
+
+/// Sessions listed as examples in an [`ErrorPattern`].
+const MAX_EXAMPLE_SESSIONS: usize = 3;
+
+/// An error message that recurs across action events.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct ErrorPattern {
+    /// Lowercased, trimmed message.
+    pub normalized_message: String,
+    pub count: i64,
+    /// Up to three sessions the error occurred in.
+    pub example_session_ids: Vec<String>,
+}
+
+/// A turn action sequence that recurred at one call depth of a session.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct RepeatedSequence {
+    pub call_depth: u32,
+    pub sequence: Vec<String>,
+    pub occurrences: i64,
+}
+
+/// Ordered event types of one completed turn.
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct TurnSequenceRow {
+    pub turn_id: String,
+    /// When the turn completed.
+    pub ts_epoch_ms: i64,
+    pub sequence: Vec<String>,
+}
+
+/// Columns read into [`ActionEventRow`], in the order
+/// [`action_event_from_row`] expects.
+const ACTION_EVENT_COLUMNS: &str =
+    "ts, ts_epoch_ms, session_id, turn_id, sequence_index, event_type,
+    provider, model, tool_name, arguments_hash, tool_success,
+    duration_ms, tokens_in, tokens_out, is_user_initiated,
+    iteration_index, previous_action_type, turn_action_sequence,
+    error_message, correlation_id, id, parent_action_id, estimated_cost_usd,
+    metadata_json, call_depth, tool_type_embedding";
+
+fn action_event_from_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<ActionEventRow> {
+    Ok(ActionEventRow {
+        ts: row.get(0)?,
+        ts_epoch_ms: row.get(1)?,
+        session_id: row.get(2)?,
+        turn_id: row.get(3)?,
+        sequence_index: row.get(4)?,
+        event_type: row.get(5)?,
+        provider: row.get(6)?,
+        model: row.get(7)?,
+        tool_name: row.get(8)?,
+        arguments_hash: row.get(9)?,
+        tool_success: row.get::<_, Option<i32>>(10)?.map(|v| v != 0),
+        duration_ms: row.get(11)?,
+        tokens_in: row.get(12)?,
+        tokens_out: row.get(13)?,
+        is_user_initiated: row.get::<_, i32>(14)? != 0,
+        iteration_index: row.get(15)?,
+        previous_action_type: row.get(16)?,
+        turn_action_sequence: row.get(17)?,
+        error_message: row.get(18)?,
+        correlation_id: row.get(19)?,
+        id: row.get(20)?,
+        parent_action_id: row.get(21)?,
+        estimated_cost_usd: row.get(22)?,
+        metadata_json: row.get(23)?,
+        call_depth: row.get(24)?,
+        tool_type_embedding: row
+            .get::<_, Option<Vec<u8>>>(25)?
+            .map(|blob| decompress_embedding(&blob))
+            .transpose()
+            .map_err(|e| {
+                rusqlite::Error::FromSqlConversionFailure(25, rusqlite::types::Type::Blob, e.into())
+            })?,
+    })
+}
+
+/// Stream the action events matching `filter` to an async consumer.
+///
+/// [`TelemetryReader::iter_action_events`] runs on the blocking pool and
+/// sends each row through a channel holding up to `buffer` rows, so the
+/// query only runs ahead of the consumer by that much. The reader sits
+/// behind a mutex because SQLite connections cannot be shared between
+/// threads; it is locked only while the stream opens its own connection
+/// with [`TelemetryReader::reopen`], so other users are not held up for the
+/// stream's lifetime. Must be called within a tokio runtime.
+pub fn stream_action_events(
+    reader: Arc<Mutex<TelemetryReader>>,
+    filter: ActionEventFilter,
+    buffer: usize,
+) -> ReceiverStream<Result<ActionEventRow>> {
+    let (tx, rx) = mpsc::channel(buffer.max(1));
+    tokio::task::spawn_blocking(move || {
+        let reopened = reader.lock().reopen();
+        let reader = match reopened {
+            Ok(reader) => reader,
+            Err(e) => {
+                let _ = tx.blocking_send(Err(e));
+                return;
+            }
+        };
+        let rows = match reader.iter_action_events(filter) {
+            Ok(rows) => rows,
+            Err(e) => {
+                let _ = tx.blocking_send(Err(e));
+                return;
+            }
+        };
+        for row in rows {
+            let failed = row.is_err();
+            // A send error means the stream was dropped.
+            if tx.blocking_send(row).is_err() || failed {
+                break;
+            }
+        }
+    });
+    ReceiverStream::new(rx)
+}
+
+/// Rows fetched per query by [`TelemetryReader::iter_action_events`].
+pub const ACTION_EVENT_PAGE_SIZE: usize = 1_000;
+
+/// Iterator behind [`TelemetryReader::iter_action_events`]: pages through
+/// the matching events by `(ts_epoch_ms, sequence_index, id)`.
+struct ActionEventPages<'a> {
+    reader: &'a TelemetryReader,
+    filter: ActionEventFilter,
+    /// Sort key of the last row fetched.
+    after: (i64, i64, i64),
+    page: std::vec::IntoIter<ActionEventRow>,
+    exhausted: bool,
+    /// This iterator opened the read transaction that keeps every page on
+    /// one snapshot, and ends it once exhausted or dropped.
+    owns_snapshot: bool,
+}
+
+impl ActionEventPages<'_> {
+    fn fetch_page(&mut self) -> Result<()> {
+        let (ts, seq, id) = self.after;
+        let rows = self.reader.query_action_events(
+            &format!(
+                "SELECT {ACTION_EVENT_COLUMNS}
+                 FROM action_events
+                 WHERE ts_epoch_ms >= MAX(?1, ?2)
+                   AND (ts_epoch_ms, sequence_index, id) > (?2, ?3, ?4)
+                   AND (?5 IS NULL OR ts_epoch_ms <= ?5)
+                   AND (?6 IS NULL OR session_id IN (
+                       SELECT session_id FROM session_tags WHERE tag = ?6
+                   ))
+                 ORDER BY ts_epoch_ms ASC, sequence_index ASC, id ASC
+                 LIMIT ?7"
+            ),
+            rusqlite::params![
+                self.filter.since_epoch_ms.unwrap_or(0),
+                ts,
+                seq,
+                id,
+                self.filter.until_epoch_ms,
+                self.filter.tag,
+                ACTION_EVENT_PAGE_SIZE as i64,
+            ],
+        )?;
+        self.exhausted = rows.len() < ACTION_EVENT_PAGE_SIZE;
+        if let Some(last) = rows.last() {
+            self.after = (last.ts_epoch_ms, last.sequence_index, last.id);
+        }
+        self.page = rows.into_iter();
+        if self.exhausted {
+            self.end_snapshot();
+        }
+        Ok(())
+    }
+
+    fn end_snapshot(&mut self) {
+        if std::mem::take(&mut self.owns_snapshot) {
+            if let Err(e) = self.reader.conn.execute_batch("COMMIT") {
+                tracing::warn!("ending telemetry read snapshot failed: {e}");
+            }
+        }
+    }
+}
+
+impl Drop for ActionEventPages<'_> {
+    fn drop(&mut self) {
+        self.end_snapshot();
+    }
+}
+
+impl Iterator for ActionEventPages<'_> {
+    type Item = Result<ActionEventRow>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(row) = self.page.next() {
+            return Some(Ok(row));
+        }
+        if self.exhausted {
+            return None;
+        }
+        if let Err(e) = self.fetch_page() {
+            self.exhausted = true;
+            self.end_snapshot();
+            return Some(Err(e));
+        }
+        self.page.next().map(Ok)
+    }
+}
+
+/// `num / den`, or 0 when `den` is 0.
+fn ratio(num: i64, den: i64) -> f64 {
+    if den == 0 {
+        0.0
+    } else {
+        num as f64 / den as f64
+    }
+}
+
+/// `LEFT JOIN` of `action_events ae` onto the tool call running at
+/// `{alias}.ts_epoch_ms`.
+///
+/// Tool calls are recorded when they finish, so a call covers
+/// `[ts_epoch_ms - duration_ms, ts_epoch_ms]`. When calls overlap, the one
+/// that finished first after the event wins.
+///
+/// The statement binds `?3` to the longest recorded tool call duration
+/// ([`TelemetryReader::max_tool_call_duration_ms`]), which bounds the
+/// `idx_ae_epoch` range scanned per event; without it an event outside any
+/// call would scan to the end of the table.
+fn tool_call_join(alias: &str) -> String {
+    format!(
+        "LEFT JOIN action_events ae ON ae.id = (
+             SELECT id FROM action_events
+             WHERE event_type = 'tool_call'
+               AND ts_epoch_ms >= {alias}.ts_epoch_ms
+               AND ts_epoch_ms <= {alias}.ts_epoch_ms + ?3
+               AND ts_epoch_ms - COALESCE(duration_ms, 0) <= {alias}.ts_epoch_ms
+             ORDER BY ts_epoch_ms ASC, id ASC LIMIT 1
+         )"
+    )
+}
+
+/// Columns read into [`SystemSampleRow`], in the order
+/// [`system_sample_from_row`] expects.
+const SYSTEM_SAMPLE_COLUMNS: &str =
+    "ts, ts_epoch_ms, cpu_usage_pct, memory_used_bytes, memory_total_bytes,
+    process_count, process_spawn_rate, file_read_bytes, file_write_bytes,
+    net_connections, dest_ip_entropy, tcp_state_json, syscall_freq_json";
+
+fn system_sample_from_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<SystemSampleRow> {
+    Ok(SystemSampleRow {
+        ts: row.get(0)?,
+        ts_epoch_ms: row.get(1)?,
+        cpu_usage_pct: row.get(2)?,
+        memory_used_bytes: row.get(3)?,
+        memory_total_bytes: row.get(4)?,
+        process_count: row.get(5)?,
+        process_spawn_rate: row.get(6)?,
+        file_read_bytes: row.get(7)?,
+        file_write_bytes: row.get(8)?,
+        net_connections: row.get(9)?,
+        dest_ip_entropy: row.get(10)?,
+        tcp_state_json: row.get(11)?,
+        syscall_freq_json: row.get(12)?,
+    })
+}
+
+/// The sample at `ts_epoch_ms`, between `prev` and `next`.
+// The interpolated memory lies between two i64 readings.
+#[allow(clippy::cast_possible_truncation)]
+fn interpolate_sample(
+    prev: &SystemSampleRow,
+    next: &SystemSampleRow,
+    ts_epoch_ms: i64,
+) -> SystemSampleRow {
+    let fraction =
+        (ts_epoch_ms - prev.ts_epoch_ms) as f64 / (next.ts_epoch_ms - prev.ts_epoch_ms) as f64;
+    let memory_step = (next.memory_used_bytes - prev.memory_used_bytes) as f64 * fraction;
+    SystemSampleRow {
+        ts: chrono::DateTime::from_timestamp_millis(ts_epoch_ms)
+            .map(|ts| ts.to_rfc3339())
+            .unwrap_or_default(),
+        ts_epoch_ms,
+        cpu_usage_pct: prev.cpu_usage_pct + (next.cpu_usage_pct - prev.cpu_usage_pct) * fraction,
+        memory_used_bytes: prev.memory_used_bytes + memory_step.round() as i64,
+        tcp_state_json: None,
+        syscall_freq_json: None,
+        ..prev.clone()
+    }
+}
+
 impl TelemetryReader {
     /// Open a read-only connection to the telemetry database.
     pub fn open(db_path: &Path) -> Result<Self> {
@@ -59,7 +652,45 @@ impl TelemetryReader {
             rusqlite::OpenFlags::SQLITE_OPEN_READ_ONLY | rusqlite::OpenFlags::SQLITE_OPEN_NO_MUTEX,
         )
         .with_context(|| format!("opening telemetry db read-only: {}", db_path.display()))?;
-        Ok(Self { conn })
+        // Databases from before soft-delete have nothing to hide.
+        let has_deleted_at: bool = conn.query_row(
+            "SELECT COUNT(*) > 0 FROM pragma_table_info('action_events')
+             WHERE name = 'deleted_at'",
+            [],
+            |row| row.get(0),
+        )?;
+        if has_deleted_at {
+            conn.execute_batch(schema::LIVE_ACTION_EVENTS_VIEW_DDL)
+                .context("hiding soft-deleted action events")?;
+        }
+        Ok(Self {
+            conn,
+            decryption_key: None,
+        })
+    }
+
+    /// Open another read-only connection to the same database, with the same
+    /// decryption key, e.g. for use on another thread.
+    pub fn reopen(&self) -> Result<Self> {
+        let path = self
+            .conn
+            .path()
+            .filter(|path| !path.is_empty())
+            .context("telemetry reader is not backed by a file")?;
+        let reader = Self::open(Path::new(path))?;
+        Ok(Self {
+            decryption_key: self.decryption_key,
+            ..reader
+        })
+    }
+
+    /// Decrypt `error_message` values written with field encryption enabled.
+    ///
+    /// Values that fail to decrypt (e.g. rows stored before encryption was
+    /// turned on) are returned unchanged.
+    pub fn with_decryption_key(mut self, key: [u8; 32]) -> Self {
+        self.decryption_key = Some(key);
+        self
     }
 
     /// Export action events, optionally filtered by timestamp.
@@ -68,126 +699,1352 @@ impl TelemetryReader {
         since_epoch_ms: Option<i64>,
         limit: usize,
     ) -> Result<Vec<ActionEventRow>> {
+        let filter = ActionEventFilter {
+            since_epoch_ms,
+            ..ActionEventFilter::default()
+        };
+        self.export_action_events_filtered(&filter, limit)
+    }
+
+    /// Export the action events matching `filter`, oldest first.
+    pub fn export_action_events_filtered(
+        &self,
+        filter: &ActionEventFilter,
+        limit: usize,
+    ) -> Result<Vec<ActionEventRow>> {
+        self.query_action_events(
+            &format!(
+                "SELECT {ACTION_EVENT_COLUMNS}
+                 FROM action_events
+                 WHERE ts_epoch_ms >= ?1
+                   AND (?4 IS NULL OR ts_epoch_ms <= ?4)
+                   AND (?3 IS NULL OR session_id IN (
+                       SELECT session_id FROM session_tags WHERE tag = ?3
+                   ))
+                 ORDER BY ts_epoch_ms ASC, sequence_index ASC
+                 LIMIT ?2"
+            ),
+            rusqlite::params![
+                filter.since_epoch_ms.unwrap_or(0),
+                limit as i64,
+                filter.tag,
+                filter.until_epoch_ms,
+            ],
+        )
+    }
+
+    /// Stream the action events matching `filter`, oldest first, without
+    /// collecting them all. Rows are fetched in keyset-paged batches of
+    /// [`ACTION_EVENT_PAGE_SIZE`], so memory stays bounded however many
+    /// match; each page is a fresh query, which also keeps the iterator free
+    /// of a borrowed `Statement`.
+    ///
+    /// The pages share one read transaction, so rows committed while the
+    /// iterator is alive are not seen and the result is a consistent
+    /// snapshot. The transaction ends when the iterator is exhausted or
+    /// dropped; until then it holds back WAL checkpoints. An iterator
+    /// created inside another's transaction shares it.
+    pub fn iter_action_events(
+        &self,
+        filter: ActionEventFilter,
+    ) -> Result<impl Iterator<Item = Result<ActionEventRow>> + '_> {
+        let owns_snapshot = self.conn.is_autocommit();
+        if owns_snapshot {
+            self.conn
+                .execute_batch("BEGIN")
+                .context("starting telemetry read snapshot")?;
+        }
+        let mut pages = ActionEventPages {
+            reader: self,
+            filter,
+            after: (i64::MIN, i64::MIN, i64::MIN),
+            page: Vec::new().into_iter(),
+            exhausted: false,
+            owns_snapshot,
+        };
+        pages.fetch_page()?;
+        Ok(pages)
+    }
+
+    /// Export failed tool calls (`tool_success = 0`), oldest first,
+    /// optionally filtered by timestamp. Reads only the partial
+    /// `idx_ae_failed_tools` index rather than every action event.
+    pub fn export_failed_tool_calls(
+        &self,
+        since_epoch_ms: Option<i64>,
+        limit: usize,
+    ) -> Result<Vec<ActionEventRow>> {
+        self.query_action_events(
+            &failed_tool_calls_sql(),
+            rusqlite::params![since_epoch_ms.unwrap_or(0), limit as i64],
+        )
+    }
+
+    /// Write action events to `out` as newline-delimited JSON, one
+    /// [`ActionEventRow`] per line. Returns the number of lines written.
+    /// [`import_action_events_json`](crate::telemetry::import::import_action_events_json)
+    /// reads this format back.
+    pub fn export_action_events_ndjson(
+        &self,
+        since_epoch_ms: Option<i64>,
+        limit: usize,
+        out: &mut dyn std::io::Write,
+    ) -> Result<usize> {
+        let events = self.export_action_events(since_epoch_ms, limit)?;
+        for event in &events {
+            serde_json::to_writer(&mut *out, event)?;
+            out.write_all(b"\n")?;
+        }
+        Ok(events.len())
+    }
+
+    /// Write action events to `out` as CSV with a header row; see
+    /// [`crate::telemetry::csv`] for the column order. Returns the number of
+    /// events written.
+    pub fn export_action_events_csv(
+        &self,
+        since_epoch_ms: Option<i64>,
+        limit: usize,
+        out: &mut dyn std::io::Write,
+    ) -> Result<usize> {
+        let events = self.export_action_events(since_epoch_ms, limit)?;
+        csv::write_header(out)?;
+        for event in &events {
+            csv::write_action_event(out, event)?;
+        }
+        Ok(events.len())
+    }
+
+    /// [`export_action_events_csv`](Self::export_action_events_csv), gzip
+    /// compressed at `level`.
+    pub fn export_action_events_csv_gz(
+        &self,
+        since_epoch_ms: Option<i64>,
+        limit: usize,
+        out: &mut dyn std::io::Write,
+        level: CompressionLevel,
+    ) -> Result<usize> {
+        let mut gz = GzEncoder::new(out, level.into());
+        let written = self.export_action_events_csv(since_epoch_ms, limit, &mut gz)?;
+        gz.finish().context("finishing gzip stream")?;
+        Ok(written)
+    }
+
+    /// [`export_action_events_ndjson`](Self::export_action_events_ndjson),
+    /// gzip compressed at `level`.
+    pub fn export_action_events_ndjson_gz(
+        &self,
+        since_epoch_ms: Option<i64>,
+        limit: usize,
+        out: &mut dyn std::io::Write,
+        level: CompressionLevel,
+    ) -> Result<usize> {
+        let mut gz = GzEncoder::new(out, level.into());
+        let written = self.export_action_events_ndjson(since_epoch_ms, limit, &mut gz)?;
+        gz.finish().context("finishing gzip stream")?;
+        Ok(written)
+    }
+
+    /// Export action events as a serialized Arrow IPC stream, using the
+    /// schema from [`action_events_schema`](crate::telemetry::arrow::action_events_schema).
+    pub fn export_action_events_arrow(
+        &self,
+        since_epoch_ms: Option<i64>,
+        limit: usize,
+    ) -> Result<Vec<u8>> {
+        let events = self.export_action_events(since_epoch_ms, limit)?;
+        crate::telemetry::arrow::action_events_to_ipc(&events)
+    }
+
+    /// Export up to `limit` action events with a row id greater than
+    /// `after_id`, in insertion order, skipping those recorded before
+    /// `since_epoch_ms` (none when `None`).
+    pub fn action_events_after(
+        &self,
+        after_id: i64,
+        since_epoch_ms: Option<i64>,
+        limit: usize,
+    ) -> Result<Vec<ActionEventRow>> {
+        self.query_action_events(
+            &format!(
+                "SELECT {ACTION_EVENT_COLUMNS}
+                 FROM action_events
+                 WHERE id > ?1
+                   AND ts_epoch_ms >= ?2
+                 ORDER BY id ASC
+                 LIMIT ?3"
+            ),
+            rusqlite::params![after_id, since_epoch_ms.unwrap_or(0), limit as i64],
+        )
+    }
+
+    /// Export every action event recorded for `session_id`, in the order
+    /// it was recorded: by timestamp, then `sequence_index`, since parallel
+    /// writers can commit events out of order.
+    pub fn export_session_events(&self, session_id: &str) -> Result<Vec<ActionEventRow>> {
+        self.query_action_events(
+            &format!(
+                "SELECT {ACTION_EVENT_COLUMNS}
+                 FROM action_events
+                 WHERE session_id = ?1
+                 ORDER BY ts_epoch_ms ASC, sequence_index ASC, id ASC"
+            ),
+            rusqlite::params![session_id],
+        )
+    }
+
+    /// Session that recorded `turn_id`, via `idx_ae_turn`.
+    pub fn session_id_for_turn(&self, turn_id: &str) -> Result<Option<String>> {
+        Ok(self
+            .conn
+            .query_row(
+                "SELECT session_id FROM action_events
+                 WHERE turn_id = ?1
+                 ORDER BY id ASC
+                 LIMIT 1",
+                rusqlite::params![turn_id],
+                |row| row.get(0),
+            )
+            .optional()?)
+    }
+
+    /// Every action event of `turn_id`, in `sequence_index` order.
+    pub fn events_for_turn(&self, turn_id: &str) -> Result<Vec<ActionEventRow>> {
+        self.query_action_events(
+            &format!(
+                "SELECT {ACTION_EVENT_COLUMNS}
+                 FROM action_events
+                 WHERE turn_id = ?1
+                 ORDER BY sequence_index ASC, id ASC"
+            ),
+            rusqlite::params![turn_id],
+        )
+    }
+
+    /// Export every action event tagged with correlation ID `id`.
+    pub fn export_by_correlation_id(&self, id: &str) -> Result<Vec<ActionEventRow>> {
+        self.query_action_events(
+            &format!(
+                "SELECT {ACTION_EVENT_COLUMNS}
+                 FROM action_events
+                 WHERE correlation_id = ?1
+                 ORDER BY ts_epoch_ms ASC, id ASC"
+            ),
+            rusqlite::params![id],
+        )
+    }
+
+    /// Export the action event `root_id` and every descendant reachable
+    /// through `parent_action_id`, ordered by time.
+    pub fn export_action_tree(&self, root_id: i64) -> Result<Vec<ActionEventRow>> {
+        self.query_action_events(
+            &format!(
+                "WITH RECURSIVE tree(id) AS (
+                     SELECT id FROM action_events WHERE id = ?1
+                     UNION ALL
+                     SELECT ae.id FROM action_events ae
+                     JOIN tree ON ae.parent_action_id = tree.id
+                 )
+                 SELECT {ACTION_EVENT_COLUMNS}
+                 FROM action_events
+                 WHERE id IN (SELECT id FROM tree)
+                 ORDER BY ts_epoch_ms ASC, id ASC"
+            ),
+            rusqlite::params![root_id],
+        )
+    }
+
+    /// Summarize a session from its recorded events.
+    pub fn session_summary(&self, session_id: &str) -> Result<SessionSummary> {
+        let (started_at_ms, ended_at_ms, action_count) = self.conn.query_row(
+            "SELECT MIN(CASE WHEN event_type = 'session_start' THEN ts_epoch_ms END),
+                    MAX(CASE WHEN event_type = 'session_end' THEN ts_epoch_ms END),
+                    COALESCE(SUM(event_type NOT IN ('session_start', 'session_end')), 0)
+             FROM action_events
+             WHERE session_id = ?1",
+            rusqlite::params![session_id],
+            |row| {
+                Ok((
+                    row.get::<_, Option<i64>>(0)?,
+                    row.get::<_, Option<i64>>(1)?,
+                    row.get::<_, i64>(2)?,
+                ))
+            },
+        )?;
+        Ok(SessionSummary {
+            session_id: session_id.to_string(),
+            started_at_ms,
+            ended_at_ms,
+            duration_ms: started_at_ms
+                .zip(ended_at_ms)
+                .map(|(start, end)| end - start),
+            action_count,
+        })
+    }
+
+    /// Aggregate the events and system samples recorded at or after
+    /// `since_epoch_ms` (everything when `None`) into a [`DashboardSummary`],
+    /// in two queries.
+    pub fn dashboard_summary(&self, since_epoch_ms: Option<i64>) -> Result<DashboardSummary> {
         let since = since_epoch_ms.unwrap_or(0);
+        let mut summary = self.conn.query_row(
+            "WITH ev AS (SELECT * FROM action_events WHERE ts_epoch_ms >= ?1),
+                  ss AS (SELECT * FROM system_samples WHERE ts_epoch_ms >= ?1),
+                  p95 AS (SELECT MAX((COUNT(*) * 95 + 99) / 100 - 1, 0) AS rank FROM ss)
+             SELECT
+                 (SELECT COUNT(DISTINCT session_id) FROM ev),
+                 (SELECT COUNT(*) FROM (SELECT DISTINCT session_id, turn_id FROM ev)),
+                 (SELECT COUNT(*) FROM ev WHERE event_type = 'llm_response'),
+                 (SELECT COUNT(*) FROM ev WHERE event_type = 'tool_call'),
+                 (SELECT AVG(duration_ms) FROM ev WHERE event_type = 'llm_response'),
+                 (SELECT COALESCE(SUM(tokens_in), 0) FROM ev),
+                 (SELECT COALESCE(SUM(tokens_out), 0) FROM ev),
+                 (SELECT AVG(tool_success) FROM ev
+                  WHERE event_type = 'tool_call' AND tool_success IS NOT NULL),
+                 (SELECT cpu_usage_pct FROM ss ORDER BY cpu_usage_pct
+                  LIMIT 1 OFFSET (SELECT rank FROM p95)),
+                 (SELECT memory_used_bytes FROM ss ORDER BY memory_used_bytes
+                  LIMIT 1 OFFSET (SELECT rank FROM p95))",
+            rusqlite::params![since],
+            |row| {
+                Ok(DashboardSummary {
+                    total_sessions: row.get(0)?,
+                    total_turns: row.get(1)?,
+                    total_llm_calls: row.get(2)?,
+                    total_tool_calls: row.get(3)?,
+                    avg_llm_latency_ms: row.get(4)?,
+                    total_tokens_in: row.get(5)?,
+                    total_tokens_out: row.get(6)?,
+                    tool_success_rate: row.get(7)?,
+                    top_5_tools: Vec::new(),
+                    cpu_p95: row.get(8)?,
+                    memory_p95: row.get(9)?,
+                })
+            },
+        )?;
+
+        let mut stmt = self.conn.prepare(
+            "SELECT tool_name, COUNT(*) AS calls FROM action_events
+             WHERE ts_epoch_ms >= ?1 AND event_type = 'tool_call' AND tool_name IS NOT NULL
+             GROUP BY tool_name
+             ORDER BY calls DESC, tool_name ASC
+             LIMIT 5",
+        )?;
+        summary.top_5_tools = stmt
+            .query_map(rusqlite::params![since], |row| {
+                Ok((row.get(0)?, row.get::<_, i64>(1)?.unsigned_abs()))
+            })?
+            .collect::<rusqlite::Result<_>>()?;
+        Ok(summary)
+    }
+
+    /// Fraction of recorded calls of `tool_name` that succeeded, from the
+    /// `tool_success_rates` counters; `None` if none were recorded. Soft
+    /// deletes do not reduce the counters.
+    pub fn tool_success_rate(&self, tool_name: &str) -> Result<Option<f64>> {
+        let counts = self
+            .conn
+            .query_row(
+                "SELECT success_count, failure_count FROM tool_success_rates
+                 WHERE tool_name = ?1",
+                rusqlite::params![tool_name],
+                |row| Ok((row.get::<_, i64>(0)?, row.get::<_, i64>(1)?)),
+            )
+            .optional()?;
+        Ok(counts.and_then(|(success, failure)| {
+            let total = success + failure;
+            (total > 0).then(|| success as f64 / total as f64)
+        }))
+    }
+
+    /// The `k` tools in `tool_embeddings_cache` whose embeddings are most
+    /// cosine-similar to `query_embedding`, most similar first. Each byte is
+    /// one dimension; cached embeddings of a different length, and
+    /// normalized ones, are skipped.
+    pub fn find_nearest_tools(
+        &self,
+        query_embedding: &[u8],
+        k: usize,
+    ) -> Result<Vec<(String, f32)>> {
+        self.rank_cached_tools(false, k, |embedding| {
+            (embedding.len() == query_embedding.len())
+                .then(|| cosine_similarity(query_embedding, embedding))
+        })
+    }
+
+    /// Like [`Self::find_nearest_tools`] over the `normalized` rows: stored
+    /// vectors are already unit-length, so only `query_embedding` is
+    /// normalized and each score is a dot product.
+    pub fn find_nearest_normalized_tools(
+        &self,
+        query_embedding: &[f32],
+        k: usize,
+    ) -> Result<Vec<(String, f32)>> {
+        let query = l2_normalize(query_embedding.to_vec());
+        self.rank_cached_tools(true, k, |bytes| {
+            let embedding = embedding_from_le_bytes(bytes);
+            (embedding.len() == query.len())
+                .then(|| query.iter().zip(&embedding).map(|(a, b)| a * b).sum())
+        })
+    }
+
+    /// Score every cached embedding with the given `normalized` flag and
+    /// return the `k` best; `score` returns `None` to skip a row.
+    fn rank_cached_tools(
+        &self,
+        normalized: bool,
+        k: usize,
+        score: impl Fn(&[u8]) -> Option<f32>,
+    ) -> Result<Vec<(String, f32)>> {
         let mut stmt = self.conn.prepare(
-            "SELECT ts, ts_epoch_ms, session_id, turn_id, sequence_index, event_type,
-                    provider, model, tool_name, arguments_hash, tool_success,
-                    duration_ms, tokens_in, tokens_out, is_user_initiated,
-                    iteration_index, previous_action_type, turn_action_sequence,
-                    error_message
+            "SELECT tool_name, embedding FROM tool_embeddings_cache WHERE normalized = ?1",
+        )?;
+        let mut scored = Vec::new();
+        let mut rows = stmt.query(rusqlite::params![normalized])?;
+        while let Some(row) = rows.next()? {
+            let embedding: Vec<u8> = row.get(1)?;
+            if let Some(similarity) = score(&embedding) {
+                scored.push((row.get(0)?, similarity));
+            }
+        }
+        scored.sort_by(|a: &(String, f32), b| b.1.total_cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        scored.truncate(k);
+        Ok(scored)
+    }
+
+    /// Event counts, time span and token totals for every session, from
+    /// the `session_stats` view, earliest session first.
+    pub fn query_session_stats(&self) -> Result<Vec<SessionStats>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT session_id, event_count, start_ms, end_ms,
+                    COALESCE(total_tokens_in, 0), COALESCE(total_tokens_out, 0)
+             FROM session_stats
+             ORDER BY start_ms ASC, session_id ASC",
+        )?;
+        let stats = stmt
+            .query_map([], |row| {
+                Ok(SessionStats {
+                    session_id: row.get(0)?,
+                    event_count: row.get(1)?,
+                    start_ms: row.get(2)?,
+                    end_ms: row.get(3)?,
+                    total_tokens_in: row.get(4)?,
+                    total_tokens_out: row.get(5)?,
+                })
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(stats)
+    }
+
+    /// Compare a session's LLM token usage with the tool calls it led to.
+    pub fn token_efficiency_report(&self, session_id: &str) -> Result<TokenEfficiencyReport> {
+        let (tokens_in, tokens_out, llm_calls, tool_calls) = self.conn.query_row(
+            "SELECT COALESCE(SUM(CASE WHEN event_type = 'llm_response' THEN tokens_in END), 0),
+                    COALESCE(SUM(CASE WHEN event_type = 'llm_response' THEN tokens_out END), 0),
+                    COALESCE(SUM(event_type = 'llm_response'), 0),
+                    COALESCE(SUM(event_type = 'tool_call'), 0)
              FROM action_events
-             WHERE ts_epoch_ms >= ?1
-             ORDER BY ts_epoch_ms ASC
-             LIMIT ?2",
+             WHERE session_id = ?1",
+            rusqlite::params![session_id],
+            |row| {
+                Ok((
+                    row.get::<_, i64>(0)?,
+                    row.get::<_, i64>(1)?,
+                    row.get::<_, i64>(2)?,
+                    row.get::<_, i64>(3)?,
+                ))
+            },
+        )?;
+        Ok(TokenEfficiencyReport {
+            total_tokens_in: tokens_in,
+            total_tokens_out: tokens_out,
+            total_tool_calls: tool_calls,
+            tokens_per_tool_call: ratio(tokens_in, tool_calls),
+            output_to_input_ratio: ratio(tokens_out, tokens_in),
+            tool_calls_per_llm_call: ratio(tool_calls, llm_calls),
+        })
+    }
+
+    /// Aggregate LLM response stats for `model`, optionally only since a
+    /// timestamp.
+    pub fn model_stats(
+        &self,
+        model: &str,
+        since_epoch_ms: Option<i64>,
+    ) -> Result<ProviderModelStat> {
+        let (call_count, avg_latency_ms, tokens_in, tokens_out, failures) = self.conn.query_row(
+            "SELECT COUNT(*),
+                    COALESCE(AVG(duration_ms), 0.0),
+                    COALESCE(SUM(tokens_in), 0),
+                    COALESCE(SUM(tokens_out), 0),
+                    COALESCE(SUM(tool_success = 0), 0)
+             FROM action_events
+             WHERE event_type = 'llm_response' AND model = ?1 AND ts_epoch_ms >= ?2",
+            rusqlite::params![model, since_epoch_ms.unwrap_or(0)],
+            |row| {
+                Ok((
+                    row.get::<_, i64>(0)?,
+                    row.get::<_, f64>(1)?,
+                    row.get::<_, i64>(2)?,
+                    row.get::<_, i64>(3)?,
+                    row.get::<_, i64>(4)?,
+                ))
+            },
+        )?;
+        Ok(ProviderModelStat {
+            model: model.to_string(),
+            call_count,
+            avg_latency_ms,
+            total_tokens_in: tokens_in,
+            total_tokens_out: tokens_out,
+            token_efficiency: ratio(tokens_out, tokens_in),
+            error_rate: ratio(failures, call_count),
+        })
+    }
+
+    /// Token usage per provider/model over the last `window_secs` seconds,
+    /// heaviest input users first.
+    pub fn token_usage_by_model(&self, window_secs: u64) -> Result<Vec<ModelTokenUsage>> {
+        let window_ms = i64::try_from(window_secs.saturating_mul(1000)).unwrap_or(i64::MAX);
+        let since = chrono::Utc::now()
+            .timestamp_millis()
+            .saturating_sub(window_ms);
+        let mut stmt = self.conn.prepare(
+            "SELECT COALESCE(provider, ''), COALESCE(model, ''),
+                    COALESCE(SUM(tokens_in), 0), COALESCE(SUM(tokens_out), 0),
+                    COUNT(*), MIN(ts_epoch_ms), MAX(ts_epoch_ms)
+             FROM action_events
+             WHERE event_type = 'llm_response' AND ts_epoch_ms >= ?1
+             GROUP BY provider, model
+             ORDER BY 3 DESC, provider, model",
         )?;
+        let rows = stmt.query_map(rusqlite::params![since], |row| {
+            Ok(ModelTokenUsage {
+                provider: row.get(0)?,
+                model: row.get(1)?,
+                tokens_in_total: row.get(2)?,
+                tokens_out_total: row.get(3)?,
+                call_count: row.get(4)?,
+                first_seen_ts: row.get(5)?,
+                last_seen_ts: row.get(6)?,
+            })
+        })?;
+        Ok(rows.collect::<rusqlite::Result<_>>()?)
+    }
 
-        let rows = stmt.query_map(rusqlite::params![since, limit as i64], |row| {
-            Ok(ActionEventRow {
-                ts: row.get(0)?,
-                ts_epoch_ms: row.get(1)?,
-                session_id: row.get(2)?,
-                turn_id: row.get(3)?,
-                sequence_index: row.get(4)?,
-                event_type: row.get(5)?,
-                provider: row.get(6)?,
-                model: row.get(7)?,
-                tool_name: row.get(8)?,
-                arguments_hash: row.get(9)?,
-                tool_success: row.get::<_, Option<i32>>(10)?.map(|v| v != 0),
-                duration_ms: row.get(11)?,
-                tokens_in: row.get(12)?,
-                tokens_out: row.get(13)?,
-                is_user_initiated: row.get::<_, i32>(14)? != 0,
-                iteration_index: row.get(15)?,
-                previous_action_type: row.get(16)?,
-                turn_action_sequence: row.get(17)?,
-                error_message: row.get(18)?,
+    /// LLM responses with a system sample above `cpu_threshold` percent
+    /// within `window_ms` of the response, in time order. Shows whether
+    /// local model inference is saturating the CPU.
+    pub fn cpu_spikes_during_llm_calls(
+        &self,
+        cpu_threshold: f64,
+        window_ms: i64,
+    ) -> Result<Vec<CpuSpikeDuringCall>> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT * FROM (
+                 SELECT {ACTION_EVENT_COLUMNS},
+                        (SELECT cpu_usage_pct FROM system_samples
+                         WHERE ts_epoch_ms BETWEEN ae.ts_epoch_ms - ?2 AND ae.ts_epoch_ms + ?2
+                           AND cpu_usage_pct > ?1
+                         ORDER BY cpu_usage_pct DESC, ts_epoch_ms ASC LIMIT 1) AS peak_cpu,
+                        (SELECT ts_epoch_ms FROM system_samples
+                         WHERE ts_epoch_ms BETWEEN ae.ts_epoch_ms - ?2 AND ae.ts_epoch_ms + ?2
+                           AND cpu_usage_pct > ?1
+                         ORDER BY cpu_usage_pct DESC, ts_epoch_ms ASC LIMIT 1) AS sample_ts
+                 FROM action_events ae
+                 WHERE event_type = 'llm_response'
+             )
+             WHERE peak_cpu IS NOT NULL
+             ORDER BY ts_epoch_ms ASC, id ASC"
+        ))?;
+        // The two extra columns follow the 26 of ACTION_EVENT_COLUMNS.
+        let rows = stmt.query_map(rusqlite::params![cpu_threshold, window_ms], |row| {
+            Ok(CpuSpikeDuringCall {
+                action_event: action_event_from_row(row)?,
+                peak_cpu: row.get(26)?,
+                sample_ts: row.get(27)?,
             })
         })?;
 
         let mut results = Vec::new();
         for row in rows {
-            results.push(row?);
+            let mut spike = row?;
+            self.decrypt_error_message(&mut spike.action_event);
+            results.push(spike);
         }
         Ok(results)
     }
 
-    /// Export system samples, optionally filtered by timestamp.
-    pub fn export_system_samples(
+    /// Error messages occurring at least `min_count` times, most frequent
+    /// first. Messages are compared lowercased and trimmed.
+    ///
+    /// Encrypted messages differ on every row, so when a decryption key is
+    /// set they are decrypted and grouped here rather than in SQL.
+    pub fn common_error_patterns(
         &self,
+        min_count: usize,
         since_epoch_ms: Option<i64>,
-        limit: usize,
-    ) -> Result<Vec<SystemSampleRow>> {
+    ) -> Result<Vec<ErrorPattern>> {
         let since = since_epoch_ms.unwrap_or(0);
+        let min_count = i64::try_from(min_count).unwrap_or(i64::MAX);
+        let Some(key) = &self.decryption_key else {
+            // Sessions are listed in order of their first occurrence, as in
+            // the decrypting path below.
+            let mut stmt = self.conn.prepare(
+                "SELECT message, SUM(occurrences),
+                        json_group_array(session_id ORDER BY first_id)
+                 FROM (
+                     SELECT LOWER(TRIM(error_message)) AS message, session_id,
+                            COUNT(*) AS occurrences, MIN(id) AS first_id
+                     FROM action_events
+                     WHERE error_message IS NOT NULL AND ts_epoch_ms >= ?1
+                     GROUP BY message, session_id
+                 )
+                 GROUP BY message
+                 HAVING SUM(occurrences) >= ?2
+                 ORDER BY 2 DESC, message ASC",
+            )?;
+            let rows = stmt.query_map(rusqlite::params![since, min_count], |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, i64>(1)?,
+                    row.get::<_, String>(2)?,
+                ))
+            })?;
+            let mut patterns = Vec::new();
+            for row in rows {
+                let (normalized_message, count, sessions) = row?;
+                let mut example_session_ids: Vec<String> = serde_json::from_str(&sessions)
+                    .context("decoding error pattern session ids")?;
+                example_session_ids.truncate(MAX_EXAMPLE_SESSIONS);
+                patterns.push(ErrorPattern {
+                    normalized_message,
+                    count,
+                    example_session_ids,
+                });
+            }
+            return Ok(patterns);
+        };
+
         let mut stmt = self.conn.prepare(
-            "SELECT ts, ts_epoch_ms, cpu_usage_pct, memory_used_bytes, memory_total_bytes,
-                    process_count, process_spawn_rate, file_read_bytes, file_write_bytes,
-                    net_connections, dest_ip_entropy, syscall_freq_json
-             FROM system_samples
-             WHERE ts_epoch_ms >= ?1
-             ORDER BY ts_epoch_ms ASC
-             LIMIT ?2",
+            "SELECT session_id, error_message
+             FROM action_events
+             WHERE error_message IS NOT NULL AND ts_epoch_ms >= ?1
+             ORDER BY id",
         )?;
-
-        let rows = stmt.query_map(rusqlite::params![since, limit as i64], |row| {
-            Ok(SystemSampleRow {
-                ts: row.get(0)?,
-                ts_epoch_ms: row.get(1)?,
-                cpu_usage_pct: row.get(2)?,
-                memory_used_bytes: row.get(3)?,
-                memory_total_bytes: row.get(4)?,
-                process_count: row.get(5)?,
-                process_spawn_rate: row.get(6)?,
-                file_read_bytes: row.get(7)?,
-                file_write_bytes: row.get(8)?,
-                net_connections: row.get(9)?,
-                dest_ip_entropy: row.get(10)?,
-                syscall_freq_json: row.get(11)?,
-            })
+        let rows = stmt.query_map(rusqlite::params![since], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
         })?;
-
-        let mut results = Vec::new();
+        let mut groups: BTreeMap<String, ErrorPattern> = BTreeMap::new();
         for row in rows {
-            results.push(row?);
+            let (session_id, message) = row?;
+            let message = crypto::decrypt_field(&message, key).unwrap_or(message);
+            let normalized = message.trim().to_lowercase();
+            let pattern = groups
+                .entry(normalized.clone())
+                .or_insert_with(|| ErrorPattern {
+                    normalized_message: normalized,
+                    count: 0,
+                    example_session_ids: Vec::new(),
+                });
+            pattern.count += 1;
+            if pattern.example_session_ids.len() < MAX_EXAMPLE_SESSIONS
+                && !pattern.example_session_ids.contains(&session_id)
+            {
+                pattern.example_session_ids.push(session_id);
+            }
         }
-        Ok(results)
+        let mut patterns: Vec<ErrorPattern> = groups
+            .into_values()
+            .filter(|p| p.count >= min_count)
+            .collect();
+        // Stable sort keeps the alphabetical order among equal counts.
+        patterns.sort_by(|a, b| b.count.cmp(&a.count));
+        Ok(patterns)
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::telemetry::store::{ActionRecord, TelemetrySqliteStore};
-    use tempfile::TempDir;
+    /// Compare LLM response stats of two models for A/B testing.
+    pub fn compare_models(
+        &self,
+        model_a: &str,
+        model_b: &str,
+        since_epoch_ms: Option<i64>,
+    ) -> Result<ModelComparison> {
+        let a = self.model_stats(model_a, since_epoch_ms)?;
+        let b = self.model_stats(model_b, since_epoch_ms)?;
+        Ok(ModelComparison {
+            latency_diff_ms: b.avg_latency_ms - a.avg_latency_ms,
+            token_efficiency_diff: b.token_efficiency - a.token_efficiency,
+            error_rate_diff: b.error_rate - a.error_rate,
+            model_a_stats: a,
+            model_b_stats: b,
+        })
+    }
+
+    /// Export the action sequence of every completed turn in a session,
+    /// read from its `turn_complete` row.
+    pub fn export_turn_sequences(&self, session_id: &str) -> Result<Vec<TurnSequenceRow>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT turn_id, ts_epoch_ms, turn_action_sequence
+             FROM action_events
+             WHERE session_id = ?1
+               AND event_type = 'turn_complete'
+               AND turn_action_sequence IS NOT NULL
+             ORDER BY ts_epoch_ms ASC, id ASC",
+        )?;
+        let rows = stmt.query_map(rusqlite::params![session_id], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, i64>(1)?,
+                row.get::<_, String>(2)?,
+            ))
+        })?;
+
+        let mut results = Vec::new();
+        for row in rows {
+            let (turn_id, ts_epoch_ms, json) = row?;
+            let sequence = serde_json::from_str(&json)
+                .with_context(|| format!("parsing action sequence of turn {turn_id}"))?;
+            results.push(TurnSequenceRow {
+                turn_id,
+                ts_epoch_ms,
+                sequence,
+            });
+        }
+        Ok(results)
+    }
+
+    /// Completed-turn action sequences that occur more than once in a
+    /// session, most frequent first within each call depth.
+    ///
+    /// Sequences are only compared with others at the same depth, so a
+    /// sub-agent's turns never count as repeats of its caller's.
+    pub fn find_repeated_sequences(&self, session_id: &str) -> Result<Vec<RepeatedSequence>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT call_depth, turn_action_sequence, COUNT(*) AS occurrences
+             FROM action_events
+             WHERE session_id = ?1
+               AND event_type = 'turn_complete'
+               AND turn_action_sequence IS NOT NULL
+             GROUP BY call_depth, turn_action_sequence
+             HAVING COUNT(*) > 1
+             ORDER BY call_depth ASC, occurrences DESC, turn_action_sequence ASC",
+        )?;
+        let rows = stmt.query_map(rusqlite::params![session_id], |row| {
+            Ok((
+                row.get::<_, u32>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, i64>(2)?,
+            ))
+        })?;
+
+        let mut results = Vec::new();
+        for row in rows {
+            let (call_depth, json, occurrences) = row?;
+            let sequence = serde_json::from_str(&json)
+                .with_context(|| format!("parsing repeated action sequence {json}"))?;
+            results.push(RepeatedSequence {
+                call_depth,
+                sequence,
+                occurrences,
+            });
+        }
+        Ok(results)
+    }
+
+    /// The most common completed-turn action sequence of every session, as
+    /// `(session_id, sequence)` sorted by session. Ties go to the sequence
+    /// that sorts first.
+    pub fn mode_turn_sequences(&self) -> Result<Vec<(String, Vec<String>)>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT session_id, turn_action_sequence
+             FROM (
+                 SELECT session_id, turn_action_sequence,
+                        ROW_NUMBER() OVER (
+                            PARTITION BY session_id
+                            ORDER BY COUNT(*) DESC, turn_action_sequence ASC
+                        ) AS rank
+                 FROM action_events
+                 WHERE event_type = 'turn_complete' AND turn_action_sequence IS NOT NULL
+                 GROUP BY session_id, turn_action_sequence
+             )
+             WHERE rank = 1
+             ORDER BY session_id",
+        )?;
+        let rows = stmt.query_map([], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+        })?;
+
+        let mut results = Vec::new();
+        for row in rows {
+            let (session_id, json) = row?;
+            let sequence = serde_json::from_str(&json)
+                .with_context(|| format!("parsing action sequence of session {session_id}"))?;
+            results.push((session_id, sequence));
+        }
+        Ok(results)
+    }
+
+    /// Tags stored for `session_id`, sorted alphabetically.
+    pub fn session_tags(&self, session_id: &str) -> Result<Vec<String>> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT tag FROM session_tags WHERE session_id = ?1 ORDER BY tag")?;
+        let tags = stmt
+            .query_map(rusqlite::params![session_id], |row| row.get(0))?
+            .collect::<rusqlite::Result<_>>()?;
+        Ok(tags)
+    }
+
+    /// Sessions tagged `tag`, sorted by id.
+    pub fn sessions_with_tag(&self, tag: &str) -> Result<Vec<String>> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT session_id FROM session_tags WHERE tag = ?1 ORDER BY session_id")?;
+        let sessions = stmt
+            .query_map(rusqlite::params![tag], |row| row.get(0))?
+            .collect::<rusqlite::Result<_>>()?;
+        Ok(sessions)
+    }
+
+    /// Tables, columns and indexes of the database, sorted by table name.
+    ///
+    /// Reads the `main` schema, so soft-delete filtering on this connection
+    /// does not hide anything and SQLite's internal tables are left out.
+    pub fn schema_info(&self) -> Result<SchemaInfo> {
+        let mut tables_stmt = self.conn.prepare(
+            "SELECT name FROM main.sqlite_master
+             WHERE type = 'table' AND name NOT LIKE 'sqlite_%'
+             ORDER BY name",
+        )?;
+        let names = tables_stmt
+            .query_map([], |row| row.get::<_, String>(0))?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+
+        let mut columns_stmt = self.conn.prepare(
+            "SELECT name, type, \"notnull\", pk FROM pragma_table_info(?1, 'main') ORDER BY cid",
+        )?;
+        let mut indexes_stmt = self
+            .conn
+            .prepare("SELECT name, \"unique\" FROM pragma_index_list(?1, 'main') ORDER BY name")?;
+        let mut index_columns_stmt = self
+            .conn
+            .prepare("SELECT name FROM pragma_index_info(?1, 'main') ORDER BY seqno")?;
+
+        let mut tables = Vec::with_capacity(names.len());
+        for name in names {
+            let columns = columns_stmt
+                .query_map(rusqlite::params![name], |row| {
+                    Ok(ColumnInfo {
+                        name: row.get(0)?,
+                        sql_type: row.get(1)?,
+                        not_null: row.get(2)?,
+                        primary_key: row.get::<_, i64>(3)? > 0,
+                    })
+                })?
+                .collect::<rusqlite::Result<Vec<_>>>()?;
+            let index_names = indexes_stmt
+                .query_map(rusqlite::params![name], |row| {
+                    Ok((row.get::<_, String>(0)?, row.get::<_, bool>(1)?))
+                })?
+                .collect::<rusqlite::Result<Vec<_>>>()?;
+            let mut indexes = Vec::with_capacity(index_names.len());
+            for (index_name, unique) in index_names {
+                let columns = index_columns_stmt
+                    .query_map(rusqlite::params![index_name], |row| {
+                        // NULL for expression columns.
+                        Ok(row.get::<_, Option<String>>(0)?.unwrap_or_default())
+                    })?
+                    .collect::<rusqlite::Result<Vec<_>>>()?;
+                indexes.push(IndexInfo {
+                    name: index_name,
+                    unique,
+                    columns,
+                });
+            }
+            tables.push(TableInfo {
+                name,
+                columns,
+                indexes,
+            });
+        }
+        Ok(SchemaInfo { tables })
+    }
+
+    /// Run an action-event query selecting [`ACTION_EVENT_COLUMNS`] and
+    /// decrypt `error_message` when a key is configured.
+    fn query_action_events(
+        &self,
+        sql: &str,
+        params: impl rusqlite::Params,
+    ) -> Result<Vec<ActionEventRow>> {
+        let mut stmt = self.conn.prepare(sql)?;
+        let rows = stmt.query_map(params, action_event_from_row)?;
+
+        let mut results = Vec::new();
+        for row in rows {
+            let mut row = row?;
+            self.decrypt_error_message(&mut row);
+            results.push(row);
+        }
+        Ok(results)
+    }
+
+    fn decrypt_error_message(&self, row: &mut ActionEventRow) {
+        if let (Some(key), Some(msg)) = (&self.decryption_key, &row.error_message) {
+            if let Ok(plain) = crypto::decrypt_field(msg, key) {
+                row.error_message = Some(plain);
+            }
+        }
+    }
+
+    /// Export system samples, optionally filtered by timestamp.
+    pub fn export_system_samples(
+        &self,
+        since_epoch_ms: Option<i64>,
+        limit: usize,
+    ) -> Result<Vec<SystemSampleRow>> {
+        let since = since_epoch_ms.unwrap_or(0);
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {SYSTEM_SAMPLE_COLUMNS}
+             FROM system_samples
+             WHERE ts_epoch_ms >= ?1
+             ORDER BY ts_epoch_ms ASC
+             LIMIT ?2"
+        ))?;
+
+        let rows = stmt.query_map(
+            rusqlite::params![since, limit as i64],
+            system_sample_from_row,
+        )?;
+
+        let mut results = Vec::new();
+        for row in rows {
+            results.push(row?);
+        }
+        Ok(results)
+    }
+
+    /// System samples since `since_epoch_ms`, in time order, each with its
+    /// change from the sample before it (computed with `LAG()`). The first
+    /// sample in the window is compared with the last one before
+    /// `since_epoch_ms`.
+    pub fn system_sample_deltas(
+        &self,
+        since_epoch_ms: Option<i64>,
+        limit: usize,
+    ) -> Result<Vec<SystemSampleDelta>> {
+        let since = since_epoch_ms.unwrap_or(0);
+        let mut stmt = self.conn.prepare(
+            "SELECT ts_epoch_ms,
+                    ts_epoch_ms - prev_ts,
+                    cpu_usage_pct, cpu_usage_pct - prev_cpu,
+                    memory_used_bytes, memory_used_bytes - prev_memory,
+                    file_read_bytes, file_read_bytes - prev_read,
+                    file_write_bytes, file_write_bytes - prev_write,
+                    net_connections, net_connections - prev_connections
+             FROM (
+                 SELECT ts_epoch_ms, cpu_usage_pct, memory_used_bytes,
+                        file_read_bytes, file_write_bytes, net_connections,
+                        LAG(ts_epoch_ms)       OVER w AS prev_ts,
+                        LAG(cpu_usage_pct)     OVER w AS prev_cpu,
+                        LAG(memory_used_bytes) OVER w AS prev_memory,
+                        LAG(file_read_bytes)   OVER w AS prev_read,
+                        LAG(file_write_bytes)  OVER w AS prev_write,
+                        LAG(net_connections)   OVER w AS prev_connections
+                 FROM system_samples
+                 WHERE ts_epoch_ms >= COALESCE(
+                     (SELECT MAX(ts_epoch_ms) FROM system_samples WHERE ts_epoch_ms < ?1),
+                     ?1
+                 )
+                 WINDOW w AS (ORDER BY ts_epoch_ms, id)
+             )
+             WHERE ts_epoch_ms >= ?1
+             ORDER BY ts_epoch_ms ASC
+             LIMIT ?2",
+        )?;
+        let deltas = stmt
+            .query_map(rusqlite::params![since, limit as i64], |row| {
+                Ok(SystemSampleDelta {
+                    ts_epoch_ms: row.get(0)?,
+                    interval_ms: row.get(1)?,
+                    cpu_usage_pct: row.get(2)?,
+                    cpu_usage_pct_delta: row.get(3)?,
+                    memory_used_bytes: row.get(4)?,
+                    memory_used_bytes_delta: row.get(5)?,
+                    file_read_bytes: row.get(6)?,
+                    file_read_bytes_delta: row.get(7)?,
+                    file_write_bytes: row.get(8)?,
+                    file_write_bytes_delta: row.get(9)?,
+                    net_connections: row.get(10)?,
+                    net_connections_delta: row.get(11)?,
+                })
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(deltas)
+    }
+
+    /// One system sample per `step_ms` interval from `since` to `until`
+    /// (inclusive, epoch ms), filling gaps such as collector restarts.
+    ///
+    /// An interval with a real sample yields its first one. An empty
+    /// interval yields a row at its start with `cpu_usage_pct` and
+    /// `memory_used_bytes` linearly interpolated between the real samples
+    /// either side; its other counters repeat the earlier sample and its
+    /// JSON columns are empty. Intervals with no real sample on one side
+    /// are left out rather than extrapolated.
+    pub fn interpolated_samples(
+        &self,
+        since: i64,
+        until: i64,
+        step_ms: u64,
+    ) -> Result<Vec<SystemSampleRowEx>> {
+        if step_ms == 0 {
+            anyhow::bail!("step_ms must be positive");
+        }
+        let step = i64::try_from(step_ms).context("step_ms out of range")?;
+        // Include the nearest sample outside each end to interpolate from.
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {SYSTEM_SAMPLE_COLUMNS}
+             FROM system_samples
+             WHERE ts_epoch_ms >= COALESCE(
+                       (SELECT MAX(ts_epoch_ms) FROM system_samples WHERE ts_epoch_ms < ?1), ?1)
+               AND ts_epoch_ms <= COALESCE(
+                       (SELECT MIN(ts_epoch_ms) FROM system_samples WHERE ts_epoch_ms > ?2), ?2)
+             ORDER BY ts_epoch_ms ASC, id ASC"
+        ))?;
+        let samples = stmt
+            .query_map(rusqlite::params![since, until], system_sample_from_row)?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+
+        let mut rows = Vec::new();
+        let mut start = since;
+        while start <= until {
+            let end = start.saturating_add(step);
+            let first = samples.partition_point(|s| s.ts_epoch_ms < start);
+            match samples.get(first) {
+                Some(real) if real.ts_epoch_ms < end => rows.push(SystemSampleRowEx {
+                    sample: real.clone(),
+                    interpolated: false,
+                }),
+                Some(next) if first > 0 => rows.push(SystemSampleRowEx {
+                    sample: interpolate_sample(&samples[first - 1], next, start),
+                    interpolated: true,
+                }),
+                _ => {}
+            }
+            start = end;
+        }
+        Ok(rows)
+    }
+
+    /// Longest `duration_ms` of any recorded tool call, or 0 when there are
+    /// none.
+    fn max_tool_call_duration_ms(&self) -> Result<i64> {
+        Ok(self.conn.query_row(
+            "SELECT COALESCE(MAX(duration_ms), 0) FROM action_events
+             WHERE event_type = 'tool_call'",
+            [],
+            |row| row.get(0),
+        )?)
+    }
+
+    /// Network events since `since_epoch_ms`, in time order, each matched to
+    /// the tool call that triggered it (see [`tool_call_join`]).
+    pub fn network_events_with_tool_calls(
+        &self,
+        since_epoch_ms: Option<i64>,
+        limit: usize,
+    ) -> Result<Vec<NetworkEventRow>> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT ne.ts, ne.ts_epoch_ms, ne.pid, ne.dest_ip, ne.dest_port, ne.success,
+                    ae.id, ae.tool_name
+             FROM network_events ne
+             {}
+             WHERE ne.ts_epoch_ms >= ?1
+             ORDER BY ne.ts_epoch_ms ASC, ne.id ASC
+             LIMIT ?2",
+            tool_call_join("ne")
+        ))?;
+        let rows = stmt.query_map(
+            rusqlite::params![
+                since_epoch_ms.unwrap_or(0),
+                limit as i64,
+                self.max_tool_call_duration_ms()?
+            ],
+            |row| {
+                Ok(NetworkEventRow {
+                    ts: row.get(0)?,
+                    ts_epoch_ms: row.get(1)?,
+                    pid: row.get(2)?,
+                    dest_ip: row.get(3)?,
+                    dest_port: row.get(4)?,
+                    success: row.get::<_, i32>(5)? != 0,
+                    action_event_id: row.get(6)?,
+                    tool_name: row.get(7)?,
+                })
+            },
+        )?;
+
+        let mut results = Vec::new();
+        for row in rows {
+            results.push(row?);
+        }
+        Ok(results)
+    }
+
+    /// DNS lookups since `since_epoch_ms`, in time order, each matched to the
+    /// tool call that made it (see [`tool_call_join`]).
+    pub fn dns_queries_with_tool_calls(
+        &self,
+        since_epoch_ms: Option<i64>,
+        limit: usize,
+    ) -> Result<Vec<DnsQueryRow>> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT dq.ts, dq.ts_epoch_ms, dq.hostname, dq.resolution_ms, dq.success,
+                    ae.id, ae.tool_name
+             FROM dns_queries dq
+             {}
+             WHERE dq.ts_epoch_ms >= ?1
+             ORDER BY dq.ts_epoch_ms ASC, dq.id ASC
+             LIMIT ?2",
+            tool_call_join("dq")
+        ))?;
+        let rows = stmt.query_map(
+            rusqlite::params![
+                since_epoch_ms.unwrap_or(0),
+                limit as i64,
+                self.max_tool_call_duration_ms()?
+            ],
+            |row| {
+                Ok(DnsQueryRow {
+                    ts: row.get(0)?,
+                    ts_epoch_ms: row.get(1)?,
+                    hostname: row.get(2)?,
+                    resolution_ms: row.get(3)?,
+                    success: row.get::<_, i32>(4)? != 0,
+                    action_event_id: row.get(5)?,
+                    tool_name: row.get(6)?,
+                })
+            },
+        )?;
+
+        let mut results = Vec::new();
+        for row in rows {
+            results.push(row?);
+        }
+        Ok(results)
+    }
+
+    /// System samples whose `field` deviates from the mean of the `window`
+    /// samples before it by more than `z_threshold` standard deviations.
+    ///
+    /// Samples are streamed in time order and scored once a full window
+    /// precedes them; a window with zero variance flags nothing.
+    pub fn flag_anomalous_samples(
+        &self,
+        field: SampleField,
+        window: usize,
+        z_threshold: f64,
+    ) -> Result<Vec<AnomalousSample>> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {SYSTEM_SAMPLE_COLUMNS}
+             FROM system_samples
+             ORDER BY ts_epoch_ms ASC, id ASC"
+        ))?;
+        let mut rows = stmt.query([])?;
+
+        let window = window.max(1);
+        let mut values = VecDeque::with_capacity(window);
+        let mut stats = RollingStats::new();
+        let mut anomalies = Vec::new();
+        while let Some(row) = rows.next()? {
+            let sample = system_sample_from_row(row)?;
+            let value = field.value(&sample);
+            if stats.len() == window {
+                let std_dev = stats.std_dev();
+                if std_dev > 0.0 {
+                    let z_score = (value - stats.mean()) / std_dev;
+                    if z_score.abs() > z_threshold {
+                        anomalies.push(AnomalousSample { sample, z_score });
+                    }
+                }
+                if let Some(oldest) = values.pop_front() {
+                    stats.pop(oldest);
+                }
+            }
+            values.push_back(value);
+            stats.push(value);
+        }
+        Ok(anomalies)
+    }
+}
+
+/// Query behind [`TelemetryReader::export_failed_tool_calls`]. The unary
+/// `+` keeps the planner off `idx_ae_epoch`, so it scans the failed-call
+/// partial index and sorts only those rows.
+fn failed_tool_calls_sql() -> String {
+    format!(
+        "SELECT {ACTION_EVENT_COLUMNS}
+         FROM action_events
+         WHERE tool_success = 0 AND +ts_epoch_ms >= ?1
+         ORDER BY ts_epoch_ms ASC, sequence_index ASC
+         LIMIT ?2"
+    )
+}
+
+/// Lookups the reader and its callers run often, as `(table, filtered
+/// columns, query)`, checked by [`detect_missing_indexes`].
+const REPRESENTATIVE_QUERIES: &[(&str, &[&str], &str)] = &[
+    (
+        "action_events",
+        &["session_id"],
+        "SELECT * FROM main.action_events WHERE session_id = ?1",
+    ),
+    (
+        "action_events",
+        &["turn_id"],
+        "SELECT * FROM main.action_events WHERE turn_id = ?1",
+    ),
+    (
+        "action_events",
+        &["ts_epoch_ms"],
+        "SELECT * FROM main.action_events WHERE ts_epoch_ms >= ?1",
+    ),
+    (
+        "action_events",
+        &["tool_name"],
+        "SELECT * FROM main.action_events WHERE tool_name = ?1",
+    ),
+    (
+        "action_events",
+        &["event_type"],
+        "SELECT * FROM main.action_events WHERE event_type = ?1",
+    ),
+    (
+        "action_events",
+        &["correlation_id"],
+        "SELECT * FROM main.action_events WHERE correlation_id = ?1",
+    ),
+    (
+        "system_samples",
+        &["ts_epoch_ms"],
+        "SELECT * FROM main.system_samples WHERE ts_epoch_ms >= ?1",
+    ),
+    (
+        "session_tags",
+        &["tag"],
+        "SELECT * FROM main.session_tags WHERE tag = ?1",
+    ),
+    (
+        "network_events",
+        &["ts_epoch_ms"],
+        "SELECT * FROM main.network_events WHERE ts_epoch_ms >= ?1",
+    ),
+    (
+        "dns_queries",
+        &["ts_epoch_ms"],
+        "SELECT * FROM main.dns_queries WHERE ts_epoch_ms >= ?1",
+    ),
+];
+
+/// Developer diagnostic: run `EXPLAIN QUERY PLAN` over a fixed set of
+/// representative lookups and suggest an index for each that has to scan
+/// its whole table. Nothing is changed in the database.
+///
+/// Without `ENABLE_STAT4` compiled in, the planner has coarser statistics
+/// and may pick scans an analyzed database would avoid; that is logged.
+pub fn detect_missing_indexes(reader: &TelemetryReader) -> Result<Vec<IndexSuggestion>> {
+    let conn = &reader.conn;
+    let stat4 = conn
+        .prepare("PRAGMA compile_options")?
+        .query_map([], |row| row.get::<_, String>(0))?
+        .collect::<rusqlite::Result<Vec<_>>>()?
+        .iter()
+        .any(|option| option == "ENABLE_STAT4");
+    if !stat4 {
+        tracing::debug!("SQLite built without ENABLE_STAT4; query plans use basic statistics");
+    }
+
+    let mut suggestions: Vec<IndexSuggestion> = Vec::new();
+    for (table, columns, sql) in REPRESENTATIVE_QUERIES {
+        let plan = conn
+            .prepare(&format!("EXPLAIN QUERY PLAN {sql}"))
+            .with_context(|| format!("planning {sql}"))?
+            // Each query takes one parameter; its value does not change the plan.
+            .query_map([rusqlite::types::Null], |row| row.get::<_, String>(3))?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        // "SCAN [main.]t" on current SQLite, "SCAN TABLE t" on older builds; an
+        // index scan ("SCAN t USING INDEX ...") still has an index to use.
+        let full_scan = plan.iter().any(|detail| {
+            let target = detail.strip_prefix("SCAN ").unwrap_or_default();
+            let target = target.strip_prefix("TABLE ").unwrap_or(target);
+            let target = target.strip_prefix("main.").unwrap_or(target);
+            target.split_whitespace().next() == Some(*table) && !detail.contains("INDEX")
+        });
+        if !full_scan
+            || suggestions
+                .iter()
+                .any(|s| s.table == *table && s.columns.iter().eq(columns.iter()))
+        {
+            continue;
+        }
+        let rows: i64 =
+            conn.query_row(&format!("SELECT COUNT(*) FROM main.{table}"), [], |row| {
+                row.get(0)
+            })?;
+        let rows = rows as f64;
+        suggestions.push(IndexSuggestion {
+            table: (*table).to_string(),
+            columns: columns.iter().map(|c| (*c).to_string()).collect(),
+            estimated_improvement: if rows > 1.0 { rows / rows.log2() } else { 1.0 },
+        });
+    }
+    Ok(suggestions)
+}
+
+/// Cosine similarity of two equal-length byte vectors; 0 when either is all
+/// zeros.
+#[allow(clippy::cast_possible_truncation)] // a similarity in [0, 1]
+fn cosine_similarity(a: &[u8], b: &[u8]) -> f32 {
+    let (mut dot, mut norm_a, mut norm_b) = (0f64, 0f64, 0f64);
+    for (&x, &y) in a.iter().zip(b) {
+        let (x, y) = (f64::from(x), f64::from(y));
+        dot += x * y;
+        norm_a += x * x;
+        norm_b += y * y;
+    }
+    if norm_a == 0.0 || norm_b == 0.0 {
+        return 0.0;
+    }
+    (dot / (norm_a * norm_b).sqrt()) as f32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::TelemetryConfig;
+    use crate::telemetry::store::{
+        ActionRecord, DnsQuery, NetworkEvent, SystemSample, TelemetrySqliteStore,
+    };
+    use crate::telemetry::testing;
+    use tempfile::TempDir;
 
     #[test]
     fn reader_exports_action_events() {
         let tmp = TempDir::new().unwrap();
-        let store = TelemetrySqliteStore::open(tmp.path(), 64).unwrap();
+        let store = TelemetrySqliteStore::open(tmp.path(), TelemetryConfig::default()).unwrap();
         store.submit_action(ActionRecord {
-            ts: "2026-01-01T00:00:00Z".into(),
-            ts_epoch_ms: 1_000,
-            session_id: "s1".into(),
-            turn_id: "t1".into(),
-            sequence_index: 0,
-            event_type: "llm_response".into(),
             provider: Some("openai".into()),
             model: Some("gpt-4".into()),
-            tool_name: None,
-            tool_type_embedding: None,
-            arguments_hash: None,
-            tool_success: None,
+            tool_type_embedding: Some(vec![7u8; 64]),
             duration_ms: Some(100),
             tokens_in: Some(50),
             tokens_out: Some(25),
             is_user_initiated: true,
-            iteration_index: 0,
-            previous_action_type: None,
-            turn_action_sequence: None,
-            error_message: None,
+            ..testing::action("s1", "t1", 1_000, "llm_response")
         });
         // Let writer flush
-        std::thread::sleep(std::time::Duration::from_millis(300));
         drop(store);
 
         let reader = TelemetryReader::open(&tmp.path().join("research.db")).unwrap();
@@ -195,12 +2052,505 @@ mod tests {
         assert_eq!(events.len(), 1);
         assert_eq!(events[0].event_type, "llm_response");
         assert_eq!(events[0].tokens_in, Some(50));
+        assert_eq!(events[0].tool_type_embedding, Some(vec![7u8; 64]));
+    }
+
+    #[test]
+    fn failed_tool_calls_use_partial_index() {
+        let tmp = TempDir::new().unwrap();
+        let store = TelemetrySqliteStore::open(tmp.path(), TelemetryConfig::default()).unwrap();
+        for (i, success) in [Some(true), Some(false), None, Some(false)]
+            .into_iter()
+            .enumerate()
+        {
+            store.submit_action(ActionRecord {
+                sequence_index: i as i64,
+                tool_name: Some("shell".into()),
+                tool_success: success,
+                ..testing::action("s1", "t1", 1_000 + i as i64, "tool_call_result")
+            });
+        }
+        drop(store);
+
+        let reader = TelemetryReader::open(&tmp.path().join("research.db")).unwrap();
+        let failed = reader.export_failed_tool_calls(None, 100).unwrap();
+        let epochs: Vec<i64> = failed.iter().map(|r| r.ts_epoch_ms).collect();
+        assert_eq!(epochs, [1_001, 1_003]);
+        assert_eq!(
+            reader
+                .export_failed_tool_calls(Some(1_002), 100)
+                .unwrap()
+                .len(),
+            1
+        );
+
+        let plan: Vec<String> = reader
+            .conn
+            .prepare(&format!("EXPLAIN QUERY PLAN {}", failed_tool_calls_sql()))
+            .unwrap()
+            .query_map(rusqlite::params![0, 100], |r| r.get::<_, String>(3))
+            .unwrap()
+            .collect::<rusqlite::Result<_>>()
+            .unwrap();
+        assert!(
+            plan.iter().any(|step| step.contains("idx_ae_failed_tools")),
+            "{plan:?}"
+        );
+    }
+
+    #[test]
+    fn session_stats_view_aggregates_live_sessions() {
+        let tmp = TempDir::new().unwrap();
+        let store = TelemetrySqliteStore::open(tmp.path(), TelemetryConfig::default()).unwrap();
+        for (session_id, ts_epoch_ms, tokens_in) in [
+            ("s1", 1_000, Some(10)),
+            ("s1", 1_500, Some(5)),
+            ("s1", 2_000, None),
+            ("s2", 3_000, Some(7)),
+            ("gone", 500, Some(1)),
+        ] {
+            store.submit_action(ActionRecord {
+                tokens_in,
+                ..testing::action(session_id, "t1", ts_epoch_ms, "llm_response")
+            });
+        }
+        store.delete_actions_in_session("gone").unwrap();
+        drop(store);
+
+        let reader = TelemetryReader::open(&tmp.path().join("research.db")).unwrap();
+        let stats = reader.query_session_stats().unwrap();
+        assert_eq!(
+            stats,
+            [
+                SessionStats {
+                    session_id: "s1".into(),
+                    event_count: 3,
+                    start_ms: 1_000,
+                    end_ms: 2_000,
+                    total_tokens_in: 15,
+                    total_tokens_out: 0,
+                },
+                SessionStats {
+                    session_id: "s2".into(),
+                    event_count: 1,
+                    start_ms: 3_000,
+                    end_ms: 3_000,
+                    total_tokens_in: 7,
+                    total_tokens_out: 0,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn tool_success_rate_counts_outcomes() {
+        let tmp = TempDir::new().unwrap();
+        let store = TelemetrySqliteStore::open(tmp.path(), TelemetryConfig::default()).unwrap();
+        for (i, success) in [Some(true), Some(true), Some(false), None]
+            .into_iter()
+            .enumerate()
+        {
+            store.submit_action(ActionRecord {
+                sequence_index: i as i64,
+                tool_name: Some("shell".into()),
+                tool_success: success,
+                ..testing::action("s1", "t1", 1_000 + i as i64, "tool_call_result")
+            });
+        }
+        store.submit_tool_stats("shell", false);
+        drop(store);
+
+        let reader = TelemetryReader::open(&tmp.path().join("research.db")).unwrap();
+        assert_eq!(reader.tool_success_rate("shell").unwrap(), Some(0.5));
+        assert_eq!(reader.tool_success_rate("browser").unwrap(), None);
+    }
+
+    #[test]
+    fn nearest_tools_ranks_exact_match_first() {
+        use crate::config::EmbeddingDim;
+        use crate::telemetry::embeddings::compute_tool_embedding_with_dim;
+
+        let tmp = TempDir::new().unwrap();
+        let store = TelemetrySqliteStore::open(tmp.path(), TelemetryConfig::default()).unwrap();
+        for i in 0..10 {
+            let tool = format!("tool_{i}");
+            let (embedding, dims) = compute_tool_embedding_with_dim(&tool, EmbeddingDim::D32);
+            store.submit_tool_embedding(&tool, embedding, dims);
+        }
+        store.submit_tool_embedding("short", vec![1; 8], 8);
+        drop(store);
+
+        let reader = TelemetryReader::open(&tmp.path().join("research.db")).unwrap();
+        let (query, _) = compute_tool_embedding_with_dim("tool_3", EmbeddingDim::D32);
+        let nearest = reader.find_nearest_tools(&query, 3).unwrap();
+        assert_eq!(nearest.len(), 3);
+        assert_eq!(nearest[0], ("tool_3".to_string(), 1.0));
+        assert!(nearest[1].1 <= nearest[0].1 && nearest[2].1 <= nearest[1].1);
+        assert_eq!(reader.find_nearest_tools(&query, 50).unwrap().len(), 10);
+    }
+
+    #[test]
+    fn nearest_normalized_tools_use_dot_product() {
+        use crate::telemetry::embeddings::compute_normalized_tool_embedding;
+
+        let tmp = TempDir::new().unwrap();
+        let store = TelemetrySqliteStore::open(tmp.path(), TelemetryConfig::default()).unwrap();
+        for i in 0..10 {
+            let tool = format!("tool_{i}");
+            store
+                .submit_normalized_tool_embedding(&tool, &compute_normalized_tool_embedding(&tool));
+        }
+        store.submit_tool_embedding("raw", vec![1; 32], 32);
+        drop(store);
+
+        let reader = TelemetryReader::open(&tmp.path().join("research.db")).unwrap();
+        // Scaling the query does not change its ranking.
+        let query: Vec<f32> = compute_normalized_tool_embedding("tool_7")
+            .iter()
+            .map(|v| v * 3.0)
+            .collect();
+        let nearest = reader.find_nearest_normalized_tools(&query, 10).unwrap();
+        assert_eq!(nearest.len(), 10);
+        assert_eq!(nearest[0].0, "tool_7");
+        assert!((nearest[0].1 - 1.0).abs() < 1e-6);
+        assert_eq!(reader.find_nearest_tools(&[1; 128], 10).unwrap(), []);
+    }
+
+    #[test]
+    fn iter_action_events_streams_every_row_in_order() {
+        const ROWS: i64 = 100_000;
+        let tmp = TempDir::new().unwrap();
+        drop(TelemetrySqliteStore::open(tmp.path(), TelemetryConfig::default()).unwrap());
+
+        let db = tmp.path().join("research.db");
+        let mut conn = Connection::open(&db).unwrap();
+        let tx = conn.transaction().unwrap();
+        {
+            let mut insert = tx
+                .prepare(
+                    "INSERT INTO action_events (ts, ts_epoch_ms, session_id, turn_id,
+                         sequence_index, event_type, is_user_initiated, iteration_index)
+                     VALUES ('2026-01-01T00:00:00Z', ?1, 's1', 't1', ?2, 'tool_call', 0, 0)",
+                )
+                .unwrap();
+            // Ten events share each millisecond, some with equal sequence
+            // numbers, so paging has to break ties on id.
+            for i in 0..ROWS {
+                insert
+                    .execute(rusqlite::params![i / 10, i % 10 / 2])
+                    .unwrap();
+            }
+        }
+        tx.commit().unwrap();
+        drop(conn);
+
+        let reader = TelemetryReader::open(&db).unwrap();
+        let mut count = 0;
+        let mut prev = None;
+        for row in reader
+            .iter_action_events(ActionEventFilter::default())
+            .unwrap()
+        {
+            let row = row.unwrap();
+            let key = (row.ts_epoch_ms, row.sequence_index, row.id);
+            assert!(prev < Some(key));
+            prev = Some(key);
+            count += 1;
+        }
+        assert_eq!(count, ROWS);
+
+        let window = ActionEventFilter {
+            since_epoch_ms: Some(500),
+            until_epoch_ms: Some(749),
+            ..ActionEventFilter::default()
+        };
+        assert_eq!(reader.iter_action_events(window).unwrap().count(), 2_500);
+    }
+
+    #[test]
+    fn iter_action_events_reads_one_snapshot() {
+        let tmp = TempDir::new().unwrap();
+        drop(TelemetrySqliteStore::open(tmp.path(), TelemetryConfig::default()).unwrap());
+        let db = tmp.path().join("research.db");
+        let conn = Connection::open(&db).unwrap();
+        let insert = |range: std::ops::Range<i64>| {
+            for i in range {
+                conn.execute(
+                    "INSERT INTO action_events (ts, ts_epoch_ms, session_id, turn_id,
+                         sequence_index, event_type, is_user_initiated, iteration_index)
+                     VALUES ('2026-01-01T00:00:00Z', ?1, 's1', 't1', 0, 'tool_call', 0, 0)",
+                    rusqlite::params![i],
+                )
+                .unwrap();
+            }
+        };
+        let page = ACTION_EVENT_PAGE_SIZE as i64;
+        insert(0..page + 10);
+
+        let reader = TelemetryReader::open(&db).unwrap();
+        let mut rows = reader
+            .iter_action_events(ActionEventFilter::default())
+            .unwrap();
+        assert!(rows.next().is_some());
+        // Lands on the second page, which has not been fetched yet.
+        insert(page + 10..page + 20);
+        assert_eq!(rows.count() as i64, page + 10 - 1);
+
+        // The snapshot ends with the iterator.
+        let all = reader
+            .iter_action_events(ActionEventFilter::default())
+            .unwrap()
+            .count() as i64;
+        assert_eq!(all, page + 20);
+    }
+
+    #[tokio::test]
+    async fn stream_action_events_yields_every_match() {
+        use crate::config::ChannelKind;
+        use tokio_stream::StreamExt;
+
+        let tmp = TempDir::new().unwrap();
+        let config = TelemetryConfig {
+            channel_kind: ChannelKind::Unbounded,
+            ..TelemetryConfig::default()
+        };
+        let store = TelemetrySqliteStore::open(tmp.path(), config).unwrap();
+        for i in 0..2_500 {
+            store.submit_action(ActionRecord {
+                sequence_index: i,
+                ..testing::action("s1", "t1", i, "tool_call")
+            });
+        }
+        drop(store);
+
+        let reader = TelemetryReader::open(&tmp.path().join("research.db")).unwrap();
+        let reader = Arc::new(Mutex::new(reader));
+        let filter = ActionEventFilter {
+            since_epoch_ms: Some(1_000),
+            ..ActionEventFilter::default()
+        };
+        let rows: Vec<_> = stream_action_events(reader.clone(), filter, 8)
+            .map(Result::unwrap)
+            .collect()
+            .await;
+        assert_eq!(rows.len(), 1_500);
+        assert_eq!(rows[0].ts_epoch_ms, 1_000);
+        assert!(rows.windows(2).all(|w| w[0].ts_epoch_ms < w[1].ts_epoch_ms));
+
+        // A stream in progress does not hold the shared reader.
+        let mut partial = stream_action_events(reader.clone(), ActionEventFilter::default(), 1);
+        assert!(partial.next().await.is_some());
+        assert_eq!(
+            reader.lock().export_action_events(None, 10).unwrap().len(),
+            10
+        );
+        assert!(partial.next().await.is_some());
+    }
+
+    #[test]
+    fn dashboard_summary_aggregates_events_and_samples() {
+        let tmp = TempDir::new().unwrap();
+        let store = TelemetrySqliteStore::open(tmp.path(), TelemetryConfig::default()).unwrap();
+        let tools = [
+            "shell",
+            "shell",
+            "shell",
+            "file_read",
+            "file_read",
+            "a",
+            "b",
+            "c",
+            "d",
+        ];
+        for (i, tool) in tools.iter().enumerate() {
+            store.submit_action(ActionRecord {
+                sequence_index: i as i64,
+                tool_name: Some((*tool).into()),
+                tool_success: Some(i != 0),
+                ..testing::action(&format!("s{}", i % 2), "t1", 1_000 + i as i64, "tool_call")
+            });
+        }
+        for (i, duration_ms) in [100, 300].into_iter().enumerate() {
+            store.submit_action(ActionRecord {
+                sequence_index: i as i64,
+                duration_ms: Some(duration_ms),
+                tokens_in: Some(10),
+                tokens_out: Some(5),
+                ..testing::action("s2", &format!("t{i}"), 2_000 + i as i64, "llm_response")
+            });
+        }
+        for i in 1..=20 {
+            store.submit_system_sample(SystemSample {
+                cpu_usage_pct: i as f64,
+                memory_used_bytes: 100 * i,
+                ..testing::sample(1_000 * i)
+            });
+        }
+        drop(store);
+
+        let reader = TelemetryReader::open(&tmp.path().join("research.db")).unwrap();
+        let summary = reader.dashboard_summary(None).unwrap();
+        assert_eq!(summary.total_sessions, 3);
+        assert_eq!(summary.total_turns, 4);
+        assert_eq!((summary.total_llm_calls, summary.total_tool_calls), (2, 9));
+        assert_eq!(summary.avg_llm_latency_ms, Some(200.0));
+        assert_eq!(
+            (summary.total_tokens_in, summary.total_tokens_out),
+            (20, 10)
+        );
+        assert_eq!(summary.tool_success_rate, Some(8.0 / 9.0));
+        assert_eq!(
+            summary.top_5_tools,
+            [
+                ("shell".to_string(), 3),
+                ("file_read".to_string(), 2),
+                ("a".to_string(), 1),
+                ("b".to_string(), 1),
+                ("c".to_string(), 1),
+            ]
+        );
+        assert_eq!(summary.cpu_p95, Some(19.0));
+        assert_eq!(summary.memory_p95, Some(1_900));
+
+        let later = reader.dashboard_summary(Some(2_000)).unwrap();
+        assert_eq!((later.total_sessions, later.total_tool_calls), (1, 0));
+        assert_eq!(later.tool_success_rate, None);
+        assert!(later.top_5_tools.is_empty());
+        assert_eq!(later.cpu_p95, Some(20.0));
+    }
+
+    #[test]
+    fn detect_missing_indexes_flags_unindexed_filters() {
+        let tmp = TempDir::new().unwrap();
+        let store = TelemetrySqliteStore::open(tmp.path(), TelemetryConfig::default()).unwrap();
+        for i in 0..16 {
+            store.submit_action(ActionRecord {
+                sequence_index: i,
+                ..testing::action("s1", "t1", i, "tool_call")
+            });
+        }
+        drop(store);
+
+        let db = tmp.path().join("research.db");
+        let reader = TelemetryReader::open(&db).unwrap();
+        let suggestions = detect_missing_indexes(&reader).unwrap();
+        assert_eq!(
+            suggestions,
+            [IndexSuggestion {
+                table: "action_events".into(),
+                columns: vec!["event_type".into()],
+                estimated_improvement: 4.0,
+            }]
+        );
+        drop(reader);
+
+        Connection::open(&db)
+            .unwrap()
+            .execute_batch("DROP INDEX idx_ae_turn")
+            .unwrap();
+        let reader = TelemetryReader::open(&db).unwrap();
+        let columns: Vec<_> = detect_missing_indexes(&reader)
+            .unwrap()
+            .into_iter()
+            .map(|s| s.columns.join(","))
+            .collect();
+        assert_eq!(columns, ["turn_id", "event_type"]);
+    }
+
+    #[test]
+    fn gzip_exports_decompress_to_plain_exports() {
+        use flate2::read::GzDecoder;
+        use std::io::Read;
+
+        let tmp = TempDir::new().unwrap();
+        let store = TelemetrySqliteStore::open(tmp.path(), TelemetryConfig::default()).unwrap();
+        for i in 0..20 {
+            store.submit_action(ActionRecord {
+                sequence_index: i,
+                error_message: Some("failed: \"quoted\", with comma".into()),
+                ..testing::action("s1", "t1", 1_000 + i, "tool_call")
+            });
+        }
+        store.flush().unwrap();
+        let reader = TelemetryReader::open(&tmp.path().join("research.db")).unwrap();
+
+        let mut csv = Vec::new();
+        assert_eq!(
+            reader
+                .export_action_events_csv(None, 100, &mut csv)
+                .unwrap(),
+            20
+        );
+        let mut ndjson = Vec::new();
+        reader
+            .export_action_events_ndjson(None, 100, &mut ndjson)
+            .unwrap();
+
+        for level in [
+            CompressionLevel::None,
+            CompressionLevel::Fast,
+            CompressionLevel::Default,
+            CompressionLevel::Best,
+        ] {
+            let mut gz = Vec::new();
+            reader
+                .export_action_events_csv_gz(None, 100, &mut gz, level)
+                .unwrap();
+            let mut decoded = Vec::new();
+            GzDecoder::new(gz.as_slice())
+                .read_to_end(&mut decoded)
+                .unwrap();
+            assert_eq!(decoded, csv);
+
+            let mut gz = Vec::new();
+            reader
+                .export_action_events_ndjson_gz(None, 100, &mut gz, level)
+                .unwrap();
+            let mut decoded = Vec::new();
+            GzDecoder::new(gz.as_slice())
+                .read_to_end(&mut decoded)
+                .unwrap();
+            assert_eq!(decoded, ndjson);
+        }
+        assert!(String::from_utf8(csv)
+            .unwrap()
+            .starts_with("ts,ts_epoch_ms,session_id,"));
+        drop(store);
+    }
+
+    #[test]
+    fn reader_decrypts_error_messages() {
+        let tmp = TempDir::new().unwrap();
+        let key = [3u8; 32];
+        let store = TelemetrySqliteStore::open(tmp.path(), TelemetryConfig::default()).unwrap();
+        for (i, msg) in [
+            crypto::encrypt_field("boom", &key).unwrap(),
+            "legacy".to_string(),
+        ]
+        .into_iter()
+        .enumerate()
+        {
+            store.submit_action(ActionRecord {
+                sequence_index: i as i64,
+                tool_success: Some(false),
+                error_message: Some(msg),
+                ..testing::action("s1", "t1", 1_000 + i as i64, "llm_response")
+            });
+        }
+        drop(store);
+
+        let reader = TelemetryReader::open(&tmp.path().join("research.db"))
+            .unwrap()
+            .with_decryption_key(key);
+        let events = reader.export_action_events(None, 100).unwrap();
+        assert_eq!(events[0].error_message.as_deref(), Some("boom"));
+        assert_eq!(events[1].error_message.as_deref(), Some("legacy"));
     }
 
     #[test]
     fn reader_filters_by_since() {
         let tmp = TempDir::new().unwrap();
-        let store = TelemetrySqliteStore::open(tmp.path(), 64).unwrap();
+        let store = TelemetrySqliteStore::open(tmp.path(), TelemetryConfig::default()).unwrap();
         for i in 0..3 {
             store.submit_action(ActionRecord {
                 ts: format!("2026-01-01T00:00:0{i}Z"),
@@ -223,13 +2573,752 @@ mod tests {
                 previous_action_type: None,
                 turn_action_sequence: None,
                 error_message: None,
+                correlation_id: None,
+                parent_action_id: None,
+                estimated_cost_usd: None,
+                metadata_json: None,
+                call_depth: 0,
             });
         }
-        std::thread::sleep(std::time::Duration::from_millis(300));
         drop(store);
 
         let reader = TelemetryReader::open(&tmp.path().join("research.db")).unwrap();
         let events = reader.export_action_events(Some(2000), 100).unwrap();
         assert_eq!(events.len(), 2); // ts_epoch_ms 2000 and 3000
     }
+
+    #[test]
+    fn reader_exports_by_correlation_id() {
+        let tmp = TempDir::new().unwrap();
+        let store = TelemetrySqliteStore::open(tmp.path(), TelemetryConfig::default()).unwrap();
+        for (i, corr) in [Some("req-1"), None, Some("req-1"), Some("req-2")]
+            .into_iter()
+            .enumerate()
+        {
+            store.submit_action(ActionRecord {
+                tool_name: Some("shell".into()),
+                tool_success: Some(true),
+                duration_ms: Some(10),
+                correlation_id: corr.map(String::from),
+                ..testing::action(&format!("s{i}"), "t1", 1_000 + i as i64, "tool_call")
+            });
+        }
+        drop(store);
+
+        let reader = TelemetryReader::open(&tmp.path().join("research.db")).unwrap();
+        let events = reader.export_by_correlation_id("req-1").unwrap();
+        let sessions: Vec<&str> = events.iter().map(|e| e.session_id.as_str()).collect();
+        assert_eq!(sessions, ["s0", "s2"]);
+    }
+
+    #[test]
+    fn reader_exports_action_tree() {
+        let tmp = TempDir::new().unwrap();
+        let store = TelemetrySqliteStore::open(tmp.path(), TelemetryConfig::default()).unwrap();
+        // Rows get ids 1..=5 in submission order.
+        for (i, parent) in [None, Some(1), Some(2), None, Some(1)]
+            .into_iter()
+            .enumerate()
+        {
+            store.submit_action(ActionRecord {
+                sequence_index: i as i64,
+                tool_name: Some("delegate".into()),
+                tool_success: Some(true),
+                parent_action_id: parent,
+                ..testing::action("s1", "t1", 1_000 + i as i64, "tool_call")
+            });
+        }
+        drop(store);
+
+        let reader = TelemetryReader::open(&tmp.path().join("research.db")).unwrap();
+        let ids: Vec<i64> = reader
+            .export_action_tree(1)
+            .unwrap()
+            .iter()
+            .map(|e| e.id)
+            .collect();
+        assert_eq!(ids, [1, 2, 3, 5]);
+        let subtree: Vec<i64> = reader
+            .export_action_tree(2)
+            .unwrap()
+            .iter()
+            .map(|e| e.id)
+            .collect();
+        assert_eq!(subtree, [2, 3]);
+    }
+
+    #[test]
+    fn session_summary_computes_duration() {
+        let tmp = TempDir::new().unwrap();
+        let store = TelemetrySqliteStore::open(tmp.path(), TelemetryConfig::default()).unwrap();
+        for (ts_epoch_ms, event_type) in [
+            (1_000, "session_start"),
+            (1_500, "llm_response"),
+            (2_000, "tool_call"),
+            (4_000, "session_end"),
+        ] {
+            store.submit_action(testing::action("s1", "s1-t0", ts_epoch_ms, event_type));
+        }
+        drop(store);
+
+        let reader = TelemetryReader::open(&tmp.path().join("research.db")).unwrap();
+        let summary = reader.session_summary("s1").unwrap();
+        assert_eq!(summary.started_at_ms, Some(1_000));
+        assert_eq!(summary.ended_at_ms, Some(4_000));
+        assert_eq!(summary.duration_ms, Some(3_000));
+        assert_eq!(summary.action_count, 2);
+
+        let missing = reader.session_summary("other").unwrap();
+        assert_eq!(missing.duration_ms, None);
+        assert_eq!(missing.action_count, 0);
+    }
+
+    #[test]
+    fn exports_turn_sequences_for_session() {
+        let tmp = TempDir::new().unwrap();
+        let store = TelemetrySqliteStore::open(tmp.path(), TelemetryConfig::default()).unwrap();
+        for (ts_epoch_ms, session_id, turn_id, event_type, sequence) in [
+            (1_000, "s1", "s1-t0", "tool_call", None),
+            (
+                1_100,
+                "s1",
+                "s1-t0",
+                "turn_complete",
+                Some(r#"["tool_call"]"#),
+            ),
+            (
+                2_000,
+                "s1",
+                "s1-t1",
+                "turn_complete",
+                Some(r#"["llm_response","tool_call"]"#),
+            ),
+            (
+                3_000,
+                "s2",
+                "s2-t0",
+                "turn_complete",
+                Some(r#"["llm_response"]"#),
+            ),
+        ] {
+            store.submit_action(ActionRecord {
+                turn_action_sequence: sequence.map(Into::into),
+                ..testing::action(session_id, turn_id, ts_epoch_ms, event_type)
+            });
+        }
+        drop(store);
+
+        let reader = TelemetryReader::open(&tmp.path().join("research.db")).unwrap();
+        let turns = reader.export_turn_sequences("s1").unwrap();
+        assert_eq!(
+            turns,
+            [
+                TurnSequenceRow {
+                    turn_id: "s1-t0".into(),
+                    ts_epoch_ms: 1_100,
+                    sequence: vec!["tool_call".into()],
+                },
+                TurnSequenceRow {
+                    turn_id: "s1-t1".into(),
+                    ts_epoch_ms: 2_000,
+                    sequence: vec!["llm_response".into(), "tool_call".into()],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn token_efficiency_report_computes_ratios() {
+        let tmp = TempDir::new().unwrap();
+        let store = TelemetrySqliteStore::open(tmp.path(), TelemetryConfig::default()).unwrap();
+        for (event_type, tokens_in, tokens_out) in [
+            ("llm_response", Some(300), Some(60)),
+            ("tool_call", None, None),
+            ("tool_call", None, None),
+            ("llm_response", Some(100), None),
+            ("tool_call", None, None),
+            ("agent_thinking", Some(1_000), None),
+        ] {
+            store.submit_action(ActionRecord {
+                tokens_in,
+                tokens_out,
+                ..testing::action("s1", "s1-t0", 1_000, event_type)
+            });
+        }
+        drop(store);
+
+        let reader = TelemetryReader::open(&tmp.path().join("research.db")).unwrap();
+        let report = reader.token_efficiency_report("s1").unwrap();
+        assert_eq!(report.total_tokens_in, 400);
+        assert_eq!(report.total_tokens_out, 60);
+        assert_eq!(report.total_tool_calls, 3);
+        assert!((report.tokens_per_tool_call - 400.0 / 3.0).abs() < 1e-9);
+        assert!((report.output_to_input_ratio - 0.15).abs() < 1e-9);
+        assert!((report.tool_calls_per_llm_call - 1.5).abs() < 1e-9);
+
+        let empty = reader.token_efficiency_report("missing").unwrap();
+        assert_eq!(empty.total_tool_calls, 0);
+        assert!(empty.tokens_per_tool_call.abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn compare_models_reports_signed_differences() {
+        let tmp = TempDir::new().unwrap();
+        let store = TelemetrySqliteStore::open(tmp.path(), TelemetryConfig::default()).unwrap();
+        for (ts_epoch_ms, model, duration_ms, success, tokens_out) in [
+            (1_000, "model-a", 100, true, 50),
+            (2_000, "model-a", 300, false, 50),
+            (3_000, "model-b", 150, true, 100),
+            (500, "model-b", 10_000, false, 0),
+        ] {
+            store.submit_action(ActionRecord {
+                model: Some(model.into()),
+                tool_success: Some(success),
+                duration_ms: Some(duration_ms),
+                tokens_in: Some(100),
+                tokens_out: Some(tokens_out),
+                ..testing::action("s1", "s1-t0", ts_epoch_ms, "llm_response")
+            });
+        }
+        drop(store);
+
+        let reader = TelemetryReader::open(&tmp.path().join("research.db")).unwrap();
+        let cmp = reader
+            .compare_models("model-a", "model-b", Some(1_000))
+            .unwrap();
+        assert_eq!(cmp.model_a_stats.call_count, 2);
+        assert_eq!(cmp.model_b_stats.call_count, 1);
+        assert!((cmp.latency_diff_ms - (150.0 - 200.0)).abs() < 1e-9);
+        assert!((cmp.token_efficiency_diff - (1.0 - 0.5)).abs() < 1e-9);
+        assert!((cmp.error_rate_diff - (0.0 - 0.5)).abs() < 1e-9);
+    }
+
+    #[test]
+    fn repeated_sequences_are_grouped_by_call_depth() {
+        let tmp = TempDir::new().unwrap();
+        let store = TelemetrySqliteStore::open(tmp.path(), TelemetryConfig::default()).unwrap();
+        for (i, (call_depth, sequence)) in [
+            (0, r#"["tool_call"]"#),
+            (1, r#"["tool_call"]"#),
+            (0, r#"["tool_call"]"#),
+            (1, r#"["llm_response"]"#),
+            (1, r#"["llm_response"]"#),
+        ]
+        .into_iter()
+        .enumerate()
+        {
+            store.submit_action(ActionRecord {
+                turn_action_sequence: Some(sequence.into()),
+                call_depth,
+                ..testing::action("s1", &format!("s1-t{i}"), 1_000 + i as i64, "turn_complete")
+            });
+        }
+        drop(store);
+
+        let reader = TelemetryReader::open(&tmp.path().join("research.db")).unwrap();
+        assert_eq!(
+            reader.find_repeated_sequences("s1").unwrap(),
+            [
+                RepeatedSequence {
+                    call_depth: 0,
+                    sequence: vec!["tool_call".into()],
+                    occurrences: 2,
+                },
+                RepeatedSequence {
+                    call_depth: 1,
+                    sequence: vec!["llm_response".into()],
+                    occurrences: 2,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn token_usage_by_model_only_counts_window() {
+        let tmp = TempDir::new().unwrap();
+        let store = TelemetrySqliteStore::open(tmp.path(), TelemetryConfig::default()).unwrap();
+        let now = chrono::Utc::now().timestamp_millis();
+        for (ts_epoch_ms, model, tokens_in) in [
+            (now - 5_000, "gpt-4o", 100),
+            (now - 1_000, "gpt-4o", 50),
+            (now - 2_000, "gpt-4o-mini", 10),
+            (now - 120_000, "gpt-4o", 1_000),
+        ] {
+            store.submit_action(ActionRecord {
+                provider: Some("openai".into()),
+                model: Some(model.into()),
+                tokens_in: Some(tokens_in),
+                tokens_out: Some(1),
+                ..testing::action("s1", "s1-t0", ts_epoch_ms, "llm_response")
+            });
+        }
+        drop(store);
+
+        let reader = TelemetryReader::open(&tmp.path().join("research.db")).unwrap();
+        let usage = reader.token_usage_by_model(60).unwrap();
+        assert_eq!(
+            usage,
+            [
+                ModelTokenUsage {
+                    provider: "openai".into(),
+                    model: "gpt-4o".into(),
+                    tokens_in_total: 150,
+                    tokens_out_total: 2,
+                    call_count: 2,
+                    first_seen_ts: now - 5_000,
+                    last_seen_ts: now - 1_000,
+                },
+                ModelTokenUsage {
+                    provider: "openai".into(),
+                    model: "gpt-4o-mini".into(),
+                    tokens_in_total: 10,
+                    tokens_out_total: 1,
+                    call_count: 1,
+                    first_seen_ts: now - 2_000,
+                    last_seen_ts: now - 2_000,
+                },
+            ]
+        );
+    }
+
+    fn submit_errors(store: &TelemetrySqliteStore, errors: &[(&str, String)]) {
+        for (session_id, message) in errors {
+            store.submit_action(ActionRecord {
+                error_message: Some(message.clone()),
+                ..testing::action(session_id, "t0", 1_000, "tool_call")
+            });
+        }
+    }
+
+    #[test]
+    fn common_error_patterns_groups_normalized_messages() {
+        let tmp = TempDir::new().unwrap();
+        let store = TelemetrySqliteStore::open(tmp.path(), TelemetryConfig::default()).unwrap();
+        submit_errors(
+            &store,
+            &[
+                ("s2", "Request timed out".into()),
+                ("s1,a", "  request TIMED OUT ".into()),
+                ("s2", "request timed out".into()),
+                ("s3", "missing API key".into()),
+            ],
+        );
+        drop(store);
+
+        let reader = TelemetryReader::open(&tmp.path().join("research.db")).unwrap();
+        let patterns = reader.common_error_patterns(2, None).unwrap();
+        assert_eq!(patterns.len(), 1);
+        assert_eq!(patterns[0].normalized_message, "request timed out");
+        assert_eq!(patterns[0].count, 3);
+        // Ids containing commas survive, in order of first occurrence.
+        assert_eq!(patterns[0].example_session_ids, ["s2", "s1,a"]);
+    }
+
+    #[test]
+    fn common_error_patterns_decrypts_before_grouping() {
+        let tmp = TempDir::new().unwrap();
+        let key = [9u8; 32];
+        let store = TelemetrySqliteStore::open(tmp.path(), TelemetryConfig::default()).unwrap();
+        submit_errors(
+            &store,
+            &[
+                ("s1", crypto::encrypt_field("Rate limited", &key).unwrap()),
+                ("s2", crypto::encrypt_field("rate limited", &key).unwrap()),
+                ("s3", "other".into()),
+            ],
+        );
+        drop(store);
+
+        let reader = TelemetryReader::open(&tmp.path().join("research.db"))
+            .unwrap()
+            .with_decryption_key(key);
+        let patterns = reader.common_error_patterns(1, None).unwrap();
+        assert_eq!(
+            patterns,
+            [
+                ErrorPattern {
+                    normalized_message: "rate limited".into(),
+                    count: 2,
+                    example_session_ids: vec!["s1".into(), "s2".into()],
+                },
+                ErrorPattern {
+                    normalized_message: "other".into(),
+                    count: 1,
+                    example_session_ids: vec!["s3".into()],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn flags_samples_beyond_z_threshold() {
+        let tmp = TempDir::new().unwrap();
+        let store = TelemetrySqliteStore::open(tmp.path(), TelemetryConfig::default()).unwrap();
+        let cpu = [10.0, 12.0, 10.0, 12.0, 11.0, 95.0, 11.0, 10.0];
+        for (i, cpu_usage_pct) in cpu.into_iter().enumerate() {
+            store.submit_system_sample(SystemSample {
+                cpu_usage_pct,
+                ..testing::sample(1_000 * i as i64)
+            });
+        }
+        drop(store);
+
+        let reader = TelemetryReader::open(&tmp.path().join("research.db")).unwrap();
+        let anomalies = reader
+            .flag_anomalous_samples(SampleField::CpuUsagePct, 4, 3.0)
+            .unwrap();
+        assert_eq!(anomalies.len(), 1);
+        assert_eq!(anomalies[0].sample.ts_epoch_ms, 5_000);
+        assert!(anomalies[0].z_score > 3.0);
+    }
+
+    #[test]
+    fn finds_cpu_spikes_near_llm_calls() {
+        let tmp = TempDir::new().unwrap();
+        let store = TelemetrySqliteStore::open(tmp.path(), TelemetryConfig::default()).unwrap();
+        for (ts_epoch_ms, cpu_usage_pct) in [(9_000, 95.0), (10_500, 97.0), (20_000, 20.0)] {
+            store.submit_system_sample(SystemSample {
+                cpu_usage_pct,
+                ..testing::sample(ts_epoch_ms)
+            });
+        }
+        for (ts_epoch_ms, event_type) in [
+            (10_000, "llm_response"),
+            (10_000, "tool_call"),
+            (20_000, "llm_response"),
+        ] {
+            store.submit_action(testing::action("s1", "s1-t0", ts_epoch_ms, event_type));
+        }
+        drop(store);
+
+        let reader = TelemetryReader::open(&tmp.path().join("research.db")).unwrap();
+        let spikes = reader.cpu_spikes_during_llm_calls(90.0, 1_000).unwrap();
+        assert_eq!(spikes.len(), 1);
+        assert_eq!(spikes[0].action_event.ts_epoch_ms, 10_000);
+        assert_eq!(spikes[0].action_event.event_type, "llm_response");
+        assert!((spikes[0].peak_cpu - 97.0).abs() < f64::EPSILON);
+        assert_eq!(spikes[0].sample_ts, 10_500);
+    }
+
+    #[test]
+    fn correlates_network_events_with_tool_calls() {
+        let tmp = TempDir::new().unwrap();
+        let store = TelemetrySqliteStore::open(tmp.path(), TelemetryConfig::default()).unwrap();
+        // web_fetch runs 9_000..=10_000, shell runs 19_500..=20_000.
+        for (ts_epoch_ms, tool, duration_ms) in
+            [(10_000, "web_fetch", 1_000), (20_000, "shell", 500)]
+        {
+            store.submit_action(ActionRecord {
+                tool_name: Some(tool.into()),
+                duration_ms: Some(duration_ms),
+                ..testing::action("s1", "s1-t0", ts_epoch_ms, "tool_call")
+            });
+        }
+        for (ts_epoch_ms, dest_port) in [(9_500, 443), (15_000, 53), (19_800, 22)] {
+            store.submit_network_event(NetworkEvent {
+                ts: "2026-01-01T00:00:00Z".into(),
+                ts_epoch_ms,
+                pid: 42,
+                dest_ip: "93.184.216.34".into(),
+                dest_port,
+                success: true,
+            });
+        }
+        drop(store);
+
+        let reader = TelemetryReader::open(&tmp.path().join("research.db")).unwrap();
+        let events = reader.network_events_with_tool_calls(None, 100).unwrap();
+        let matched: Vec<(u16, Option<&str>)> = events
+            .iter()
+            .map(|e| (e.dest_port, e.tool_name.as_deref()))
+            .collect();
+        assert_eq!(
+            matched,
+            [(443, Some("web_fetch")), (53, None), (22, Some("shell"))]
+        );
+        assert_eq!(events[0].action_event_id, Some(1));
+        assert!(events[0].success);
+    }
+
+    #[test]
+    fn correlates_dns_queries_with_tool_calls() {
+        let tmp = TempDir::new().unwrap();
+        let store = TelemetrySqliteStore::open(tmp.path(), TelemetryConfig::default()).unwrap();
+        store.submit_action(ActionRecord {
+            tool_name: Some("web_fetch".into()),
+            duration_ms: Some(2_000),
+            ..testing::action("s1", "s1-t0", 10_000, "tool_call")
+        });
+        for (ts_epoch_ms, hostname, success) in [
+            (8_500, "example.com", true),
+            (12_000, "nope.invalid", false),
+        ] {
+            store.submit_dns_query(DnsQuery {
+                ts: "2026-01-01T00:00:00Z".into(),
+                ts_epoch_ms,
+                hostname: hostname.into(),
+                resolution_ms: 12.5,
+                success,
+            });
+        }
+        drop(store);
+
+        let reader = TelemetryReader::open(&tmp.path().join("research.db")).unwrap();
+        let queries = reader.dns_queries_with_tool_calls(None, 100).unwrap();
+        assert_eq!(queries.len(), 2);
+        assert_eq!(queries[0].hostname, "example.com");
+        assert_eq!(queries[0].tool_name.as_deref(), Some("web_fetch"));
+        assert!((queries[0].resolution_ms - 12.5).abs() < f64::EPSILON);
+        assert!(!queries[1].success);
+        assert_eq!(queries[1].tool_name, None);
+    }
+
+    #[test]
+    fn reader_hides_soft_deleted_actions() {
+        let tmp = TempDir::new().unwrap();
+        let store = TelemetrySqliteStore::open(tmp.path(), TelemetryConfig::default()).unwrap();
+        for session_id in ["s1", "s2"] {
+            store.submit_action(testing::action(
+                session_id,
+                &format!("{session_id}-t0"),
+                1_000,
+                "tool_call",
+            ));
+        }
+        assert_eq!(store.delete_actions_in_session("s1").unwrap(), 1);
+        drop(store);
+
+        let reader = TelemetryReader::open(&tmp.path().join("research.db")).unwrap();
+        assert!(reader.export_session_events("s1").unwrap().is_empty());
+        let all = reader.export_action_events(None, 100).unwrap();
+        assert_eq!(all.len(), 1);
+        assert_eq!(all[0].session_id, "s2");
+    }
+
+    #[test]
+    fn filters_action_events_by_time_window() {
+        let tmp = TempDir::new().unwrap();
+        let store = TelemetrySqliteStore::open(tmp.path(), TelemetryConfig::default()).unwrap();
+        for batch in 0..3 {
+            for i in 0..4 {
+                store.submit_action(ActionRecord {
+                    sequence_index: i,
+                    ..testing::action(
+                        &format!("batch-{batch}"),
+                        "t0",
+                        (batch + 1) * 1_000 + i,
+                        "tool_call",
+                    )
+                });
+            }
+        }
+        drop(store);
+
+        let reader = TelemetryReader::open(&tmp.path().join("research.db")).unwrap();
+        let filter = ActionEventFilter {
+            since_epoch_ms: Some(2_000),
+            until_epoch_ms: Some(2_003),
+            ..ActionEventFilter::default()
+        };
+        let events = reader.export_action_events_filtered(&filter, 100).unwrap();
+        assert_eq!(events.len(), 4);
+        assert!(events.iter().all(|e| e.session_id == "batch-1"));
+    }
+
+    #[test]
+    fn looks_up_events_and_session_by_turn() {
+        let tmp = TempDir::new().unwrap();
+        let store = TelemetrySqliteStore::open(tmp.path(), TelemetryConfig::default()).unwrap();
+        for (session_id, turn_id, sequence_index) in [
+            ("s1", "s1-t0", 1),
+            ("s1", "s1-t0", 0),
+            ("s1", "s1-t1", 0),
+            ("s2", "s2-t0", 0),
+        ] {
+            store.submit_action(ActionRecord {
+                sequence_index,
+                ..testing::action(session_id, turn_id, 1_000, "tool_call")
+            });
+        }
+        drop(store);
+
+        let reader = TelemetryReader::open(&tmp.path().join("research.db")).unwrap();
+        assert_eq!(
+            reader.session_id_for_turn("s2-t0").unwrap().as_deref(),
+            Some("s2")
+        );
+        assert_eq!(reader.session_id_for_turn("missing").unwrap(), None);
+        let sequence: Vec<i64> = reader
+            .events_for_turn("s1-t0")
+            .unwrap()
+            .iter()
+            .map(|e| e.sequence_index)
+            .collect();
+        assert_eq!(sequence, [0, 1]);
+    }
+
+    #[test]
+    fn system_sample_deltas_follow_previous_sample() {
+        let tmp = TempDir::new().unwrap();
+        let store = TelemetrySqliteStore::open(tmp.path(), TelemetryConfig::default()).unwrap();
+        for (ts_epoch_ms, cpu, connections) in
+            [(1_000, 10.0, 2), (2_000, 30.0, 5), (4_000, 20.0, 1)]
+        {
+            store.submit_system_sample(SystemSample {
+                cpu_usage_pct: cpu,
+                memory_used_bytes: ts_epoch_ms * 10,
+                net_connections: connections,
+                ..testing::sample(ts_epoch_ms)
+            });
+        }
+        drop(store);
+
+        let reader = TelemetryReader::open(&tmp.path().join("research.db")).unwrap();
+        let deltas = reader.system_sample_deltas(None, 100).unwrap();
+        assert_eq!(deltas.len(), 3);
+        assert_eq!(deltas[0].interval_ms, None);
+        assert_eq!(deltas[0].cpu_usage_pct_delta, None);
+        assert_eq!(deltas[1].cpu_usage_pct_delta, Some(20.0));
+        assert_eq!(deltas[1].memory_used_bytes_delta, Some(10_000));
+        assert_eq!(deltas[2].interval_ms, Some(2_000));
+        assert_eq!(deltas[2].net_connections_delta, Some(-4));
+
+        // The window's first sample is still compared with the one before it.
+        let windowed = reader.system_sample_deltas(Some(2_000), 100).unwrap();
+        assert_eq!(windowed.len(), 2);
+        assert_eq!(windowed[0].cpu_usage_pct_delta, Some(20.0));
+    }
+
+    #[test]
+    fn interpolates_across_sample_gaps() {
+        let tmp = TempDir::new().unwrap();
+        let store = TelemetrySqliteStore::open(tmp.path(), TelemetryConfig::default()).unwrap();
+        // A collector gap between 2s and 6s.
+        for (ts_epoch_ms, cpu, memory) in
+            [(1_000, 10.0, 100), (2_000, 20.0, 200), (6_000, 60.0, 600)]
+        {
+            store.submit_system_sample(SystemSample {
+                cpu_usage_pct: cpu,
+                memory_used_bytes: memory,
+                memory_total_bytes: 1_000,
+                process_count: 3,
+                ..testing::sample(ts_epoch_ms)
+            });
+        }
+        drop(store);
+
+        let reader = TelemetryReader::open(&tmp.path().join("research.db")).unwrap();
+        let rows = reader.interpolated_samples(1_000, 6_000, 1_000).unwrap();
+        let summary: Vec<(i64, f64, i64, bool)> = rows
+            .iter()
+            .map(|r| {
+                (
+                    r.sample.ts_epoch_ms,
+                    r.sample.cpu_usage_pct,
+                    r.sample.memory_used_bytes,
+                    r.interpolated,
+                )
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                (1_000, 10.0, 100, false),
+                (2_000, 20.0, 200, false),
+                (3_000, 30.0, 300, true),
+                (4_000, 40.0, 400, true),
+                (5_000, 50.0, 500, true),
+                (6_000, 60.0, 600, false),
+            ]
+        );
+        assert_eq!(rows[2].sample.process_count, 3);
+        // Nothing after the last sample to interpolate towards.
+        assert_eq!(
+            reader
+                .interpolated_samples(7_000, 9_000, 1_000)
+                .unwrap()
+                .len(),
+            0
+        );
+        assert!(reader.interpolated_samples(0, 1, 0).is_err());
+    }
+
+    #[test]
+    fn filters_action_events_by_session_tag() {
+        let tmp = TempDir::new().unwrap();
+        let store = TelemetrySqliteStore::open(tmp.path(), TelemetryConfig::default()).unwrap();
+        for (i, session_id) in ["s1", "s2", "s3"].into_iter().enumerate() {
+            store.submit_action(testing::action(
+                session_id,
+                &format!("{session_id}-t0"),
+                1_000 + i as i64,
+                "tool_call",
+            ));
+        }
+        store.tag_session("s1", "tenant:acme");
+        store.tag_session("s3", "tenant:acme");
+        store.tag_session("s2", "experiment:b");
+        drop(store);
+
+        let reader = TelemetryReader::open(&tmp.path().join("research.db")).unwrap();
+        assert_eq!(
+            reader.sessions_with_tag("tenant:acme").unwrap(),
+            ["s1", "s3"]
+        );
+        let filter = ActionEventFilter {
+            tag: Some("tenant:acme".into()),
+            ..ActionEventFilter::default()
+        };
+        let events = reader.export_action_events_filtered(&filter, 100).unwrap();
+        let sessions: Vec<&str> = events.iter().map(|e| e.session_id.as_str()).collect();
+        assert_eq!(sessions, ["s1", "s3"]);
+        assert_eq!(
+            reader
+                .export_action_events_filtered(&ActionEventFilter::default(), 100)
+                .unwrap()
+                .len(),
+            3
+        );
+    }
+
+    #[test]
+    fn schema_info_lists_tables_columns_and_indexes() {
+        let tmp = TempDir::new().unwrap();
+        drop(TelemetrySqliteStore::open(tmp.path(), TelemetryConfig::default()).unwrap());
+
+        let reader = TelemetryReader::open(&tmp.path().join("research.db")).unwrap();
+        let info = reader.schema_info().unwrap();
+        let names: Vec<&str> = info.tables.iter().map(|t| t.name.as_str()).collect();
+        assert!(names.contains(&"action_events"));
+        assert!(names.contains(&"system_samples"));
+        assert!(!names.iter().any(|n| n.starts_with("sqlite_")));
+
+        let actions = info
+            .tables
+            .iter()
+            .find(|t| t.name == "action_events")
+            .unwrap();
+        let id = &actions.columns[0];
+        assert_eq!((id.name.as_str(), id.sql_type.as_str()), ("id", "INTEGER"));
+        assert!(id.primary_key);
+        // Migrated columns are visible despite the soft-delete view.
+        assert!(actions.columns.iter().any(|c| c.name == "deleted_at"));
+        let session_idx = actions
+            .indexes
+            .iter()
+            .find(|i| i.name == "idx_ae_deleted")
+            .unwrap();
+        assert!(!session_idx.unique);
+        assert_eq!(session_idx.columns, ["session_id", "deleted_at"]);
+
+        let tags = info
+            .tables
+            .iter()
+            .find(|t| t.name == "session_tags")
+            .unwrap();
+        assert!(tags
+            .indexes
+            .iter()
+            .any(|i| i.unique && i.columns == ["session_id", "tag"]));
+    }
 }
@@ -0,0 +1,181 @@
+use crate::observability::traits::ObserverEvent;
+use crate::telemetry::reader::{ActionEventRow, TelemetryReader};
+use anyhow::Result;
+use std::time::Duration;
+
+/// Rebuild the observer events of a recorded session, in chronological
+/// order, so a past session can be fed into a mock agent to reproduce a bug.
+///
+/// `llm_response` and `tool_call` rows become `LlmResponse` and `ToolCall`
+/// events, and session markers become `SessionStart` / `SessionEnd`. A
+/// `TurnComplete` is emitted for each stored `turn_complete` row and, for
+/// databases that predate those rows, whenever `turn_id` changes. Tool
+/// arguments are not stored, so replayed calls carry only their hash. Other
+/// event types are skipped.
+pub fn replay_session(reader: &TelemetryReader, session_id: &str) -> Result<Vec<ObserverEvent>> {
+    let mut events = Vec::new();
+    let mut current_turn: Option<String> = None;
+    let mut turn_open = false;
+
+    for row in reader.export_session_events(session_id)? {
+        if current_turn.as_deref() != Some(row.turn_id.as_str()) {
+            if turn_open {
+                events.push(ObserverEvent::TurnComplete);
+                turn_open = false;
+            }
+            current_turn = Some(row.turn_id.clone());
+        }
+
+        match row.event_type.as_str() {
+            "turn_complete" => {
+                events.push(ObserverEvent::TurnComplete);
+                turn_open = false;
+            }
+            "session_start" => events.push(ObserverEvent::SessionStart {
+                session_id: row.session_id,
+            }),
+            "session_end" => events.push(ObserverEvent::SessionEnd {
+                session_id: row.session_id,
+            }),
+            _ => {
+                if let Some(event) = action_event(row) {
+                    events.push(event);
+                    turn_open = true;
+                }
+            }
+        }
+    }
+    Ok(events)
+}
+
+/// Inverse of the observer's mapping for per-turn action events.
+fn action_event(row: ActionEventRow) -> Option<ObserverEvent> {
+    let duration = Duration::from_millis(
+        row.duration_ms
+            .and_then(|ms| u64::try_from(ms).ok())
+            .unwrap_or(0),
+    );
+    match row.event_type.as_str() {
+        "llm_response" => Some(ObserverEvent::LlmResponse {
+            provider: row.provider.unwrap_or_default(),
+            model: row.model.unwrap_or_default(),
+            duration,
+            success: row.tool_success.unwrap_or(true),
+            error_message: row.error_message,
+            tokens_in: row.tokens_in.and_then(|t| u64::try_from(t).ok()),
+            tokens_out: row.tokens_out.and_then(|t| u64::try_from(t).ok()),
+        }),
+        "tool_call" => Some(ObserverEvent::ToolCall {
+            tool: row.tool_name.unwrap_or_default(),
+            duration,
+            success: row.tool_success.unwrap_or(true),
+            arguments: None,
+            arguments_hash: row.arguments_hash,
+            iteration: u32::try_from(row.iteration_index).ok(),
+        }),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::TelemetryConfig;
+    use crate::observability::Observer;
+    use crate::telemetry::store::{ActionRecord, TelemetrySqliteStore};
+    use crate::telemetry::testing;
+    use crate::telemetry::TelemetryObserver;
+    use std::sync::Arc;
+    use tempfile::TempDir;
+
+    #[test]
+    fn replays_recorded_session() {
+        let tmp = TempDir::new().unwrap();
+        let store =
+            Arc::new(TelemetrySqliteStore::open(tmp.path(), TelemetryConfig::default()).unwrap());
+        let obs = TelemetryObserver::new(store.clone(), "s1".into());
+        obs.record_event(&ObserverEvent::LlmResponse {
+            provider: "openai".into(),
+            model: "gpt-4o".into(),
+            duration: Duration::from_millis(120),
+            success: true,
+            error_message: None,
+            tokens_in: Some(10),
+            tokens_out: Some(5),
+        });
+        obs.record_event(&ObserverEvent::ToolCall {
+            tool: "shell".into(),
+            duration: Duration::from_millis(30),
+            success: false,
+            arguments: Some(serde_json::json!({ "command": "ls" })),
+            arguments_hash: None,
+            iteration: Some(2),
+        });
+        obs.record_event(&ObserverEvent::TurnComplete);
+        drop(obs);
+        drop(store);
+
+        let reader = TelemetryReader::open(&tmp.path().join("research.db")).unwrap();
+        let events = replay_session(&reader, "s1").unwrap();
+        assert_eq!(events.len(), 3);
+        assert!(matches!(
+            &events[0],
+            ObserverEvent::LlmResponse {
+                provider,
+                duration,
+                tokens_out: Some(5),
+                ..
+            } if provider == "openai" && *duration == Duration::from_millis(120)
+        ));
+        assert!(matches!(
+            &events[1],
+            ObserverEvent::ToolCall {
+                tool,
+                success: false,
+                arguments: None,
+                arguments_hash: Some(_),
+                iteration: Some(2),
+                ..
+            } if tool == "shell"
+        ));
+        assert!(matches!(events[2], ObserverEvent::TurnComplete));
+    }
+
+    #[test]
+    fn turn_id_change_closes_previous_turn() {
+        let tmp = TempDir::new().unwrap();
+        let store = TelemetrySqliteStore::open(tmp.path(), TelemetryConfig::default()).unwrap();
+        for (ts_epoch_ms, turn_id) in [(1_000, "s1-t0"), (2_000, "s1-t1")] {
+            store.submit_action(ActionRecord {
+                tool_name: Some("shell".into()),
+                ..testing::action("s1", turn_id, ts_epoch_ms, "tool_call")
+            });
+        }
+        drop(store);
+
+        let reader = TelemetryReader::open(&tmp.path().join("research.db")).unwrap();
+        let events = replay_session(&reader, "s1").unwrap();
+        assert_eq!(events.len(), 3);
+        assert!(matches!(events[0], ObserverEvent::ToolCall { .. }));
+        assert!(matches!(events[1], ObserverEvent::TurnComplete));
+        assert!(matches!(events[2], ObserverEvent::ToolCall { .. }));
+    }
+
+    #[test]
+    fn same_millisecond_events_replay_in_sequence_order() {
+        let tmp = TempDir::new().unwrap();
+        let store = TelemetrySqliteStore::open(tmp.path(), TelemetryConfig::default()).unwrap();
+        for (sequence_index, event_type) in [(1, "tool_call"), (0, "llm_response")] {
+            store.submit_action(ActionRecord {
+                sequence_index,
+                ..testing::action("s1", "s1-t0", 1_000, event_type)
+            });
+        }
+        drop(store);
+
+        let reader = TelemetryReader::open(&tmp.path().join("research.db")).unwrap();
+        let events = replay_session(&reader, "s1").unwrap();
+        assert!(matches!(events[0], ObserverEvent::LlmResponse { .. }));
+        assert!(matches!(events[1], ObserverEvent::ToolCall { .. }));
+    }
+}
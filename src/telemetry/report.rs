@@ -0,0 +1,232 @@
+use crate::telemetry::reader::{ActionEventRow, TelemetryReader};
+use anyhow::Result;
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+const REPORT_CSS: &str = "\
+body{font-family:system-ui,sans-serif;margin:2rem;color:#222}\
+h1{font-size:1.4rem}h2{font-size:1.1rem;margin-top:2rem}\
+table{border-collapse:collapse;font-size:.9rem}\
+th,td{border:1px solid #ccc;padding:.3rem .6rem;text-align:left}\
+th{background:#f2f2f2}table.sortable th{cursor:pointer}\
+dl{display:grid;grid-template-columns:max-content auto;gap:.2rem 1rem}\
+dt{font-weight:600}.fail{color:#b00020}";
+
+/// Sorts a `table.sortable` by the clicked column, numerically when both
+/// cells parse as numbers.
+const SORT_SCRIPT: &str = "\
+document.querySelectorAll('table.sortable th').forEach(function(th,col){\
+th.addEventListener('click',function(){\
+var body=th.closest('table').tBodies[0];var asc=th.dataset.asc!=='1';th.dataset.asc=asc?'1':'0';\
+Array.from(body.rows).sort(function(a,b){\
+var x=a.cells[col].textContent,y=b.cells[col].textContent,nx=parseFloat(x),ny=parseFloat(y);\
+var c=(isNaN(nx)||isNaN(ny))?x.localeCompare(y):nx-ny;return asc?c:-c;})\
+.forEach(function(r){body.appendChild(r);});});});";
+
+#[derive(Default)]
+struct ToolUsage {
+    calls: u64,
+    failures: u64,
+    total_duration_ms: i64,
+}
+
+impl TelemetryReader {
+    /// Render a self-contained HTML summary of a session: duration, cost,
+    /// a sortable tool usage table, LLM call stats, and a timeline of every
+    /// event. CSS and the sorting script are inlined so the page renders
+    /// offline.
+    pub fn generate_html_report(&self, session_id: &str) -> Result<String> {
+        let summary = self.session_summary(session_id)?;
+        let events = self.export_session_events(session_id)?;
+
+        let total_cost: f64 = events.iter().filter_map(|e| e.estimated_cost_usd).sum();
+        let llm_latencies: Vec<i64> = events
+            .iter()
+            .filter(|e| e.event_type == "llm_response")
+            .filter_map(|e| e.duration_ms)
+            .collect();
+        let llm_calls = events
+            .iter()
+            .filter(|e| e.event_type == "llm_response")
+            .count();
+        let avg_llm_latency = if llm_latencies.is_empty() {
+            0.0
+        } else {
+            llm_latencies.iter().sum::<i64>() as f64 / llm_latencies.len() as f64
+        };
+
+        let mut tools: BTreeMap<&str, ToolUsage> = BTreeMap::new();
+        for event in events.iter().filter(|e| e.event_type == "tool_call") {
+            let usage = tools
+                .entry(event.tool_name.as_deref().unwrap_or("unknown"))
+                .or_default();
+            usage.calls += 1;
+            if event.tool_success == Some(false) {
+                usage.failures += 1;
+            }
+            usage.total_duration_ms += event.duration_ms.unwrap_or(0);
+        }
+
+        let title = escape_html(session_id);
+        let duration = summary
+            .duration_ms
+            .map_or_else(|| "in progress".to_string(), format_duration);
+
+        let mut html = String::new();
+        let _ = write!(
+            html,
+            "<!DOCTYPE html>\n<html lang=\"en\"><head><meta charset=\"utf-8\">\
+             <title>Session {title}</title><style>{REPORT_CSS}</style></head><body>\
+             <h1>Session {title}</h1><dl>\
+             <dt>Duration</dt><dd>{duration}</dd>\
+             <dt>Total cost</dt><dd>${total_cost:.4}</dd>\
+             <dt>Actions</dt><dd>{actions}</dd>\
+             <dt>LLM calls</dt><dd>{llm_calls}</dd>\
+             <dt>Avg LLM latency</dt><dd>{avg_llm_latency:.0} ms</dd></dl>",
+            actions = summary.action_count,
+        );
+
+        html.push_str(
+            "<h2>Tool usage</h2><table class=\"sortable\"><thead><tr>\
+             <th>Tool</th><th>Calls</th><th>Failures</th><th>Avg duration (ms)</th>\
+             </tr></thead><tbody>",
+        );
+        for (tool, usage) in &tools {
+            let _ = write!(
+                html,
+                "<tr><td>{}</td><td>{}</td><td>{}</td><td>{:.0}</td></tr>",
+                escape_html(tool),
+                usage.calls,
+                usage.failures,
+                usage.total_duration_ms as f64 / usage.calls as f64,
+            );
+        }
+        html.push_str("</tbody></table>");
+
+        html.push_str(
+            "<h2>Timeline</h2><table class=\"sortable\"><thead><tr>\
+             <th>Time</th><th>Turn</th><th>Event</th><th>Detail</th>\
+             <th>Duration (ms)</th><th>Result</th></tr></thead><tbody>",
+        );
+        for event in &events {
+            timeline_row(&mut html, event);
+        }
+        let _ = write!(
+            html,
+            "</tbody></table><script>{SORT_SCRIPT}</script></body></html>"
+        );
+        Ok(html)
+    }
+}
+
+fn timeline_row(html: &mut String, event: &ActionEventRow) {
+    let detail = match (&event.tool_name, &event.provider, &event.model) {
+        (Some(tool), _, _) => tool.clone(),
+        (None, Some(provider), Some(model)) => format!("{provider}/{model}"),
+        (None, _, Some(model)) => model.clone(),
+        _ => String::new(),
+    };
+    let result = match (event.tool_success, &event.error_message) {
+        (_, Some(error)) => format!("<span class=\"fail\">{}</span>", escape_html(error)),
+        (Some(false), None) => "<span class=\"fail\">failed</span>".to_string(),
+        (Some(true), None) => "ok".to_string(),
+        (None, None) => String::new(),
+    };
+    let _ = write!(
+        html,
+        "<tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>",
+        escape_html(&event.ts),
+        escape_html(&event.turn_id),
+        escape_html(&event.event_type),
+        escape_html(&detail),
+        event
+            .duration_ms
+            .map(|ms| ms.to_string())
+            .unwrap_or_default(),
+        result,
+    );
+}
+
+fn format_duration(ms: i64) -> String {
+    let secs = ms / 1000;
+    if secs >= 60 {
+        format!("{}m {}s", secs / 60, secs % 60)
+    } else {
+        format!("{:.1}s", ms as f64 / 1000.0)
+    }
+}
+
+fn escape_html(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::TelemetryConfig;
+    use crate::telemetry::store::{ActionRecord, TelemetrySqliteStore};
+    use tempfile::TempDir;
+
+    #[test]
+    fn report_summarizes_session() {
+        let tmp = TempDir::new().unwrap();
+        let store = TelemetrySqliteStore::open(tmp.path(), TelemetryConfig::default()).unwrap();
+        let events = [
+            (1_000, "session_start", None, None, None),
+            (2_000, "llm_response", None, Some(400), Some(0.02)),
+            (3_000, "tool_call", Some("shell"), Some(100), None),
+            (4_000, "tool_call", Some("<script>"), Some(50), None),
+            (91_000, "session_end", None, None, None),
+        ];
+        for (ts_epoch_ms, event_type, tool_name, duration_ms, cost) in events {
+            store.submit_action(ActionRecord {
+                ts: format!("t{ts_epoch_ms}"),
+                ts_epoch_ms,
+                session_id: "s1".into(),
+                turn_id: "s1-t0".into(),
+                event_type: event_type.into(),
+                model: (event_type == "llm_response").then(|| "gpt-4o".into()),
+                tool_name: tool_name.map(Into::into),
+                tool_success: tool_name.map(|t| t == "shell"),
+                duration_ms,
+                estimated_cost_usd: cost,
+                ..ActionRecord::default()
+            });
+        }
+        drop(store);
+
+        let reader = TelemetryReader::open(&tmp.path().join("research.db")).unwrap();
+        let html = reader.generate_html_report("s1").unwrap();
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(html.contains("<dd>1m 30s</dd>"));
+        assert!(html.contains("<dd>$0.0200</dd>"));
+        assert!(html.contains("<dt>LLM calls</dt><dd>1</dd>"));
+        assert!(html.contains("<dd>400 ms</dd>"));
+        assert!(html.contains("<tr><td>shell</td><td>1</td><td>0</td><td>100</td></tr>"));
+        assert!(html.contains("&lt;script&gt;"));
+        assert!(!html.contains("<td><script>"));
+        assert!(
+            !html.contains("http"),
+            "report must not reference network resources"
+        );
+    }
+
+    #[test]
+    fn escapes_html_metacharacters() {
+        assert_eq!(
+            escape_html(r#"<a href="x">'&'</a>"#),
+            "&lt;a href=&quot;x&quot;&gt;&#39;&amp;&#39;&lt;/a&gt;"
+        );
+    }
+}
@@ -1,5 +1,7 @@
 // DDL constants for the research telemetry database.
 
+use crate::config::{AutoVacuumMode, SqliteSynchronous, TelemetryConfig};
+
 pub const ACTION_EVENTS_DDL: &str = "\
 CREATE TABLE IF NOT EXISTS action_events (
     id                  INTEGER PRIMARY KEY AUTOINCREMENT,
@@ -22,11 +24,14 @@ CREATE TABLE IF NOT EXISTS action_events (
     iteration_index     INTEGER NOT NULL,
     previous_action_type TEXT,
     turn_action_sequence TEXT,
-    error_message       TEXT
+    error_message       TEXT,
+    correlation_id      TEXT,
+    parent_action_id    INTEGER REFERENCES action_events(id),
+    estimated_cost_usd  REAL,
+    metadata_json       TEXT,
+    call_depth          INTEGER NOT NULL DEFAULT 0,
+    deleted_at          TEXT
 );
-CREATE INDEX IF NOT EXISTS idx_ae_session ON action_events(session_id);
-CREATE INDEX IF NOT EXISTS idx_ae_turn    ON action_events(turn_id);
-CREATE INDEX IF NOT EXISTS idx_ae_epoch   ON action_events(ts_epoch_ms);
 ";
 
 pub const SYSTEM_SAMPLES_DDL: &str = "\
@@ -43,9 +48,9 @@ CREATE TABLE IF NOT EXISTS system_samples (
     file_write_bytes    INTEGER NOT NULL,
     net_connections     INTEGER NOT NULL,
     dest_ip_entropy     REAL    NOT NULL,
-    syscall_freq_json   TEXT
+    syscall_freq_json   TEXT,
+    tcp_state_json      TEXT
 );
-CREATE INDEX IF NOT EXISTS idx_ss_epoch ON system_samples(ts_epoch_ms);
 ";
 
 pub const TOOL_EMBEDDINGS_CACHE_DDL: &str = "\
@@ -53,17 +58,153 @@ CREATE TABLE IF NOT EXISTS tool_embeddings_cache (
     tool_name   TEXT PRIMARY KEY,
     embedding   BLOB NOT NULL,
     dimensions  INTEGER NOT NULL,
-    computed_at TEXT NOT NULL
+    computed_at TEXT NOT NULL,
+    normalized  INTEGER NOT NULL DEFAULT 0
 );
 ";
 
-pub const PRAGMAS: &str = "\
+/// Running success/failure counts per tool, upserted by the writer so a
+/// tool's success rate is a primary-key lookup.
+pub const TOOL_SUCCESS_RATES_DDL: &str = "\
+CREATE TABLE IF NOT EXISTS tool_success_rates (
+    tool_name     TEXT PRIMARY KEY,
+    success_count INTEGER NOT NULL DEFAULT 0,
+    failure_count INTEGER NOT NULL DEFAULT 0,
+    last_updated  TEXT
+);
+";
+
+pub const SESSION_TAGS_DDL: &str = "\
+CREATE TABLE IF NOT EXISTS session_tags (
+    session_id  TEXT NOT NULL,
+    tag         TEXT NOT NULL,
+    added_at    TEXT,
+    PRIMARY KEY (session_id, tag)
+);
+";
+
+pub const NETWORK_EVENTS_DDL: &str = "\
+CREATE TABLE IF NOT EXISTS network_events (
+    id          INTEGER PRIMARY KEY AUTOINCREMENT,
+    ts          TEXT    NOT NULL,
+    ts_epoch_ms INTEGER NOT NULL,
+    pid         INTEGER NOT NULL,
+    dest_ip     TEXT    NOT NULL,
+    dest_port   INTEGER NOT NULL,
+    success     INTEGER NOT NULL
+);
+";
+
+pub const DNS_QUERIES_DDL: &str = "\
+CREATE TABLE IF NOT EXISTS dns_queries (
+    id            INTEGER PRIMARY KEY AUTOINCREMENT,
+    ts            TEXT    NOT NULL,
+    ts_epoch_ms   INTEGER NOT NULL,
+    hostname      TEXT    NOT NULL,
+    resolution_ms REAL    NOT NULL,
+    success       INTEGER NOT NULL
+);
+";
+
+/// Columns added after the initial schema, as `(table, column, sql_type)`.
+///
+/// Databases created by older builds are upgraded in place by adding any
+/// column that is missing; fresh databases already have them from the DDL.
+pub const COLUMN_MIGRATIONS: &[(&str, &str, &str)] = &[
+    ("system_samples", "tcp_state_json", "TEXT"),
+    ("action_events", "correlation_id", "TEXT"),
+    (
+        "action_events",
+        "parent_action_id",
+        "INTEGER REFERENCES action_events(id)",
+    ),
+    ("action_events", "estimated_cost_usd", "REAL"),
+    ("action_events", "metadata_json", "TEXT"),
+    ("action_events", "call_depth", "INTEGER NOT NULL DEFAULT 0"),
+    ("action_events", "deleted_at", "TEXT"),
+    ("session_tags", "added_at", "TEXT"),
+    (
+        "tool_embeddings_cache",
+        "normalized",
+        "INTEGER NOT NULL DEFAULT 0",
+    ),
+];
+
+/// Every index, kept apart from the table DDL so a bulk import can defer
+/// them (see `IndexStrategy`). Run after [`COLUMN_MIGRATIONS`] so migrated
+/// columns exist on upgraded databases.
+pub const INDEXES_DDL: &str = "\
+CREATE INDEX IF NOT EXISTS idx_ae_session ON action_events(session_id);
+CREATE INDEX IF NOT EXISTS idx_ae_turn    ON action_events(turn_id);
+CREATE INDEX IF NOT EXISTS idx_ae_epoch   ON action_events(ts_epoch_ms);
+CREATE INDEX IF NOT EXISTS idx_ae_tool    ON action_events(tool_name);
+CREATE INDEX IF NOT EXISTS idx_ae_failed_tools
+    ON action_events(tool_name, ts_epoch_ms) WHERE tool_success = 0;
+CREATE INDEX IF NOT EXISTS idx_ss_epoch ON system_samples(ts_epoch_ms);
+CREATE INDEX IF NOT EXISTS idx_st_tag ON session_tags(tag);
+CREATE INDEX IF NOT EXISTS idx_ne_epoch ON network_events(ts_epoch_ms);
+CREATE INDEX IF NOT EXISTS idx_dq_epoch ON dns_queries(ts_epoch_ms);
+CREATE INDEX IF NOT EXISTS idx_ae_correlation ON action_events(correlation_id);
+CREATE INDEX IF NOT EXISTS idx_ae_parent      ON action_events(parent_action_id);
+CREATE INDEX IF NOT EXISTS idx_ae_deleted     ON action_events(session_id, deleted_at);
+";
+
+/// Views over the telemetry tables. Run after [`COLUMN_MIGRATIONS`]: a view
+/// in `main` reads `main.action_events` directly, so it filters out
+/// soft-deleted rows itself.
+pub const VIEWS_DDL: &str = "\
+CREATE VIEW IF NOT EXISTS session_stats AS
+    SELECT session_id,
+           COUNT(*)         AS event_count,
+           MIN(ts_epoch_ms) AS start_ms,
+           MAX(ts_epoch_ms) AS end_ms,
+           SUM(tokens_in)   AS total_tokens_in,
+           SUM(tokens_out)  AS total_tokens_out
+    FROM action_events
+    WHERE deleted_at IS NULL
+    GROUP BY session_id;
+";
+
+/// Shadows `action_events` on a reader connection with a view of the rows
+/// that have not been soft-deleted. Unqualified names resolve to the `temp`
+/// schema first, so every reader query is filtered without changing it.
+pub const LIVE_ACTION_EVENTS_VIEW_DDL: &str = "\
+CREATE TEMP VIEW IF NOT EXISTS action_events AS
+    SELECT * FROM main.action_events WHERE deleted_at IS NULL;
+";
+
+/// Page cache size used when `TelemetryConfig::cache_size_kb` is unset.
+pub const DEFAULT_CACHE_SIZE_KB: i64 = 1000;
+/// mmap size used when `TelemetryConfig::mmap_size_bytes` is unset.
+pub const DEFAULT_MMAP_SIZE_BYTES: i64 = 4_194_304;
+
+/// Connection PRAGMAs for the telemetry database, sized from `config`.
+pub fn pragmas(config: &TelemetryConfig) -> String {
+    let cache_size_kb = config.cache_size_kb.unwrap_or(DEFAULT_CACHE_SIZE_KB);
+    let mmap_size = config.mmap_size_bytes.unwrap_or(DEFAULT_MMAP_SIZE_BYTES);
+    let synchronous = match config.synchronous {
+        SqliteSynchronous::Off => "OFF",
+        SqliteSynchronous::Normal => "NORMAL",
+        SqliteSynchronous::Full => "FULL",
+    };
+    let auto_vacuum = match config.auto_vacuum {
+        AutoVacuumMode::None => "NONE",
+        AutoVacuumMode::Full => "FULL",
+        AutoVacuumMode::Incremental => "INCREMENTAL",
+    };
+    // auto_vacuum must precede every table; on an existing database it is a
+    // no-op. A negative cache_size is interpreted by SQLite as KiB rather than pages.
+    format!(
+        "\
+PRAGMA auto_vacuum  = {auto_vacuum};
 PRAGMA journal_mode = WAL;
-PRAGMA synchronous  = NORMAL;
-PRAGMA mmap_size    = 4194304;
-PRAGMA cache_size   = -1000;
+PRAGMA synchronous  = {synchronous};
+PRAGMA mmap_size    = {mmap_size};
+PRAGMA cache_size   = -{cache_size_kb};
 PRAGMA temp_store   = MEMORY;
-";
+"
+    )
+}
 
 #[cfg(test)]
 mod tests {
@@ -73,20 +214,50 @@ mod tests {
     #[test]
     fn ddl_executes_on_in_memory_db() {
         let conn = Connection::open_in_memory().unwrap();
-        conn.execute_batch(PRAGMAS).unwrap();
+        conn.execute_batch(&pragmas(&TelemetryConfig::default()))
+            .unwrap();
         conn.execute_batch(ACTION_EVENTS_DDL).unwrap();
         conn.execute_batch(SYSTEM_SAMPLES_DDL).unwrap();
         conn.execute_batch(TOOL_EMBEDDINGS_CACHE_DDL).unwrap();
+        conn.execute_batch(NETWORK_EVENTS_DDL).unwrap();
+        conn.execute_batch(DNS_QUERIES_DDL).unwrap();
+        conn.execute_batch(SESSION_TAGS_DDL).unwrap();
+        conn.execute_batch(INDEXES_DDL).unwrap();
     }
 
     #[test]
     fn ddl_is_idempotent() {
         let conn = Connection::open_in_memory().unwrap();
-        conn.execute_batch(PRAGMAS).unwrap();
+        conn.execute_batch(&pragmas(&TelemetryConfig::default()))
+            .unwrap();
         // Execute twice to verify IF NOT EXISTS
         conn.execute_batch(ACTION_EVENTS_DDL).unwrap();
         conn.execute_batch(ACTION_EVENTS_DDL).unwrap();
         conn.execute_batch(SYSTEM_SAMPLES_DDL).unwrap();
         conn.execute_batch(SYSTEM_SAMPLES_DDL).unwrap();
+        conn.execute_batch(NETWORK_EVENTS_DDL).unwrap();
+        conn.execute_batch(NETWORK_EVENTS_DDL).unwrap();
+    }
+
+    #[test]
+    fn pragmas_use_configured_sizes() {
+        let conn = Connection::open_in_memory().unwrap();
+        let config = TelemetryConfig {
+            cache_size_kb: Some(64_000),
+            ..TelemetryConfig::default()
+        };
+        conn.execute_batch(&pragmas(&config)).unwrap();
+        let cache_size: i64 = conn
+            .query_row("PRAGMA cache_size", [], |r| r.get(0))
+            .unwrap();
+        assert_eq!(cache_size, -64_000);
+    }
+
+    #[test]
+    fn default_pragmas_match_previous_values() {
+        let sql = pragmas(&TelemetryConfig::default());
+        assert!(sql.contains("mmap_size    = 4194304;"));
+        assert!(sql.contains("cache_size   = -1000;"));
+        assert!(sql.contains("synchronous  = NORMAL;"));
     }
 }
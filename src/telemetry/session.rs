@@ -0,0 +1,39 @@
+/// Prefix shared by every generated session ID.
+pub const SESSION_ID_PREFIX: &str = "sess-";
+
+/// Generate a random session ID: `sess-` followed by a UUID v4.
+pub fn new_session_id() -> String {
+    format!("{SESSION_ID_PREFIX}{}", uuid::Uuid::new_v4())
+}
+
+/// Generate a session ID that sorts by creation time: `sess-` followed by
+/// a ULID.
+pub fn new_session_id_ulid() -> String {
+    format!("{SESSION_ID_PREFIX}{}", ulid::Ulid::new())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn uuid_session_ids_are_unique_and_prefixed() {
+        let ids: HashSet<String> = (0..10_000).map(|_| new_session_id()).collect();
+        assert_eq!(ids.len(), 10_000);
+        for id in &ids {
+            let uuid = id.strip_prefix(SESSION_ID_PREFIX).unwrap();
+            assert_eq!(uuid::Uuid::parse_str(uuid).unwrap().get_version_num(), 4);
+        }
+    }
+
+    #[test]
+    fn ulid_session_ids_are_unique_and_prefixed() {
+        let ids: HashSet<String> = (0..10_000).map(|_| new_session_id_ulid()).collect();
+        assert_eq!(ids.len(), 10_000);
+        for id in &ids {
+            let ulid = id.strip_prefix(SESSION_ID_PREFIX).unwrap();
+            assert!(ulid.parse::<ulid::Ulid>().is_ok(), "invalid ULID in {id}");
+        }
+    }
+}
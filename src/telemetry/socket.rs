@@ -0,0 +1,252 @@
+use crate::telemetry::store::{
+    ActionRecord, DnsQuery, NetworkEvent, SystemSample, TelemetrySqliteStore,
+};
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::os::unix::fs::FileTypeExt;
+use std::path::Path;
+use std::sync::Arc;
+use tokio::io::{AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{UnixListener, UnixStream};
+use tokio::task::JoinHandle;
+
+/// Frames larger than this are rejected and the connection dropped.
+const MAX_FRAME_BYTES: usize = 16 * 1024 * 1024;
+
+/// Version byte leading every frame. Bump it whenever [`SocketMessage`]
+/// changes shape; a listener drops clients speaking another version.
+pub const SOCKET_PROTOCOL_VERSION: u8 = 1;
+
+/// What a client can send to [`serve_unix_socket`].
+///
+/// Kept apart from the store's internal write queue so the writer can change
+/// without breaking clients built against an older release.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum SocketMessage {
+    ActionEvent(Box<ActionRecord>),
+    SystemSample(SystemSample),
+    SessionTags {
+        session_id: String,
+        tags: Vec<String>,
+    },
+    NetworkEvent(NetworkEvent),
+    DnsQuery(DnsQuery),
+}
+
+/// Accept telemetry from other processes on the Unix socket at `path`.
+///
+/// Each frame is a `u32` little-endian length followed by
+/// [`SOCKET_PROTOCOL_VERSION`] and a bincode-encoded [`SocketMessage`],
+/// which is forwarded to `store`. A stale socket at `path` is replaced; any
+/// other kind of file there is left alone and the listener does not start.
+pub fn serve_unix_socket(store: Arc<TelemetrySqliteStore>, path: &Path) -> JoinHandle<()> {
+    let path = path.to_path_buf();
+    tokio::spawn(async move {
+        match std::fs::symlink_metadata(&path) {
+            Ok(meta) if meta.file_type().is_socket() => {
+                let _ = std::fs::remove_file(&path);
+            }
+            Ok(_) => {
+                tracing::error!(
+                    "telemetry socket: {} exists and is not a socket",
+                    path.display()
+                );
+                return;
+            }
+            Err(_) => {}
+        }
+        let listener = match UnixListener::bind(&path) {
+            Ok(listener) => listener,
+            Err(e) => {
+                tracing::error!("telemetry socket: bind {} failed: {e}", path.display());
+                return;
+            }
+        };
+        tracing::info!("telemetry socket listening on {}", path.display());
+        loop {
+            let stream = match listener.accept().await {
+                Ok((stream, _)) => stream,
+                Err(e) => {
+                    tracing::warn!("telemetry socket: accept failed: {e}");
+                    continue;
+                }
+            };
+            let store = store.clone();
+            tokio::spawn(async move {
+                if let Err(e) = forward_frames(&store, stream).await {
+                    tracing::warn!("telemetry socket: client dropped: {e:#}");
+                }
+            });
+        }
+    })
+}
+
+async fn forward_frames(store: &TelemetrySqliteStore, stream: UnixStream) -> Result<()> {
+    let config = bincode::config::standard();
+    let mut stream = BufReader::new(stream);
+    let mut buf = Vec::new();
+    loop {
+        let mut len_bytes = [0u8; 4];
+        match stream.read_exact(&mut len_bytes).await {
+            Ok(_) => {}
+            Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => return Ok(()),
+            Err(e) => return Err(e.into()),
+        }
+        let len = u32::from_le_bytes(len_bytes) as usize;
+        if len > MAX_FRAME_BYTES {
+            bail!("frame of {len} bytes exceeds {MAX_FRAME_BYTES}");
+        }
+        buf.resize(len, 0);
+        stream
+            .read_exact(&mut buf)
+            .await
+            .context("truncated frame")?;
+        let Some((&version, payload)) = buf.split_first() else {
+            bail!("empty telemetry frame");
+        };
+        if version != SOCKET_PROTOCOL_VERSION {
+            bail!("unsupported telemetry protocol version {version}");
+        }
+        let (message, _) = bincode::serde::decode_from_slice::<SocketMessage, _>(payload, config)
+            .context("decoding telemetry frame")?;
+        match message {
+            SocketMessage::ActionEvent(record) => store.submit_action(*record),
+            SocketMessage::SystemSample(sample) => store.submit_system_sample(sample),
+            SocketMessage::SessionTags { session_id, tags } => {
+                store.submit_session_tags(&session_id, tags);
+            }
+            SocketMessage::NetworkEvent(event) => store.submit_network_event(event),
+            SocketMessage::DnsQuery(query) => store.submit_dns_query(query),
+        }
+    }
+}
+
+/// Send `messages` to a [`serve_unix_socket`] listener at `path`.
+pub async fn send_to_unix_socket(path: &Path, messages: &[SocketMessage]) -> Result<()> {
+    let config = bincode::config::standard();
+    let mut stream = UnixStream::connect(path)
+        .await
+        .with_context(|| format!("connecting to telemetry socket {}", path.display()))?;
+    let mut frame = Vec::new();
+    for message in messages {
+        frame.clear();
+        frame.push(SOCKET_PROTOCOL_VERSION);
+        bincode::serde::encode_into_std_write(message, &mut frame, config)?;
+        let len = u32::try_from(frame.len()).context("telemetry frame too large")?;
+        stream.write_all(&len.to_le_bytes()).await?;
+        stream.write_all(&frame).await?;
+    }
+    stream.shutdown().await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::TelemetryConfig;
+    use crate::telemetry::testing;
+    use std::time::Duration;
+    use tempfile::TempDir;
+
+    const CHILD_SOCKET_ENV: &str = "ZEROCLAW_TEST_TELEMETRY_SOCKET";
+    const CHILD_SESSION_ENV: &str = "ZEROCLAW_TEST_TELEMETRY_SESSION";
+
+    /// Client half of `sub_agent_processes_share_store`; a no-op unless
+    /// that test re-runs this binary with the socket path in the env.
+    #[tokio::test]
+    async fn socket_client_process() {
+        let (Ok(path), Ok(session_id)) = (
+            std::env::var(CHILD_SOCKET_ENV),
+            std::env::var(CHILD_SESSION_ENV),
+        ) else {
+            return;
+        };
+        let messages = [
+            SocketMessage::ActionEvent(Box::new(testing::action(
+                &session_id,
+                &format!("{session_id}-t0"),
+                0,
+                "tool_call",
+            ))),
+            SocketMessage::SystemSample(SystemSample {
+                cpu_usage_pct: 12.5,
+                process_count: 1,
+                ..testing::sample(1)
+            }),
+        ];
+        send_to_unix_socket(Path::new(&path), &messages)
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn sub_agent_processes_share_store() {
+        let tmp = TempDir::new().unwrap();
+        let store =
+            Arc::new(TelemetrySqliteStore::open(tmp.path(), TelemetryConfig::default()).unwrap());
+        let socket_path = tmp.path().join("telemetry.sock");
+        let server = serve_unix_socket(store.clone(), &socket_path);
+        while !socket_path.exists() {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+
+        let exe = std::env::current_exe().unwrap();
+        let children: Vec<_> = ["agent-a", "agent-b"]
+            .into_iter()
+            .map(|session_id| {
+                tokio::process::Command::new(&exe)
+                    .args(["--exact", "telemetry::socket::tests::socket_client_process"])
+                    .env(CHILD_SOCKET_ENV, &socket_path)
+                    .env(CHILD_SESSION_ENV, session_id)
+                    .stdout(std::process::Stdio::null())
+                    .status()
+            })
+            .collect();
+        for status in futures_util::future::join_all(children).await {
+            assert!(status.unwrap().success());
+        }
+
+        // Each connection is forwarded on its own task; wait for both.
+        let conn = rusqlite::Connection::open(tmp.path().join("research.db")).unwrap();
+        let count = |table: &str| -> i64 {
+            conn.query_row(&format!("SELECT COUNT(*) FROM {table}"), [], |row| {
+                row.get(0)
+            })
+            .unwrap()
+        };
+        tokio::time::timeout(Duration::from_secs(5), async {
+            loop {
+                store.flush().unwrap();
+                if count("action_events") == 2 && count("system_samples") == 2 {
+                    break;
+                }
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .expect("frames forwarded within timeout");
+        server.abort();
+        let _ = server.await;
+
+        let sessions: Vec<String> = conn
+            .prepare("SELECT session_id FROM action_events ORDER BY session_id")
+            .unwrap()
+            .query_map([], |row| row.get(0))
+            .unwrap()
+            .collect::<rusqlite::Result<_>>()
+            .unwrap();
+        assert_eq!(sessions, ["agent-a", "agent-b"]);
+    }
+
+    #[tokio::test]
+    async fn refuses_to_replace_a_regular_file() {
+        let tmp = TempDir::new().unwrap();
+        let store =
+            Arc::new(TelemetrySqliteStore::open(tmp.path(), TelemetryConfig::default()).unwrap());
+        let path = tmp.path().join("telemetry.sock");
+        std::fs::write(&path, "not a socket").unwrap();
+
+        serve_unix_socket(store, &path).await.unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "not a socket");
+    }
+}
@@ -0,0 +1,150 @@
+use crate::telemetry::reader::{ActionEventRow, TelemetryReader};
+use anyhow::{Context, Result};
+use axum::body::Bytes;
+use futures_util::stream::{self, Stream};
+use parking_lot::Mutex;
+use std::collections::VecDeque;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::time::Instant;
+
+/// How often the database is polled for rows past the cursor.
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+/// Idle connections get a comment line this often so proxies keep them open.
+const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(15);
+/// Rows fetched per poll.
+const POLL_PAGE: usize = 500;
+
+struct SseCursor {
+    reader: Arc<Mutex<TelemetryReader>>,
+    since_epoch_ms: Option<i64>,
+    last_id: i64,
+    pending: VecDeque<Bytes>,
+    next_heartbeat: Instant,
+    failed: bool,
+}
+
+/// Stream action events as server-sent events, starting with those
+/// recorded at or after `since_epoch_ms` (all of them when `None`).
+///
+/// Each event is framed as `id: <ts_epoch_ms>\ndata: <json>\n\n`. New rows
+/// are picked up by polling every 500 ms with a cursor on the row id, and a
+/// `: heartbeat` comment is sent every 15 s. The reader sits behind a mutex
+/// because SQLite connections cannot be shared between threads; queries run
+/// on the blocking pool. The stream ends after yielding its first error.
+pub fn sse_handler(
+    reader: Arc<Mutex<TelemetryReader>>,
+    since_epoch_ms: Option<i64>,
+) -> impl Stream<Item = Result<Bytes>> + Send + 'static {
+    let cursor = SseCursor {
+        reader,
+        since_epoch_ms,
+        last_id: 0,
+        pending: VecDeque::new(),
+        next_heartbeat: Instant::now() + HEARTBEAT_INTERVAL,
+        failed: false,
+    };
+    stream::unfold(cursor, |mut cursor| async move {
+        if cursor.failed {
+            return None;
+        }
+        loop {
+            if let Some(frame) = cursor.pending.pop_front() {
+                return Some((Ok(frame), cursor));
+            }
+            if Instant::now() >= cursor.next_heartbeat {
+                cursor.next_heartbeat = Instant::now() + HEARTBEAT_INTERVAL;
+                return Some((Ok(Bytes::from_static(b": heartbeat\n\n")), cursor));
+            }
+            match poll_rows(&cursor).await {
+                Ok(rows) if rows.is_empty() => {
+                    tokio::time::sleep_until(
+                        cursor.next_heartbeat.min(Instant::now() + POLL_INTERVAL),
+                    )
+                    .await;
+                }
+                Ok(rows) => {
+                    cursor.last_id = rows.last().map_or(cursor.last_id, |row| row.id);
+                    for row in &rows {
+                        match event_frame(row) {
+                            Ok(frame) => cursor.pending.push_back(frame),
+                            Err(e) => {
+                                cursor.failed = true;
+                                return Some((Err(e), cursor));
+                            }
+                        }
+                    }
+                }
+                Err(e) => {
+                    cursor.failed = true;
+                    return Some((Err(e), cursor));
+                }
+            }
+        }
+    })
+}
+
+async fn poll_rows(cursor: &SseCursor) -> Result<Vec<ActionEventRow>> {
+    let reader = cursor.reader.clone();
+    let last_id = cursor.last_id;
+    let since_epoch_ms = cursor.since_epoch_ms;
+    tokio::task::spawn_blocking(move || {
+        reader
+            .lock()
+            .action_events_after(last_id, since_epoch_ms, POLL_PAGE)
+    })
+    .await
+    .context("SSE poll task panicked")?
+}
+
+fn event_frame(row: &ActionEventRow) -> Result<Bytes> {
+    let json = serde_json::to_string(row)?;
+    Ok(Bytes::from(format!(
+        "id: {}\ndata: {json}\n\n",
+        row.ts_epoch_ms
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::TelemetryConfig;
+    use crate::telemetry::store::{ActionRecord, TelemetrySqliteStore};
+    use crate::telemetry::testing;
+    use futures_util::StreamExt;
+    use tempfile::TempDir;
+
+    fn record(ts_epoch_ms: i64, event_type: &str) -> ActionRecord {
+        testing::action("s1", "s1-t0", ts_epoch_ms, event_type)
+    }
+
+    async fn next_frame(events: &mut (impl Stream<Item = Result<Bytes>> + Unpin)) -> String {
+        let frame = tokio::time::timeout(Duration::from_secs(5), events.next())
+            .await
+            .expect("frame within timeout")
+            .unwrap()
+            .unwrap();
+        String::from_utf8(frame.to_vec()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn streams_existing_then_new_rows() {
+        let tmp = TempDir::new().unwrap();
+        let store = TelemetrySqliteStore::open(tmp.path(), TelemetryConfig::default()).unwrap();
+        store.submit_action(record(1_000, "session_start"));
+        store.submit_action(record(2_000, "llm_request"));
+        store.flush().unwrap();
+
+        let reader = TelemetryReader::open(&tmp.path().join("research.db")).unwrap();
+        let mut events = Box::pin(sse_handler(Arc::new(Mutex::new(reader)), Some(1_500)));
+
+        let first = next_frame(&mut events).await;
+        assert!(first.starts_with("id: 2000\ndata: {"), "{first}");
+        assert!(first.ends_with("}\n\n"));
+        assert!(first.contains("\"event_type\":\"llm_request\""));
+
+        store.submit_action(record(3_000, "llm_response"));
+        let second = next_frame(&mut events).await;
+        assert!(second.starts_with("id: 3000\n"), "{second}");
+    }
+}
@@ -1,13 +1,31 @@
+use crate::config::{ChannelKind, OverflowStrategy, TelemetryConfig};
+use crate::telemetry::backup;
+use crate::telemetry::bus::TelemetryBus;
+use crate::telemetry::embeddings::{compress_embedding, embedding_to_le_bytes};
+use crate::telemetry::growable::{self, GrowableReceiver, GrowableSender};
+use crate::telemetry::pool::ActionRecordPool;
+use crate::telemetry::reader::ActionEventRow;
 use crate::telemetry::schema;
+use crate::telemetry::wal::{self, WriteAheadLog};
+use crate::telemetry::writers::ParallelWriters;
 use anyhow::{Context, Result};
+use parking_lot::{Mutex, RwLock};
 use rusqlite::Connection;
+use std::borrow::Cow;
 use std::path::{Path, PathBuf};
-use std::sync::mpsc::{self, SyncSender, TrySendError};
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::mpsc::{self, Receiver, SendError, Sender, SyncSender, TrySendError};
+use std::sync::Arc;
 use std::thread;
-use std::time::Duration;
+use std::time::{Duration, Instant};
+use tokio::sync::broadcast;
 
 /// A single action event record ready for insertion.
-#[derive(Debug, Clone)]
+///
+/// Fields missing when deserializing from a self-describing format (e.g.
+/// JSON exported by an older build) take their default values.
+#[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
 pub struct ActionRecord {
     pub ts: String,
     pub ts_epoch_ms: i64,
@@ -29,10 +47,159 @@ pub struct ActionRecord {
     pub previous_action_type: Option<String>,
     pub turn_action_sequence: Option<String>,
     pub error_message: Option<String>,
+    pub correlation_id: Option<String>,
+    /// Row id of the action that spawned this one (e.g. the tool call that
+    /// started a sub-agent turn).
+    pub parent_action_id: Option<i64>,
+    /// Cost of an LLM call estimated from its token counts.
+    pub estimated_cost_usd: Option<f64>,
+    /// Event-specific details as a JSON object (e.g. the operation of a
+    /// `file_op` event).
+    pub metadata_json: Option<String>,
+    /// Nesting level of the agent that recorded the event; 0 for the
+    /// top-level agent, 1 for a sub-agent it called, and so on.
+    pub call_depth: u32,
+}
+
+/// Borrowed view of an [`ActionRecord`].
+///
+/// Callers that already hold the string fields (session id, provider, model,
+/// tool name) can describe a record without cloning them. Fields are copied
+/// only once the record is enqueued for the writer thread, into a pooled
+/// [`ActionRecord`] whose buffers are reused where possible (see
+/// [`TelemetrySqliteStore::submit_action_ref`]). Records that are never
+/// enqueued — e.g. after the store has shut down — cost no string
+/// allocations.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct ActionRecordRef<'a> {
+    pub ts: Cow<'a, str>,
+    pub ts_epoch_ms: i64,
+    pub session_id: Cow<'a, str>,
+    pub turn_id: Cow<'a, str>,
+    pub sequence_index: i64,
+    pub event_type: Cow<'a, str>,
+    pub provider: Option<Cow<'a, str>>,
+    pub model: Option<Cow<'a, str>>,
+    pub tool_name: Option<Cow<'a, str>>,
+    pub tool_type_embedding: Option<Cow<'a, [u8]>>,
+    pub arguments_hash: Option<Cow<'a, str>>,
+    pub tool_success: Option<bool>,
+    pub duration_ms: Option<i64>,
+    pub tokens_in: Option<i64>,
+    pub tokens_out: Option<i64>,
+    pub is_user_initiated: bool,
+    pub iteration_index: i64,
+    pub previous_action_type: Option<Cow<'a, str>>,
+    pub turn_action_sequence: Option<Cow<'a, str>>,
+    pub error_message: Option<Cow<'a, str>>,
+    pub correlation_id: Option<Cow<'a, str>>,
+    pub parent_action_id: Option<i64>,
+    pub estimated_cost_usd: Option<f64>,
+    pub metadata_json: Option<Cow<'a, str>>,
+    pub call_depth: u32,
+}
+
+impl ActionRecordRef<'_> {
+    /// Copy any borrowed fields into an owned [`ActionRecord`].
+    pub fn into_owned(self) -> ActionRecord {
+        ActionRecord {
+            ts: self.ts.into_owned(),
+            ts_epoch_ms: self.ts_epoch_ms,
+            session_id: self.session_id.into_owned(),
+            turn_id: self.turn_id.into_owned(),
+            sequence_index: self.sequence_index,
+            event_type: self.event_type.into_owned(),
+            provider: self.provider.map(Cow::into_owned),
+            model: self.model.map(Cow::into_owned),
+            tool_name: self.tool_name.map(Cow::into_owned),
+            tool_type_embedding: self.tool_type_embedding.map(Cow::into_owned),
+            arguments_hash: self.arguments_hash.map(Cow::into_owned),
+            tool_success: self.tool_success,
+            duration_ms: self.duration_ms,
+            tokens_in: self.tokens_in,
+            tokens_out: self.tokens_out,
+            is_user_initiated: self.is_user_initiated,
+            iteration_index: self.iteration_index,
+            previous_action_type: self.previous_action_type.map(Cow::into_owned),
+            turn_action_sequence: self.turn_action_sequence.map(Cow::into_owned),
+            error_message: self.error_message.map(Cow::into_owned),
+            correlation_id: self.correlation_id.map(Cow::into_owned),
+            parent_action_id: self.parent_action_id,
+            estimated_cost_usd: self.estimated_cost_usd,
+            metadata_json: self.metadata_json.map(Cow::into_owned),
+            call_depth: self.call_depth,
+        }
+    }
+
+    /// Overwrite every field of `record` with this view, reusing the
+    /// allocations of its non-optional strings.
+    pub(crate) fn write_into(self, record: &mut ActionRecord) {
+        fn assign(dst: &mut String, src: Cow<'_, str>) {
+            dst.clear();
+            dst.push_str(&src);
+        }
+        assign(&mut record.ts, self.ts);
+        record.ts_epoch_ms = self.ts_epoch_ms;
+        assign(&mut record.session_id, self.session_id);
+        assign(&mut record.turn_id, self.turn_id);
+        record.sequence_index = self.sequence_index;
+        assign(&mut record.event_type, self.event_type);
+        record.provider = self.provider.map(Cow::into_owned);
+        record.model = self.model.map(Cow::into_owned);
+        record.tool_name = self.tool_name.map(Cow::into_owned);
+        record.tool_type_embedding = self.tool_type_embedding.map(Cow::into_owned);
+        record.arguments_hash = self.arguments_hash.map(Cow::into_owned);
+        record.tool_success = self.tool_success;
+        record.duration_ms = self.duration_ms;
+        record.tokens_in = self.tokens_in;
+        record.tokens_out = self.tokens_out;
+        record.is_user_initiated = self.is_user_initiated;
+        record.iteration_index = self.iteration_index;
+        record.previous_action_type = self.previous_action_type.map(Cow::into_owned);
+        record.turn_action_sequence = self.turn_action_sequence.map(Cow::into_owned);
+        record.error_message = self.error_message.map(Cow::into_owned);
+        record.correlation_id = self.correlation_id.map(Cow::into_owned);
+        record.parent_action_id = self.parent_action_id;
+        record.estimated_cost_usd = self.estimated_cost_usd;
+        record.metadata_json = self.metadata_json.map(Cow::into_owned);
+        record.call_depth = self.call_depth;
+    }
+}
+
+impl<'a> From<&'a ActionRecord> for ActionRecordRef<'a> {
+    fn from(record: &'a ActionRecord) -> Self {
+        Self {
+            ts: Cow::Borrowed(&record.ts),
+            ts_epoch_ms: record.ts_epoch_ms,
+            session_id: Cow::Borrowed(&record.session_id),
+            turn_id: Cow::Borrowed(&record.turn_id),
+            sequence_index: record.sequence_index,
+            event_type: Cow::Borrowed(&record.event_type),
+            provider: record.provider.as_deref().map(Cow::Borrowed),
+            model: record.model.as_deref().map(Cow::Borrowed),
+            tool_name: record.tool_name.as_deref().map(Cow::Borrowed),
+            tool_type_embedding: record.tool_type_embedding.as_deref().map(Cow::Borrowed),
+            arguments_hash: record.arguments_hash.as_deref().map(Cow::Borrowed),
+            tool_success: record.tool_success,
+            duration_ms: record.duration_ms,
+            tokens_in: record.tokens_in,
+            tokens_out: record.tokens_out,
+            is_user_initiated: record.is_user_initiated,
+            iteration_index: record.iteration_index,
+            previous_action_type: record.previous_action_type.as_deref().map(Cow::Borrowed),
+            turn_action_sequence: record.turn_action_sequence.as_deref().map(Cow::Borrowed),
+            error_message: record.error_message.as_deref().map(Cow::Borrowed),
+            correlation_id: record.correlation_id.as_deref().map(Cow::Borrowed),
+            parent_action_id: record.parent_action_id,
+            estimated_cost_usd: record.estimated_cost_usd,
+            metadata_json: record.metadata_json.as_deref().map(Cow::Borrowed),
+            call_depth: record.call_depth,
+        }
+    }
 }
 
 /// A single system metrics sample ready for insertion.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct SystemSample {
     pub ts: String,
     pub ts_epoch_ms: i64,
@@ -45,26 +212,343 @@ pub struct SystemSample {
     pub file_write_bytes: i64,
     pub net_connections: i64,
     pub dest_ip_entropy: f64,
+    pub tcp_state_json: Option<String>,
     pub syscall_freq_json: Option<String>,
 }
 
+/// An outbound TCP connection attempt made by the agent process.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct NetworkEvent {
+    pub ts: String,
+    pub ts_epoch_ms: i64,
+    pub pid: u32,
+    pub dest_ip: String,
+    pub dest_port: u16,
+    /// Whether the kernel sent the SYN; not whether the peer accepted it.
+    pub success: bool,
+}
+
+/// A hostname lookup made by the agent process through `getaddrinfo`.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct DnsQuery {
+    pub ts: String,
+    pub ts_epoch_ms: i64,
+    pub hostname: String,
+    pub resolution_ms: f64,
+    pub success: bool,
+}
+
 /// Operations the writer thread can perform.
+#[derive(serde::Serialize, serde::Deserialize)]
 pub enum WriteOp {
     ActionEvent(Box<ActionRecord>),
     SystemSample(SystemSample),
+    SessionTags {
+        session_id: String,
+        tags: Vec<String>,
+    },
+    NetworkEvent(NetworkEvent),
+    DnsQuery(DnsQuery),
+    /// Mark the session's action events deleted as of `deleted_at`.
+    SoftDelete {
+        session_id: String,
+        deleted_at: String,
+        /// Receives the number of rows marked once committed, or the error
+        /// that kept them from being marked. Not carried through the
+        /// write-ahead log.
+        #[serde(skip)]
+        done: Option<SyncSender<Result<usize>>>,
+    },
+    /// Barrier: `done` is signalled once every op queued before it has been
+    /// committed. Not carried through the write-ahead log.
+    Flush {
+        #[serde(skip)]
+        done: Option<SyncSender<()>>,
+    },
     Shutdown,
+    /// Count one call of `tool_name` in `tool_success_rates`. Action events
+    /// carrying a tool outcome do this implicitly.
+    UpdateToolStats {
+        tool_name: String,
+        success: bool,
+    },
+    /// Insert or replace `tool_name`'s row in `tool_embeddings_cache`.
+    CacheToolEmbedding {
+        tool_name: String,
+        embedding: Vec<u8>,
+        dimensions: usize,
+        computed_at: String,
+        /// `embedding` holds unit-length little-endian `f32`s.
+        normalized: bool,
+    },
+}
+
+/// Writer health reported by [`TelemetrySqliteStore::health_check`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HealthStatus {
+    /// The writer thread is still running.
+    pub writer_alive: bool,
+    /// Queued writes over channel capacity, for the fuller of the action and
+    /// sample channels. Auto-grow channels are measured against their
+    /// maximum; unbounded ones against `buffer_capacity`, so can exceed 1.
+    pub channel_fill_ratio: f64,
+    /// A `SELECT 1` succeeded on a fresh read-only connection.
+    pub db_reachable: bool,
+    /// When the writer last committed a batch; `None` before the first.
+    pub last_flush_ts: Option<Instant>,
+    /// Dropped action events per minute, averaged since the store opened.
+    pub drop_rate_per_minute: f64,
+}
+
+/// Which write failed in a [`TelemetryError`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TelemetryErrorKind {
+    ActionInsert,
+    SystemSampleInsert,
+}
+
+/// A failed write, passed to the callback registered with
+/// [`TelemetrySqliteStore::on_error`].
+#[derive(Debug)]
+pub struct TelemetryError {
+    pub kind: TelemetryErrorKind,
+    /// The action that was not stored, for `ActionInsert` failures.
+    pub record: Option<Box<ActionRecord>>,
+    pub message: String,
+}
+
+type ErrorCallback = Box<dyn Fn(TelemetryError) + Send>;
+
+/// The store's error callback, shared with every thread that commits
+/// batches. Empty until [`TelemetrySqliteStore::on_error`] is called.
+#[derive(Clone, Default)]
+pub(crate) struct ErrorHook(Arc<Mutex<Option<ErrorCallback>>>);
+
+impl ErrorHook {
+    /// Pass the error built by `error` to the callback, if one is set.
+    fn report(&self, error: impl FnOnce() -> TelemetryError) {
+        if let Some(callback) = self.0.lock().as_ref() {
+            callback(error());
+        }
+    }
+}
+
+#[derive(Clone)]
+enum SenderKind {
+    Bounded(crossbeam_channel::Sender<WriteOp>),
+    Unbounded(Sender<WriteOp>),
+    AutoGrow(GrowableSender<WriteOp>),
+}
+
+/// Sending half of a writer channel; see [`ChannelKind`].
+#[derive(Clone)]
+struct WriteSender {
+    tx: SenderKind,
+    /// Ops sent but not yet received, shared with the [`WriteReceiver`].
+    depth: Arc<AtomicUsize>,
+    /// Depth at which the channel counts as full: `buffer_capacity`, or
+    /// `max` for auto-grow channels. Unbounded channels use
+    /// `buffer_capacity` as a nominal size.
+    capacity: usize,
+}
+
+// Errors hand the op back to the caller, as std's senders do.
+#[allow(clippy::result_large_err)]
+impl WriteSender {
+    fn channel(kind: ChannelKind, capacity: usize) -> (Self, WriteReceiver) {
+        let (tx, rx, capacity) = match kind {
+            ChannelKind::Bounded => {
+                let (tx, rx) = crossbeam_channel::bounded(capacity);
+                (SenderKind::Bounded(tx), ReceiverKind::Bounded(rx), capacity)
+            }
+            ChannelKind::Unbounded => {
+                let (tx, rx) = mpsc::channel();
+                (SenderKind::Unbounded(tx), ReceiverKind::Std(rx), capacity)
+            }
+            ChannelKind::AutoGrow { initial, max } => {
+                let (tx, rx) = growable::channel(initial, max);
+                (SenderKind::AutoGrow(tx), ReceiverKind::AutoGrow(rx), max)
+            }
+        };
+        let depth = Arc::new(AtomicUsize::new(0));
+        (
+            Self {
+                tx,
+                depth: depth.clone(),
+                capacity,
+            },
+            WriteReceiver { rx, depth },
+        )
+    }
+
+    /// Never blocks; an unbounded channel is never full, and an auto-grow
+    /// one only once it reaches its maximum.
+    fn try_send(&self, op: WriteOp) -> Result<(), TrySendError<WriteOp>> {
+        // Counted before sending so the receiver never sees a negative depth.
+        self.depth.fetch_add(1, Ordering::Relaxed);
+        let sent = match &self.tx {
+            SenderKind::Bounded(tx) => tx.try_send(op).map_err(|e| match e {
+                crossbeam_channel::TrySendError::Full(op) => TrySendError::Full(op),
+                crossbeam_channel::TrySendError::Disconnected(op) => TrySendError::Disconnected(op),
+            }),
+            SenderKind::Unbounded(tx) => tx
+                .send(op)
+                .map_err(|SendError(op)| TrySendError::Disconnected(op)),
+            SenderKind::AutoGrow(tx) => tx.try_send(op),
+        };
+        if sent.is_err() {
+            self.depth.fetch_sub(1, Ordering::Relaxed);
+        }
+        sent
+    }
+
+    /// Blocks while a bounded channel is full, for at most `timeout`;
+    /// `Full` means the wait timed out.
+    fn send_timeout(&self, op: WriteOp, timeout: Duration) -> Result<(), TrySendError<WriteOp>> {
+        self.depth.fetch_add(1, Ordering::Relaxed);
+        let sent = match &self.tx {
+            SenderKind::Bounded(tx) => tx.send_timeout(op, timeout).map_err(|e| match e {
+                crossbeam_channel::SendTimeoutError::Timeout(op) => TrySendError::Full(op),
+                crossbeam_channel::SendTimeoutError::Disconnected(op) => {
+                    TrySendError::Disconnected(op)
+                }
+            }),
+            SenderKind::Unbounded(tx) => tx
+                .send(op)
+                .map_err(|SendError(op)| TrySendError::Disconnected(op)),
+            SenderKind::AutoGrow(tx) => tx.send_timeout(op, timeout),
+        };
+        if sent.is_err() {
+            self.depth.fetch_sub(1, Ordering::Relaxed);
+        }
+        sent
+    }
+
+    /// Blocks while a bounded channel is full.
+    fn send(&self, op: WriteOp) -> Result<(), SendError<WriteOp>> {
+        self.depth.fetch_add(1, Ordering::Relaxed);
+        let sent = match &self.tx {
+            SenderKind::Bounded(tx) => tx
+                .send(op)
+                .map_err(|crossbeam_channel::SendError(op)| SendError(op)),
+            SenderKind::Unbounded(tx) => tx.send(op),
+            SenderKind::AutoGrow(tx) => tx.send(op),
+        };
+        if sent.is_err() {
+            self.depth.fetch_sub(1, Ordering::Relaxed);
+        }
+        sent
+    }
+
+    /// Queued ops as a fraction of `capacity`.
+    fn fill_ratio(&self) -> f64 {
+        self.depth.load(Ordering::Relaxed) as f64 / self.capacity.max(1) as f64
+    }
+}
+
+enum ReceiverKind {
+    Bounded(crossbeam_channel::Receiver<WriteOp>),
+    Std(Receiver<WriteOp>),
+    AutoGrow(GrowableReceiver<WriteOp>),
+}
+
+/// Receiving half of a writer channel.
+struct WriteReceiver {
+    rx: ReceiverKind,
+    depth: Arc<AtomicUsize>,
+}
+
+impl From<Receiver<WriteOp>> for WriteReceiver {
+    fn from(rx: Receiver<WriteOp>) -> Self {
+        Self {
+            rx: ReceiverKind::Std(rx),
+            depth: Arc::default(),
+        }
+    }
+}
+
+impl WriteReceiver {
+    fn recv_timeout(&self, timeout: Duration) -> Result<WriteOp, mpsc::RecvTimeoutError> {
+        let op = match &self.rx {
+            ReceiverKind::Bounded(rx) => rx.recv_timeout(timeout).map_err(|e| match e {
+                crossbeam_channel::RecvTimeoutError::Timeout => mpsc::RecvTimeoutError::Timeout,
+                crossbeam_channel::RecvTimeoutError::Disconnected => {
+                    mpsc::RecvTimeoutError::Disconnected
+                }
+            }),
+            ReceiverKind::Std(rx) => rx.recv_timeout(timeout),
+            ReceiverKind::AutoGrow(rx) => rx.recv_timeout(timeout),
+        }?;
+        self.received();
+        Ok(op)
+    }
+
+    fn try_recv(&self) -> Result<WriteOp, mpsc::TryRecvError> {
+        let op = match &self.rx {
+            ReceiverKind::Bounded(rx) => rx.try_recv().map_err(|e| match e {
+                crossbeam_channel::TryRecvError::Empty => mpsc::TryRecvError::Empty,
+                crossbeam_channel::TryRecvError::Disconnected => mpsc::TryRecvError::Disconnected,
+            }),
+            ReceiverKind::Std(rx) => rx.try_recv(),
+            ReceiverKind::AutoGrow(rx) => rx.try_recv(),
+        }?;
+        self.received();
+        Ok(op)
+    }
+
+    /// Ops sent straight through a `std` sender (as in tests) were never
+    /// counted, so the depth saturates at zero.
+    fn received(&self) {
+        let _ = self
+            .depth
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |d| d.checked_sub(1));
+    }
+
+    /// Ops already queued, without blocking.
+    fn try_iter(&self) -> impl Iterator<Item = WriteOp> + '_ {
+        std::iter::from_fn(|| self.try_recv().ok())
+    }
 }
 
 /// Persistent telemetry store backed by a dedicated SQLite writer thread.
+///
+/// Action events and system samples travel on separate channels so that a
+/// burst of high-frequency samples cannot crowd out action records.
 pub struct TelemetrySqliteStore {
-    sender: Option<SyncSender<WriteOp>>,
+    sender: Option<WriteSender>,
+    sample_sender: Option<WriteSender>,
     join_handle: Option<thread::JoinHandle<()>>,
+    /// Writes admitted past a full channel, waiting in order for the
+    /// overflow thread to deliver them.
+    overflow: Option<mpsc::Sender<(WriteSender, WriteOp)>>,
+    overflow_handle: Option<thread::JoinHandle<()>>,
     db_path: PathBuf,
+    config: SharedConfig,
+    compactor: Option<thread::JoinHandle<()>>,
+    compactor_stop: Arc<AtomicBool>,
+    wal_pending_bytes: Arc<AtomicU64>,
+    record_pool: ActionRecordPool,
+    /// Fan-out of committed action events, as records and as rows.
+    bus: TelemetryBus,
+    dropped_actions: Arc<AtomicU64>,
+    writer_restarts: Arc<AtomicU64>,
+    errors: ErrorHook,
+    net_high_watermark: AtomicU64,
+    net_surges: AtomicU64,
+    net_surge_active: AtomicBool,
+    entropy_alerts: AtomicU64,
+    spawn_burst_alerts: AtomicU64,
+    opened_at: Instant,
+    /// When the writer last handed a batch to its sink.
+    last_flush: Arc<Mutex<Option<Instant>>>,
 }
 
 impl TelemetrySqliteStore {
     /// Open (or create) the telemetry database at `db_dir/research.db`.
-    pub fn open(db_dir: &Path, buffer_capacity: usize) -> Result<Self> {
+    /// `config` must pass [`TelemetryConfig::validate`]; it stays
+    /// replaceable through [`Self::update_config`].
+    pub fn open(db_dir: &Path, config: TelemetryConfig) -> Result<Self> {
+        config.validate()?;
         std::fs::create_dir_all(db_dir)
             .with_context(|| format!("creating telemetry dir: {}", db_dir.display()))?;
 
@@ -72,46 +556,533 @@ impl TelemetrySqliteStore {
         let conn = Connection::open(&db_path)
             .with_context(|| format!("opening telemetry db: {}", db_path.display()))?;
 
-        conn.execute_batch(schema::PRAGMAS)
+        conn.execute_batch(&schema::pragmas(&config))
             .context("telemetry PRAGMA setup")?;
-        conn.execute_batch(schema::ACTION_EVENTS_DDL)
-            .context("action_events DDL")?;
-        conn.execute_batch(schema::SYSTEM_SAMPLES_DDL)
-            .context("system_samples DDL")?;
-        conn.execute_batch(schema::TOOL_EMBEDDINGS_CACHE_DDL)
-            .context("tool_embeddings_cache DDL")?;
+        init_tables(&conn)?;
+        if config.index_strategy.create_before_insert {
+            init_indexes(&conn)?;
+        }
+        if let Some(ttl) = config.embedding_cache_ttl_secs {
+            let evicted = evict_stale_embeddings(&conn, ttl)?;
+            if evicted > 0 {
+                tracing::debug!("evicted {evicted} stale tool embeddings");
+            }
+        }
+
+        let bus = TelemetryBus::default();
+        let record_pool = ActionRecordPool::new(config.buffer_capacity);
+        let compactor_stop = Arc::new(AtomicBool::new(false));
+        let wal_pending_bytes = Arc::new(AtomicU64::new(0));
+        let errors = ErrorHook::default();
+        let mut compactor = None;
+        let sink_kind = match config.write_ahead_dir.as_deref() {
+            Some(dir) => SinkKind::WriteAhead(PathBuf::from(shellexpand::tilde(dir).as_ref())),
+            None if config.num_writer_threads > 1 => SinkKind::Parallel(config.num_writer_threads),
+            None => SinkKind::Sqlite,
+        };
+        let sink = sink_kind.open(
+            &db_path,
+            &config,
+            &wal_pending_bytes,
+            &record_pool,
+            &bus,
+            &errors,
+        )?;
+        if let SinkKind::WriteAhead(dir) = &sink_kind {
+            let dir = dir.clone();
+            let pending = wal_pending_bytes.clone();
+            let stop = compactor_stop.clone();
+            let live = bus.clone();
+            let errors = errors.clone();
+            let use_savepoints = config.use_savepoints;
+            compactor = Some(
+                thread::Builder::new()
+                    .name("telemetry-compactor".into())
+                    .spawn(move || {
+                        wal::run_compactor(
+                            conn,
+                            dir,
+                            pending,
+                            stop,
+                            &live,
+                            &errors,
+                            use_savepoints,
+                        );
+                    })
+                    .context("spawning telemetry compactor thread")?,
+            );
+        }
 
-        let (tx, rx) = mpsc::sync_channel::<WriteOp>(buffer_capacity);
+        let (tx, rx) = WriteSender::channel(config.channel_kind, config.buffer_capacity);
+        let (sample_tx, sample_rx) =
+            WriteSender::channel(config.channel_kind, config.buffer_capacity);
+        let writer_pool = record_pool.clone();
+        let writer_live = bus.clone();
+        let writer_errors = errors.clone();
+        let config: SharedConfig = Arc::new(RwLock::new(Arc::new(config)));
+        let writer_config = config.clone();
+        let writer_restarts = Arc::new(AtomicU64::new(0));
+        let last_flush = Arc::new(Mutex::new(None));
+        let writer_last_flush = last_flush.clone();
+        let restart = WriterRestart {
+            kind: sink_kind,
+            db_path: db_path.clone(),
+            wal_pending_bytes: wal_pending_bytes.clone(),
+            restarts: writer_restarts.clone(),
+        };
 
         let handle = thread::Builder::new()
             .name("telemetry-writer".into())
-            .spawn(move || writer_loop(conn, rx))
+            .spawn(move || {
+                run_writer(
+                    sink,
+                    &restart,
+                    &rx,
+                    &sample_rx,
+                    &writer_pool,
+                    &writer_live,
+                    &writer_errors,
+                    &writer_config,
+                    &writer_last_flush,
+                );
+            })
             .context("spawning telemetry writer thread")?;
 
+        let dropped_actions = Arc::new(AtomicU64::new(0));
+        let (overflow, overflow_rx) = mpsc::channel::<(WriteSender, WriteOp)>();
+        let overflow_dropped = dropped_actions.clone();
+        let overflow_handle = thread::Builder::new()
+            .name("telemetry-overflow".into())
+            .spawn(move || {
+                for (sender, op) in overflow_rx {
+                    if let Err(SendError(op)) = sender.send(op) {
+                        count_drop(&overflow_dropped, &op);
+                    }
+                }
+            })
+            .context("spawning telemetry overflow thread")?;
+
         Ok(Self {
             sender: Some(tx),
+            sample_sender: Some(sample_tx),
             join_handle: Some(handle),
+            overflow: Some(overflow),
+            overflow_handle: Some(overflow_handle),
             db_path: db_path.clone(),
+            config,
+            compactor,
+            compactor_stop,
+            wal_pending_bytes,
+            record_pool,
+            bus,
+            dropped_actions,
+            writer_restarts,
+            errors,
+            net_high_watermark: AtomicU64::new(0),
+            net_surges: AtomicU64::new(0),
+            net_surge_active: AtomicBool::new(false),
+            entropy_alerts: AtomicU64::new(0),
+            spawn_burst_alerts: AtomicU64::new(0),
+            opened_at: Instant::now(),
+            last_flush,
         })
     }
 
-    /// Non-blocking submit of an action event. Drops with a warning if the
-    /// channel is full.
+    /// Move `record` into a pooled box, allocating only when the pool is
+    /// exhausted. The pooled record's string buffers are freed, since
+    /// `record` brings its own.
+    fn boxed(&self, record: ActionRecord) -> Box<ActionRecord> {
+        let mut boxed = self.record_pool.acquire();
+        *boxed = record;
+        boxed
+    }
+
+    /// Non-blocking submit of an action event. When the channel is full the
+    /// configured [`OverflowStrategy`] decides whether it is dropped.
+    ///
+    /// Only the pooled box is reused; [`Self::submit_action_ref`] also reuses
+    /// its string buffers.
     pub fn submit_action(&self, record: ActionRecord) {
-        if let Some(ref sender) = self.sender {
-            if let Err(TrySendError::Full(_)) = sender.try_send(WriteOp::ActionEvent(Box::new(record))) {
-                tracing::warn!("telemetry action channel full — dropping record");
+        self.submit(
+            self.sender.as_ref(),
+            WriteOp::ActionEvent(self.boxed(record)),
+            "action record",
+        );
+    }
+
+    /// Non-blocking submit of a borrowed action event. The view is copied
+    /// into a pooled record, and only if the store is still accepting
+    /// records.
+    pub fn submit_action_ref(&self, record: ActionRecordRef<'_>) {
+        if self.sender.is_none() {
+            return;
+        }
+        let mut boxed = self.record_pool.acquire();
+        record.write_into(&mut boxed);
+        self.submit(
+            self.sender.as_ref(),
+            WriteOp::ActionEvent(boxed),
+            "action record",
+        );
+    }
+
+    /// Submit an action event, waiting up to `timeout` for channel space.
+    ///
+    /// For records that must not be silently dropped (e.g. session-end
+    /// summaries). On timeout, or if the store has shut down, the record is
+    /// handed back so the caller can retry, log, or alert.
+    #[allow(clippy::result_large_err)]
+    pub fn submit_action_blocking(
+        &self,
+        record: ActionRecord,
+        timeout: Duration,
+    ) -> Result<(), ActionRecord> {
+        let Some(ref sender) = self.sender else {
+            return Err(record);
+        };
+        match sender.send_timeout(WriteOp::ActionEvent(self.boxed(record)), timeout) {
+            Ok(()) => Ok(()),
+            Err(TrySendError::Full(op) | TrySendError::Disconnected(op)) => {
+                let WriteOp::ActionEvent(record) = op else {
+                    unreachable!("only action events are submitted here");
+                };
+                Err(*record)
             }
         }
     }
 
+    /// Non-blocking submit of tags for `session_id`. Tags the session
+    /// already has are kept once.
+    pub fn submit_session_tags(&self, session_id: &str, tags: Vec<String>) {
+        self.submit(
+            self.sender.as_ref(),
+            WriteOp::SessionTags {
+                session_id: session_id.to_string(),
+                tags,
+            },
+            "session tags",
+        );
+    }
+
+    /// Non-blocking submit of a single tag for `session_id`, e.g. a tenant
+    /// or experiment label. Re-tagging keeps the original `added_at`.
+    pub fn tag_session(&self, session_id: &str, tag: &str) {
+        self.submit_session_tags(session_id, vec![tag.to_string()]);
+    }
+
+    /// Non-blocking submit of a tool outcome for `tool_success_rates`,
+    /// for calls that are not also recorded as action events.
+    pub fn submit_tool_stats(&self, tool_name: &str, success: bool) {
+        self.submit(
+            self.sender.as_ref(),
+            WriteOp::UpdateToolStats {
+                tool_name: tool_name.to_string(),
+                success,
+            },
+            "tool stats",
+        );
+    }
+
+    /// Non-blocking submit of a `tool_embeddings_cache` entry.
+    pub fn submit_tool_embedding(&self, tool_name: &str, embedding: Vec<u8>, dimensions: usize) {
+        self.submit_cached_embedding(tool_name, embedding, dimensions, false);
+    }
+
+    /// Non-blocking submit of a unit-length `tool_embeddings_cache` entry,
+    /// stored as little-endian `f32`s and marked `normalized`.
+    pub fn submit_normalized_tool_embedding(&self, tool_name: &str, embedding: &[f32]) {
+        self.submit_cached_embedding(
+            tool_name,
+            embedding_to_le_bytes(embedding),
+            embedding.len(),
+            true,
+        );
+    }
+
+    fn submit_cached_embedding(
+        &self,
+        tool_name: &str,
+        embedding: Vec<u8>,
+        dimensions: usize,
+        normalized: bool,
+    ) {
+        self.submit(
+            self.sender.as_ref(),
+            WriteOp::CacheToolEmbedding {
+                tool_name: tool_name.to_string(),
+                embedding,
+                dimensions,
+                computed_at: chrono::Utc::now().to_rfc3339(),
+                normalized,
+            },
+            "tool embedding",
+        );
+    }
+
     /// Non-blocking submit of a system sample.
     pub fn submit_system_sample(&self, sample: SystemSample) {
-        if let Some(ref sender) = self.sender {
-            if let Err(TrySendError::Full(_)) = sender.try_send(WriteOp::SystemSample(sample)) {
-                tracing::warn!("telemetry system channel full — dropping sample");
+        self.submit(
+            self.sample_sender.as_ref(),
+            WriteOp::SystemSample(sample),
+            "system sample",
+        );
+    }
+
+    /// Non-blocking submit of a network event. Shares the sample channel:
+    /// like samples, connection events are high-volume and may be dropped
+    /// under pressure.
+    pub fn submit_network_event(&self, event: NetworkEvent) {
+        self.submit(
+            self.sample_sender.as_ref(),
+            WriteOp::NetworkEvent(event),
+            "network event",
+        );
+    }
+
+    /// Non-blocking submit of a DNS lookup, on the sample channel like
+    /// network events.
+    pub fn submit_dns_query(&self, query: DnsQuery) {
+        self.submit(
+            self.sample_sender.as_ref(),
+            WriteOp::DnsQuery(query),
+            "DNS query",
+        );
+    }
+
+    /// Mark every action event of `session_id` as deleted without removing
+    /// it; [`TelemetryReader`](crate::telemetry::reader::TelemetryReader)
+    /// stops returning those rows. Returns the number of rows marked.
+    ///
+    /// Waits for the writer thread to commit the change, and fails if the
+    /// update or its commit fails or the writer stops first. With a
+    /// write-ahead log configured the change is applied when the log is
+    /// imported, and 0 is returned once it has been logged.
+    pub fn delete_actions_in_session(&self, session_id: &str) -> Result<usize> {
+        let sender = self
+            .sender
+            .as_ref()
+            .context("telemetry store is shut down")?;
+        let (done_tx, done_rx) = mpsc::sync_channel(1);
+        sender
+            .send(WriteOp::SoftDelete {
+                session_id: session_id.to_string(),
+                deleted_at: chrono::Utc::now().to_rfc3339(),
+                done: Some(done_tx),
+            })
+            .map_err(|_| anyhow::anyhow!("telemetry writer thread has stopped"))?;
+        done_rx
+            .recv()
+            .map_err(|_| anyhow::anyhow!("telemetry writer stopped before committing the delete"))?
+    }
+
+    /// Block until every record submitted before this call has been
+    /// committed, or logged when a write-ahead log is configured.
+    ///
+    /// Fails if the store is shut down, or if the writer stops or its
+    /// commit fails first.
+    pub fn flush(&self) -> Result<()> {
+        let overflow = self
+            .overflow
+            .as_ref()
+            .context("telemetry store is shut down")?;
+        let mut pending = Vec::with_capacity(2);
+        for sender in [&self.sender, &self.sample_sender] {
+            let sender = sender.as_ref().context("telemetry store is shut down")?;
+            let (done_tx, done_rx) = mpsc::sync_channel(1);
+            // Queued behind any overflowing writes still waiting for space.
+            overflow
+                .send((
+                    sender.clone(),
+                    WriteOp::Flush {
+                        done: Some(done_tx),
+                    },
+                ))
+                .map_err(|_| anyhow::anyhow!("telemetry overflow thread has stopped"))?;
+            pending.push(done_rx);
+        }
+        for done_rx in pending {
+            done_rx.recv().map_err(|_| {
+                anyhow::anyhow!("telemetry writer stopped before committing the flush")
+            })?;
+        }
+        Ok(())
+    }
+
+    fn submit(&self, sender: Option<&WriteSender>, op: WriteOp, kind: &str) {
+        let Some(sender) = sender else {
+            return;
+        };
+        let op = match sender.try_send(op) {
+            Ok(()) => return,
+            Err(TrySendError::Disconnected(op)) => {
+                count_drop(&self.dropped_actions, &op);
+                return;
             }
+            Err(TrySendError::Full(op)) => op,
+        };
+        let overflow_strategy = self.config.read().overflow_strategy;
+        let admit = match overflow_strategy {
+            OverflowStrategy::Drop => false,
+            OverflowStrategy::Block => true,
+            OverflowStrategy::SampleRandom(rate) => rand::random::<f64>() < rate,
+        };
+        if !admit {
+            tracing::warn!("telemetry channel full — dropping {kind}");
+            count_drop(&self.dropped_actions, &op);
+            return;
+        }
+        // Wait for space off the caller's thread so async callers never block.
+        let Some(overflow) = &self.overflow else {
+            count_drop(&self.dropped_actions, &op);
+            return;
+        };
+        if let Err(SendError((_, op))) = overflow.send((sender.clone(), op)) {
+            count_drop(&self.dropped_actions, &op);
+        }
+    }
+
+    /// Call `callback` from the writing thread whenever an action or system
+    /// sample fails to insert, in addition to the usual `tracing` error.
+    /// Use it for alerting or to dead-letter the failed record. Replaces
+    /// any previously registered callback.
+    pub fn on_error(self, callback: impl Fn(TelemetryError) + Send + 'static) -> Self {
+        *self.errors.0.lock() = Some(Box::new(callback));
+        self
+    }
+
+    /// Snapshot of the writer's state for liveness probes such as a `/health`
+    /// endpoint. Cheap apart from `db_reachable`, which opens a read-only
+    /// connection.
+    pub fn health_check(&self) -> HealthStatus {
+        let fill_ratio =
+            |sender: &Option<WriteSender>| sender.as_ref().map_or(0.0, WriteSender::fill_ratio);
+        let minutes_open = (self.opened_at.elapsed().as_secs_f64() / 60.0).max(1.0 / 60.0);
+        HealthStatus {
+            writer_alive: self
+                .join_handle
+                .as_ref()
+                .is_some_and(|handle| !handle.is_finished()),
+            channel_fill_ratio: fill_ratio(&self.sender).max(fill_ratio(&self.sample_sender)),
+            db_reachable: Connection::open_with_flags(
+                &self.db_path,
+                rusqlite::OpenFlags::SQLITE_OPEN_READ_ONLY,
+            )
+            .and_then(|conn| conn.query_row("SELECT 1", [], |row| row.get::<_, i64>(0)))
+            .is_ok(),
+            last_flush_ts: *self.last_flush.lock(),
+            drop_rate_per_minute: self.dropped_action_count() as f64 / minutes_open,
+        }
+    }
+
+    /// Action events dropped since the store was opened, because the channel
+    /// was full (see [`OverflowStrategy`]) or the writer thread had stopped.
+    pub fn dropped_action_count(&self) -> u64 {
+        self.dropped_actions.load(Ordering::Relaxed)
+    }
+
+    /// Times the writer thread has been restarted after a panic. Always 0
+    /// in builds with `panic = "abort"`, such as the release profile.
+    pub fn writer_restart_count(&self) -> u64 {
+        self.writer_restarts.load(Ordering::Relaxed)
+    }
+
+    /// Most open network connections seen in any collector sample.
+    pub fn net_connection_high_watermark(&self) -> u64 {
+        self.net_high_watermark.load(Ordering::Relaxed)
+    }
+
+    /// Times the collector's connection count rose above
+    /// `net_connection_alert_threshold`. A surge lasting several samples
+    /// counts once.
+    pub fn net_surges_detected(&self) -> u64 {
+        self.net_surges.load(Ordering::Relaxed)
+    }
+
+    /// System samples whose destination IP entropy exceeded
+    /// `max_entropy_threshold`.
+    pub fn entropy_alerts(&self) -> u64 {
+        self.entropy_alerts.load(Ordering::Relaxed)
+    }
+
+    pub(crate) fn count_entropy_alert(&self) {
+        self.entropy_alerts.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// System samples whose process spawn rate exceeded `max_spawn_rate`.
+    pub fn spawn_burst_alerts(&self) -> u64 {
+        self.spawn_burst_alerts.load(Ordering::Relaxed)
+    }
+
+    pub(crate) fn count_spawn_burst_alert(&self) {
+        self.spawn_burst_alerts.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Fold one sample's connection count into the surge counters.
+    pub(crate) fn record_net_connections(&self, net_connections: i64) {
+        let count = u64::try_from(net_connections).unwrap_or(0);
+        self.net_high_watermark.fetch_max(count, Ordering::Relaxed);
+        let Some(threshold) = self.config.read().net_connection_alert_threshold else {
+            return;
+        };
+        let surging = count > threshold;
+        if self.net_surge_active.swap(surging, Ordering::Relaxed) || !surging {
+            return;
         }
+        self.net_surges.fetch_add(1, Ordering::Relaxed);
+        tracing::warn!(
+            "network connection surge: {count} open connections (threshold {threshold})"
+        );
+    }
+
+    /// Replace the configuration the running store reads from.
+    ///
+    /// `overflow_strategy` applies to the next submit, `max_batch_size` and
+    /// `flush_timeout_ms` to the writer's next batch, and
+    /// `system_interval_secs` and `net_connection_alert_threshold` to the
+    /// collector's next sample. Settings fixed when the store was opened
+    /// (channel kind and capacity, write-ahead dir, PRAGMAs, savepoints) keep
+    /// their original values. An invalid configuration is rejected with a
+    /// [`ConfigError`](crate::config::ConfigError) and the current one kept.
+    pub fn update_config(&self, new_config: TelemetryConfig) -> Result<()> {
+        new_config.validate()?;
+        *self.config.write() = Arc::new(new_config);
+        Ok(())
+    }
+
+    /// The configuration currently in effect.
+    pub fn config(&self) -> Arc<TelemetryConfig> {
+        self.config.read().clone()
+    }
+
+    /// Bytes written to the write-ahead log that have not yet been imported
+    /// into SQLite. Always 0 when the write-ahead log is disabled.
+    pub fn wal_pending_bytes(&self) -> u64 {
+        self.wal_pending_bytes.load(Ordering::Relaxed)
+    }
+
+    /// Subscribe to action events as they are committed to SQLite. Each row
+    /// carries its database `id`; with the write-ahead log enabled, rows are
+    /// published when the compactor imports them.
+    pub fn subscribe_live(&self) -> broadcast::Receiver<ActionEventRow> {
+        self.bus.subscribe_rows()
+    }
+
+    /// In-process bus that every action event is broadcast on once
+    /// committed; [`Self::subscribe_live`] is a shortcut to its row feed.
+    pub fn bus(&self) -> &TelemetryBus {
+        &self.bus
+    }
+
+    /// Build any missing indexes, e.g. after a bulk import into a store
+    /// opened with `index_strategy.create_before_insert = false`. The build
+    /// holds the write lock, and a writer batch that waits on it longer
+    /// than the busy timeout fails, so call [`Self::shutdown`] first when
+    /// the import is large.
+    pub fn create_indexes(&self) -> Result<()> {
+        let conn = Connection::open(&self.db_path)
+            .with_context(|| format!("opening telemetry db: {}", self.db_path.display()))?;
+        conn.busy_timeout(WRITER_BUSY_TIMEOUT)?;
+        init_indexes(&conn)
     }
 
     /// Path to the underlying database file.
@@ -119,8 +1090,29 @@ impl TelemetrySqliteStore {
         &self.db_path
     }
 
+    /// Copy the SQLite WAL frames committed after `since` to
+    /// `dest_dir/wal_<since.frame>_<epoch_ms>.bin`, without pausing the
+    /// writer thread. Returns the position of the last frame copied; pass
+    /// it as `since` next time. Fails once the WAL has been restarted by a
+    /// checkpoint. See [`backup`](crate::telemetry::backup).
+    pub fn incremental_backup(
+        &self,
+        dest_dir: &Path,
+        since: backup::WalPosition,
+    ) -> Result<backup::WalPosition> {
+        let mut wal_path = self.db_path.clone().into_os_string();
+        wal_path.push("-wal");
+        backup::copy_wal_frames(Path::new(&wal_path), dest_dir, since)
+    }
+
     /// Graceful shutdown: signal the writer thread and wait for it to finish.
     pub fn shutdown(&mut self) {
+        // Deliver overflowing writes while the writer is still running.
+        drop(self.overflow.take());
+        if let Some(handle) = self.overflow_handle.take() {
+            let _ = handle.join();
+        }
+        drop(self.sample_sender.take());
         if let Some(sender) = self.sender.take() {
             let _ = sender.try_send(WriteOp::Shutdown);
             // Drop the sender so the writer thread sees a disconnect even if
@@ -130,6 +1122,12 @@ impl TelemetrySqliteStore {
         if let Some(handle) = self.join_handle.take() {
             let _ = handle.join();
         }
+        // The writer has closed its last segment; let the compactor import
+        // it before exiting.
+        self.compactor_stop.store(true, Ordering::Release);
+        if let Some(handle) = self.compactor.take() {
+            let _ = handle.join();
+        }
     }
 }
 
@@ -139,79 +1137,541 @@ impl Drop for TelemetrySqliteStore {
     }
 }
 
-/// Writer thread main loop: batches writes in transactions.
-fn writer_loop(conn: Connection, rx: mpsc::Receiver<WriteOp>) {
-    let mut batch: Vec<WriteOp> = Vec::with_capacity(10);
+/// Create every telemetry table and index on `conn`, upgrading tables left
+/// by older builds in place.
+pub(crate) fn init_schema(conn: &Connection) -> Result<()> {
+    init_tables(conn)?;
+    init_indexes(conn)
+}
+
+/// Create or migrate every table and view, without indexes.
+fn init_tables(conn: &Connection) -> Result<()> {
+    conn.execute_batch(schema::ACTION_EVENTS_DDL)
+        .context("action_events DDL")?;
+    conn.execute_batch(schema::SYSTEM_SAMPLES_DDL)
+        .context("system_samples DDL")?;
+    conn.execute_batch(schema::TOOL_EMBEDDINGS_CACHE_DDL)
+        .context("tool_embeddings_cache DDL")?;
+    conn.execute_batch(schema::SESSION_TAGS_DDL)
+        .context("session_tags DDL")?;
+    let had_tool_stats = table_exists(conn, "tool_success_rates")?;
+    conn.execute_batch(schema::TOOL_SUCCESS_RATES_DDL)
+        .context("tool_success_rates DDL")?;
+    if !had_tool_stats {
+        backfill_tool_stats(conn)?;
+    }
+    conn.execute_batch(schema::NETWORK_EVENTS_DDL)
+        .context("network_events DDL")?;
+    conn.execute_batch(schema::DNS_QUERIES_DDL)
+        .context("dns_queries DDL")?;
+    for (table, column, sql_type) in schema::COLUMN_MIGRATIONS {
+        add_column_if_missing(conn, table, column, sql_type)?;
+    }
+    conn.execute_batch(schema::VIEWS_DDL)
+        .context("telemetry views DDL")?;
+    Ok(())
+}
+
+fn table_exists(conn: &Connection, table: &str) -> Result<bool> {
+    let count: i64 = conn.query_row(
+        "SELECT COUNT(*) FROM sqlite_master WHERE type = 'table' AND name = ?1",
+        rusqlite::params![table],
+        |row| row.get(0),
+    )?;
+    Ok(count > 0)
+}
+
+/// Seed a newly created `tool_success_rates` from the events already on
+/// disk, so a database from a build without the table starts with the same
+/// counts the writer would have accumulated.
+fn backfill_tool_stats(conn: &Connection) -> Result<()> {
+    conn.execute(
+        "INSERT INTO tool_success_rates (tool_name, success_count, failure_count, last_updated)
+         SELECT tool_name, SUM(tool_success != 0), SUM(tool_success = 0), ?1
+         FROM action_events
+         WHERE tool_name IS NOT NULL AND tool_success IS NOT NULL
+         GROUP BY tool_name",
+        rusqlite::params![chrono::Utc::now().to_rfc3339()],
+    )
+    .context("backfilling tool_success_rates")?;
+    Ok(())
+}
+
+fn init_indexes(conn: &Connection) -> Result<()> {
+    conn.execute_batch(schema::INDEXES_DDL)
+        .context("telemetry indexes DDL")
+}
 
+/// Add `table.column` when an older database predates it.
+fn add_column_if_missing(
+    conn: &Connection,
+    table: &str,
+    column: &str,
+    sql_type: &str,
+) -> Result<()> {
+    let mut stmt = conn.prepare(&format!("PRAGMA table_info({table})"))?;
+    let mut rows = stmt.query([])?;
+    while let Some(row) = rows.next()? {
+        let name: String = row.get(1)?;
+        if name == column {
+            return Ok(());
+        }
+    }
+    drop(rows);
+    drop(stmt);
+
+    conn.execute(
+        &format!("ALTER TABLE {table} ADD COLUMN {column} {sql_type}"),
+        [],
+    )
+    .with_context(|| format!("adding telemetry column {table}.{column}"))?;
+    Ok(())
+}
+
+/// Configuration shared with the writer thread; swapped by
+/// [`TelemetrySqliteStore::update_config`].
+type SharedConfig = Arc<RwLock<Arc<TelemetryConfig>>>;
+
+/// How long the writer waits on the action channel before checking for
+/// queued system samples.
+const SAMPLE_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// How long a parallel writer waits for another's transaction to commit.
+const WRITER_BUSY_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Where the writer thread commits batches.
+enum BatchSink {
+    /// Insert directly into SQLite.
+    Sqlite {
+        conn: Connection,
+        use_savepoints: bool,
+    },
+    /// Append to the local write-ahead log; the compactor imports it later.
+    WriteAhead(WriteAheadLog),
+    /// Hand batches to parallel writer threads (`num_writer_threads > 1`).
+    Parallel(ParallelWriters),
+    /// Panics on the first batch, to exercise the writer restart.
+    #[cfg(test)]
+    Panic,
+    /// Records the kind of each write in every non-empty batch.
+    #[cfg(test)]
+    Record(Arc<Mutex<Vec<Vec<&'static str>>>>),
+}
+
+impl BatchSink {
+    /// Write `batch` and return its pooled records to `pool`.
+    fn write(
+        &mut self,
+        batch: Vec<WriteOp>,
+        pool: &ActionRecordPool,
+        live: &TelemetryBus,
+        errors: &ErrorHook,
+    ) {
+        match self {
+            Self::Sqlite {
+                conn,
+                use_savepoints,
+            } => {
+                if let Err(e) = flush_batch(conn, &batch, live, errors, *use_savepoints) {
+                    tracing::error!("{e:#}");
+                }
+            }
+            Self::WriteAhead(log) => {
+                let appended = log.append(&batch);
+                if let Err(e) = &appended {
+                    tracing::error!("telemetry WAL append failed: {e}");
+                }
+                for op in &batch {
+                    match op {
+                        WriteOp::SoftDelete {
+                            done: Some(done), ..
+                        } => {
+                            let reply = match &appended {
+                                Ok(()) => Ok(0),
+                                Err(e) => {
+                                    Err(anyhow::anyhow!("telemetry WAL append failed: {e:#}"))
+                                }
+                            };
+                            let _ = done.try_send(reply);
+                        }
+                        WriteOp::Flush { done: Some(done) } if appended.is_ok() => {
+                            let _ = done.try_send(());
+                        }
+                        _ => {}
+                    }
+                }
+            }
+            // The worker that commits the batch releases its records.
+            Self::Parallel(writers) => return writers.submit(batch),
+            #[cfg(test)]
+            Self::Panic => assert!(batch.is_empty(), "injected telemetry writer panic"),
+            #[cfg(test)]
+            Self::Record(batches) => {
+                if !batch.is_empty() {
+                    batches.lock().push(
+                        batch
+                            .iter()
+                            .map(|op| match op {
+                                WriteOp::ActionEvent(_) => "action",
+                                WriteOp::SystemSample(_) => "sample",
+                                _ => "other",
+                            })
+                            .collect(),
+                    );
+                }
+            }
+        }
+        for op in batch {
+            if let WriteOp::ActionEvent(record) = op {
+                pool.release(record);
+            }
+        }
+    }
+
+    fn close(self) {
+        match self {
+            Self::Sqlite { .. } => {}
+            Self::WriteAhead(log) => {
+                if let Err(e) = log.finish() {
+                    tracing::error!("closing telemetry WAL segment failed: {e}");
+                }
+            }
+            Self::Parallel(writers) => writers.close(),
+            #[cfg(test)]
+            Self::Panic | Self::Record(_) => {}
+        }
+    }
+}
+
+/// Which [`BatchSink`] the store writes through, kept so a restarted writer
+/// thread can open a fresh one.
+enum SinkKind {
+    Sqlite,
+    WriteAhead(PathBuf),
+    /// Parallel writers with this many connections.
+    Parallel(usize),
+}
+
+impl SinkKind {
+    fn open(
+        &self,
+        db_path: &Path,
+        config: &TelemetryConfig,
+        wal_pending_bytes: &Arc<AtomicU64>,
+        pool: &ActionRecordPool,
+        live: &TelemetryBus,
+        errors: &ErrorHook,
+    ) -> Result<BatchSink> {
+        let connect = || -> Result<Connection> {
+            let conn = Connection::open(db_path)
+                .with_context(|| format!("opening telemetry db: {}", db_path.display()))?;
+            conn.execute_batch(&schema::pragmas(config))
+                .context("telemetry PRAGMA setup")?;
+            Ok(conn)
+        };
+        Ok(match self {
+            Self::Sqlite => BatchSink::Sqlite {
+                conn: connect()?,
+                use_savepoints: config.use_savepoints,
+            },
+            Self::WriteAhead(dir) => {
+                BatchSink::WriteAhead(WriteAheadLog::open(dir, wal_pending_bytes.clone())?)
+            }
+            Self::Parallel(threads) => {
+                let conns = (0..*threads)
+                    .map(|_| {
+                        let conn = connect()?;
+                        conn.busy_timeout(WRITER_BUSY_TIMEOUT)?;
+                        Ok(conn)
+                    })
+                    .collect::<Result<Vec<_>>>()?;
+                BatchSink::Parallel(ParallelWriters::spawn(
+                    conns,
+                    pool,
+                    live,
+                    errors,
+                    config.use_savepoints,
+                )?)
+            }
+        })
+    }
+}
+
+/// What the writer thread needs to recover from a panic.
+struct WriterRestart {
+    kind: SinkKind,
+    db_path: PathBuf,
+    wal_pending_bytes: Arc<AtomicU64>,
+    restarts: Arc<AtomicU64>,
+}
+
+/// Run [`writer_loop`], restarting it on a freshly opened sink whenever it
+/// panics.
+///
+/// The channels outlive the panic, so submits keep queueing (and are not
+/// dropped) while the writer restarts; only the batch being written when
+/// it panicked is lost. Builds with `panic = "abort"` (the release profile)
+/// never get here: the process exits instead.
+#[allow(clippy::too_many_arguments)]
+fn run_writer(
+    mut sink: BatchSink,
+    restart: &WriterRestart,
+    rx: &WriteReceiver,
+    sample_rx: &WriteReceiver,
+    pool: &ActionRecordPool,
+    live: &TelemetryBus,
+    errors: &ErrorHook,
+    config: &SharedConfig,
+    last_flush: &Mutex<Option<Instant>>,
+) {
     loop {
-        // Block on the first message.
-        match rx.recv() {
-            Ok(WriteOp::Shutdown) | Err(_) => break,
+        let run = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            writer_loop(sink, rx, sample_rx, pool, live, errors, config, last_flush);
+        }));
+        if run.is_ok() {
+            return;
+        }
+        restart.restarts.fetch_add(1, Ordering::Relaxed);
+        tracing::error!("telemetry writer thread panicked; restarting");
+        let current = config.read().clone();
+        sink = match restart.kind.open(
+            &restart.db_path,
+            &current,
+            &restart.wal_pending_bytes,
+            pool,
+            live,
+            errors,
+        ) {
+            Ok(sink) => sink,
+            Err(e) => {
+                tracing::error!("reopening telemetry writer failed, giving up: {e:#}");
+                return;
+            }
+        };
+    }
+}
+
+/// Count `op` toward [`TelemetrySqliteStore::dropped_action_count`] if it
+/// is an action event.
+fn count_drop(dropped: &AtomicU64, op: &WriteOp) {
+    if matches!(op, WriteOp::ActionEvent(_)) {
+        dropped.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+/// Writer thread main loop: batches writes in transactions.
+///
+/// Pending action events always fill a batch before any system sample is
+/// taken; samples only use the space actions leave over.
+#[allow(clippy::too_many_arguments)]
+fn writer_loop(
+    mut sink: BatchSink,
+    rx: &WriteReceiver,
+    sample_rx: &WriteReceiver,
+    pool: &ActionRecordPool,
+    live: &TelemetryBus,
+    errors: &ErrorHook,
+    config: &SharedConfig,
+    last_flush: &Mutex<Option<Instant>>,
+) {
+    let mut batch: Vec<WriteOp> = Vec::new();
+    let mut shutting_down = false;
+
+    while !shutting_down {
+        let max_batch = config.read().max_batch_size.max(1);
+        match rx.recv_timeout(SAMPLE_POLL_INTERVAL) {
+            Ok(WriteOp::Shutdown) | Err(mpsc::RecvTimeoutError::Disconnected) => {
+                shutting_down = true;
+            }
             Ok(op) => batch.push(op),
+            Err(mpsc::RecvTimeoutError::Timeout) => {}
         }
 
-        // Drain up to 9 more without blocking (batch of 10 max).
-        while batch.len() < 10 {
+        // Drain more action events without blocking.
+        while !shutting_down && batch.len() < max_batch {
             match rx.try_recv() {
                 Ok(WriteOp::Shutdown) | Err(mpsc::TryRecvError::Disconnected) => {
-                    flush_batch(&conn, &batch);
-                    return;
+                    shutting_down = true;
                 }
                 Ok(op) => batch.push(op),
                 Err(mpsc::TryRecvError::Empty) => break,
             }
         }
 
-        // If we only got one message, wait briefly for more (up to 1s).
-        if batch.len() == 1 {
-            let deadline = Duration::from_secs(1);
-            match rx.recv_timeout(deadline) {
-                Ok(WriteOp::Shutdown) => {
-                    flush_batch(&conn, &batch);
-                    return;
+        // Top up with system samples; on shutdown take everything left.
+        if shutting_down {
+            batch.extend(sample_rx.try_iter());
+        } else {
+            batch.extend(
+                sample_rx
+                    .try_iter()
+                    .take(max_batch.saturating_sub(batch.len())),
+            );
+        }
+
+        // If we only got one message, wait briefly for more. A flush is
+        // written straight away.
+        if batch.len() == 1 && !shutting_down && !matches!(batch[0], WriteOp::Flush { .. }) {
+            let flush_timeout = Duration::from_millis(config.read().flush_timeout_ms);
+            match rx.recv_timeout(flush_timeout) {
+                Ok(WriteOp::Shutdown) | Err(mpsc::RecvTimeoutError::Disconnected) => {
+                    shutting_down = true;
+                    batch.extend(sample_rx.try_iter());
                 }
                 Ok(op) => batch.push(op),
-                Err(_) => {} // timeout or disconnect — flush what we have
+                Err(mpsc::RecvTimeoutError::Timeout) => {}
             }
         }
 
-        flush_batch(&conn, &batch);
-        batch.clear();
+        // Written even when empty: the WAL sink rotates idle segments here.
+        let flushed = !batch.is_empty();
+        sink.write(std::mem::take(&mut batch), pool, live, errors);
+        if flushed {
+            *last_flush.lock() = Some(Instant::now());
+        }
     }
+    sink.close();
 }
 
-fn flush_batch(conn: &Connection, batch: &[WriteOp]) {
+/// Insert `batch` in one transaction, then publish the committed action
+/// events on `live`.
+pub(crate) fn flush_batch(
+    conn: &Connection,
+    batch: &[WriteOp],
+    live: &TelemetryBus,
+    errors: &ErrorHook,
+    use_savepoints: bool,
+) -> Result<()> {
     if batch.is_empty() {
-        return;
-    }
-    if let Err(e) = conn.execute_batch("BEGIN") {
-        tracing::error!("telemetry BEGIN failed: {e}");
-        return;
+        return Ok(());
     }
-    for op in batch {
+    let publish = live.has_subscribers();
+    let mut committed = Vec::new();
+    let mut deleted = Vec::new();
+    let mut flushed = Vec::new();
+    conn.execute_batch("BEGIN")
+        .context("telemetry BEGIN failed")?;
+    for (i, op) in batch.iter().enumerate() {
+        if use_savepoints {
+            if let Err(e) = conn.execute_batch(&format!("SAVEPOINT sp_{i}")) {
+                tracing::error!("telemetry SAVEPOINT failed: {e}");
+                continue;
+            }
+        }
         let result = match op {
-            WriteOp::ActionEvent(rec) => insert_action(conn, rec.as_ref()),
+            WriteOp::ActionEvent(rec) => insert_action(conn, rec.as_ref()).and_then(|id| {
+                if let (Some(tool_name), Some(success)) = (&rec.tool_name, rec.tool_success) {
+                    update_tool_stats(conn, tool_name, success)?;
+                }
+                if publish {
+                    committed.push((id, rec));
+                }
+                Ok(())
+            }),
             WriteOp::SystemSample(sample) => insert_system_sample(conn, sample),
-            WriteOp::Shutdown => Ok(()),
-        };
+            WriteOp::SessionTags { session_id, tags } => {
+                insert_session_tags(conn, session_id, tags)
+            }
+            WriteOp::NetworkEvent(event) => insert_network_event(conn, event),
+            WriteOp::DnsQuery(query) => insert_dns_query(conn, query),
+            WriteOp::SoftDelete {
+                session_id,
+                deleted_at,
+                done,
+            } => {
+                let result = soft_delete_session(conn, session_id, deleted_at);
+                if let Some(done) = done {
+                    match &result {
+                        Ok(count) => deleted.push((done, *count)),
+                        Err(e) => {
+                            let _ =
+                                done.try_send(Err(anyhow::anyhow!("soft delete failed: {e:#}")));
+                        }
+                    }
+                }
+                result.map(|_| ())
+            }
+            WriteOp::Flush { done } => {
+                flushed.extend(done);
+                Ok(())
+            }
+            WriteOp::UpdateToolStats { tool_name, success } => {
+                update_tool_stats(conn, tool_name, *success)
+            }
+            WriteOp::CacheToolEmbedding {
+                tool_name,
+                embedding,
+                dimensions,
+                computed_at,
+                normalized,
+            } => cache_tool_embedding(
+                conn,
+                tool_name,
+                embedding,
+                *dimensions,
+                computed_at,
+                *normalized,
+            ),
+            WriteOp::Shutdown => Ok(()),
+        };
+        if use_savepoints {
+            let end = if result.is_ok() {
+                format!("RELEASE sp_{i}")
+            } else {
+                format!("ROLLBACK TO sp_{i}; RELEASE sp_{i}")
+            };
+            if let Err(e) = conn.execute_batch(&end) {
+                tracing::error!("telemetry {end} failed: {e}");
+            }
+        }
         if let Err(e) = result {
             tracing::error!("telemetry insert failed: {e}");
+            let kind = match op {
+                WriteOp::ActionEvent(_) => TelemetryErrorKind::ActionInsert,
+                WriteOp::SystemSample(_) => TelemetryErrorKind::SystemSampleInsert,
+                _ => continue,
+            };
+            errors.report(|| TelemetryError {
+                kind,
+                record: match op {
+                    WriteOp::ActionEvent(rec) => Some(rec.clone()),
+                    _ => None,
+                },
+                message: format!("{e:#}"),
+            });
         }
     }
     if let Err(e) = conn.execute_batch("COMMIT") {
-        tracing::error!("telemetry COMMIT failed: {e}");
+        let _ = conn.execute_batch("ROLLBACK");
+        for (done, _) in deleted {
+            let _ = done.try_send(Err(anyhow::anyhow!("telemetry COMMIT failed: {e}")));
+        }
+        return Err(e).context("telemetry COMMIT failed");
     }
+    for (id, record) in committed {
+        live.publish(id, record);
+    }
+    for (done, count) in deleted {
+        let _ = done.try_send(Ok(count));
+    }
+    for done in flushed {
+        let _ = done.try_send(());
+    }
+    Ok(())
 }
 
-fn insert_action(conn: &Connection, r: &ActionRecord) -> Result<()> {
+/// Insert one action event and return its row id.
+fn insert_action(conn: &Connection, r: &ActionRecord) -> Result<i64> {
     conn.execute(
         "INSERT INTO action_events (
             ts, ts_epoch_ms, session_id, turn_id, sequence_index, event_type,
             provider, model, tool_name, tool_type_embedding, arguments_hash,
             tool_success, duration_ms, tokens_in, tokens_out,
             is_user_initiated, iteration_index, previous_action_type,
-            turn_action_sequence, error_message
-        ) VALUES (?1,?2,?3,?4,?5,?6,?7,?8,?9,?10,?11,?12,?13,?14,?15,?16,?17,?18,?19,?20)",
+            turn_action_sequence, error_message, correlation_id, parent_action_id,
+            estimated_cost_usd, metadata_json, call_depth
+        ) VALUES (?1,?2,?3,?4,?5,?6,?7,?8,?9,?10,?11,?12,?13,?14,?15,?16,?17,?18,?19,?20,?21,?22,
+                  ?23,?24,?25)",
         rusqlite::params![
             r.ts,
             r.ts_epoch_ms,
@@ -222,7 +1682,7 @@ fn insert_action(conn: &Connection, r: &ActionRecord) -> Result<()> {
             r.provider,
             r.model,
             r.tool_name,
-            r.tool_type_embedding,
+            r.tool_type_embedding.as_deref().map(compress_embedding),
             r.arguments_hash,
             r.tool_success.map(|b| if b { 1 } else { 0 }),
             r.duration_ms,
@@ -233,8 +1693,24 @@ fn insert_action(conn: &Connection, r: &ActionRecord) -> Result<()> {
             r.previous_action_type,
             r.turn_action_sequence,
             r.error_message,
+            r.correlation_id,
+            r.parent_action_id,
+            r.estimated_cost_usd,
+            r.metadata_json,
+            r.call_depth,
         ],
     )?;
+    Ok(conn.last_insert_rowid())
+}
+
+fn insert_session_tags(conn: &Connection, session_id: &str, tags: &[String]) -> Result<()> {
+    let added_at = chrono::Utc::now().to_rfc3339();
+    let mut stmt = conn.prepare_cached(
+        "INSERT OR IGNORE INTO session_tags (session_id, tag, added_at) VALUES (?1, ?2, ?3)",
+    )?;
+    for tag in tags {
+        stmt.execute(rusqlite::params![session_id, tag, added_at])?;
+    }
     Ok(())
 }
 
@@ -243,8 +1719,8 @@ fn insert_system_sample(conn: &Connection, s: &SystemSample) -> Result<()> {
         "INSERT INTO system_samples (
             ts, ts_epoch_ms, cpu_usage_pct, memory_used_bytes, memory_total_bytes,
             process_count, process_spawn_rate, file_read_bytes, file_write_bytes,
-            net_connections, dest_ip_entropy, syscall_freq_json
-        ) VALUES (?1,?2,?3,?4,?5,?6,?7,?8,?9,?10,?11,?12)",
+            net_connections, dest_ip_entropy, syscall_freq_json, tcp_state_json
+        ) VALUES (?1,?2,?3,?4,?5,?6,?7,?8,?9,?10,?11,?12,?13)",
         rusqlite::params![
             s.ts,
             s.ts_epoch_ms,
@@ -258,48 +1734,132 @@ fn insert_system_sample(conn: &Connection, s: &SystemSample) -> Result<()> {
             s.net_connections,
             s.dest_ip_entropy,
             s.syscall_freq_json,
+            s.tcp_state_json,
         ],
     )?;
     Ok(())
 }
 
+fn insert_network_event(conn: &Connection, e: &NetworkEvent) -> Result<()> {
+    conn.prepare_cached(
+        "INSERT INTO network_events (ts, ts_epoch_ms, pid, dest_ip, dest_port, success)
+         VALUES (?1,?2,?3,?4,?5,?6)",
+    )?
+    .execute(rusqlite::params![
+        e.ts,
+        e.ts_epoch_ms,
+        e.pid,
+        e.dest_ip,
+        e.dest_port,
+        i32::from(e.success),
+    ])?;
+    Ok(())
+}
+
+/// Mark the live action events of `session_id` deleted; returns how many.
+fn soft_delete_session(conn: &Connection, session_id: &str, deleted_at: &str) -> Result<usize> {
+    Ok(conn.execute(
+        "UPDATE action_events SET deleted_at = ?2
+         WHERE session_id = ?1 AND deleted_at IS NULL",
+        rusqlite::params![session_id, deleted_at],
+    )?)
+}
+
+fn update_tool_stats(conn: &Connection, tool_name: &str, success: bool) -> Result<()> {
+    conn.prepare_cached(
+        "INSERT INTO tool_success_rates (tool_name, success_count, failure_count, last_updated)
+         VALUES (?1, ?2, ?3, ?4)
+         ON CONFLICT (tool_name) DO UPDATE SET
+             success_count = success_count + excluded.success_count,
+             failure_count = failure_count + excluded.failure_count,
+             last_updated  = excluded.last_updated",
+    )?
+    .execute(rusqlite::params![
+        tool_name,
+        i64::from(success),
+        i64::from(!success),
+        chrono::Utc::now().to_rfc3339(),
+    ])?;
+    Ok(())
+}
+
+/// Delete `tool_embeddings_cache` rows computed more than `max_age_secs`
+/// ago, returning how many were removed.
+pub(crate) fn evict_stale_embeddings(conn: &Connection, max_age_secs: u64) -> Result<usize> {
+    let evicted = conn
+        .execute(
+            "DELETE FROM tool_embeddings_cache
+             WHERE julianday(computed_at) < julianday('now', ?1)",
+            rusqlite::params![format!("-{max_age_secs} seconds")],
+        )
+        .context("evicting stale tool embeddings")?;
+    Ok(evicted)
+}
+
+fn cache_tool_embedding(
+    conn: &Connection,
+    tool_name: &str,
+    embedding: &[u8],
+    dimensions: usize,
+    computed_at: &str,
+    normalized: bool,
+) -> Result<()> {
+    conn.prepare_cached(
+        "INSERT OR REPLACE INTO tool_embeddings_cache
+             (tool_name, embedding, dimensions, computed_at, normalized)
+         VALUES (?1, ?2, ?3, ?4, ?5)",
+    )?
+    .execute(rusqlite::params![
+        tool_name,
+        embedding,
+        i64::try_from(dimensions).unwrap_or(i64::MAX),
+        computed_at,
+        normalized,
+    ])?;
+    Ok(())
+}
+
+fn insert_dns_query(conn: &Connection, q: &DnsQuery) -> Result<()> {
+    conn.prepare_cached(
+        "INSERT INTO dns_queries (ts, ts_epoch_ms, hostname, resolution_ms, success)
+         VALUES (?1,?2,?3,?4,?5)",
+    )?
+    .execute(rusqlite::params![
+        q.ts,
+        q.ts_epoch_ms,
+        q.hostname,
+        q.resolution_ms,
+        i32::from(q.success),
+    ])?;
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::config::{ConfigError, IndexStrategy, SqliteSynchronous};
+    use crate::telemetry::testing;
     use tempfile::TempDir;
 
     fn make_action_record() -> ActionRecord {
         ActionRecord {
-            ts: "2026-01-01T00:00:00Z".into(),
-            ts_epoch_ms: 1_767_225_600_000,
-            session_id: "sess-1".into(),
-            turn_id: "turn-1".into(),
-            sequence_index: 0,
-            event_type: "llm_response".into(),
             provider: Some("openai".into()),
             model: Some("gpt-4".into()),
-            tool_name: None,
-            tool_type_embedding: None,
-            arguments_hash: None,
-            tool_success: None,
             duration_ms: Some(150),
             tokens_in: Some(100),
             tokens_out: Some(50),
             is_user_initiated: true,
-            iteration_index: 0,
-            previous_action_type: None,
             turn_action_sequence: Some(r#"["llm_response"]"#.into()),
-            error_message: None,
+            ..testing::action("sess-1", "turn-1", 1_767_225_600_000, "llm_response")
         }
     }
 
     #[test]
     fn store_open_and_insert_action() {
         let tmp = TempDir::new().unwrap();
-        let store = TelemetrySqliteStore::open(tmp.path(), 10).unwrap();
+        let store = TelemetrySqliteStore::open(tmp.path(), TelemetryConfig::default()).unwrap();
         store.submit_action(make_action_record());
-        // Give the writer thread time to process.
-        std::thread::sleep(Duration::from_millis(200));
+        // Dropping the store joins the writer thread.
         drop(store);
 
         // Verify the record was inserted.
@@ -310,10 +1870,139 @@ mod tests {
         assert_eq!(count, 1);
     }
 
+    #[test]
+    fn submitted_actions_are_broadcast_on_bus() {
+        let tmp = TempDir::new().unwrap();
+        let store = TelemetrySqliteStore::open(tmp.path(), TelemetryConfig::default()).unwrap();
+        let mut records = store.bus().subscribe();
+        let mut rows = store.subscribe_live();
+        store.submit_action(make_action_record());
+        store
+            .submit_action_blocking(make_action_record(), Duration::from_secs(1))
+            .unwrap();
+        // Events are published once committed; shutting down commits both.
+        drop(store);
+        assert_eq!(records.try_recv().unwrap().session_id, "sess-1");
+        assert!(records.try_recv().is_ok());
+        assert_eq!(rows.try_recv().unwrap().id, 1);
+        assert_eq!(rows.try_recv().unwrap().id, 2);
+    }
+
+    #[test]
+    fn action_record_ref_into_owned_copies_fields() {
+        let tmp = TempDir::new().unwrap();
+        let store = TelemetrySqliteStore::open(tmp.path(), TelemetryConfig::default()).unwrap();
+        let owned = make_action_record();
+        let view = ActionRecordRef {
+            ts: Cow::Borrowed(&owned.ts),
+            ts_epoch_ms: owned.ts_epoch_ms,
+            session_id: Cow::Borrowed(&owned.session_id),
+            turn_id: Cow::Borrowed(&owned.turn_id),
+            sequence_index: owned.sequence_index,
+            event_type: Cow::Borrowed(&owned.event_type),
+            provider: owned.provider.as_deref().map(Cow::Borrowed),
+            model: owned.model.as_deref().map(Cow::Borrowed),
+            tool_name: None,
+            tool_type_embedding: None,
+            arguments_hash: None,
+            tool_success: owned.tool_success,
+            duration_ms: owned.duration_ms,
+            tokens_in: owned.tokens_in,
+            tokens_out: owned.tokens_out,
+            is_user_initiated: owned.is_user_initiated,
+            iteration_index: owned.iteration_index,
+            previous_action_type: None,
+            turn_action_sequence: owned.turn_action_sequence.as_deref().map(Cow::Borrowed),
+            error_message: None,
+            correlation_id: None,
+            parent_action_id: None,
+            estimated_cost_usd: None,
+            metadata_json: None,
+            call_depth: 0,
+        };
+        let copy = view.clone().into_owned();
+        assert_eq!(copy.session_id, owned.session_id);
+        assert_eq!(copy.model, owned.model);
+        assert_eq!(copy.turn_action_sequence, owned.turn_action_sequence);
+
+        store.submit_action_ref(view);
+        drop(store);
+        assert_eq!(count_actions(&tmp), 1);
+    }
+
+    #[test]
+    fn action_record_ref_write_into_reuses_buffers() {
+        let source = ActionRecord {
+            provider: None,
+            ..make_action_record()
+        };
+        let mut target = ActionRecord {
+            session_id: String::with_capacity(64),
+            provider: Some("stale".into()),
+            ..ActionRecord::default()
+        };
+        let buffer = target.session_id.as_ptr();
+
+        ActionRecordRef::from(&source).write_into(&mut target);
+        assert_eq!(target.session_id.as_ptr(), buffer);
+        assert_eq!(target.session_id, source.session_id);
+        assert_eq!(target.ts, source.ts);
+        assert_eq!(target.provider, None);
+        assert_eq!(target.turn_action_sequence, source.turn_action_sequence);
+    }
+
+    #[test]
+    fn inserted_records_return_to_pool() {
+        let tmp = TempDir::new().unwrap();
+        let config = TelemetryConfig {
+            buffer_capacity: 10,
+            ..TelemetryConfig::default()
+        };
+        let store = TelemetrySqliteStore::open(tmp.path(), config).unwrap();
+        let pool = store.record_pool.clone();
+        store.submit_action_ref(ActionRecordRef::from(&make_action_record()));
+        store.submit_action_ref(ActionRecordRef::from(&make_action_record()));
+        // Dropping the store joins the writer, which has released both.
+        drop(store);
+        assert_eq!(count_actions(&tmp), 2);
+        assert_eq!(pool.available(), 10);
+
+        // Released records sit behind the untouched ones and keep their
+        // string buffers.
+        let reused = (0..10)
+            .map(|_| pool.acquire())
+            .filter(|record| record.session_id.capacity() > 0)
+            .count();
+        assert_eq!(reused, 2);
+    }
+
+    #[test]
+    fn store_inserts_with_each_synchronous_level() {
+        for synchronous in [
+            SqliteSynchronous::Off,
+            SqliteSynchronous::Normal,
+            SqliteSynchronous::Full,
+        ] {
+            let tmp = TempDir::new().unwrap();
+            let config = TelemetryConfig {
+                buffer_capacity: 1000,
+                synchronous,
+                ..TelemetryConfig::default()
+            };
+            let mut store = TelemetrySqliteStore::open(tmp.path(), config).unwrap();
+            for _ in 0..1000 {
+                store.submit_action(make_action_record());
+            }
+            store.shutdown();
+
+            assert_eq!(count_actions(&tmp), 1000, "synchronous = {synchronous:?}");
+        }
+    }
+
     #[test]
     fn store_open_and_insert_system_sample() {
         let tmp = TempDir::new().unwrap();
-        let store = TelemetrySqliteStore::open(tmp.path(), 10).unwrap();
+        let store = TelemetrySqliteStore::open(tmp.path(), TelemetryConfig::default()).unwrap();
         store.submit_system_sample(SystemSample {
             ts: "2026-01-01T00:00:01Z".into(),
             ts_epoch_ms: 1_767_225_601_000,
@@ -326,9 +2015,9 @@ mod tests {
             file_write_bytes: 2048,
             net_connections: 15,
             dest_ip_entropy: 2.3,
+            tcp_state_json: Some(r#"{"ESTABLISHED":12,"TIME_WAIT":3}"#.into()),
             syscall_freq_json: None,
         });
-        std::thread::sleep(Duration::from_millis(200));
         drop(store);
 
         let conn = Connection::open(tmp.path().join("research.db")).unwrap();
@@ -338,12 +2027,686 @@ mod tests {
         assert_eq!(count, 1);
     }
 
+    #[test]
+    fn store_open_migrates_legacy_system_samples() {
+        let tmp = TempDir::new().unwrap();
+        {
+            let conn = Connection::open(tmp.path().join("research.db")).unwrap();
+            conn.execute_batch(
+                "CREATE TABLE system_samples (
+                    id INTEGER PRIMARY KEY AUTOINCREMENT,
+                    ts TEXT NOT NULL,
+                    ts_epoch_ms INTEGER NOT NULL,
+                    cpu_usage_pct REAL NOT NULL,
+                    memory_used_bytes INTEGER NOT NULL,
+                    memory_total_bytes INTEGER NOT NULL,
+                    process_count INTEGER NOT NULL,
+                    process_spawn_rate INTEGER NOT NULL,
+                    file_read_bytes INTEGER NOT NULL,
+                    file_write_bytes INTEGER NOT NULL,
+                    net_connections INTEGER NOT NULL,
+                    dest_ip_entropy REAL NOT NULL,
+                    syscall_freq_json TEXT
+                );",
+            )
+            .unwrap();
+        }
+
+        let store = TelemetrySqliteStore::open(tmp.path(), TelemetryConfig::default()).unwrap();
+        drop(store);
+
+        let conn = Connection::open(tmp.path().join("research.db")).unwrap();
+        let has_column: bool = conn
+            .prepare(
+                "SELECT 1 FROM pragma_table_info('system_samples')
+                 WHERE name = 'tcp_state_json'",
+            )
+            .unwrap()
+            .exists([])
+            .unwrap();
+        assert!(has_column);
+    }
+
+    fn count_actions(tmp: &TempDir) -> i64 {
+        let conn = Connection::open(tmp.path().join("research.db")).unwrap();
+        conn.query_row("SELECT COUNT(*) FROM action_events", [], |r| r.get(0))
+            .unwrap()
+    }
+
+    #[test]
+    fn delete_actions_in_session_marks_rows_deleted() {
+        let tmp = TempDir::new().unwrap();
+        let store = TelemetrySqliteStore::open(tmp.path(), TelemetryConfig::default()).unwrap();
+        for session_id in ["sess-1", "sess-1", "sess-2"] {
+            store.submit_action(ActionRecord {
+                session_id: session_id.into(),
+                ..make_action_record()
+            });
+        }
+        assert_eq!(store.delete_actions_in_session("sess-1").unwrap(), 2);
+        assert_eq!(store.delete_actions_in_session("sess-1").unwrap(), 0);
+        drop(store);
+
+        // Rows are kept, only marked.
+        assert_eq!(count_actions(&tmp), 3);
+        let conn = Connection::open(tmp.path().join("research.db")).unwrap();
+        let live: i64 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM action_events WHERE deleted_at IS NULL",
+                [],
+                |r| r.get(0),
+            )
+            .unwrap();
+        assert_eq!(live, 1);
+    }
+
+    #[test]
+    fn delete_actions_in_session_reports_failed_update() {
+        let tmp = TempDir::new().unwrap();
+        let store = TelemetrySqliteStore::open(tmp.path(), TelemetryConfig::default()).unwrap();
+        store.submit_action(make_action_record());
+        let conn = Connection::open(tmp.path().join("research.db")).unwrap();
+        conn.execute_batch(
+            "CREATE TRIGGER block_delete BEFORE UPDATE ON action_events
+             BEGIN SELECT RAISE(ABORT, 'blocked'); END;",
+        )
+        .unwrap();
+        drop(conn);
+
+        let err = store.delete_actions_in_session("sess-1").unwrap_err();
+        assert!(format!("{err:#}").contains("blocked"), "{err:#}");
+    }
+
+    #[test]
+    fn flush_waits_for_queued_records() {
+        for num_writer_threads in [1, 4] {
+            let tmp = TempDir::new().unwrap();
+            let config = TelemetryConfig {
+                num_writer_threads,
+                max_batch_size: 5,
+                ..TelemetryConfig::default()
+            };
+            let store = TelemetrySqliteStore::open(tmp.path(), config).unwrap();
+            for _ in 0..50 {
+                store.submit_action(make_action_record());
+            }
+            store.submit_system_sample(testing::sample(0));
+            store.flush().unwrap();
+
+            // Visible while the store is still open.
+            let conn = Connection::open(tmp.path().join("research.db")).unwrap();
+            let samples: i64 = conn
+                .query_row("SELECT COUNT(*) FROM system_samples", [], |r| r.get(0))
+                .unwrap();
+            assert_eq!(count_actions(&tmp), 50);
+            assert_eq!(samples, 1);
+        }
+    }
+
+    fn open_with_overflow(tmp: &TempDir, strategy: OverflowStrategy) -> TelemetrySqliteStore {
+        let config = TelemetryConfig {
+            buffer_capacity: 10,
+            overflow_strategy: strategy,
+            ..TelemetryConfig::default()
+        };
+        TelemetrySqliteStore::open(tmp.path(), config).unwrap()
+    }
+
+    #[test]
+    fn health_check_reports_writer_state() {
+        let tmp = TempDir::new().unwrap();
+        let config = TelemetryConfig {
+            flush_timeout_ms: 10,
+            ..TelemetryConfig::default()
+        };
+        let store = TelemetrySqliteStore::open(tmp.path(), config).unwrap();
+        let idle = store.health_check();
+        assert!(idle.writer_alive && idle.db_reachable);
+        assert_eq!(idle.last_flush_ts, None);
+        assert_eq!(idle.drop_rate_per_minute, 0.0);
+
+        store.submit_action(make_action_record());
+        store.flush().unwrap();
+        let health = store.health_check();
+        assert!(health.last_flush_ts.is_some());
+        assert_eq!(health.channel_fill_ratio, 0.0);
+
+        std::fs::remove_file(tmp.path().join("research.db")).unwrap();
+        assert!(!store.health_check().db_reachable);
+    }
+
+    #[test]
+    fn write_sender_tracks_queue_depth() {
+        let (tx, rx) = WriteSender::channel(ChannelKind::Bounded, 10);
+        for _ in 0..5 {
+            tx.try_send(WriteOp::Shutdown).unwrap();
+        }
+        assert_eq!(tx.fill_ratio(), 0.5);
+        rx.try_recv().unwrap();
+        rx.recv_timeout(Duration::from_millis(10)).unwrap();
+        assert_eq!(tx.fill_ratio(), 0.3);
+        drop(rx);
+        assert!(tx.try_send(WriteOp::Shutdown).is_err());
+        assert_eq!(tx.fill_ratio(), 0.3);
+    }
+
+    #[test]
+    fn auto_grow_channel_absorbs_bursts_without_drops() {
+        let tmp = TempDir::new().unwrap();
+        let config = TelemetryConfig {
+            channel_kind: ChannelKind::AutoGrow {
+                initial: 4,
+                max: 4096,
+            },
+            overflow_strategy: OverflowStrategy::Drop,
+            ..TelemetryConfig::default()
+        };
+        let store = TelemetrySqliteStore::open(tmp.path(), config).unwrap();
+        for _ in 0..1_000 {
+            store.submit_action(make_action_record());
+        }
+        assert_eq!(store.dropped_action_count(), 0);
+        drop(store);
+        assert_eq!(count_actions(&tmp), 1_000);
+    }
+
+    #[test]
+    fn store_block_overflow_keeps_every_record() {
+        let tmp = TempDir::new().unwrap();
+        let store = open_with_overflow(&tmp, OverflowStrategy::Block);
+        for _ in 0..100 {
+            store.submit_action(make_action_record());
+        }
+        drop(store);
+        assert_eq!(count_actions(&tmp), 100);
+    }
+
+    #[test]
+    fn flush_waits_for_overflowing_records() {
+        let tmp = TempDir::new().unwrap();
+        let store = open_with_overflow(&tmp, OverflowStrategy::Block);
+        for _ in 0..1_000 {
+            store.submit_action(make_action_record());
+        }
+        store.flush().unwrap();
+        assert_eq!(count_actions(&tmp), 1_000);
+        assert_eq!(store.dropped_action_count(), 0);
+    }
+
+    #[test]
+    fn unbounded_channel_never_drops() {
+        let tmp = TempDir::new().unwrap();
+        let config = TelemetryConfig {
+            buffer_capacity: 10,
+            overflow_strategy: OverflowStrategy::Drop,
+            channel_kind: ChannelKind::Unbounded,
+            ..TelemetryConfig::default()
+        };
+        let store = TelemetrySqliteStore::open(tmp.path(), config).unwrap();
+        for _ in 0..100 {
+            store.submit_action(make_action_record());
+        }
+        drop(store);
+        assert_eq!(count_actions(&tmp), 100);
+    }
+
+    #[test]
+    fn parallel_writers_commit_every_batch() {
+        let tmp = TempDir::new().unwrap();
+        let config = TelemetryConfig {
+            num_writer_threads: 4,
+            max_batch_size: 5,
+            flush_timeout_ms: 10,
+            channel_kind: ChannelKind::Unbounded,
+            ..TelemetryConfig::default()
+        };
+        let store = TelemetrySqliteStore::open(tmp.path(), config).unwrap();
+        for _ in 0..500 {
+            store.submit_action(make_action_record());
+        }
+        // Waits for the 500 inserts before marking them.
+        assert_eq!(store.delete_actions_in_session("sess-1").unwrap(), 500);
+        for _ in 0..100 {
+            store.submit_action(make_action_record());
+        }
+        drop(store);
+        assert_eq!(count_actions(&tmp), 600);
+    }
+
+    #[test]
+    fn writer_restarts_after_panic() {
+        let tmp = TempDir::new().unwrap();
+        let db_path = tmp.path().join("research.db");
+        init_schema(&Connection::open(&db_path).unwrap()).unwrap();
+        let config = TelemetryConfig {
+            flush_timeout_ms: 10,
+            ..TelemetryConfig::default()
+        };
+        let (tx, rx) = WriteSender::channel(config.channel_kind, config.buffer_capacity);
+        let (sample_tx, sample_rx) =
+            WriteSender::channel(config.channel_kind, config.buffer_capacity);
+        let restarts = Arc::new(AtomicU64::new(0));
+        let restart = WriterRestart {
+            kind: SinkKind::Sqlite,
+            db_path,
+            wal_pending_bytes: Arc::default(),
+            restarts: restarts.clone(),
+        };
+        let config: SharedConfig = Arc::new(RwLock::new(Arc::new(config)));
+        let writer = thread::spawn(move || {
+            run_writer(
+                BatchSink::Panic,
+                &restart,
+                &rx,
+                &sample_rx,
+                &ActionRecordPool::new(16),
+                &TelemetryBus::default(),
+                &ErrorHook::default(),
+                &config,
+                &Mutex::new(None),
+            );
+        });
+
+        let action = || WriteOp::ActionEvent(Box::new(make_action_record()));
+        tx.try_send(action()).unwrap();
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while restarts.load(Ordering::Relaxed) == 0 && Instant::now() < deadline {
+            thread::sleep(Duration::from_millis(5));
+        }
+        assert_eq!(restarts.load(Ordering::Relaxed), 1);
+
+        for _ in 0..10 {
+            tx.try_send(action()).unwrap();
+        }
+        drop((tx, sample_tx));
+        writer.join().unwrap();
+        // Only the batch the panicking sink was handed is lost.
+        assert_eq!(count_actions(&tmp), 10);
+    }
+
+    #[test]
+    fn on_error_receives_failed_actions() {
+        let tmp = TempDir::new().unwrap();
+        let (tx, rx) = mpsc::channel();
+        let store = TelemetrySqliteStore::open(tmp.path(), TelemetryConfig::default())
+            .unwrap()
+            .on_error(move |e| {
+                let _ = tx.send(e);
+            });
+        Connection::open(tmp.path().join("research.db"))
+            .unwrap()
+            .execute_batch(
+                "CREATE TRIGGER reject_bad BEFORE INSERT ON action_events
+                 WHEN NEW.session_id = 'bad'
+                 BEGIN SELECT RAISE(ABORT, 'rejected'); END;",
+            )
+            .unwrap();
+
+        store.submit_action(make_action_record());
+        store.submit_action(ActionRecord {
+            session_id: "bad".into(),
+            ..make_action_record()
+        });
+        drop(store);
+
+        let errors: Vec<TelemetryError> = rx.try_iter().collect();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].kind, TelemetryErrorKind::ActionInsert);
+        assert_eq!(errors[0].record.as_ref().unwrap().session_id, "bad");
+        assert!(errors[0].message.contains("rejected"));
+        assert_eq!(count_actions(&tmp), 1);
+    }
+
+    #[test]
+    fn savepoints_isolate_a_failed_write() {
+        let tmp = TempDir::new().unwrap();
+        let conn = Connection::open(tmp.path().join("research.db")).unwrap();
+        init_schema(&conn).unwrap();
+        conn.execute_batch(
+            "CREATE TRIGGER reject_bad BEFORE INSERT ON session_tags
+             WHEN NEW.tag = 'bad'
+             BEGIN SELECT RAISE(ABORT, 'rejected'); END;",
+        )
+        .unwrap();
+
+        let batch = vec![
+            WriteOp::ActionEvent(Box::new(make_action_record())),
+            WriteOp::SessionTags {
+                session_id: "sess-1".into(),
+                tags: vec!["good".into(), "bad".into()],
+            },
+            WriteOp::ActionEvent(Box::new(make_action_record())),
+        ];
+        let live = TelemetryBus::default();
+        flush_batch(&conn, &batch, &live, &ErrorHook::default(), true).unwrap();
+
+        assert_eq!(count_actions(&tmp), 2);
+        // "good" was inserted before "bad" failed and is rolled back with it.
+        let tags: i64 = conn
+            .query_row("SELECT COUNT(*) FROM session_tags", [], |r| r.get(0))
+            .unwrap();
+        assert_eq!(tags, 0);
+    }
+
+    #[test]
+    fn tool_success_rates_is_backfilled_when_created() {
+        let tmp = TempDir::new().unwrap();
+        let conn = Connection::open(tmp.path().join("research.db")).unwrap();
+        init_schema(&conn).unwrap();
+        for success in [true, true, false] {
+            insert_action(
+                &conn,
+                &ActionRecord {
+                    event_type: "tool_call".into(),
+                    tool_name: Some("shell".into()),
+                    tool_success: Some(success),
+                    ..make_action_record()
+                },
+            )
+            .unwrap();
+        }
+        insert_action(&conn, &make_action_record()).unwrap();
+        conn.execute_batch("DROP TABLE tool_success_rates").unwrap();
+
+        init_schema(&conn).unwrap();
+        let counts: Vec<(String, i64, i64)> = conn
+            .prepare("SELECT tool_name, success_count, failure_count FROM tool_success_rates")
+            .unwrap()
+            .query_map([], |r| Ok((r.get(0)?, r.get(1)?, r.get(2)?)))
+            .unwrap()
+            .collect::<rusqlite::Result<_>>()
+            .unwrap();
+        assert_eq!(counts, vec![("shell".to_string(), 2, 1)]);
+
+        // An existing table is left alone.
+        init_schema(&conn).unwrap();
+        let rows: i64 = conn
+            .query_row("SELECT success_count FROM tool_success_rates", [], |r| {
+                r.get(0)
+            })
+            .unwrap();
+        assert_eq!(rows, 2);
+    }
+
+    #[test]
+    fn deferred_indexes_are_built_on_request() {
+        let tmp = TempDir::new().unwrap();
+        let config = TelemetryConfig {
+            index_strategy: IndexStrategy {
+                create_before_insert: false,
+            },
+            ..TelemetryConfig::default()
+        };
+        let mut store = TelemetrySqliteStore::open(tmp.path(), config).unwrap();
+        let index_count = || -> i64 {
+            Connection::open(tmp.path().join("research.db"))
+                .unwrap()
+                .query_row(
+                    "SELECT COUNT(*) FROM sqlite_master
+                     WHERE type = 'index' AND name LIKE 'idx_%'",
+                    [],
+                    |r| r.get(0),
+                )
+                .unwrap()
+        };
+        assert_eq!(index_count(), 0);
+
+        store.submit_action(make_action_record());
+        store.shutdown();
+        store.create_indexes().unwrap();
+        assert_eq!(count_actions(&tmp), 1);
+        assert_eq!(
+            index_count(),
+            schema::INDEXES_DDL.matches("CREATE INDEX").count() as i64
+        );
+    }
+
+    #[test]
+    fn net_surges_count_each_crossing_once() {
+        let tmp = TempDir::new().unwrap();
+        let config = TelemetryConfig {
+            net_connection_alert_threshold: Some(10),
+            ..TelemetryConfig::default()
+        };
+        let store = TelemetrySqliteStore::open(tmp.path(), config).unwrap();
+        for count in [3, 12, 40, 11, 5, 15] {
+            store.record_net_connections(count);
+        }
+        assert_eq!(store.net_connection_high_watermark(), 40);
+        assert_eq!(store.net_surges_detected(), 2);
+    }
+
+    #[test]
+    fn open_evicts_embeddings_past_ttl() {
+        let tmp = TempDir::new().unwrap();
+        let store = TelemetrySqliteStore::open(tmp.path(), TelemetryConfig::default()).unwrap();
+        store.submit_tool_embedding("fresh", vec![1; 32], 32);
+        drop(store);
+        let conn = Connection::open(tmp.path().join("research.db")).unwrap();
+        conn.execute(
+            "INSERT INTO tool_embeddings_cache (tool_name, embedding, dimensions, computed_at)
+             VALUES ('stale', x'00', 1, '2020-01-01T00:00:00+00:00')",
+            [],
+        )
+        .unwrap();
+        drop(conn);
+
+        let config = TelemetryConfig {
+            embedding_cache_ttl_secs: Some(3600),
+            ..TelemetryConfig::default()
+        };
+        drop(TelemetrySqliteStore::open(tmp.path(), config).unwrap());
+
+        let conn = Connection::open(tmp.path().join("research.db")).unwrap();
+        let tools: Vec<String> = conn
+            .prepare("SELECT tool_name FROM tool_embeddings_cache")
+            .unwrap()
+            .query_map([], |r| r.get(0))
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(tools, ["fresh"]);
+        assert_eq!(evict_stale_embeddings(&conn, 0).unwrap(), 1);
+    }
+
+    #[test]
+    fn update_config_switches_overflow_strategy() {
+        let tmp = TempDir::new().unwrap();
+        let store = open_with_overflow(&tmp, OverflowStrategy::Drop);
+        store
+            .update_config(TelemetryConfig {
+                buffer_capacity: 10,
+                max_batch_size: 1,
+                overflow_strategy: OverflowStrategy::Block,
+                ..TelemetryConfig::default()
+            })
+            .unwrap();
+        assert_eq!(store.config().max_batch_size, 1);
+        for _ in 0..100 {
+            store.submit_action(make_action_record());
+        }
+        drop(store);
+        assert_eq!(count_actions(&tmp), 100);
+    }
+
+    #[test]
+    fn store_sample_random_full_rate_keeps_every_record() {
+        let tmp = TempDir::new().unwrap();
+        let store = open_with_overflow(&tmp, OverflowStrategy::SampleRandom(1.0));
+        for _ in 0..100 {
+            store.submit_action(make_action_record());
+        }
+        drop(store);
+        assert_eq!(count_actions(&tmp), 100);
+    }
+
+    #[test]
+    fn store_sample_random_zero_rate_drops_overflow() {
+        let tmp = TempDir::new().unwrap();
+        let store = open_with_overflow(&tmp, OverflowStrategy::SampleRandom(0.0));
+        for _ in 0..1000 {
+            store.submit_action(make_action_record());
+        }
+        drop(store);
+        assert!(count_actions(&tmp) < 1000);
+    }
+
+    #[test]
+    fn submit_action_blocking_inserts_record() {
+        let tmp = TempDir::new().unwrap();
+        let store = TelemetrySqliteStore::open(tmp.path(), TelemetryConfig::default()).unwrap();
+        assert!(store
+            .submit_action_blocking(make_action_record(), Duration::from_millis(100))
+            .is_ok());
+        drop(store);
+        assert_eq!(count_actions(&tmp), 1);
+    }
+
+    #[test]
+    fn submit_action_blocking_returns_record_after_shutdown() {
+        let tmp = TempDir::new().unwrap();
+        let mut store = TelemetrySqliteStore::open(tmp.path(), TelemetryConfig::default()).unwrap();
+        store.shutdown();
+        let returned = store
+            .submit_action_blocking(make_action_record(), Duration::from_millis(10))
+            .unwrap_err();
+        assert_eq!(returned.session_id, "sess-1");
+    }
+
+    #[test]
+    fn writer_prefers_actions_and_drains_samples_on_shutdown() {
+        let sample = || WriteOp::SystemSample(testing::sample(0));
+        let action = || WriteOp::ActionEvent(Box::new(make_action_record()));
+        let run = |tx: mpsc::SyncSender<WriteOp>, rx, sample_rx| {
+            let batches = Arc::new(Mutex::new(Vec::new()));
+            let sink = BatchSink::Record(batches.clone());
+            let writer = thread::spawn(move || {
+                writer_loop(
+                    sink,
+                    &WriteReceiver::from(rx),
+                    &WriteReceiver::from(sample_rx),
+                    &ActionRecordPool::new(0),
+                    &TelemetryBus::default(),
+                    &ErrorHook::default(),
+                    &Arc::new(RwLock::new(Arc::new(TelemetryConfig::default()))),
+                    &Mutex::new(None),
+                );
+            });
+            (tx, writer, batches)
+        };
+        let expected = |actions: usize, samples: usize| {
+            let mut kinds = vec!["action"; actions];
+            kinds.extend(vec!["sample"; samples]);
+            kinds
+        };
+
+        // Queued actions fill a batch first; samples take the space left
+        // over (the default max_batch_size is 20), the rest go next time.
+        let (tx, rx) = mpsc::sync_channel::<WriteOp>(64);
+        let (sample_tx, sample_rx) = mpsc::sync_channel::<WriteOp>(64);
+        for _ in 0..25 {
+            sample_tx.send(sample()).unwrap();
+        }
+        for _ in 0..3 {
+            tx.send(action()).unwrap();
+        }
+        let (tx, writer, batches) = run(tx, rx, sample_rx);
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while batches.lock().iter().map(Vec::len).sum::<usize>() < 28 && Instant::now() < deadline {
+            thread::sleep(Duration::from_millis(5));
+        }
+        drop(tx);
+        writer.join().unwrap();
+        assert_eq!(*batches.lock(), [expected(3, 17), expected(0, 8)]);
+
+        // On shutdown every queued sample goes in the last batch, after the
+        // actions, however many there are.
+        let (tx, rx) = mpsc::sync_channel::<WriteOp>(64);
+        let (sample_tx, sample_rx) = mpsc::sync_channel::<WriteOp>(64);
+        for _ in 0..25 {
+            sample_tx.send(sample()).unwrap();
+        }
+        for _ in 0..3 {
+            tx.send(action()).unwrap();
+        }
+        tx.send(WriteOp::Shutdown).unwrap();
+        let (_tx, writer, batches) = run(tx, rx, sample_rx);
+        writer.join().unwrap();
+        assert_eq!(*batches.lock(), [expected(3, 25)]);
+    }
+
+    #[test]
+    fn store_write_ahead_log_imports_into_sqlite() {
+        let tmp = TempDir::new().unwrap();
+        let wal_dir = tmp.path().join("wal");
+        let config = TelemetryConfig {
+            write_ahead_dir: Some(wal_dir.to_string_lossy().into_owned()),
+            ..TelemetryConfig::default()
+        };
+        let store = TelemetrySqliteStore::open(tmp.path(), config).unwrap();
+        for _ in 0..5 {
+            store.submit_action(make_action_record());
+        }
+        drop(store);
+
+        assert_eq!(count_actions(&tmp), 5);
+        assert_eq!(std::fs::read_dir(&wal_dir).unwrap().count(), 0);
+    }
+
+    #[test]
+    fn wal_pending_bytes_is_zero_without_wal() {
+        let tmp = TempDir::new().unwrap();
+        let store = TelemetrySqliteStore::open(tmp.path(), TelemetryConfig::default()).unwrap();
+        store.submit_action(make_action_record());
+        assert_eq!(store.wal_pending_bytes(), 0);
+    }
+
+    #[test]
+    fn open_rejects_invalid_config() {
+        let tmp = TempDir::new().unwrap();
+        let config = TelemetryConfig {
+            buffer_capacity: 2,
+            ..TelemetryConfig::default()
+        };
+        let Err(err) = TelemetrySqliteStore::open(tmp.path(), config) else {
+            panic!("buffer_capacity 2 should be rejected");
+        };
+        assert!(matches!(
+            err.downcast_ref::<ConfigError>(),
+            Some(ConfigError::InvalidTelemetryField {
+                field: "buffer_capacity",
+                ..
+            })
+        ));
+        assert!(!tmp.path().join("research.db").exists());
+    }
+
+    #[test]
+    fn update_config_rejects_invalid_config() {
+        let tmp = TempDir::new().unwrap();
+        let store = TelemetrySqliteStore::open(tmp.path(), TelemetryConfig::default()).unwrap();
+        let err = store
+            .update_config(TelemetryConfig {
+                max_batch_size: 0,
+                ..TelemetryConfig::default()
+            })
+            .unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<ConfigError>(),
+            Some(ConfigError::InvalidTelemetryField {
+                field: "max_batch_size",
+                ..
+            })
+        ));
+        assert_eq!(store.config().max_batch_size, 20);
+    }
+
     #[test]
     fn store_backpressure_does_not_panic() {
         let tmp = TempDir::new().unwrap();
-        let store = TelemetrySqliteStore::open(tmp.path(), 2).unwrap();
+        let store = TelemetrySqliteStore::open(tmp.path(), TelemetryConfig::default()).unwrap();
         // Submit more than the capacity — should not panic, just drop.
-        for _ in 0..20 {
+        for _ in 0..200 {
             store.submit_action(make_action_record());
         }
         drop(store);
@@ -0,0 +1,152 @@
+use crate::telemetry::reader::TelemetryReader;
+use anyhow::Result;
+
+/// Condition a session must meet for a [`TagRule`] to apply.
+#[derive(Debug, Clone, PartialEq)]
+pub enum TagPredicate {
+    /// At least one action event has this `event_type`.
+    HasEventType(String),
+    /// More than this many `tool_call` events.
+    ToolCallCountExceeds(usize),
+    /// Failed LLM responses and tool calls make up more than this fraction
+    /// (`0.0..=1.0`) of those with a recorded outcome.
+    ErrorRateAbove(f64),
+    /// Some completed turn contains these event types consecutively.
+    ContainsTurnSequence(Vec<String>),
+}
+
+/// Tag applied to a session when its predicate holds.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TagRule {
+    pub tag: String,
+    pub predicate: TagPredicate,
+}
+
+/// Evaluate `rules` against a recorded session and return the tags of the
+/// rules that match, in rule order.
+///
+/// Tags can be persisted with
+/// [`TelemetrySqliteStore::submit_session_tags`](crate::telemetry::TelemetrySqliteStore::submit_session_tags)
+/// to classify sessions as "debug", "agentic-loop", "high-cost", etc.
+pub fn tag_session(
+    reader: &TelemetryReader,
+    session_id: &str,
+    rules: &[TagRule],
+) -> Result<Vec<String>> {
+    let events = reader.export_session_events(session_id)?;
+    let needs_turns = rules
+        .iter()
+        .any(|rule| matches!(rule.predicate, TagPredicate::ContainsTurnSequence(_)));
+    let turns = if needs_turns {
+        reader.export_turn_sequences(session_id)?
+    } else {
+        Vec::new()
+    };
+
+    let tool_calls = events
+        .iter()
+        .filter(|e| e.event_type == "tool_call")
+        .count();
+    let outcomes: Vec<bool> = events
+        .iter()
+        .filter(|e| matches!(e.event_type.as_str(), "llm_response" | "tool_call"))
+        .filter_map(|e| e.tool_success)
+        .collect();
+    let error_rate = if outcomes.is_empty() {
+        0.0
+    } else {
+        outcomes.iter().filter(|ok| !**ok).count() as f64 / outcomes.len() as f64
+    };
+
+    let tags = rules
+        .iter()
+        .filter(|rule| match &rule.predicate {
+            TagPredicate::HasEventType(event_type) => {
+                events.iter().any(|e| &e.event_type == event_type)
+            }
+            TagPredicate::ToolCallCountExceeds(limit) => tool_calls > *limit,
+            TagPredicate::ErrorRateAbove(threshold) => error_rate > *threshold,
+            TagPredicate::ContainsTurnSequence(pattern) => {
+                !pattern.is_empty()
+                    && turns.iter().any(|turn| {
+                        turn.sequence
+                            .windows(pattern.len())
+                            .any(|w| w == pattern.as_slice())
+                    })
+            }
+        })
+        .map(|rule| rule.tag.clone())
+        .collect();
+    Ok(tags)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::TelemetryConfig;
+    use crate::telemetry::store::{ActionRecord, TelemetrySqliteStore};
+    use crate::telemetry::testing;
+    use tempfile::TempDir;
+
+    fn rule(tag: &str, predicate: TagPredicate) -> TagRule {
+        TagRule {
+            tag: tag.into(),
+            predicate,
+        }
+    }
+
+    #[test]
+    fn tags_session_and_stores_tags() {
+        let tmp = TempDir::new().unwrap();
+        let store = TelemetrySqliteStore::open(tmp.path(), TelemetryConfig::default()).unwrap();
+        for (event_type, success, sequence) in [
+            ("llm_response", Some(true), None),
+            ("tool_call", Some(false), None),
+            ("tool_call", Some(true), None),
+            (
+                "turn_complete",
+                None,
+                Some(r#"["llm_response","tool_call","tool_call"]"#),
+            ),
+        ] {
+            store.submit_action(ActionRecord {
+                tool_success: success,
+                turn_action_sequence: sequence.map(Into::into),
+                ..testing::action("s1", "s1-t0", 1_000, event_type)
+            });
+        }
+        store.flush().unwrap();
+
+        let reader = TelemetryReader::open(&tmp.path().join("research.db")).unwrap();
+        let tags = tag_session(
+            &reader,
+            "s1",
+            &[
+                rule(
+                    "thinking",
+                    TagPredicate::HasEventType("agent_thinking".into()),
+                ),
+                rule("busy", TagPredicate::ToolCallCountExceeds(1)),
+                rule("very-busy", TagPredicate::ToolCallCountExceeds(2)),
+                rule("flaky", TagPredicate::ErrorRateAbove(0.3)),
+                rule(
+                    "agentic-loop",
+                    TagPredicate::ContainsTurnSequence(vec![
+                        "tool_call".into(),
+                        "tool_call".into(),
+                    ]),
+                ),
+            ],
+        )
+        .unwrap();
+        assert_eq!(tags, ["busy", "flaky", "agentic-loop"]);
+
+        store.submit_session_tags("s1", tags);
+        store.submit_session_tags("s1", vec!["busy".into()]);
+        drop(store);
+        assert_eq!(
+            reader.session_tags("s1").unwrap(),
+            ["agentic-loop", "busy", "flaky"]
+        );
+    }
+}
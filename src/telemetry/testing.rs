@@ -0,0 +1,40 @@
+//! Record builders shared by the telemetry tests.
+
+use crate::telemetry::store::{ActionRecord, SystemSample};
+
+/// An action event of `session_id`/`turn_id` at `ts_epoch_ms`; every other
+/// field is left at its default.
+pub(crate) fn action(
+    session_id: &str,
+    turn_id: &str,
+    ts_epoch_ms: i64,
+    event_type: &str,
+) -> ActionRecord {
+    ActionRecord {
+        ts: "2026-01-01T00:00:00Z".into(),
+        ts_epoch_ms,
+        session_id: session_id.into(),
+        turn_id: turn_id.into(),
+        event_type: event_type.into(),
+        ..ActionRecord::default()
+    }
+}
+
+/// A system sample taken at `ts_epoch_ms` with every reading zero.
+pub(crate) fn sample(ts_epoch_ms: i64) -> SystemSample {
+    SystemSample {
+        ts: "2026-01-01T00:00:00Z".into(),
+        ts_epoch_ms,
+        cpu_usage_pct: 0.0,
+        memory_used_bytes: 0,
+        memory_total_bytes: 0,
+        process_count: 0,
+        process_spawn_rate: 0,
+        file_read_bytes: 0,
+        file_write_bytes: 0,
+        net_connections: 0,
+        dest_ip_entropy: 0.0,
+        tcp_state_json: None,
+        syscall_freq_json: None,
+    }
+}
@@ -0,0 +1,143 @@
+//! Off-site archival of telemetry database files to S3 or any other
+//! [`ObjectStore`] backend.
+
+use anyhow::{Context, Result};
+use object_store::path::Path as ObjectPath;
+use object_store::{ObjectStore, PutPayload};
+use std::path::Path;
+use tokio::io::AsyncReadExt;
+
+/// Size of each chunk read from disk and, for multipart uploads, of each
+/// part. S3 rejects non-final parts smaller than 5 MiB.
+const CHUNK_SIZE: usize = 5 * 1024 * 1024;
+/// Files larger than this are sent as a multipart upload.
+const MULTIPART_THRESHOLD: u64 = 100 * 1024 * 1024;
+
+/// Upload the database file at `db_path` to `store` under `dest_key`.
+///
+/// The file is streamed in 5 MiB chunks. Files over 100 MiB go up as a
+/// multipart upload, which is aborted on failure so no orphaned parts are
+/// left behind. Upload a file the writer is no longer using, such as a
+/// rotated database, so the object is consistent.
+pub async fn upload_db_to_object_store(
+    db_path: &Path,
+    store: &dyn ObjectStore,
+    dest_key: &str,
+) -> Result<()> {
+    upload_file(db_path, store, dest_key, CHUNK_SIZE, MULTIPART_THRESHOLD).await
+}
+
+async fn upload_file(
+    db_path: &Path,
+    store: &dyn ObjectStore,
+    dest_key: &str,
+    chunk_size: usize,
+    multipart_threshold: u64,
+) -> Result<()> {
+    let location =
+        ObjectPath::parse(dest_key).with_context(|| format!("invalid object key: {dest_key}"))?;
+    let mut file = tokio::fs::File::open(db_path)
+        .await
+        .with_context(|| format!("opening {}", db_path.display()))?;
+    let len = file.metadata().await?.len();
+
+    if len <= multipart_threshold {
+        let mut chunks = Vec::new();
+        while let Some(chunk) = read_chunk(&mut file, chunk_size).await? {
+            chunks.extend(PutPayload::from(chunk));
+        }
+        store
+            .put(&location, chunks.into_iter().collect())
+            .await
+            .with_context(|| format!("uploading {dest_key}"))?;
+        return Ok(());
+    }
+
+    let mut upload = store
+        .put_multipart(&location)
+        .await
+        .with_context(|| format!("starting multipart upload of {dest_key}"))?;
+    let result = async {
+        while let Some(chunk) = read_chunk(&mut file, chunk_size).await? {
+            upload.put_part(PutPayload::from(chunk)).await?;
+        }
+        upload.complete().await?;
+        anyhow::Ok(())
+    }
+    .await;
+    if let Err(e) = result {
+        if let Err(abort) = upload.abort().await {
+            tracing::warn!("aborting multipart upload of {dest_key} failed: {abort}");
+        }
+        return Err(e.context(format!("multipart upload of {dest_key}")));
+    }
+    Ok(())
+}
+
+/// Read up to `chunk_size` bytes, filling the chunk unless the file ends;
+/// `None` at end of file.
+async fn read_chunk(file: &mut tokio::fs::File, chunk_size: usize) -> Result<Option<Vec<u8>>> {
+    let mut chunk = Vec::with_capacity(chunk_size);
+    while chunk.len() < chunk_size {
+        let read = (&mut *file)
+            .take((chunk_size - chunk.len()) as u64)
+            .read_to_end(&mut chunk)
+            .await?;
+        if read == 0 {
+            break;
+        }
+    }
+    Ok((!chunk.is_empty()).then_some(chunk))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use object_store::memory::InMemory;
+    use tempfile::TempDir;
+
+    async fn round_trip(multipart_threshold: u64) {
+        let tmp = TempDir::new().unwrap();
+        let db = tmp.path().join("research.db");
+        let data: Vec<u8> = (0..10_000u32).map(|i| (i % 251) as u8).collect();
+        std::fs::write(&db, &data).unwrap();
+
+        let store = InMemory::new();
+        upload_file(
+            &db,
+            &store,
+            "archive/research.db",
+            4096,
+            multipart_threshold,
+        )
+        .await
+        .unwrap();
+        let uploaded = store
+            .get(&ObjectPath::from("archive/research.db"))
+            .await
+            .unwrap()
+            .bytes()
+            .await
+            .unwrap();
+        assert_eq!(uploaded.as_ref(), data.as_slice());
+    }
+
+    #[tokio::test]
+    async fn uploads_small_file_in_one_put() {
+        round_trip(u64::MAX).await;
+    }
+
+    #[tokio::test]
+    async fn uploads_large_file_in_parts() {
+        round_trip(1024).await;
+    }
+
+    #[tokio::test]
+    async fn missing_file_is_an_error() {
+        let store = InMemory::new();
+        let err = upload_db_to_object_store(Path::new("/nonexistent/db"), &store, "x")
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("opening"));
+    }
+}
@@ -0,0 +1,426 @@
+use crate::telemetry::bus::TelemetryBus;
+use crate::telemetry::store::{flush_batch, ErrorHook, WriteOp};
+use anyhow::{Context, Result};
+use rusqlite::Connection;
+use std::fs::{self, File};
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Extension of a segment that has been closed and is ready for import.
+const SEGMENT_EXT: &str = "wal";
+/// Extension of the segment currently being appended to.
+const ACTIVE_EXT: &str = "wal.open";
+/// Close the active segment once it is this old...
+const ROTATE_INTERVAL: Duration = Duration::from_secs(1);
+/// ...or this large, whichever comes first.
+const ROTATE_BYTES: u64 = 4 * 1024 * 1024;
+/// How often the compactor looks for closed segments.
+const COMPACT_INTERVAL: Duration = Duration::from_millis(200);
+/// Subdirectory damaged segments are moved to once their readable entries
+/// have been imported.
+const QUARANTINE_DIR: &str = "quarantine";
+
+/// Local append-only log the writer thread appends batches to instead of
+/// SQLite. Closed segments are imported by [`run_compactor`].
+///
+/// Each entry is a `u32` little-endian length followed by a bincode-encoded
+/// [`WriteOp`].
+pub struct WriteAheadLog {
+    path: PathBuf,
+    file: BufWriter<File>,
+    dir: PathBuf,
+    opened_at: Instant,
+    segment_bytes: u64,
+    next_segment: u64,
+    pending_bytes: Arc<AtomicU64>,
+}
+
+impl WriteAheadLog {
+    /// Create `dir` if needed, recover segments left open by a previous
+    /// process, and start a fresh active segment.
+    pub fn open(dir: &Path, pending_bytes: Arc<AtomicU64>) -> Result<Self> {
+        fs::create_dir_all(dir)
+            .with_context(|| format!("creating telemetry WAL dir: {}", dir.display()))?;
+
+        let mut recovered = 0u64;
+        for entry in fs::read_dir(dir)? {
+            let path = entry?.path();
+            if is_segment(&path, ACTIVE_EXT) {
+                fs::rename(&path, closed_path(&path))?;
+            }
+        }
+        for path in closed_segments(dir)? {
+            recovered += fs::metadata(&path).map(|m| m.len()).unwrap_or(0);
+        }
+        pending_bytes.store(recovered, Ordering::Relaxed);
+
+        let (path, file) = create_segment(dir, 0)?;
+        Ok(Self {
+            path,
+            file,
+            dir: dir.to_path_buf(),
+            opened_at: Instant::now(),
+            segment_bytes: 0,
+            next_segment: 1,
+            pending_bytes,
+        })
+    }
+
+    /// Append a batch to the active segment, rotating it when due.
+    pub fn append(&mut self, batch: &[WriteOp]) -> Result<()> {
+        let config = bincode::config::standard();
+        for op in batch {
+            if matches!(op, WriteOp::Shutdown | WriteOp::Flush { .. }) {
+                continue;
+            }
+            let bytes = bincode::serde::encode_to_vec(op, config)?;
+            let len = u32::try_from(bytes.len()).context("WAL entry too large")?;
+            self.file.write_all(&len.to_le_bytes())?;
+            self.file.write_all(&bytes)?;
+            let written = 4 + u64::from(len);
+            self.segment_bytes += written;
+            self.pending_bytes.fetch_add(written, Ordering::Relaxed);
+        }
+        self.file.flush()?;
+
+        if self.segment_bytes >= ROTATE_BYTES || self.opened_at.elapsed() >= ROTATE_INTERVAL {
+            self.rotate()?;
+        }
+        Ok(())
+    }
+
+    /// Close the active segment so the compactor can import it.
+    pub fn finish(mut self) -> Result<()> {
+        self.file.flush()?;
+        close_segment(&self.path, self.segment_bytes)
+    }
+
+    fn rotate(&mut self) -> Result<()> {
+        if self.segment_bytes == 0 {
+            self.opened_at = Instant::now();
+            return Ok(());
+        }
+        let (path, file) = create_segment(&self.dir, self.next_segment)?;
+        self.next_segment += 1;
+        let old_path = std::mem::replace(&mut self.path, path);
+        let mut old_file = std::mem::replace(&mut self.file, file);
+        old_file.flush()?;
+        drop(old_file);
+        close_segment(&old_path, self.segment_bytes)?;
+        self.segment_bytes = 0;
+        self.opened_at = Instant::now();
+        Ok(())
+    }
+}
+
+fn create_segment(dir: &Path, seq: u64) -> Result<(PathBuf, BufWriter<File>)> {
+    let now_ms = chrono::Utc::now().timestamp_millis();
+    let path = dir.join(format!("{now_ms:013}-{seq:06}.{ACTIVE_EXT}"));
+    let file = File::create(&path)
+        .with_context(|| format!("creating telemetry WAL segment: {}", path.display()))?;
+    Ok((path, BufWriter::new(file)))
+}
+
+/// Rename an active segment to its closed name, or delete it when empty.
+fn close_segment(path: &Path, bytes: u64) -> Result<()> {
+    if bytes == 0 {
+        fs::remove_file(path)?;
+    } else {
+        fs::rename(path, closed_path(path))?;
+    }
+    Ok(())
+}
+
+/// Closed name of an active segment: strip the trailing `.open`.
+fn closed_path(active: &Path) -> PathBuf {
+    active.with_extension("")
+}
+
+fn is_segment(path: &Path, ext: &str) -> bool {
+    path.file_name()
+        .and_then(|n| n.to_str())
+        .and_then(|n| n.split_once('.'))
+        .is_some_and(|(_, e)| e == ext)
+}
+
+/// Closed segments in `dir`, oldest first.
+fn closed_segments(dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut segments: Vec<PathBuf> = fs::read_dir(dir)?
+        .filter_map(|e| e.ok().map(|e| e.path()))
+        .filter(|p| is_segment(p, SEGMENT_EXT))
+        .collect();
+    segments.sort();
+    Ok(segments)
+}
+
+/// Entries decoded from a segment.
+struct SegmentContents {
+    ops: Vec<WriteOp>,
+    /// False when decoding stopped early at a truncated or corrupt entry;
+    /// `ops` then holds the entries before it.
+    intact: bool,
+}
+
+/// Decode every complete entry in a segment. A truncated or corrupt entry
+/// (e.g. from a crash mid-write) ends decoding with a warning; the entries
+/// before it are still returned.
+fn read_segment(path: &Path) -> Result<SegmentContents> {
+    let data = fs::read(path)?;
+    let config = bincode::config::standard();
+    let mut ops = Vec::new();
+    let mut pos = 0usize;
+    while pos < data.len() {
+        let Some(len_bytes) = data.get(pos..pos + 4) else {
+            tracing::warn!("truncated telemetry WAL entry in {}", path.display());
+            return Ok(SegmentContents { ops, intact: false });
+        };
+        let len = u32::from_le_bytes(len_bytes.try_into()?) as usize;
+        let Some(entry) = data.get(pos + 4..pos + 4 + len) else {
+            tracing::warn!("truncated telemetry WAL entry in {}", path.display());
+            return Ok(SegmentContents { ops, intact: false });
+        };
+        match bincode::serde::decode_from_slice::<WriteOp, _>(entry, config) {
+            Ok((op, _)) => ops.push(op),
+            Err(e) => {
+                tracing::warn!("corrupt telemetry WAL entry in {}: {e}", path.display());
+                return Ok(SegmentContents { ops, intact: false });
+            }
+        }
+        pos += 4 + len;
+    }
+    Ok(SegmentContents { ops, intact: true })
+}
+
+/// Move a damaged segment into the quarantine subdirectory of `dir`.
+fn quarantine_segment(dir: &Path, path: &Path) -> std::io::Result<()> {
+    let quarantine = dir.join(QUARANTINE_DIR);
+    fs::create_dir_all(&quarantine)?;
+    let name = path.file_name().unwrap_or_default();
+    fs::rename(path, quarantine.join(name))
+}
+
+/// Import every closed segment into SQLite and delete it. A segment whose
+/// import fails is kept so the next pass retries it. A damaged segment has
+/// its readable entries imported and is then moved to `quarantine/` rather
+/// than deleted.
+fn import_closed_segments(
+    conn: &Connection,
+    dir: &Path,
+    pending_bytes: &AtomicU64,
+    live: &TelemetryBus,
+    errors: &ErrorHook,
+    use_savepoints: bool,
+) {
+    let segments = match closed_segments(dir) {
+        Ok(s) => s,
+        Err(e) => {
+            tracing::error!("listing telemetry WAL segments failed: {e}");
+            return;
+        }
+    };
+    for path in segments {
+        let size = fs::metadata(&path).map(|m| m.len()).unwrap_or(0);
+        let contents = match read_segment(&path) {
+            Ok(contents) => contents,
+            Err(e) => {
+                tracing::error!("telemetry WAL segment {} unreadable: {e}", path.display());
+                SegmentContents {
+                    ops: Vec::new(),
+                    intact: false,
+                }
+            }
+        };
+        if let Err(e) = flush_batch(conn, &contents.ops, live, errors, use_savepoints) {
+            tracing::error!(
+                "importing telemetry WAL segment {} failed, will retry: {e:#}",
+                path.display()
+            );
+            // Later segments must not overtake this one.
+            break;
+        }
+        let disposed = if contents.intact {
+            fs::remove_file(&path)
+        } else {
+            tracing::warn!(
+                "moving damaged telemetry WAL segment {} to {QUARANTINE_DIR}/",
+                path.display()
+            );
+            quarantine_segment(dir, &path)
+        };
+        if let Err(e) = disposed {
+            tracing::error!(
+                "removing telemetry WAL segment {} failed: {e}",
+                path.display()
+            );
+            continue;
+        }
+        let _ = pending_bytes.fetch_update(Ordering::Relaxed, Ordering::Relaxed, |v| {
+            Some(v.saturating_sub(size))
+        });
+    }
+}
+
+/// Compactor thread main loop: imports closed WAL segments into SQLite until
+/// `stop` is set, then performs a final pass.
+pub(crate) fn run_compactor(
+    conn: Connection,
+    dir: PathBuf,
+    pending_bytes: Arc<AtomicU64>,
+    stop: Arc<AtomicBool>,
+    live: &TelemetryBus,
+    errors: &ErrorHook,
+    use_savepoints: bool,
+) {
+    loop {
+        // Read the flag before importing so the last pass sees every
+        // segment closed before shutdown.
+        let stopping = stop.load(Ordering::Acquire);
+        import_closed_segments(&conn, &dir, &pending_bytes, live, errors, use_savepoints);
+        if stopping {
+            break;
+        }
+        thread::sleep(COMPACT_INTERVAL);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::telemetry::store::SystemSample;
+    use crate::telemetry::testing;
+    use tempfile::TempDir;
+
+    fn sample(ts_epoch_ms: i64) -> WriteOp {
+        WriteOp::SystemSample(SystemSample {
+            cpu_usage_pct: 12.5,
+            memory_used_bytes: 1,
+            memory_total_bytes: 2,
+            process_count: 3,
+            syscall_freq_json: Some("{}".into()),
+            ..testing::sample(ts_epoch_ms)
+        })
+    }
+
+    #[test]
+    fn segment_round_trips_entries() {
+        let tmp = TempDir::new().unwrap();
+        let pending = Arc::new(AtomicU64::new(0));
+        let mut wal = WriteAheadLog::open(tmp.path(), pending.clone()).unwrap();
+        wal.append(&[sample(1), sample(2), WriteOp::Shutdown])
+            .unwrap();
+        assert!(pending.load(Ordering::Relaxed) > 0);
+        wal.finish().unwrap();
+
+        let segments = closed_segments(tmp.path()).unwrap();
+        assert_eq!(segments.len(), 1);
+        let contents = read_segment(&segments[0]).unwrap();
+        assert!(contents.intact);
+        let ops = contents.ops;
+        assert_eq!(ops.len(), 2);
+        match &ops[1] {
+            WriteOp::SystemSample(s) => assert_eq!(s.ts_epoch_ms, 2),
+            _ => panic!("expected a system sample"),
+        }
+    }
+
+    #[test]
+    fn truncated_tail_is_skipped() {
+        let tmp = TempDir::new().unwrap();
+        let pending = Arc::new(AtomicU64::new(0));
+        let mut wal = WriteAheadLog::open(tmp.path(), pending).unwrap();
+        wal.append(&[sample(1), sample(2)]).unwrap();
+        wal.finish().unwrap();
+
+        let path = closed_segments(tmp.path()).unwrap().remove(0);
+        let mut data = fs::read(&path).unwrap();
+        data.truncate(data.len() - 3);
+        fs::write(&path, data).unwrap();
+
+        let contents = read_segment(&path).unwrap();
+        assert_eq!(contents.ops.len(), 1);
+        assert!(!contents.intact);
+    }
+
+    #[test]
+    fn truncated_segment_is_imported_and_quarantined() {
+        let tmp = TempDir::new().unwrap();
+        let pending = Arc::new(AtomicU64::new(0));
+        let mut wal = WriteAheadLog::open(tmp.path(), pending.clone()).unwrap();
+        wal.append(&[sample(1), sample(2), sample(3)]).unwrap();
+        wal.finish().unwrap();
+
+        let path = closed_segments(tmp.path()).unwrap().remove(0);
+        let mut data = fs::read(&path).unwrap();
+        data.truncate(data.len() - 3);
+        fs::write(&path, data).unwrap();
+
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(crate::telemetry::schema::SYSTEM_SAMPLES_DDL)
+            .unwrap();
+        let live = TelemetryBus::default();
+        import_closed_segments(
+            &conn,
+            tmp.path(),
+            &pending,
+            &live,
+            &ErrorHook::default(),
+            false,
+        );
+
+        let count: i64 = conn
+            .query_row("SELECT COUNT(*) FROM system_samples", [], |r| r.get(0))
+            .unwrap();
+        assert_eq!(count, 2);
+        assert!(closed_segments(tmp.path()).unwrap().is_empty());
+        let quarantined = tmp
+            .path()
+            .join(QUARANTINE_DIR)
+            .join(path.file_name().unwrap());
+        assert!(quarantined.exists());
+    }
+
+    #[test]
+    fn open_recovers_active_segments() {
+        let tmp = TempDir::new().unwrap();
+        let pending = Arc::new(AtomicU64::new(0));
+        let mut wal = WriteAheadLog::open(tmp.path(), pending.clone()).unwrap();
+        wal.append(&[sample(1)]).unwrap();
+        // Simulate a crash: never call finish().
+        drop(wal);
+
+        let _wal = WriteAheadLog::open(tmp.path(), pending.clone()).unwrap();
+        assert_eq!(closed_segments(tmp.path()).unwrap().len(), 1);
+        assert!(pending.load(Ordering::Relaxed) > 0);
+    }
+
+    #[test]
+    fn failed_import_keeps_segment_for_retry() {
+        let tmp = TempDir::new().unwrap();
+        let pending = Arc::new(AtomicU64::new(0));
+        let mut wal = WriteAheadLog::open(tmp.path(), pending.clone()).unwrap();
+        wal.append(&[sample(1)]).unwrap();
+        wal.finish().unwrap();
+
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(crate::telemetry::schema::SYSTEM_SAMPLES_DDL)
+            .unwrap();
+        let live = TelemetryBus::default();
+        let errors = ErrorHook::default();
+        // An open transaction makes the import's BEGIN fail.
+        conn.execute_batch("BEGIN").unwrap();
+        import_closed_segments(&conn, tmp.path(), &pending, &live, &errors, true);
+        assert_eq!(closed_segments(tmp.path()).unwrap().len(), 1);
+
+        conn.execute_batch("COMMIT").unwrap();
+        import_closed_segments(&conn, tmp.path(), &pending, &live, &errors, true);
+        assert!(closed_segments(tmp.path()).unwrap().is_empty());
+        let count: i64 = conn
+            .query_row("SELECT COUNT(*) FROM system_samples", [], |r| r.get(0))
+            .unwrap();
+        assert_eq!(count, 1);
+    }
+}
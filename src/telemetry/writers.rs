@@ -0,0 +1,189 @@
+//! Parallel SQLite writer threads for `num_writer_threads > 1`.
+//!
+//! The store's writer thread still drains the submit channels and forms
+//! batches; instead of committing them itself it pushes each batch onto a
+//! shared [`Injector`]. Every worker owns a SQLite connection, takes batches
+//! from the injector, and steals from the other workers' local queues when
+//! the injector runs dry.
+//!
+//! SQLite admits one write transaction at a time even in WAL mode, so the
+//! workers overlap statement preparation, row encoding and fsync rather than
+//! the inserts themselves; contended commits wait on `busy_timeout`.
+//! Batches may commit out of submission order. A batch carrying a
+//! soft-delete or flush waits until every earlier batch has committed.
+
+use crate::telemetry::bus::TelemetryBus;
+use crate::telemetry::pool::ActionRecordPool;
+use crate::telemetry::store::{flush_batch, ErrorHook, WriteOp};
+use anyhow::{Context, Result};
+use crossbeam_deque::{Injector, Stealer, Worker};
+use rusqlite::Connection;
+use std::panic::AssertUnwindSafe;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+/// How long an idle worker sleeps before looking for work again.
+const IDLE_POLL_INTERVAL: Duration = Duration::from_millis(1);
+
+type Batch = Vec<WriteOp>;
+
+/// Handle to the running worker threads.
+pub(crate) struct ParallelWriters {
+    injector: Arc<Injector<Batch>>,
+    /// Batches submitted but not yet committed.
+    in_flight: Arc<AtomicUsize>,
+    stop: Arc<AtomicBool>,
+    handles: Vec<thread::JoinHandle<()>>,
+}
+
+impl ParallelWriters {
+    /// Start one worker per connection.
+    pub(crate) fn spawn(
+        conns: Vec<Connection>,
+        pool: &ActionRecordPool,
+        live: &TelemetryBus,
+        errors: &ErrorHook,
+        use_savepoints: bool,
+    ) -> Result<Self> {
+        let injector = Arc::new(Injector::new());
+        let in_flight = Arc::new(AtomicUsize::new(0));
+        let stop = Arc::new(AtomicBool::new(false));
+        let locals: Vec<Worker<Batch>> = conns.iter().map(|_| Worker::new_fifo()).collect();
+        let stealers: Arc<[Stealer<Batch>]> = locals.iter().map(Worker::stealer).collect();
+
+        let mut handles = Vec::with_capacity(conns.len());
+        for (i, (conn, local)) in conns.into_iter().zip(locals).enumerate() {
+            let worker = WorkerState {
+                conn,
+                local,
+                injector: injector.clone(),
+                stealers: stealers.clone(),
+                in_flight: in_flight.clone(),
+                stop: stop.clone(),
+                pool: pool.clone(),
+                live: live.clone(),
+                errors: errors.clone(),
+                use_savepoints,
+            };
+            handles.push(
+                thread::Builder::new()
+                    .name(format!("telemetry-writer-{i}"))
+                    .spawn(move || worker.run())
+                    .context("spawning telemetry writer thread")?,
+            );
+        }
+        Ok(Self {
+            injector,
+            in_flight,
+            stop,
+            handles,
+        })
+    }
+
+    /// Queue `batch` for the next free worker.
+    pub(crate) fn submit(&self, batch: Batch) {
+        if batch.is_empty() {
+            return;
+        }
+        if batch
+            .iter()
+            .any(|op| matches!(op, WriteOp::SoftDelete { .. } | WriteOp::Flush { .. }))
+        {
+            // Rows submitted before the delete or flush must be committed first.
+            self.wait_idle();
+        }
+        self.in_flight.fetch_add(1, Ordering::AcqRel);
+        self.injector.push(batch);
+    }
+
+    /// Block until every submitted batch has been committed.
+    fn wait_idle(&self) {
+        while self.in_flight.load(Ordering::Acquire) > 0 {
+            thread::sleep(IDLE_POLL_INTERVAL);
+        }
+    }
+
+    /// Commit everything queued, then stop the workers.
+    pub(crate) fn close(self) {
+        drop(self);
+    }
+}
+
+impl Drop for ParallelWriters {
+    /// Also runs when the dispatching writer thread unwinds, so a restarted
+    /// writer never leaves the previous workers running.
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Release);
+        for handle in self.handles.drain(..) {
+            let _ = handle.join();
+        }
+    }
+}
+
+struct WorkerState {
+    conn: Connection,
+    local: Worker<Batch>,
+    injector: Arc<Injector<Batch>>,
+    stealers: Arc<[Stealer<Batch>]>,
+    in_flight: Arc<AtomicUsize>,
+    stop: Arc<AtomicBool>,
+    pool: ActionRecordPool,
+    live: TelemetryBus,
+    errors: ErrorHook,
+    use_savepoints: bool,
+}
+
+impl WorkerState {
+    fn run(self) {
+        loop {
+            // Read before looking for work so a batch submitted just before
+            // `close` is never missed.
+            let stopping = self.stop.load(Ordering::Acquire);
+            match self.find_batch() {
+                Some(batch) => {
+                    // A panic must not leave `in_flight` raised, or the next
+                    // soft-delete would wait forever.
+                    let flushed = std::panic::catch_unwind(AssertUnwindSafe(|| {
+                        if let Err(e) = flush_batch(
+                            &self.conn,
+                            &batch,
+                            &self.live,
+                            &self.errors,
+                            self.use_savepoints,
+                        ) {
+                            tracing::error!("{e:#}");
+                        }
+                    }));
+                    if flushed.is_err() {
+                        tracing::error!("telemetry writer worker panicked; batch lost");
+                        let _ = self.conn.execute_batch("ROLLBACK");
+                    }
+                    for op in batch {
+                        if let WriteOp::ActionEvent(record) = op {
+                            self.pool.release(record);
+                        }
+                    }
+                    self.in_flight.fetch_sub(1, Ordering::AcqRel);
+                }
+                None if stopping => return,
+                None => thread::sleep(IDLE_POLL_INTERVAL),
+            }
+        }
+    }
+
+    /// Pop local work, else take a batch from the injector, else steal
+    /// from another worker.
+    fn find_batch(&self) -> Option<Batch> {
+        self.local.pop().or_else(|| {
+            std::iter::repeat_with(|| {
+                self.injector
+                    .steal_batch_and_pop(&self.local)
+                    .or_else(|| self.stealers.iter().map(Stealer::steal).collect())
+            })
+            .find(|steal| !steal.is_retry())
+            .and_then(|steal| steal.success())
+        })
+    }
+}
@@ -0,0 +1,74 @@
+//! Telemetry database size after bulk deletes, with and without auto-vacuum.
+//!
+//! Run with: cargo test --test telemetry_auto_vacuum
+
+use rusqlite::Connection;
+use std::path::Path;
+use tempfile::TempDir;
+use zeroclaw::config::{AutoVacuumMode, ChannelKind, TelemetryConfig};
+use zeroclaw::telemetry::{ActionRecord, TelemetrySqliteStore};
+
+const ROWS: i64 = 10_000;
+
+/// Write `ROWS` action events through the store, delete them all, and
+/// return the database file size once the WAL is checkpointed.
+fn size_after_delete(dir: &Path, auto_vacuum: AutoVacuumMode) -> u64 {
+    let config = TelemetryConfig {
+        auto_vacuum,
+        channel_kind: ChannelKind::Unbounded,
+        max_batch_size: 500,
+        ..TelemetryConfig::default()
+    };
+    let store = TelemetrySqliteStore::open(dir, config).unwrap();
+    for i in 0..ROWS {
+        store.submit_action(ActionRecord {
+            ts: "2026-01-01T00:00:00Z".into(),
+            ts_epoch_ms: i,
+            session_id: "sess-1".into(),
+            turn_id: "t1".into(),
+            sequence_index: i,
+            event_type: "tool_call".into(),
+            tool_name: Some("shell".into()),
+            error_message: Some("x".repeat(200)),
+            ..ActionRecord::default()
+        });
+    }
+    drop(store);
+
+    let db = dir.join("research.db");
+    let conn = Connection::open(&db).unwrap();
+    let stored: i64 = conn
+        .query_row("SELECT COUNT(*) FROM action_events", [], |r| r.get(0))
+        .unwrap();
+    assert_eq!(stored, ROWS);
+    conn.execute_batch("DELETE FROM action_events").unwrap();
+    if auto_vacuum == AutoVacuumMode::Incremental {
+        // Frees one page per step, so run it to completion.
+        let mut vacuum = conn.prepare("PRAGMA incremental_vacuum").unwrap();
+        let mut rows = vacuum.query([]).unwrap();
+        while rows.next().unwrap().is_some() {}
+    }
+    conn.execute_batch("PRAGMA wal_checkpoint(TRUNCATE)")
+        .unwrap();
+    drop(conn);
+    std::fs::metadata(&db).unwrap().len()
+}
+
+#[test]
+fn incremental_auto_vacuum_shrinks_file_after_delete() {
+    let plain = TempDir::new().unwrap();
+    let vacuumed = TempDir::new().unwrap();
+
+    let plain_size = size_after_delete(plain.path(), AutoVacuumMode::None);
+    let vacuumed_size = size_after_delete(vacuumed.path(), AutoVacuumMode::Incremental);
+
+    let mode: i64 = Connection::open(vacuumed.path().join("research.db"))
+        .unwrap()
+        .query_row("PRAGMA auto_vacuum", [], |r| r.get(0))
+        .unwrap();
+    assert_eq!(mode, 2, "auto_vacuum should be INCREMENTAL");
+    assert!(
+        vacuumed_size < plain_size,
+        "expected {vacuumed_size} < {plain_size}"
+    );
+}